@@ -6,7 +6,9 @@
 //! ```ignore
 //! use {
 //!     ash::{vk, DefaultEntryLoader, DeviceLoader, InstanceLoader},
-//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc::{
+//!         AllocationFlags, Config, GpuAllocator, Request, RequestFlags, ResourceKind, UsageFlags,
+//!     },
 //!     gpu_alloc_ash::{device_properties, AshMemoryDevice},
 //!     std::ffi::CStr,
 //! };
@@ -60,6 +62,15 @@
 //!                 align_mask: 1,
 //!                 usage: UsageFlags::HOST_ACCESS,
 //!                 memory_types: !0,
+//!                 memory_type: None,
+//!                 access: None,
+//!                 label: None,
+//!                 dedicated: None,
+//!                 dedicated_resource: None,
+//!                 export: AllocationFlags::empty(),
+//!                 priority: None,
+//!                 flags: RequestFlags::empty(),
+//!                 resource_kind: ResourceKind::Linear,
 //!             },
 //!         )
 //!     }?;
@@ -82,11 +93,13 @@
 use {
     ash::{
         version::{DeviceV1_0 as _, InstanceV1_0 as _, InstanceV1_1 as _},
-        vk, Device, Instance,
+        vk::{self, Handle as _},
+        Device, Instance,
     },
     gpu_alloc_types::{
-        AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
-        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        AllocationFlags, DedicatedResource, DeviceBindError, DeviceImportError, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, OutOfMemory,
     },
     std::ptr::NonNull,
     tinyvec::TinyVec,
@@ -105,18 +118,31 @@ impl AshMemoryDevice {
             &*(device as *const Device as *const Self)
         }
     }
-}
 
-impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
-    unsafe fn allocate_memory(
+    /// Wraps device passing `allocation_callbacks` to `vkAllocateMemory` and `vkFreeMemory`,
+    /// so that host allocations driver makes for memory objects are reported to them.
+    ///
+    /// Memory objects must be freed with callbacks compatible with ones they were allocated with,
+    /// so same callbacks must be used for all interactions with `GpuAllocator`.
+    pub fn with_allocation_callbacks<'a>(
+        device: &'a Device,
+        allocation_callbacks: &'a vk::AllocationCallbacks,
+    ) -> AshMemoryDeviceWithCallbacks<'a> {
+        AshMemoryDeviceWithCallbacks {
+            device: AshMemoryDevice::wrap(device),
+            allocation_callbacks,
+        }
+    }
+
+    unsafe fn allocate(
         &self,
         size: u64,
         memory_type: u32,
         flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: Option<f32>,
+        allocation_callbacks: Option<&vk::AllocationCallbacks>,
     ) -> Result<vk::DeviceMemory, OutOfMemory> {
-        assert!((flags & !(AllocationFlags::DEVICE_ADDRESS)).is_empty());
-
         let mut info = vk::MemoryAllocateInfo::builder()
             .allocation_size(size)
             .memory_type_index(memory_type);
@@ -129,7 +155,43 @@ impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
             info = info.push_next(&mut info_flags);
         }
 
-        match self.device.allocate_memory(&info, None) {
+        let mut handle_types = vk::ExternalMemoryHandleTypeFlags::empty();
+        if flags.contains(AllocationFlags::EXPORT_OPAQUE_FD) {
+            handle_types |= vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD;
+        }
+        if flags.contains(AllocationFlags::EXPORT_OPAQUE_WIN32) {
+            handle_types |= vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32;
+        }
+
+        let mut info_export;
+
+        if !handle_types.is_empty() {
+            info_export = vk::ExportMemoryAllocateInfo::builder().handle_types(handle_types);
+            info = info.push_next(&mut info_export);
+        }
+
+        let mut info_dedicated;
+
+        if let Some(resource) = resource {
+            info_dedicated = match resource {
+                DedicatedResource::Buffer(buffer) => {
+                    vk::MemoryDedicatedAllocateInfo::builder().buffer(vk::Buffer::from_raw(buffer))
+                }
+                DedicatedResource::Image(image) => {
+                    vk::MemoryDedicatedAllocateInfo::builder().image(vk::Image::from_raw(image))
+                }
+            };
+            info = info.push_next(&mut info_dedicated);
+        }
+
+        let mut info_priority;
+
+        if let Some(priority) = priority {
+            info_priority = vk::MemoryPriorityAllocateInfoEXT::builder().priority(priority);
+            info = info.push_next(&mut info_priority);
+        }
+
+        match self.device.allocate_memory(&info, allocation_callbacks) {
             Ok(memory) => Ok(memory),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::OutOfDeviceMemory),
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::OutOfHostMemory),
@@ -138,6 +200,75 @@ impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
         }
     }
 
+    unsafe fn import(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+        allocation_callbacks: Option<&vk::AllocationCallbacks>,
+    ) -> Result<vk::DeviceMemory, DeviceImportError> {
+        let mut import_info = vk::ImportMemoryHostPointerInfoEXT::builder()
+            .handle_type(vk::ExternalMemoryHandleTypeFlags::HOST_ALLOCATION_EXT)
+            .host_pointer(ptr.as_ptr().cast());
+
+        let info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(size)
+            .memory_type_index(memory_type)
+            .push_next(&mut import_info);
+
+        match self.device.allocate_memory(&info, allocation_callbacks) {
+            Ok(memory) => Ok(memory),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                Err(DeviceImportError::OutOfDeviceMemory)
+            }
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceImportError::OutOfHostMemory),
+            Err(vk::Result::ERROR_INVALID_EXTERNAL_HANDLE) => {
+                Err(DeviceImportError::InvalidExternalHandle)
+            }
+            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => panic!("Too many objects"),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+}
+
+impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, None, None, None)
+    }
+
+    /// Allocates memory with `VkMemoryDedicatedAllocateInfo`,
+    /// which requires Vulkan 1.1 or "VK_KHR_dedicated_allocation" extension.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, Some(resource), None, None)
+    }
+
+    /// Allocates memory with `VkMemoryPriorityAllocateInfoEXT`,
+    /// which requires "VK_EXT_memory_priority" extension to be enabled on device creation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, resource, Some(priority), None)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     unsafe fn deallocate_memory(&self, memory: vk::DeviceMemory) {
         self.device.free_memory(memory, None);
@@ -219,6 +350,186 @@ impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
                 err => panic!("Unexpected Vulkan error: `{}`", err),
             })
     }
+
+    /// Imports host memory with "VK_EXT_external_memory_host" extension,
+    /// which must be enabled on device creation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<vk::DeviceMemory, DeviceImportError> {
+        self.import(ptr, size, memory_type, None)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &vk::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        match self
+            .device
+            .bind_buffer_memory(vk::Buffer::from_raw(buffer), *memory, offset)
+        {
+            Ok(()) => Ok(()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(DeviceBindError::OutOfDeviceMemory),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceBindError::OutOfHostMemory),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &vk::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        match self
+            .device
+            .bind_image_memory(vk::Image::from_raw(image), *memory, offset)
+        {
+            Ok(()) => Ok(()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(DeviceBindError::OutOfDeviceMemory),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceBindError::OutOfHostMemory),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+}
+
+/// Memory device that passes host allocation callbacks to Vulkan.
+/// Created with `AshMemoryDevice::with_allocation_callbacks`.
+pub struct AshMemoryDeviceWithCallbacks<'a> {
+    device: &'a AshMemoryDevice,
+    allocation_callbacks: &'a vk::AllocationCallbacks,
+}
+
+impl MemoryDevice<vk::DeviceMemory> for AshMemoryDeviceWithCallbacks<'_> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.device.allocate(
+            size,
+            memory_type,
+            flags,
+            None,
+            None,
+            Some(self.allocation_callbacks),
+        )
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.device.allocate(
+            size,
+            memory_type,
+            flags,
+            Some(resource),
+            None,
+            Some(self.allocation_callbacks),
+        )
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.device.allocate(
+            size,
+            memory_type,
+            flags,
+            resource,
+            Some(priority),
+            Some(self.allocation_callbacks),
+        )
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn deallocate_memory(&self, memory: vk::DeviceMemory) {
+        self.device
+            .device
+            .free_memory(memory, Some(self.allocation_callbacks));
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn map_memory(
+        &self,
+        memory: &mut vk::DeviceMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn unmap_memory(&self, memory: &mut vk::DeviceMemory) {
+        self.device.unmap_memory(memory)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, vk::DeviceMemory>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, vk::DeviceMemory>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<vk::DeviceMemory, DeviceImportError> {
+        self.device
+            .import(ptr, size, memory_type, Some(self.allocation_callbacks))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &vk::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_buffer_memory(buffer, memory, offset)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &vk::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_image_memory(image, memory, offset)
+    }
 }
 
 /// Returns `DeviceProperties` from ash's `InstanceLoader` for specified `PhysicalDevice`, required to create `GpuAllocator`.
@@ -257,7 +568,7 @@ pub unsafe fn device_properties(
 
     Ok(DeviceProperties {
         max_memory_allocation_count: limits.max_memory_allocation_count,
-        max_memory_allocation_size: u64::max_value(), // FIXME: Can query this information if instance is v1.1
+        max_memory_allocation_size: u64::MAX, // FIXME: Can query this information if instance is v1.1
         non_coherent_atom_size: limits.non_coherent_atom_size,
         memory_types: memory_properties.memory_types
             [..memory_properties.memory_type_count as usize]
@@ -275,6 +586,7 @@ pub unsafe fn device_properties(
             })
             .collect(),
         buffer_device_address,
+        buffer_image_granularity: limits.buffer_image_granularity,
     })
 }
 
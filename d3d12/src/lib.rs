@@ -0,0 +1,479 @@
+//!
+//! # Direct3D 12 backend for `gpu-alloc`
+//!
+//! Memory objects are `ID3D12Heap`s.
+//! Memory types map heap types and heap flags:
+//!
+//! | Index | Heap type                 | Heap flags                      | Properties                                      |
+//! |-------|---------------------------|---------------------------------|-------------------------------------------------|
+//! | 0     | `D3D12_HEAP_TYPE_DEFAULT` | `ALLOW_ONLY_BUFFERS`            | `DEVICE_LOCAL`                                  |
+//! | 1     | `D3D12_HEAP_TYPE_DEFAULT` | `ALLOW_ONLY_NON_RT_DS_TEXTURES` | `DEVICE_LOCAL`                                  |
+//! | 2     | `D3D12_HEAP_TYPE_DEFAULT` | `ALLOW_ONLY_RT_DS_TEXTURES`     | `DEVICE_LOCAL`                                  |
+//! | 3     | `D3D12_HEAP_TYPE_UPLOAD`  | `ALLOW_ONLY_BUFFERS`            | `HOST_VISIBLE \| HOST_COHERENT`                 |
+//! | 4     | `D3D12_HEAP_TYPE_READBACK`| `ALLOW_ONLY_BUFFERS`            | `HOST_VISIBLE \| HOST_COHERENT \| HOST_CACHED`  |
+//!
+//! Separate memory types for buffers and textures are compatible with resource heap tier 1.
+//! Use `BUFFER_MEMORY_TYPES`, `TEXTURE_MEMORY_TYPES` and `RT_DS_TEXTURE_MEMORY_TYPES`
+//! as `Request::memory_types`.
+//!
+//! Resources are placed with `ID3D12Device::CreatePlacedResource` into heap returned by
+//! `D3D12Memory::heap` at offset `MemoryBlock::offset`.
+//! Upload and readback heaps also contain buffer spanning whole heap, used for mapping.
+//! Heaps allocated with `AllocationFlags::EXPORT_OPAQUE_WIN32` are created with `D3D12_HEAP_FLAG_SHARED`.
+//!
+//! # Usage example
+//!
+//! ```ignore
+//! use {
+//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc_d3d12::{device_properties, D3D12MemoryDevice, BUFFER_MEMORY_TYPES},
+//! };
+//!
+//! unsafe fn write_data(
+//!     adapter: &winapi::shared::dxgi::IDXGIAdapter,
+//!     device: &winapi::um::d3d12::ID3D12Device,
+//! ) -> eyre::Result<()> {
+//!     let props = device_properties(adapter, device)?;
+//!
+//!     let mut allocator = GpuAllocator::new(Config::i_am_potato(), props);
+//!
+//!     let mut block = allocator.alloc(
+//!         D3D12MemoryDevice::wrap(device),
+//!         Request {
+//!             size: 10,
+//!             align_mask: 65535,
+//!             usage: UsageFlags::UPLOAD,
+//!             memory_types: BUFFER_MEMORY_TYPES,
+//!             ..Request::default()
+//!         },
+//!     )?;
+//!
+//!     block.write_bytes(D3D12MemoryDevice::wrap(device), 0, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9])?;
+//!
+//!     allocator.dealloc(D3D12MemoryDevice::wrap(device), block);
+//!     allocator.cleanup(D3D12MemoryDevice::wrap(device));
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+
+#![cfg(windows)]
+
+use {
+    gpu_alloc_types::{
+        AllocationFlags, DedicatedResource, DeviceMapError, DeviceProperties, MappedMemoryRange,
+        MemoryDevice, MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+    },
+    std::{
+        mem::{size_of, zeroed},
+        ptr::{null, null_mut, NonNull},
+    },
+    winapi::{
+        shared::{
+            dxgi::{IDXGIAdapter, DXGI_ADAPTER_DESC},
+            dxgiformat::DXGI_FORMAT_UNKNOWN,
+            dxgitype::DXGI_SAMPLE_DESC,
+            winerror::{E_OUTOFMEMORY, FAILED, HRESULT},
+        },
+        um::d3d12::*,
+        Interface as _,
+    },
+};
+
+/// Index of memory type for buffers in `D3D12_HEAP_TYPE_DEFAULT` heaps.
+pub const DEFAULT_BUFFER_MEMORY_TYPE: u32 = 0;
+
+/// Index of memory type for textures other than render targets and depth-stencil
+/// in `D3D12_HEAP_TYPE_DEFAULT` heaps.
+pub const DEFAULT_TEXTURE_MEMORY_TYPE: u32 = 1;
+
+/// Index of memory type for render target and depth-stencil textures
+/// in `D3D12_HEAP_TYPE_DEFAULT` heaps.
+pub const DEFAULT_RT_DS_TEXTURE_MEMORY_TYPE: u32 = 2;
+
+/// Index of memory type for buffers in `D3D12_HEAP_TYPE_UPLOAD` heaps.
+pub const UPLOAD_MEMORY_TYPE: u32 = 3;
+
+/// Index of memory type for buffers in `D3D12_HEAP_TYPE_READBACK` heaps.
+pub const READBACK_MEMORY_TYPE: u32 = 4;
+
+/// Mask of memory types that can contain buffers.
+pub const BUFFER_MEMORY_TYPES: u32 =
+    1 << DEFAULT_BUFFER_MEMORY_TYPE | 1 << UPLOAD_MEMORY_TYPE | 1 << READBACK_MEMORY_TYPE;
+
+/// Mask of memory types that can contain textures
+/// other than render targets and depth-stencil.
+pub const TEXTURE_MEMORY_TYPES: u32 = 1 << DEFAULT_TEXTURE_MEMORY_TYPE;
+
+/// Mask of memory types that can contain render target and depth-stencil textures.
+pub const RT_DS_TEXTURE_MEMORY_TYPES: u32 = 1 << DEFAULT_RT_DS_TEXTURE_MEMORY_TYPE;
+
+/// Memory object allocated by `D3D12MemoryDevice`.
+#[derive(Debug)]
+pub struct D3D12Memory {
+    heap: NonNull<ID3D12Heap>,
+    buffer: Option<NonNull<ID3D12Resource>>,
+}
+
+// Direct3D 12 objects are free-threaded.
+unsafe impl Send for D3D12Memory {}
+unsafe impl Sync for D3D12Memory {}
+
+impl D3D12Memory {
+    /// Returns heap to place resources into.
+    pub fn heap(&self) -> NonNull<ID3D12Heap> {
+        self.heap
+    }
+
+    /// Returns buffer spanning whole heap.
+    /// Available for upload and readback memory types only.
+    pub fn buffer(&self) -> Option<NonNull<ID3D12Resource>> {
+        self.buffer
+    }
+}
+
+/// Memory device over Direct3D 12 device.
+#[repr(transparent)]
+pub struct D3D12MemoryDevice {
+    device: ID3D12Device,
+}
+
+impl D3D12MemoryDevice {
+    pub fn wrap(device: &ID3D12Device) -> &Self {
+        unsafe {
+            // Safe because `Self` is `repr(transparent)`
+            // with only field being `ID3D12Device`.
+            &*(device as *const ID3D12Device as *const Self)
+        }
+    }
+}
+
+/// Returns heap type and heap flags of memory type with specified index.
+pub fn memory_type_heap(memory_type: u32) -> (D3D12_HEAP_TYPE, D3D12_HEAP_FLAGS) {
+    match memory_type {
+        DEFAULT_BUFFER_MEMORY_TYPE => (D3D12_HEAP_TYPE_DEFAULT, D3D12_HEAP_FLAG_ALLOW_ONLY_BUFFERS),
+        DEFAULT_TEXTURE_MEMORY_TYPE => (
+            D3D12_HEAP_TYPE_DEFAULT,
+            D3D12_HEAP_FLAG_ALLOW_ONLY_NON_RT_DS_TEXTURES,
+        ),
+        DEFAULT_RT_DS_TEXTURE_MEMORY_TYPE => (
+            D3D12_HEAP_TYPE_DEFAULT,
+            D3D12_HEAP_FLAG_ALLOW_ONLY_RT_DS_TEXTURES,
+        ),
+        UPLOAD_MEMORY_TYPE => (D3D12_HEAP_TYPE_UPLOAD, D3D12_HEAP_FLAG_ALLOW_ONLY_BUFFERS),
+        READBACK_MEMORY_TYPE => (D3D12_HEAP_TYPE_READBACK, D3D12_HEAP_FLAG_ALLOW_ONLY_BUFFERS),
+        _ => panic!("Invalid memory type index: `{}`", memory_type),
+    }
+}
+
+/// Returns memory properties of heap type.
+/// `uma` must be set for devices with unified memory architecture.
+pub fn memory_properties_from_heap_type(
+    heap_type: D3D12_HEAP_TYPE,
+    uma: bool,
+) -> MemoryPropertyFlags {
+    let device_local = if uma {
+        MemoryPropertyFlags::DEVICE_LOCAL
+    } else {
+        MemoryPropertyFlags::empty()
+    };
+
+    match heap_type {
+        D3D12_HEAP_TYPE_DEFAULT => MemoryPropertyFlags::DEVICE_LOCAL,
+        D3D12_HEAP_TYPE_UPLOAD => {
+            device_local | MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT
+        }
+        D3D12_HEAP_TYPE_READBACK => {
+            device_local
+                | MemoryPropertyFlags::HOST_VISIBLE
+                | MemoryPropertyFlags::HOST_COHERENT
+                | MemoryPropertyFlags::HOST_CACHED
+        }
+        _ => panic!("Unexpected heap type: `{}`", heap_type),
+    }
+}
+
+fn oom_from_hresult(hr: HRESULT) -> OutOfMemory {
+    match hr {
+        // Direct3D 12 reports exhaustion of both device and host memory this way.
+        E_OUTOFMEMORY => OutOfMemory::OutOfDeviceMemory,
+        hr => panic!("Unexpected Direct3D 12 error: `{:#x}`", hr),
+    }
+}
+
+impl MemoryDevice<D3D12Memory> for D3D12MemoryDevice {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<D3D12Memory, OutOfMemory> {
+        assert!(
+            (flags & !AllocationFlags::EXPORT_OPAQUE_WIN32).is_empty(),
+            "Direct3D 12 backend supports only `EXPORT_OPAQUE_WIN32` allocation flag"
+        );
+
+        let (heap_type, mut heap_flags) = memory_type_heap(memory_type);
+
+        // Shared heap handle is created with `ID3D12Device::CreateSharedHandle`.
+        if flags.contains(AllocationFlags::EXPORT_OPAQUE_WIN32) {
+            assert_eq!(
+                heap_type, D3D12_HEAP_TYPE_DEFAULT,
+                "Only heaps without CPU access can be shared"
+            );
+            heap_flags |= D3D12_HEAP_FLAG_SHARED;
+        }
+
+        // Heap size must be multiple of its alignment.
+        let align_mask = D3D12_DEFAULT_RESOURCE_PLACEMENT_ALIGNMENT as u64 - 1;
+        let size = size
+            .checked_add(align_mask)
+            .ok_or(OutOfMemory::OutOfDeviceMemory)?
+            & !align_mask;
+
+        let desc = D3D12_HEAP_DESC {
+            SizeInBytes: size,
+            Properties: D3D12_HEAP_PROPERTIES {
+                Type: heap_type,
+                CPUPageProperty: D3D12_CPU_PAGE_PROPERTY_UNKNOWN,
+                MemoryPoolPreference: D3D12_MEMORY_POOL_UNKNOWN,
+                CreationNodeMask: 0,
+                VisibleNodeMask: 0,
+            },
+            Alignment: align_mask + 1,
+            Flags: heap_flags,
+        };
+
+        let mut heap = null_mut::<ID3D12Heap>();
+        let hr =
+            self.device
+                .CreateHeap(&desc, &ID3D12Heap::uuidof(), &mut heap as *mut _ as *mut _);
+        if FAILED(hr) {
+            return Err(oom_from_hresult(hr));
+        }
+        let heap = NonNull::new(heap).expect("Heap must not be null");
+
+        let initial_state = match heap_type {
+            D3D12_HEAP_TYPE_UPLOAD => D3D12_RESOURCE_STATE_GENERIC_READ,
+            D3D12_HEAP_TYPE_READBACK => D3D12_RESOURCE_STATE_COPY_DEST,
+            _ => return Ok(D3D12Memory { heap, buffer: None }),
+        };
+
+        let buffer_desc = D3D12_RESOURCE_DESC {
+            Dimension: D3D12_RESOURCE_DIMENSION_BUFFER,
+            Alignment: 0,
+            Width: size,
+            Height: 1,
+            DepthOrArraySize: 1,
+            MipLevels: 1,
+            Format: DXGI_FORMAT_UNKNOWN,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Layout: D3D12_TEXTURE_LAYOUT_ROW_MAJOR,
+            Flags: D3D12_RESOURCE_FLAG_NONE,
+        };
+
+        let mut buffer = null_mut::<ID3D12Resource>();
+        let hr = self.device.CreatePlacedResource(
+            heap.as_ptr(),
+            0,
+            &buffer_desc,
+            initial_state,
+            null(),
+            &ID3D12Resource::uuidof(),
+            &mut buffer as *mut _ as *mut _,
+        );
+        if FAILED(hr) {
+            heap.as_ref().Release();
+            return Err(oom_from_hresult(hr));
+        }
+
+        Ok(D3D12Memory {
+            heap,
+            buffer: Some(NonNull::new(buffer).expect("Resource must not be null")),
+        })
+    }
+
+    /// Allocates heap and sets its residency priority with `ID3D12Device1::SetResidencyPriority`.
+    /// Priority is mapped linearly from `D3D12_RESIDENCY_PRIORITY_MINIMUM`
+    /// to `D3D12_RESIDENCY_PRIORITY_MAXIMUM`, and ignored if `ID3D12Device1` is not available.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        _resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<D3D12Memory, OutOfMemory> {
+        let memory = self.allocate_memory(size, memory_type, flags)?;
+
+        let mut device1 = null_mut::<ID3D12Device1>();
+        let hr = self
+            .device
+            .QueryInterface(&ID3D12Device1::uuidof(), &mut device1 as *mut _ as *mut _);
+        if FAILED(hr) {
+            return Ok(memory);
+        }
+        let device1 = NonNull::new(device1).expect("Device must not be null");
+
+        let range = (D3D12_RESIDENCY_PRIORITY_MAXIMUM - D3D12_RESIDENCY_PRIORITY_MINIMUM) as f32;
+        let priority = D3D12_RESIDENCY_PRIORITY_MINIMUM + (range * priority) as u32;
+        let pageable = memory.heap.as_ptr() as *mut ID3D12Pageable;
+
+        // Setting priority may fail only if device was removed.
+        device1
+            .as_ref()
+            .SetResidencyPriority(1, &pageable, &priority);
+        device1.as_ref().Release();
+
+        Ok(memory)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn deallocate_memory(&self, memory: D3D12Memory) {
+        if let Some(buffer) = memory.buffer {
+            buffer.as_ref().Release();
+        }
+        memory.heap.as_ref().Release();
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn map_memory(
+        &self,
+        memory: &mut D3D12Memory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        let buffer = memory.buffer.ok_or(DeviceMapError::MapFailed)?;
+
+        // Whole buffer is mapped and may be read back by the host.
+        let mut ptr = null_mut();
+        let hr = buffer.as_ref().Map(0, null(), &mut ptr);
+        if FAILED(hr) {
+            return Err(match hr {
+                E_OUTOFMEMORY => DeviceMapError::OutOfHostMemory,
+                _ => DeviceMapError::MapFailed,
+            });
+        }
+
+        let _ = size;
+        Ok(NonNull::new((ptr as *mut u8).add(offset as usize))
+            .expect("Pointer to memory mapping must not be null"))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn unmap_memory(&self, memory: &mut D3D12Memory) {
+        if let Some(buffer) = memory.buffer {
+            // Whole buffer may be written by the host.
+            buffer.as_ref().Unmap(0, null());
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, D3D12Memory>],
+    ) -> Result<(), OutOfMemory> {
+        // Upload and readback heaps are coherent.
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn flush_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, D3D12Memory>],
+    ) -> Result<(), OutOfMemory> {
+        // Upload and readback heaps are coherent.
+        Ok(())
+    }
+}
+
+/// Returns `DeviceProperties` for specified device, required to create `GpuAllocator`.
+///
+/// Memory types are indexed as described in crate documentation.
+/// On devices with unified memory architecture there is single heap,
+/// otherwise default heaps use heap 0 sized by dedicated video memory,
+/// and upload and readback heaps use heap 1 sized by shared system memory.
+///
+/// # Safety
+///
+/// `device` must be created from `adapter`.
+pub unsafe fn device_properties(
+    adapter: &IDXGIAdapter,
+    device: &ID3D12Device,
+) -> Result<DeviceProperties<'static>, HRESULT> {
+    let mut desc: DXGI_ADAPTER_DESC = zeroed();
+    let hr = adapter.GetDesc(&mut desc);
+    if FAILED(hr) {
+        return Err(hr);
+    }
+
+    let mut architecture = D3D12_FEATURE_DATA_ARCHITECTURE {
+        NodeIndex: 0,
+        TileBasedRenderer: 0,
+        UMA: 0,
+        CacheCoherentUMA: 0,
+    };
+    let hr = device.CheckFeatureSupport(
+        D3D12_FEATURE_ARCHITECTURE,
+        &mut architecture as *mut _ as *mut _,
+        size_of::<D3D12_FEATURE_DATA_ARCHITECTURE>() as u32,
+    );
+    if FAILED(hr) {
+        return Err(hr);
+    }
+
+    let uma = architecture.UMA != 0;
+
+    let (host_heap, memory_heaps) = if uma {
+        (
+            0,
+            vec![MemoryHeap {
+                size: desc.DedicatedVideoMemory as u64 + desc.SharedSystemMemory as u64,
+            }],
+        )
+    } else {
+        (
+            1,
+            vec![
+                MemoryHeap {
+                    size: desc.DedicatedVideoMemory as u64,
+                },
+                MemoryHeap {
+                    size: desc.SharedSystemMemory as u64,
+                },
+            ],
+        )
+    };
+
+    let memory_types = (0..=READBACK_MEMORY_TYPE)
+        .map(|index| {
+            let (heap_type, _) = memory_type_heap(index);
+            MemoryType {
+                heap: if heap_type == D3D12_HEAP_TYPE_DEFAULT {
+                    0
+                } else {
+                    host_heap
+                },
+                props: memory_properties_from_heap_type(heap_type, uma),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(DeviceProperties {
+        memory_types: memory_types.into(),
+        memory_heaps: memory_heaps.into(),
+        // Direct3D 12 does not limit number of heaps.
+        max_memory_allocation_count: u32::MAX,
+        max_memory_allocation_size: u64::MAX,
+        non_coherent_atom_size: 1,
+        buffer_device_address: false,
+        // Buffers and textures never share heap of the same memory type.
+        buffer_image_granularity: 1,
+    })
+}
@@ -6,7 +6,9 @@
 //! ```ignore
 //! use {
 //!     erupt::{vk1_0, DeviceLoader, EntryLoader, InstanceLoader},
-//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc::{
+//!         AllocationFlags, Config, GpuAllocator, Request, RequestFlags, ResourceKind, UsageFlags,
+//!     },
 //!     gpu_alloc_erupt::{device_properties, EruptMemoryDevice},
 //!     std::ffi::CStr,
 //! };
@@ -64,6 +66,15 @@
 //!                 align_mask: 1,
 //!                 usage: UsageFlags::HOST_ACCESS,
 //!                 memory_types: !0,
+//!                 memory_type: None,
+//!                 access: None,
+//!                 label: None,
+//!                 dedicated: None,
+//!                 dedicated_resource: None,
+//!                 export: AllocationFlags::empty(),
+//!                 priority: None,
+//!                 flags: RequestFlags::empty(),
+//!                 resource_kind: ResourceKind::Linear,
 //!             },
 //!         )
 //!     }?;
@@ -84,10 +95,17 @@
 //!
 
 use {
-    erupt::{vk1_0, vk1_1, DeviceLoader, ExtendableFromConst, InstanceLoader},
+    erupt::{
+        extensions::{
+            ext_external_memory_host::ImportMemoryHostPointerInfoEXTBuilder,
+            ext_memory_priority::MemoryPriorityAllocateInfoEXTBuilder,
+        },
+        vk1_0, vk1_1, DeviceLoader, ExtendableFromConst, InstanceLoader,
+    },
     gpu_alloc_types::{
-        AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
-        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        AllocationFlags, DedicatedResource, DeviceBindError, DeviceImportError, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, OutOfMemory,
     },
     std::ptr::NonNull,
     tinyvec::TinyVec,
@@ -106,18 +124,31 @@ impl EruptMemoryDevice {
             &*(device as *const DeviceLoader as *const Self)
         }
     }
-}
 
-impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
-    unsafe fn allocate_memory(
+    /// Wraps device passing `allocation_callbacks` to `vkAllocateMemory` and `vkFreeMemory`,
+    /// so that host allocations driver makes for memory objects are reported to them.
+    ///
+    /// Memory objects must be freed with callbacks compatible with ones they were allocated with,
+    /// so same callbacks must be used for all interactions with `GpuAllocator`.
+    pub fn with_allocation_callbacks<'a>(
+        device: &'a DeviceLoader,
+        allocation_callbacks: &'a vk1_0::AllocationCallbacks,
+    ) -> EruptMemoryDeviceWithCallbacks<'a> {
+        EruptMemoryDeviceWithCallbacks {
+            device: EruptMemoryDevice::wrap(device),
+            allocation_callbacks,
+        }
+    }
+
+    unsafe fn allocate(
         &self,
         size: u64,
         memory_type: u32,
         flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: Option<f32>,
+        allocation_callbacks: Option<&vk1_0::AllocationCallbacks>,
     ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
-        assert!((flags & !(AllocationFlags::DEVICE_ADDRESS)).is_empty());
-
         let mut info = vk1_0::MemoryAllocateInfoBuilder::new()
             .allocation_size(size)
             .memory_type_index(memory_type);
@@ -130,7 +161,47 @@ impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
             info = info.extend_from(&info_flags);
         }
 
-        match self.device.allocate_memory(&info, None).result() {
+        let mut handle_types = vk1_1::ExternalMemoryHandleTypeFlags::empty();
+        if flags.contains(AllocationFlags::EXPORT_OPAQUE_FD) {
+            handle_types |= vk1_1::ExternalMemoryHandleTypeFlags::OPAQUE_FD;
+        }
+        if flags.contains(AllocationFlags::EXPORT_OPAQUE_WIN32) {
+            handle_types |= vk1_1::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32;
+        }
+
+        let info_export;
+
+        if !handle_types.is_empty() {
+            info_export = vk1_1::ExportMemoryAllocateInfoBuilder::new().handle_types(handle_types);
+            info = info.extend_from(&info_export);
+        }
+
+        let info_dedicated;
+
+        if let Some(resource) = resource {
+            info_dedicated = match resource {
+                DedicatedResource::Buffer(buffer) => {
+                    vk1_1::MemoryDedicatedAllocateInfoBuilder::new().buffer(vk1_0::Buffer(buffer))
+                }
+                DedicatedResource::Image(image) => {
+                    vk1_1::MemoryDedicatedAllocateInfoBuilder::new().image(vk1_0::Image(image))
+                }
+            };
+            info = info.extend_from(&info_dedicated);
+        }
+
+        let info_priority;
+
+        if let Some(priority) = priority {
+            info_priority = MemoryPriorityAllocateInfoEXTBuilder::new().priority(priority);
+            info = info.extend_from(&info_priority);
+        }
+
+        match self
+            .device
+            .allocate_memory(&info, allocation_callbacks)
+            .result()
+        {
             Ok(memory) => Ok(memory),
             Err(vk1_0::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::OutOfDeviceMemory),
             Err(vk1_0::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::OutOfHostMemory),
@@ -139,6 +210,79 @@ impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
         }
     }
 
+    unsafe fn import(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+        allocation_callbacks: Option<&vk1_0::AllocationCallbacks>,
+    ) -> Result<vk1_0::DeviceMemory, DeviceImportError> {
+        let import_info = ImportMemoryHostPointerInfoEXTBuilder::new()
+            .handle_type(vk1_1::ExternalMemoryHandleTypeFlagBits::HOST_ALLOCATION_EXT)
+            .host_pointer(ptr.as_ptr().cast());
+
+        let info = vk1_0::MemoryAllocateInfoBuilder::new()
+            .allocation_size(size)
+            .memory_type_index(memory_type)
+            .extend_from(&import_info);
+
+        match self
+            .device
+            .allocate_memory(&info, allocation_callbacks)
+            .result()
+        {
+            Ok(memory) => Ok(memory),
+            Err(vk1_0::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                Err(DeviceImportError::OutOfDeviceMemory)
+            }
+            Err(vk1_0::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceImportError::OutOfHostMemory),
+            Err(vk1_0::Result::ERROR_INVALID_EXTERNAL_HANDLE) => {
+                Err(DeviceImportError::InvalidExternalHandle)
+            }
+            Err(vk1_0::Result::ERROR_TOO_MANY_OBJECTS) => panic!("Too many objects"),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+}
+
+impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, None, None, None)
+    }
+
+    /// Allocates memory with `VkMemoryDedicatedAllocateInfo`,
+    /// which requires Vulkan 1.1 or "VK_KHR_dedicated_allocation" extension.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, Some(resource), None, None)
+    }
+
+    /// Allocates memory with `VkMemoryPriorityAllocateInfoEXT`,
+    /// which requires "VK_EXT_memory_priority" extension to be enabled on device creation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, resource, Some(priority), None)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     unsafe fn deallocate_memory(&self, memory: vk1_0::DeviceMemory) {
         self.device.free_memory(Some(memory), None);
@@ -225,6 +369,192 @@ impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
                 err => panic!("Unexpected Vulkan error: `{}`", err),
             })
     }
+
+    /// Imports host memory with "VK_EXT_external_memory_host" extension,
+    /// which must be enabled on device creation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<vk1_0::DeviceMemory, DeviceImportError> {
+        self.import(ptr, size, memory_type, None)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &vk1_0::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        match self
+            .device
+            .bind_buffer_memory(vk1_0::Buffer(buffer), *memory, offset)
+            .result()
+        {
+            Ok(()) => Ok(()),
+            Err(vk1_0::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                Err(DeviceBindError::OutOfDeviceMemory)
+            }
+            Err(vk1_0::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceBindError::OutOfHostMemory),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &vk1_0::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        match self
+            .device
+            .bind_image_memory(vk1_0::Image(image), *memory, offset)
+            .result()
+        {
+            Ok(()) => Ok(()),
+            Err(vk1_0::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                Err(DeviceBindError::OutOfDeviceMemory)
+            }
+            Err(vk1_0::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceBindError::OutOfHostMemory),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+}
+
+/// Memory device that passes host allocation callbacks to Vulkan.
+/// Created with `EruptMemoryDevice::with_allocation_callbacks`.
+pub struct EruptMemoryDeviceWithCallbacks<'a> {
+    device: &'a EruptMemoryDevice,
+    allocation_callbacks: &'a vk1_0::AllocationCallbacks,
+}
+
+impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDeviceWithCallbacks<'_> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.device.allocate(
+            size,
+            memory_type,
+            flags,
+            None,
+            None,
+            Some(self.allocation_callbacks),
+        )
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.device.allocate(
+            size,
+            memory_type,
+            flags,
+            Some(resource),
+            None,
+            Some(self.allocation_callbacks),
+        )
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.device.allocate(
+            size,
+            memory_type,
+            flags,
+            resource,
+            Some(priority),
+            Some(self.allocation_callbacks),
+        )
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn deallocate_memory(&self, memory: vk1_0::DeviceMemory) {
+        self.device
+            .device
+            .free_memory(Some(memory), Some(self.allocation_callbacks));
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn map_memory(
+        &self,
+        memory: &mut vk1_0::DeviceMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn unmap_memory(&self, memory: &mut vk1_0::DeviceMemory) {
+        self.device.unmap_memory(memory)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, vk1_0::DeviceMemory>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, vk1_0::DeviceMemory>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<vk1_0::DeviceMemory, DeviceImportError> {
+        self.device
+            .import(ptr, size, memory_type, Some(self.allocation_callbacks))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &vk1_0::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_buffer_memory(buffer, memory, offset)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &vk1_0::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_image_memory(image, memory, offset)
+    }
 }
 
 /// Returns `DeviceProperties` from erupt's `InstanceLoader` for specified `PhysicalDevice`, required to create `GpuAllocator`.
@@ -294,7 +624,7 @@ pub unsafe fn device_properties(
 
     Ok(DeviceProperties {
         max_memory_allocation_count: limits.max_memory_allocation_count,
-        max_memory_allocation_size: u64::max_value(), // FIXME: Can query this information if instance is v1.1
+        max_memory_allocation_size: u64::MAX, // FIXME: Can query this information if instance is v1.1
         non_coherent_atom_size: limits.non_coherent_atom_size,
         memory_types: memory_properties.memory_types
             [..memory_properties.memory_type_count as usize]
@@ -312,6 +642,7 @@ pub unsafe fn device_properties(
             })
             .collect(),
         buffer_device_address,
+        buffer_image_granularity: limits.buffer_image_granularity,
     })
 }
 
@@ -0,0 +1,79 @@
+use {
+    gpu_alloc::{
+        AccessPattern, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    const CACHED: u32 = 0;
+    const COHERENT: u32 = 1;
+
+    // No memory type is both cached and coherent.
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Owned(vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_CACHED,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = |access| Request {
+        size: 256,
+        access: Some(access),
+        ..Request::default()
+    };
+
+    let upload = unsafe { allocator.alloc(&device, request(AccessPattern::SequentialWrite)) }?;
+    assert_eq!(upload.memory_type(), COHERENT);
+    assert_eq!(upload.access(), Some(AccessPattern::SequentialWrite));
+
+    // Readback prefers cached memory even if it requires invalidation.
+    let mut readback = unsafe { allocator.alloc(&device, request(AccessPattern::Readback)) }?;
+    assert_eq!(readback.memory_type(), CACHED);
+
+    let mut data = [1; 16];
+    unsafe { readback.read_bytes(&device, 0, &mut data) }?;
+    assert_eq!(data, [0; 16]);
+
+    let random = unsafe { allocator.alloc(&device, request(AccessPattern::RandomReadWrite)) }?;
+    assert_eq!(random.memory_type(), CACHED);
+
+    // Fallback to the only available type.
+    let coherent_only = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                memory_types: 1 << COHERENT,
+                ..request(AccessPattern::Readback)
+            },
+        )
+    }?;
+    assert_eq!(coherent_only.memory_type(), COHERENT);
+
+    unsafe {
+        allocator.dealloc(&device, upload);
+        allocator.dealloc(&device, readback);
+        allocator.dealloc(&device, random);
+        allocator.dealloc(&device, coherent_only);
+    }
+
+    Ok(())
+}
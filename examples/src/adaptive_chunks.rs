@@ -0,0 +1,84 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, RequestFlags, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 1024 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.adaptive_chunk_size = true;
+
+    let initial_buddy_dedicated_size = config.initial_buddy_dedicated_size;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    // Without statistics configured size is recommended.
+    assert_eq!(
+        allocator.recommended_chunk_size(0),
+        initial_buddy_dedicated_size
+    );
+
+    let request = |size| Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        memory_type: None,
+        access: None,
+        label: None,
+        dedicated: None,
+        dedicated_resource: None,
+        export: AllocationFlags::empty(),
+        priority: None,
+        flags: RequestFlags::empty(),
+        resource_kind: ResourceKind::Linear,
+    };
+
+    // Chunk sized for small requests holds eight of them.
+    let mut blocks = Vec::new();
+    for _ in 0..8 {
+        blocks.push(unsafe { allocator.alloc(&device, request(64)) }?);
+    }
+    assert_eq!(allocator.recommended_chunk_size(0), 512);
+    assert_eq!(device.total_allocations(), 1);
+
+    for _ in 8..32 {
+        blocks.push(unsafe { allocator.alloc(&device, request(64)) }?);
+    }
+    assert_eq!(device.total_allocations(), 4);
+
+    // Request larger than recommended size still gets chunk that fits it.
+    let large = unsafe { allocator.alloc(&device, request(2048)) }?;
+    assert_eq!(allocator.recommended_chunk_size(0), 512);
+    assert_eq!(large.size(), 2048);
+    assert_eq!(device.total_allocations(), 5);
+
+    unsafe {
+        allocator.dealloc(&device, large);
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+    }
+
+    assert_eq!(device.total_deallocations(), 5);
+    Ok(())
+}
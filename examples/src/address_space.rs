@@ -0,0 +1,80 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DeviceMapError, DeviceProperties, GpuAllocator,
+        MemoryHeap, MemoryPropertyFlags, MemoryType, Request, RequestFlags, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let transient = Request {
+        size: 1024,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD | UsageFlags::TRANSIENT,
+        ..Request::default()
+    };
+
+    let mapped = Request {
+        size: 1024,
+        align_mask: 255,
+        dedicated: Some(Dedicated::Required),
+        flags: RequestFlags::PERSISTENTLY_MAPPED,
+        ..Request::default()
+    };
+
+    // Free-list allocator keeps its last memory object mapped when all blocks are freed.
+    let block = unsafe { allocator.alloc(&device, transient) }?;
+    unsafe { allocator.dealloc(&device, block) };
+    assert_eq!(device.live_allocations(), 1);
+    assert_eq!(device.live_mappings(), 1);
+
+    // Idle memory object is released to free address space, then mapping is retried.
+    device.fail_map(2, DeviceMapError::MapFailed);
+    let block = unsafe { allocator.alloc(&device, mapped) }?;
+    assert!(block.mapped_ptr().is_some());
+    assert_eq!(device.live_allocations(), 1);
+    assert_eq!(device.live_mappings(), 1);
+
+    // No retry without idle memory objects to release.
+    device.fail_map(4, DeviceMapError::MapFailed);
+    assert_eq!(
+        unsafe { allocator.alloc(&device, mapped) }.err(),
+        Some(AllocationError::AddressSpaceExhausted)
+    );
+    assert_eq!(device.live_allocations(), 1);
+
+    // Host memory exhaustion is reported as is.
+    device.fail_map(5, DeviceMapError::OutOfHostMemory);
+    assert_eq!(
+        unsafe { allocator.alloc(&device, mapped) }.err(),
+        Some(AllocationError::OutOfHostMemory)
+    );
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = |size, align_mask, label| Request {
+        size,
+        align_mask,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 0b01,
+        label: Some(label),
+        ..Request::default()
+    };
+
+    // Render targets of different passes share one dedicated memory object.
+    let targets = unsafe {
+        allocator.alloc_aliased(
+            &device,
+            &[
+                request(4 * 1024 * 1024, 255, "shadow"),
+                request(8 * 1024 * 1024, 4095, "gbuffer"),
+                request(2 * 1024 * 1024, 1023, "bloom"),
+            ],
+        )
+    }?;
+    assert_eq!(device.total_allocations(), 1);
+    assert!(targets
+        .iter()
+        .all(|block| block.memory() == targets[0].memory() && block.offset() == 0));
+    assert_eq!(
+        targets.iter().map(|block| block.size()).collect::<Vec<_>>(),
+        [4 * 1024 * 1024, 8 * 1024 * 1024, 2 * 1024 * 1024],
+    );
+    assert!(targets.iter().all(|block| allocator.is_current(block)));
+
+    // Shared memory counts once, while leak check sees every block.
+    let stats = allocator.stats().total();
+    assert_eq!(stats.block_count, 1);
+    assert_eq!(stats.user_bytes, 8 * 1024 * 1024);
+    let leaks = allocator.report_leaks();
+    assert_eq!(
+        leaks.iter().map(|leak| leak.label).collect::<Vec<_>>(),
+        [Some("shadow"), Some("gbuffer"), Some("bloom")],
+    );
+
+    // Small aliases are suballocated, host-visible ones are mapped through any of them.
+    let mut staging = unsafe {
+        allocator.alloc_aliased(
+            &device,
+            &[
+                Request {
+                    size: 256,
+                    usage: UsageFlags::UPLOAD,
+                    ..Request::default()
+                },
+                Request {
+                    size: 128,
+                    usage: UsageFlags::DOWNLOAD,
+                    ..Request::default()
+                },
+            ],
+        )
+    }?;
+    assert_eq!(staging[0].memory_type(), 1);
+    assert_eq!(staging[0].memory(), staging[1].memory());
+    assert_eq!(staging[0].offset(), staging[1].offset());
+
+    let mut read = [0; 4];
+    unsafe {
+        staging[0].write_bytes(&device, 0, &[1, 2, 3, 4])?;
+        staging[1].read_bytes(&device, 0, &mut read)?;
+    }
+    assert_eq!(read, [1, 2, 3, 4]);
+    assert_eq!(allocator.report_leaks().len(), 5);
+
+    // Memory is kept until last block of the group is deallocated.
+    let mut targets = targets.into_iter();
+    let shadow = targets.next().unwrap();
+    unsafe {
+        allocator.dealloc(&device, shadow);
+        for block in targets {
+            assert_eq!(device.live_allocations(), 2);
+            allocator.dealloc(&device, block);
+        }
+    }
+    assert_eq!(device.live_allocations(), 1);
+    assert_eq!(allocator.report_leaks().len(), 2);
+
+    unsafe {
+        for block in staging.drain(..) {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert!(allocator.report_leaks().is_empty());
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, PlannedAllocation, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Sizes of buffers of a scene, smallest first.
+    let requests: Vec<_> = [256, 512, 1024, 2048, 256, 512, 1024, 2048]
+        .iter()
+        .map(|&size| Request {
+            size,
+            align_mask: 255,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            ..Request::default()
+        })
+        .collect();
+
+    let blocks = unsafe { allocator.alloc_many(&device, &requests) }?;
+
+    // Blocks come in order of requests.
+    assert_eq!(blocks.len(), requests.len());
+    for (block, request) in blocks.iter().zip(&requests) {
+        assert_eq!(block.size(), request.size);
+    }
+
+    // Whole batch fits into single memory object.
+    assert_eq!(device.live_allocations(), 1);
+
+    // Batch that can't be served leaves nothing allocated.
+    let mut failing = requests.clone();
+    failing.push(Request {
+        size: 128,
+        memory_types: 0,
+        ..requests[0]
+    });
+    assert_eq!(
+        unsafe { allocator.alloc_many(&device, &failing) }.unwrap_err(),
+        AllocationError::NoCompatibleMemoryTypes
+    );
+    assert_eq!(device.live_allocations(), 1);
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+
+    chunks_up_front()
+}
+
+/// Memory objects planned for the batch are allocated up front and claimed by its blocks.
+fn chunks_up_front() -> eyre::Result<()> {
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Scene buffers and staging buffers of different sizes, interleaved.
+    let requests: Vec<_> = (0..48u64)
+        .map(|index| Request {
+            size: 512 << (index % 4),
+            align_mask: 255,
+            usage: if index % 2 == 0 {
+                UsageFlags::FAST_DEVICE_ACCESS
+            } else {
+                UsageFlags::UPLOAD
+            },
+            ..Request::default()
+        })
+        .collect();
+
+    let mut sorted = requests.clone();
+    sorted.sort_by_key(|request| std::cmp::Reverse(request.size));
+    let plan = allocator.plan(&sorted);
+    let planned = plan
+        .allocations
+        .iter()
+        .filter(|planned| matches!(planned, PlannedAllocation::NewMemory { .. }))
+        .count();
+    assert!(planned > 2);
+
+    let blocks = unsafe { allocator.alloc_many(&device, &requests) }?;
+    for (block, request) in blocks.iter().zip(&requests) {
+        assert_eq!(block.size(), request.size);
+    }
+
+    // Every memory object allocated up front is claimed by blocks of the batch.
+    assert_eq!(device.live_allocations(), planned);
+    unsafe { allocator.trim(&device) };
+    assert_eq!(device.live_allocations(), planned);
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryBlock, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+#[inline(never)]
+fn load_mesh(
+    allocator: &mut GpuAllocator<usize>,
+    device: &MockMemoryDevice,
+) -> eyre::Result<MemoryBlock<usize>> {
+    let block = unsafe {
+        allocator.alloc(
+            device,
+            Request {
+                size: 1024,
+                ..Request::default()
+            },
+        )
+    }?;
+    Ok(block)
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let mesh = load_mesh(&mut allocator, &device)?;
+
+    // Unlabeled leak is traced back to code that allocated it.
+    let leaks = allocator.report_leaks();
+    assert_eq!(leaks.len(), 1);
+    let backtrace = leaks[0].backtrace.to_string();
+    assert!(
+        backtrace.contains("load_mesh"),
+        "Backtrace doesn't point to allocation site:\n{}",
+        backtrace
+    );
+
+    let live = allocator.report_live_allocations();
+    assert_eq!(live[0].backtrace, leaks[0].backtrace);
+
+    unsafe {
+        allocator.dealloc(&device, mesh);
+        allocator.cleanup(&device);
+    }
+    assert!(allocator.report_leaks().is_empty());
+
+    println!("Leaked block was allocated at:\n{}", backtrace);
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use {
+    gpu_alloc::{
+        AllocationCounters, Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, sync::Arc},
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let counters = Arc::new(AllocationCounters::new(device.props().memory_types.len()));
+
+    let mut config = Config::i_am_potato();
+    config.observer = Some(counters.clone());
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let mut blocks = Vec::new();
+    for _ in 0..8 {
+        let request = Request {
+            size: 1024,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            ..Request::default()
+        };
+        blocks.push(unsafe { allocator.alloc(&device, request)? });
+    }
+    let dedicated = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                size: 4096,
+                usage: UsageFlags::UPLOAD,
+                ..Request::default()
+            },
+            Dedicated::Required,
+        )?
+    };
+
+    // Counters match allocator state and can be published as gauges per memory type.
+    let stats = allocator.stats();
+    for memory_type in 0..counters.memory_type_count() as u32 {
+        let type_stats = &stats.memory_types[memory_type as usize];
+        let live_bytes: u64 = blocks
+            .iter()
+            .chain(Some(&dedicated))
+            .filter(|block| block.memory_type() == memory_type)
+            .map(|block| block.size())
+            .sum();
+        assert_eq!(counters.bytes_allocated(memory_type), live_bytes);
+        assert_eq!(
+            counters.device_allocations(memory_type),
+            type_stats.total().memory_objects
+        );
+    }
+    assert_eq!(counters.block_count(0), 8);
+    assert_eq!(counters.block_count(1), 1);
+    assert_eq!(
+        counters.device_allocations(0) + counters.device_allocations(1),
+        device.live_allocations() as u64
+    );
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.dealloc(&device, dedicated);
+        allocator.cleanup(&device);
+    }
+
+    for memory_type in 0..2 {
+        assert_eq!(counters.bytes_allocated(memory_type), 0);
+        assert_eq!(counters.block_count(memory_type), 0);
+        assert_eq!(counters.device_allocations(memory_type), 0);
+    }
+    Ok(())
+}
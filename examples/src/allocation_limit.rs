@@ -0,0 +1,74 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, Strategy,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 4096,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.max_memory_allocation_count = Some(8);
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+    assert_eq!(allocator.max_allocation_count(), 8);
+    assert_eq!(allocator.allocation_count(), 0);
+
+    let large = Request {
+        size: 64 * 1024,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..7 {
+        blocks.push(unsafe { allocator.alloc(&device, large) }?);
+    }
+    assert_eq!(allocator.allocation_count(), 7);
+
+    // Preferred dedicated request is suballocated near the limit.
+    let small = Request {
+        size: 2048,
+        ..Request::default()
+    };
+    assert_eq!(
+        allocator.strategy_for(&small, Some(Dedicated::Preferred)),
+        Some((0, Strategy::Buddy))
+    );
+    blocks.push(unsafe { allocator.alloc_with_dedicated(&device, small, Dedicated::Preferred) }?);
+    assert_eq!(allocator.allocation_count(), 8);
+
+    // Shared memory object still serves requests.
+    blocks.push(unsafe { allocator.alloc(&device, small) }?);
+    assert_eq!(allocator.allocation_count(), 8);
+
+    match unsafe { allocator.alloc(&device, large) } {
+        Err(AllocationError::TooManyObjects) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert_eq!(device.live_allocations(), 8);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    assert_eq!(allocator.allocation_count(), 0);
+
+    Ok(())
+}
@@ -3,7 +3,9 @@ use {
         version::{EntryV1_0, InstanceV1_0},
         vk, Entry,
     },
-    gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+    gpu_alloc::{
+        AllocationFlags, Config, GpuAllocator, Request, RequestFlags, ResourceKind, UsageFlags,
+    },
     gpu_alloc_ash::{device_properties, AshMemoryDevice},
     std::ffi::CStr,
 };
@@ -61,6 +63,15 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                memory_type: None,
+                access: None,
+                label: None,
+                dedicated: None,
+                dedicated_resource: None,
+                export: AllocationFlags::empty(),
+                priority: None,
+                flags: RequestFlags::empty(),
+                resource_kind: ResourceKind::Linear,
             },
         )
     }?;
@@ -0,0 +1,111 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{
+        borrow::Cow,
+        future::Future,
+        pin::Pin,
+        ptr::null,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    },
+};
+
+/// Polls future to completion on current thread.
+fn block_on<F: Future>(mut future: F) -> F::Output {
+    fn raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(null(), &VTABLE)
+    }
+
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    };
+
+    // First request needs new memory object, which is allocated asynchronously.
+    let first = block_on(unsafe { allocator.alloc_async(&device, request) })?;
+    assert_eq!(device.total_async_allocations(), 1);
+    assert_eq!(device.live_allocations(), 1);
+
+    // Second one is served from the same memory object without awaiting.
+    let second = block_on(unsafe { allocator.alloc_async(&device, request) })?;
+    assert_eq!(device.total_async_allocations(), 1);
+    assert_eq!(device.live_allocations(), 1);
+
+    // Dedicated memory objects are allocated asynchronously too.
+    let dedicated = block_on(unsafe {
+        allocator.alloc_async(
+            &device,
+            Request {
+                dedicated: Some(Dedicated::Required),
+                ..request
+            },
+        )
+    })?;
+    assert_eq!(device.total_async_allocations(), 2);
+    assert_eq!(device.live_allocations(), 2);
+
+    // Memory objects with priority are allocated synchronously.
+    let prioritized = block_on(unsafe {
+        allocator.alloc_async(
+            &device,
+            Request {
+                dedicated: Some(Dedicated::Required),
+                priority: Some(1.0),
+                ..request
+            },
+        )
+    })?;
+    assert_eq!(device.total_async_allocations(), 2);
+    assert_eq!(device.live_allocations(), 3);
+    assert_eq!(allocator.allocation_count(), 3);
+
+    unsafe {
+        for block in [first, second, dedicated, prioritized] {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
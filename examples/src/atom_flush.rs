@@ -0,0 +1,185 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, Dedicated, DeviceMapError, DeviceProperties, GpuAllocator,
+        MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        Request, RequestFlags, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, cell::RefCell, ptr::NonNull},
+};
+
+/// Wraps mock device to record flushed and invalidated ranges.
+struct FlushRecordingDevice {
+    inner: MockMemoryDevice,
+    flushed: RefCell<Vec<(usize, u64, u64)>>,
+    invalidated: RefCell<Vec<(usize, u64, u64)>>,
+}
+
+impl MemoryDevice<usize> for FlushRecordingDevice {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<usize, OutOfMemory> {
+        self.inner.allocate_memory(size, memory_type, flags)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: usize) {
+        self.inner.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut usize,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.inner.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut usize) {
+        self.inner.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        self.invalidated.borrow_mut().extend(
+            ranges
+                .iter()
+                .map(|range| (*range.memory, range.offset, range.size)),
+        );
+        self.inner.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        self.flushed.borrow_mut().extend(
+            ranges
+                .iter()
+                .map(|range| (*range.memory, range.offset, range.size)),
+        );
+        self.inner.flush_memory_ranges(ranges)
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    const ATOM: u64 = 256;
+
+    let device = FlushRecordingDevice {
+        inner: MockMemoryDevice::new(DeviceProperties {
+            memory_types: Cow::Borrowed(&[MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            }]),
+            memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+            max_memory_allocation_count: 32,
+            max_memory_allocation_size: 1024 * 1024,
+            non_coherent_atom_size: ATOM,
+            buffer_device_address: false,
+            buffer_image_granularity: 1,
+        }),
+        flushed: RefCell::new(Vec::new()),
+        invalidated: RefCell::new(Vec::new()),
+    };
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.inner.props());
+
+    let request = Request {
+        size: 16,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: !0,
+        memory_type: None,
+        access: None,
+        label: None,
+        dedicated: None,
+        dedicated_resource: None,
+        export: AllocationFlags::empty(),
+        priority: None,
+        flags: RequestFlags::empty(),
+        resource_kind: ResourceKind::Linear,
+    };
+
+    let mut first = unsafe { allocator.alloc(&device, request) }?;
+    let second = unsafe { allocator.alloc(&device, request) }?;
+
+    assert_eq!(first.memory(), second.memory(), "Blocks must share chunk");
+    assert_eq!(first.offset() % ATOM, 0);
+    assert_eq!(second.offset() % ATOM, 0);
+
+    unsafe { first.write_bytes(&device, 0, &[0xAA; 16]) }?;
+    assert!(!device.flushed.borrow().is_empty());
+
+    for &(memory, offset, size) in device.flushed.borrow().iter() {
+        assert_eq!(memory, *first.memory());
+        assert!(offset >= first.offset());
+        assert!(offset + size <= first.offset() + first.size());
+        assert!(
+            offset + size <= second.offset() || second.offset() + second.size() <= offset,
+            "Flushed range {}..{} overlaps neighbor block {}..{}",
+            offset,
+            offset + size,
+            second.offset(),
+            second.offset() + second.size(),
+        );
+    }
+
+    // Atom partially overwritten by the write is flushed whole without invalidation.
+    assert!(device.invalidated.borrow().is_empty());
+
+    // Unless write preserves bytes around written range.
+    unsafe { first.write_bytes_preserving(&device, 0, &[0xAA; 16]) }?;
+    assert_eq!(
+        *device.invalidated.borrow(),
+        [(*first.memory(), first.offset(), ATOM)]
+    );
+
+    // Write covering whole atoms needs no invalidation.
+    device.invalidated.borrow_mut().clear();
+    unsafe { first.write_bytes_preserving(&device, 0, &[0xBB; ATOM as usize]) }?;
+    assert!(device.invalidated.borrow().is_empty());
+
+    // Ranges of several blocks can be flushed in one call.
+    let range = first.mapped_range(10, 4);
+    assert_eq!(
+        (*range.memory, range.offset, range.size),
+        (*first.memory(), first.offset(), ATOM)
+    );
+
+    let range = second.mapped_range(first.size() - 1, 1);
+    assert!(range.offset >= second.offset());
+    assert_eq!(range.offset + range.size, second.offset() + second.size());
+
+    device.flushed.borrow_mut().clear();
+    unsafe { device.flush_memory_ranges(&[first.mapped_range(0, 16), second.mapped_range(0, 16)]) }
+        .map_err(|err| eyre::eyre!("Flush failed: {:?}", err))?;
+    assert_eq!(device.flushed.borrow().len(), 2);
+
+    device.flushed.borrow_mut().clear();
+
+    // Dedicated block smaller than an atom must not be flushed past its end.
+    let mut dedicated =
+        unsafe { allocator.alloc_with_dedicated(&device, request, Dedicated::Required) }?;
+    unsafe { dedicated.write_bytes(&device, 0, &[0x55; 16]) }?;
+    assert!(!device.flushed.borrow().is_empty());
+
+    for &(_, offset, size) in device.flushed.borrow().iter() {
+        assert!(offset + size <= dedicated.size());
+    }
+
+    unsafe {
+        allocator.dealloc(&device, first);
+        allocator.dealloc(&device, second);
+        allocator.dealloc(&device, dedicated);
+        allocator.cleanup(&device);
+    }
+
+    Ok(())
+}
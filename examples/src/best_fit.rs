@@ -0,0 +1,112 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, Strategy,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+const MB: u64 = 1024 * 1024;
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * MB }]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 1024 * MB,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_prototyping();
+    config.best_fit_memory_types = 0b10;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = |size, memory_types| Request {
+        size,
+        align_mask: 255,
+        memory_types,
+        ..Request::default()
+    };
+
+    // Mid-size request is rounded to power of two by buddy allocator
+    // and is served as is by best-fit allocator.
+    let texture = 5 * MB + MB / 2;
+    assert_eq!(
+        allocator.strategy_for(&request(texture, 0b10), None),
+        Some((1, Strategy::BestFit))
+    );
+    assert_eq!(
+        allocator.strategy_for(&request(MB, 0b10), None),
+        Some((1, Strategy::Buddy)),
+        "Small requests are still served by buddy allocator"
+    );
+
+    let buddy = unsafe { allocator.alloc(&device, request(texture, 0b01)) }?;
+    let best_fit = unsafe { allocator.alloc(&device, request(texture, 0b10)) }?;
+    assert_eq!(buddy.size(), 8 * MB);
+    assert_eq!(best_fit.size(), texture);
+
+    unsafe {
+        allocator.dealloc(&device, buddy);
+        allocator.dealloc(&device, best_fit);
+    }
+
+    // Freed blocks leave holes of 4.5 and 8 MiB.
+    let mut blocks = Vec::new();
+    for &size in &[6 * MB, 8 * MB, 6 * MB, 4 * MB + MB / 2, 6 * MB] {
+        blocks.push(unsafe { allocator.alloc(&device, request(size, 0b10)) }?);
+    }
+    assert!(blocks
+        .iter()
+        .all(|block| *block.memory() == *blocks[0].memory()));
+
+    let small_hole = blocks.remove(3);
+    let large_hole = blocks.remove(1);
+    let small_offset = small_hole.offset();
+    unsafe {
+        allocator.dealloc(&device, large_hole);
+        allocator.dealloc(&device, small_hole);
+    }
+
+    // Request goes to the smallest hole that fits,
+    // leaving the large one for larger requests.
+    let block = unsafe { allocator.alloc(&device, request(4 * MB + MB / 4, 0b10)) }?;
+    assert_eq!(*block.memory(), *blocks[0].memory());
+    assert_eq!(
+        block.offset() + block.size(),
+        small_offset + 4 * MB + MB / 2
+    );
+    blocks.push(block);
+
+    let block = unsafe { allocator.alloc(&device, request(8 * MB, 0b10)) }?;
+    assert_eq!(*block.memory(), *blocks[0].memory());
+    blocks.push(block);
+    assert_eq!(device.live_allocations(), 1);
+
+    let stats = allocator.stats();
+    assert_eq!(stats.memory_types[1].best_fit.block_count, 5);
+    assert_eq!(stats.memory_types[1].best_fit.memory_objects, 1);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Mid-size blocks are packed without rounding");
+    Ok(())
+}
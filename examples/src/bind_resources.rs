@@ -0,0 +1,72 @@
+use {
+    gpu_alloc::{
+        Config, DedicatedResource, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1024,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 100,
+        align_mask: 15,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    };
+
+    // Buffers are bound without granularity padding.
+    let buffer = unsafe { allocator.alloc_and_bind_buffer(&device, 1, request) }?;
+    assert_eq!(
+        device.resource_binding(DedicatedResource::Buffer(1)),
+        Some((*buffer.memory(), buffer.offset()))
+    );
+
+    // Images never share granularity page with other blocks.
+    let image = unsafe { allocator.alloc_and_bind_image(&device, 2, request) }?;
+    assert_eq!(image.offset() % 1024, 0);
+    assert_eq!(image.size() % 1024, 0);
+    assert_eq!(
+        device.resource_binding(DedicatedResource::Image(2)),
+        Some((*image.memory(), image.offset()))
+    );
+
+    let next = unsafe { allocator.alloc_and_bind_buffer(&device, 3, request) }?;
+    if next.memory() == image.memory() {
+        assert!(
+            next.offset() >= image.offset() + image.size()
+                || next.offset() + next.size() <= image.offset()
+        );
+    }
+
+    unsafe {
+        allocator.dealloc(&device, buffer);
+        allocator.dealloc(&device, image);
+        allocator.dealloc(&device, next);
+        allocator.cleanup(&device);
+    }
+
+    // Bindings are gone with memory objects.
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(device.resource_binding(DedicatedResource::Image(2)), None);
+    Ok(())
+}
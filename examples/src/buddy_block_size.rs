@@ -0,0 +1,87 @@
+use {
+    gpu_alloc::{
+        ChunkSizes, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    // Device-local memory holds only large blocks,
+    // while upload memory holds thousands of small uniform buffers.
+    let mut config = Config::i_am_potato();
+    config.minimal_buddy_size = 4096;
+    config.usage_chunk_sizes = vec![(
+        UsageFlags::UPLOAD,
+        ChunkSizes {
+            minimal_buddy: Some(256),
+            ..ChunkSizes::default()
+        },
+    )];
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let mut blocks = Vec::new();
+    for &usage in &[UsageFlags::FAST_DEVICE_ACCESS, UsageFlags::UPLOAD] {
+        for _ in 0..16 {
+            blocks.push(unsafe {
+                allocator.alloc(
+                    &device,
+                    Request {
+                        size: 200,
+                        align_mask: 63,
+                        usage,
+                        ..Request::default()
+                    },
+                )
+            }?);
+        }
+    }
+
+    for block in &blocks {
+        let expected = if block.memory_type() == 0 { 4096 } else { 256 };
+        assert_eq!(block.size(), expected);
+    }
+
+    let report = allocator.report();
+    assert_eq!(report.memory_types[0].wasted_bytes, 16 * (4096 - 200));
+    assert_eq!(report.memory_types[1].wasted_bytes, 16 * (256 - 200));
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Smallest buddy blocks follow usage of memory types");
+    Ok(())
+}
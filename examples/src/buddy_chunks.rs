@@ -0,0 +1,98 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, RequestFlags, ResourceKind, Strategy, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 1024 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let config = Config::i_am_potato();
+
+    // Large-object chunks of potato config are 32 KiB,
+    // making 16 KiB the top order of single chunk.
+    let top_order = config.dedicated_threshold / 2;
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = |size| Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        memory_type: None,
+        access: None,
+        label: None,
+        dedicated: None,
+        dedicated_resource: None,
+        export: AllocationFlags::empty(),
+        priority: None,
+        flags: RequestFlags::empty(),
+        resource_kind: ResourceKind::Linear,
+    };
+
+    assert_eq!(
+        allocator.strategy_for(&request(top_order + 1), None),
+        Some((0, Strategy::LargeBuddy)),
+    );
+
+    // Exactly at top order fits into regular chunk.
+    let at_top_order = unsafe { allocator.alloc(&device, request(top_order)) }?;
+    assert_eq!(at_top_order.size(), top_order);
+    assert_eq!(device.total_allocations(), 1);
+
+    // Just above top order gets fresh chunk sized for it instead of dedicated memory object.
+    let above_top_order = unsafe { allocator.alloc(&device, request(top_order + 1)) }?;
+    assert_eq!(above_top_order.size(), top_order * 2);
+    assert_eq!(device.total_allocations(), 2);
+    assert_ne!(above_top_order.memory(), at_top_order.memory());
+
+    unsafe { allocator.dealloc(&device, above_top_order) }
+    assert_eq!(device.total_deallocations(), 1);
+
+    // Later chunks have regular size again.
+    // Second half of the first chunk is reused.
+    let first = unsafe { allocator.alloc(&device, request(top_order)) }?;
+    assert_eq!(device.total_allocations(), 2);
+
+    // New regular chunk fits two blocks at top order.
+    let second = unsafe { allocator.alloc(&device, request(top_order)) }?;
+    let third = unsafe { allocator.alloc(&device, request(top_order)) }?;
+    assert_eq!(device.total_allocations(), 3);
+    assert_eq!(second.memory(), third.memory());
+
+    // Regular chunk has no space left, whereas chunk grown to fit
+    // the oversized request would have.
+    let fourth = unsafe { allocator.alloc(&device, request(top_order)) }?;
+    assert_eq!(device.total_allocations(), 4);
+    assert_ne!(fourth.memory(), third.memory());
+
+    unsafe {
+        allocator.dealloc(&device, at_top_order);
+        allocator.dealloc(&device, first);
+        allocator.dealloc(&device, second);
+        allocator.dealloc(&device, third);
+        allocator.dealloc(&device, fourth);
+    }
+
+    assert_eq!(device.total_deallocations(), 4);
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use {
+    gpu_alloc::{
+        BufferAllocator, BufferFactory, BufferRequirements, Config, DedicatedResource,
+        DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, collections::HashSet},
+};
+
+/// Factory that hands out sequential buffer handles.
+#[derive(Default)]
+struct MockBufferFactory {
+    next: u64,
+    live: HashSet<u64>,
+}
+
+impl BufferFactory for MockBufferFactory {
+    unsafe fn create_buffer(
+        &mut self,
+        size: u64,
+    ) -> Result<(u64, BufferRequirements), OutOfMemory> {
+        self.next += 1;
+        self.live.insert(self.next);
+        Ok((
+            self.next,
+            BufferRequirements {
+                size,
+                align_mask: 255,
+                memory_types: !0,
+            },
+        ))
+    }
+
+    unsafe fn destroy_buffer(&mut self, buffer: u64) {
+        assert!(self.live.remove(&buffer), "Buffer is not alive");
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let mut factory = MockBufferFactory::default();
+
+    // Uniform buffers of 64 KiB with offsets aligned to 256 bytes.
+    let mut buffers = BufferAllocator::new(
+        Request {
+            size: 64 * 1024,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            ..Request::default()
+        },
+        255,
+    );
+
+    let slices = (0..100)
+        .map(|_| unsafe { buffers.alloc(&mut allocator, &device, &mut factory, 1000) })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // 64 slices of 1024 bytes fit one buffer.
+    assert_eq!(buffers.buffer_count(), 2);
+    assert_eq!(factory.live.len(), 2);
+    for slice in &slices {
+        assert_eq!(slice.offset() % 256, 0);
+        assert!(slice.offset() + slice.size() <= 64 * 1024);
+    }
+    let distinct = slices
+        .iter()
+        .map(|slice| (slice.buffer(), slice.offset()))
+        .collect::<HashSet<_>>();
+    assert_eq!(distinct.len(), 100);
+
+    // Buffers are bound to their memory blocks.
+    let block = buffers.memory_block(&slices[0]);
+    assert_eq!(
+        device.resource_binding(DedicatedResource::Buffer(slices[0].buffer())),
+        Some((*block.memory(), block.offset())),
+    );
+
+    // Slice larger than buffer size gets buffer of its own.
+    let large = unsafe { buffers.alloc(&mut allocator, &device, &mut factory, 100 * 1024)? };
+    assert_eq!(large.offset(), 0);
+    assert_eq!(buffers.buffer_count(), 3);
+    assert_eq!(buffers.memory_block(&large).size(), 100 * 1024);
+
+    // Free all slices but those in the first buffer.
+    let first = slices[0].buffer();
+    let (kept, freed): (Vec<_>, Vec<_>) = slices
+        .into_iter()
+        .partition(|slice| slice.buffer() == first);
+    unsafe {
+        buffers.dealloc(large);
+        for slice in freed {
+            buffers.dealloc(slice);
+        }
+    }
+
+    // Empty buffers are destroyed on trim.
+    unsafe { buffers.trim(&mut allocator, &device, &mut factory) };
+    assert_eq!(buffers.buffer_count(), 1);
+    assert_eq!(factory.live.len(), 1);
+
+    // Slices are released together with their buffers.
+    drop(kept);
+    unsafe {
+        buffers.destroy(&mut allocator, &device, &mut factory);
+        allocator.cleanup(&device);
+    }
+
+    assert!(factory.live.is_empty());
+    assert!(allocator.report_leaks().is_empty());
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
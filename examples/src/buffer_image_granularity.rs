@@ -0,0 +1,89 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryBlock, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1024,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = |size, resource_kind| Request {
+        size,
+        align_mask: 15,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        resource_kind,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for &resource_kind in &[
+        ResourceKind::Linear,
+        ResourceKind::NonLinear,
+        ResourceKind::Linear,
+        ResourceKind::NonLinear,
+        ResourceKind::Linear,
+    ] {
+        let block = unsafe { allocator.alloc(&device, request(100, resource_kind)) }?;
+        blocks.push((resource_kind, block));
+    }
+
+    // Blocks for linear resources are not padded to granularity.
+    for (_, block) in blocks
+        .iter()
+        .filter(|(kind, _)| *kind == ResourceKind::Linear)
+    {
+        assert!(block.size() < 1024);
+    }
+
+    // Blocks for non-linear resources occupy whole pages.
+    for (_, block) in blocks
+        .iter()
+        .filter(|(kind, _)| *kind == ResourceKind::NonLinear)
+    {
+        assert_eq!(block.offset() % 1024, 0);
+        assert_eq!(block.size(), 1024);
+    }
+
+    // Linear and non-linear resources never share a page.
+    let pages = |block: &MemoryBlock<usize>| {
+        (block.offset() / 1024)..=((block.offset() + block.size() - 1) / 1024)
+    };
+    for (kind, block) in &blocks {
+        for (other_kind, other) in &blocks {
+            if kind != other_kind && block.memory() == other.memory() {
+                let other_pages = pages(other);
+                assert!(!pages(block).any(|page| other_pages.contains(&page)));
+            }
+        }
+    }
+
+    unsafe {
+        for (_, block) in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
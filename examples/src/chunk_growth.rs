@@ -0,0 +1,92 @@
+use {
+    gpu_alloc::{
+        ChunkGrowth, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, PlannedAllocation, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    // Memory objects start at 8 KiB and double up to 64 KiB,
+    // except for memory type 1 where they keep initial size.
+    let mut config = Config::i_am_potato();
+    config.chunk_growth = ChunkGrowth::Exponential {
+        factor: 2,
+        max_size: 64 * 1024,
+    };
+    config.memory_type_chunk_growth = vec![(1, ChunkGrowth::Fixed)];
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let mut blocks = Vec::new();
+    for memory_type in 0..2 {
+        let request = Request {
+            size: 2048,
+            align_mask: 255,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            memory_type: Some(memory_type),
+            ..Request::default()
+        };
+
+        let mut chunk_sizes = Vec::new();
+        for _ in 0..64 {
+            let planned = allocator.plan(&[request]).allocations[0];
+            let usage = device.heap_usage(memory_type);
+            blocks.push(unsafe { allocator.alloc(&device, request) }?);
+
+            let grown = device.heap_usage(memory_type) - usage;
+            if grown > 0 {
+                // Plan predicts sizes of grown memory objects.
+                assert!(matches!(
+                    planned,
+                    PlannedAllocation::NewMemory { size, .. } if size == grown
+                ));
+                chunk_sizes.push(grown / 1024);
+            }
+        }
+
+        match memory_type {
+            0 => assert_eq!(chunk_sizes, [8, 16, 32, 64, 64]),
+            _ => assert_eq!(chunk_sizes, [8; 16]),
+        }
+    }
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    const MIB: u64 = 1024 * 1024;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * MIB }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * MIB,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let config = Config::i_am_potato();
+    let default_chunk_size = config.initial_buddy_dedicated_size;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 256,
+        ..Request::default()
+    };
+
+    // New chunk is created with requested size.
+    let first = unsafe { allocator.alloc_in_chunk_of(&device, request, MIB) }?;
+    assert_eq!(device.heap_usage(0), MIB);
+
+    let second = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(device.total_allocations(), 1);
+    assert_eq!(first.memory(), second.memory());
+
+    unsafe {
+        allocator.dealloc(&device, first);
+        allocator.dealloc(&device, second);
+    }
+    assert_eq!(device.heap_usage(0), 0);
+
+    // Chunks created later have default size.
+    let third = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(device.heap_usage(0), default_chunk_size);
+
+    // Existing chunk is reused.
+    let fourth = unsafe { allocator.alloc_in_chunk_of(&device, request, MIB) }?;
+    assert_eq!(device.total_allocations(), 2);
+    assert_eq!(third.memory(), fourth.memory());
+
+    unsafe {
+        allocator.dealloc(&device, third);
+        allocator.dealloc(&device, fourth);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Relocation, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 2048,
+        align_mask: 255,
+        usage: UsageFlags::HOST_ACCESS,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..3 {
+        blocks.push(unsafe {
+            allocator.alloc_with_dedicated(&device, request, Dedicated::Preferred)
+        }?);
+    }
+    blocks.push(unsafe { allocator.alloc_with_dedicated(&device, request, Dedicated::Required) }?);
+    blocks.push(unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                size: 64 * 1024,
+                ..request
+            },
+            Dedicated::Preferred,
+        )
+    }?);
+    assert_eq!(device.live_allocations(), 5);
+
+    for block in &mut blocks {
+        unsafe { block.write_bytes(&device, 0, &[0xAB; 16]) }?;
+    }
+
+    let relocations = unsafe { allocator.compact_dedicated(&device, blocks) };
+    let moved: Vec<bool> = relocations
+        .iter()
+        .map(|relocation| match relocation {
+            Relocation::Keep(_) => false,
+            Relocation::Move { .. } => true,
+        })
+        .collect();
+    assert_eq!(moved, [true, true, true, false, false]);
+
+    for relocation in &relocations {
+        if let Relocation::Move { src, dst } = relocation {
+            assert_eq!(dst.memory_type(), src.memory_type());
+            assert_eq!(dst.offset() & 255, 0);
+            assert!(dst.size() >= src.size());
+        }
+    }
+
+    // Copy content to new blocks.
+    let mut relocations = relocations;
+    for relocation in &mut relocations {
+        if let Relocation::Move { src, dst } = relocation {
+            let mut data = [0; 16];
+            unsafe { src.read_bytes(&device, 0, &mut data) }?;
+            unsafe { dst.write_bytes(&device, 0, &data) }?;
+        }
+    }
+
+    let mut blocks = unsafe { allocator.commit_relocations(&device, relocations) };
+    assert_eq!(blocks.len(), 5);
+
+    // Three dedicated memory objects are replaced by single shared one.
+    assert_eq!(device.live_allocations(), 3);
+
+    for block in blocks.iter_mut().take(3) {
+        let mut data = [0; 16];
+        unsafe { block.read_bytes(&device, 0, &mut data) }?;
+        assert_eq!(data, [0xAB; 16]);
+    }
+
+    // Aborted relocations keep original blocks.
+    let relocations = unsafe { allocator.compact_dedicated(&device, blocks) };
+    let blocks = unsafe { allocator.abort_relocations(&device, relocations) };
+    assert_eq!(device.live_allocations(), 3);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use {
+    gpu_alloc::{Config, DeviceProperties, MemoryHeap, MemoryPropertyFlags, MemoryType},
+    std::borrow::Cow,
+};
+
+const MIB: u64 = 1024 * 1024;
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    // Discrete GPU with 8 GiB of device-local memory gets prototyping values.
+    let discrete = Config::auto(&DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap { size: 8192 * MIB },
+            MemoryHeap { size: 16384 * MIB },
+        ]),
+        max_memory_allocation_count: 4096,
+        max_memory_allocation_size: 4096 * MIB,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+    let prototyping = Config::i_am_prototyping();
+    assert_eq!(
+        discrete.dedicated_threshold,
+        prototyping.dedicated_threshold
+    );
+    assert_eq!(
+        discrete.final_free_list_chunk,
+        prototyping.final_free_list_chunk
+    );
+    assert_eq!(discrete.minimal_buddy_size, 64);
+
+    // Mobile GPU shares 2 GiB with the host through single heap.
+    let mobile = Config::auto(&DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL
+                | MemoryPropertyFlags::HOST_VISIBLE
+                | MemoryPropertyFlags::HOST_CACHED,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 2048 * MIB }]),
+        max_memory_allocation_count: 4096,
+        max_memory_allocation_size: 2 * MIB,
+        non_coherent_atom_size: 256,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+    let potato = Config::i_am_potato();
+    assert_eq!(
+        mobile.final_free_list_chunk,
+        potato.final_free_list_chunk * 128
+    );
+    assert_eq!(mobile.dedicated_threshold, 2 * MIB);
+    assert!(mobile.dedicated_threshold < prototyping.dedicated_threshold);
+    assert_eq!(mobile.minimal_buddy_size, 256);
+
+    // Tiny heaps never go below potato values.
+    let tiny = Config::auto(&DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: MIB }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: MIB,
+        non_coherent_atom_size: 1,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+    assert_eq!(tiny, potato);
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Pretty-prints snapshot encoded with `AllocatorSnapshot::encode` from file passed as argument.
+//! Without arguments checks that encoded snapshot of mock allocator loads back unchanged.
+
+use {
+    gpu_alloc::{
+        AllocatorSnapshot, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    if let Some(path) = std::env::args_os().nth(1) {
+        let bytes = std::fs::read(path)?;
+        let snapshot = AllocatorSnapshot::decode(&bytes)
+            .ok_or_else(|| eyre::eyre!("File is not an encoded allocator snapshot"))?;
+        print!("{}", snapshot);
+        return Ok(());
+    }
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let labels = ["vertices", "staging", "uniforms"];
+    let mut blocks = Vec::new();
+    for i in 0..12u64 {
+        let usage = match i % 3 {
+            0 => UsageFlags::FAST_DEVICE_ACCESS,
+            1 => UsageFlags::UPLOAD | UsageFlags::TRANSIENT,
+            _ => UsageFlags::HOST_ACCESS,
+        };
+        blocks.push(unsafe {
+            allocator.alloc(
+                &device,
+                Request {
+                    size: 1000 + i * 300,
+                    align_mask: 255,
+                    usage,
+                    label: Some(labels[i as usize % 3]),
+                    ..Request::default()
+                },
+            )
+        }?);
+    }
+
+    // Dedicated block is listed separately.
+    blocks.push(unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 32 * 1024 * 1024,
+                align_mask: 255,
+                usage: UsageFlags::FAST_DEVICE_ACCESS,
+                label: Some("render target"),
+                ..Request::default()
+            },
+        )
+    }?);
+
+    let block = blocks.remove(4);
+    unsafe { allocator.dealloc(&device, block) };
+
+    let snapshot = allocator.snapshot();
+    assert!(snapshot
+        .blocks
+        .iter()
+        .all(|block| block.label.is_some() && !block.usage.is_empty()));
+
+    let bytes = snapshot.encode();
+    let loaded = AllocatorSnapshot::decode(&bytes).expect("Snapshot must load back");
+    assert_eq!(loaded, snapshot);
+
+    assert!(AllocatorSnapshot::decode(&bytes[..bytes.len() - 1]).is_none());
+    assert!(AllocatorSnapshot::decode(b"not a snapshot").is_none());
+
+    let printed = loaded.to_string();
+    for label in labels.iter().chain(&["render target"]) {
+        assert!(printed.contains(label), "Label {} is not printed", label);
+    }
+    assert!(printed.contains("dedicated:"));
+    assert!(printed.contains("free"));
+    print!("{}", printed);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+
+    println!(
+        "Snapshot of {} blocks encoded into {} bytes",
+        snapshot.blocks.len(),
+        bytes.len()
+    );
+    Ok(())
+}
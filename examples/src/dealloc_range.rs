@@ -0,0 +1,122 @@
+use {
+    gpu_alloc::{
+        Config, DeallocRangeError, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.validate_deallocations = true;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    // Transient blocks are sub-allocated from free-list memory objects.
+    let mut block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 4096,
+                usage: UsageFlags::TRANSIENT,
+                ..Request::default()
+            },
+        )
+    }?;
+    let memory = *block.memory();
+    let start = block.offset();
+
+    // Mapped blocks are rejected.
+    unsafe { block.map(&device, 0, 64) }?;
+    assert_eq!(
+        unsafe { allocator.dealloc_range(&mut block, 0, 1024) }.unwrap_err(),
+        DeallocRangeError::Mapped
+    );
+    unsafe { block.unmap(&device) };
+
+    // Ranges not aligned to non-coherent atoms and whole-block ranges are rejected.
+    assert_eq!(
+        unsafe { allocator.dealloc_range(&mut block, 32, 64) }.unwrap_err(),
+        DeallocRangeError::InvalidRange
+    );
+    assert_eq!(
+        unsafe { allocator.dealloc_range(&mut block, 0, 4096) }.unwrap_err(),
+        DeallocRangeError::InvalidRange
+    );
+
+    // Range at the end shrinks the block.
+    assert!(unsafe { allocator.dealloc_range(&mut block, 3072, 1024) }?.is_none());
+    assert_eq!((block.offset(), block.size()), (start, 3072));
+    assert!(allocator.is_current(&block));
+
+    // Range at the start moves the block start.
+    assert!(unsafe { allocator.dealloc_range(&mut block, 0, 1024) }?.is_none());
+    assert_eq!((block.offset(), block.size()), (start + 1024, 2048));
+
+    // Range in the middle splits the block.
+    let tail =
+        unsafe { allocator.dealloc_range(&mut block, 512, 1024) }?.expect("Block must be split");
+    assert_eq!((block.offset(), block.size()), (start + 1024, 512));
+    assert_eq!((tail.offset(), tail.size()), (start + 2560, 512));
+    assert_eq!(*tail.memory(), memory);
+    assert!(tail.generation() > block.generation());
+    assert!(allocator.is_current(&block));
+    assert!(allocator.is_current(&tail));
+
+    // Deallocated ranges are reused.
+    let reused = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1024,
+                usage: UsageFlags::TRANSIENT,
+                ..Request::default()
+            },
+        )
+    }?;
+    assert_eq!(*reused.memory(), memory);
+
+    // Buddy blocks don't support range deallocation.
+    let mut buddy = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 4096,
+                ..Request::default()
+            },
+        )
+    }?;
+    assert_eq!(
+        unsafe { allocator.dealloc_range(&mut buddy, 0, 1024) }.unwrap_err(),
+        DeallocRangeError::Unsupported
+    );
+
+    unsafe {
+        allocator.dealloc(&device, buddy);
+        allocator.dealloc(&device, reused);
+        allocator.dealloc(&device, tail);
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.total_allocations(), device.total_deallocations());
+
+    Ok(())
+}
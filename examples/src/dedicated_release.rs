@@ -0,0 +1,70 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, RequestFlags, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 100,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: !0,
+        memory_type: None,
+        access: None,
+        label: None,
+        dedicated: None,
+        dedicated_resource: None,
+        export: AllocationFlags::empty(),
+        priority: None,
+        flags: RequestFlags::empty(),
+        resource_kind: ResourceKind::Linear,
+    };
+
+    // Mapped dedicated block is unmapped and released without allocator.
+    let mut dedicated =
+        unsafe { allocator.alloc_with_dedicated(&device, request, Dedicated::Required) }?;
+    unsafe { dedicated.map(&device, 0, 100) }?;
+    assert_eq!(device.live_allocations(), 1);
+
+    if unsafe { dedicated.into_memory_dedicated(&device) }.is_err() {
+        eyre::bail!("Dedicated block must be released");
+    }
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(device.total_deallocations(), 1);
+
+    // Shared block is handed back.
+    let shared = unsafe { allocator.alloc(&device, request) }?;
+    let shared = match unsafe { shared.into_memory_dedicated(&device) } {
+        Ok(()) => eyre::bail!("Shared block must not be released"),
+        Err(shared) => shared,
+    };
+
+    unsafe {
+        allocator.dealloc(&device, shared);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DedicatedResource, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, PlannedAllocation, Relocation, Request, Strategy,
+        UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let buffer = Request {
+        size: 256,
+        align_mask: 255,
+        usage: UsageFlags::HOST_ACCESS,
+        dedicated: Some(Dedicated::Required),
+        dedicated_resource: Some(DedicatedResource::Buffer(42)),
+        ..Request::default()
+    };
+
+    // Hint in the request is used for planning and strategy selection.
+    assert_eq!(
+        allocator.strategy_for(&buffer, None),
+        Some((0, Strategy::Dedicated))
+    );
+    assert!(matches!(
+        allocator.plan(&[buffer]).allocations[..],
+        [PlannedAllocation::NewMemory {
+            strategy: Strategy::Dedicated,
+            ..
+        }]
+    ));
+
+    // Required dedicated memory object is allocated for the buffer.
+    let required = unsafe { allocator.alloc(&device, buffer) }?;
+    assert_eq!(device.live_allocations(), 1);
+    assert_eq!(
+        device.memory_dedicated_resource(*required.memory()),
+        Some(DedicatedResource::Buffer(42))
+    );
+
+    // Preferred hint for small image is ignored and resource is not used.
+    let small_image = Request {
+        size: 256,
+        dedicated: Some(Dedicated::Preferred),
+        dedicated_resource: Some(DedicatedResource::Image(7)),
+        ..buffer
+    };
+    let shared = unsafe { allocator.alloc(&device, small_image) }?;
+    assert_eq!(device.memory_dedicated_resource(*shared.memory()), None);
+
+    // Preferred hint for large image is followed.
+    let large_image = Request {
+        size: 4096,
+        dedicated_resource: Some(DedicatedResource::Image(8)),
+        ..small_image
+    };
+    let preferred = unsafe { allocator.alloc(&device, large_image) }?;
+    assert_eq!(
+        device.memory_dedicated_resource(*preferred.memory()),
+        Some(DedicatedResource::Image(8))
+    );
+
+    // Hint passed to `alloc_with_dedicated` takes precedence.
+    let forced =
+        unsafe { allocator.alloc_with_dedicated(&device, small_image, Dedicated::Required) }?;
+    assert_eq!(
+        device.memory_dedicated_resource(*forced.memory()),
+        Some(DedicatedResource::Image(7))
+    );
+
+    // Same sized preferred block without resource can be compacted.
+    let movable = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                dedicated_resource: None,
+                ..large_image
+            },
+        )
+    }?;
+
+    // Blocks dedicated to resources are never relocated.
+    let relocations = unsafe { allocator.compact_dedicated(&device, vec![preferred, movable]) };
+    let moved: Vec<bool> = relocations
+        .iter()
+        .map(|relocation| matches!(relocation, Relocation::Move { .. }))
+        .collect();
+    assert_eq!(moved, [false, true]);
+
+    unsafe {
+        for block in allocator.commit_relocations(&device, relocations) {
+            allocator.dealloc(&device, block);
+        }
+        allocator.dealloc(&device, required);
+        allocator.dealloc(&device, shared);
+        allocator.dealloc(&device, forced);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
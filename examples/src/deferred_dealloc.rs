@@ -0,0 +1,64 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        dedicated: Some(Dedicated::Required),
+        ..Request::default()
+    };
+
+    // Blocks used by frames 1, 2 and 3 are released as each frame is recorded.
+    unsafe {
+        for frame in [3, 1, 2, 1] {
+            let block = allocator.alloc(&device, request)?;
+            allocator.dealloc_deferred(block, frame);
+        }
+    }
+    assert_eq!(allocator.deferred_count(), 4);
+    assert_eq!(device.live_allocations(), 4);
+
+    // Nothing is reclaimed before device completes the frame.
+    unsafe { allocator.flush_retired(&device, 0) };
+    assert_eq!(device.live_allocations(), 4);
+
+    // Both blocks of frame 1 are reclaimed.
+    unsafe { allocator.flush_retired(&device, 1) };
+    assert_eq!(allocator.deferred_count(), 2);
+    assert_eq!(device.live_allocations(), 2);
+
+    // Completing later frame reclaims all older ones too.
+    unsafe { allocator.flush_retired(&device, 3) };
+    assert_eq!(allocator.deferred_count(), 0);
+    assert_eq!(device.live_allocations(), 0);
+
+    unsafe { allocator.cleanup(&device) };
+    Ok(())
+}
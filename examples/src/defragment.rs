@@ -0,0 +1,91 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Relocation, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::from_bits_truncate(
+                MemoryPropertyFlags::HOST_VISIBLE.bits()
+                    | MemoryPropertyFlags::HOST_COHERENT.bits(),
+            ),
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 1024,
+        usage: UsageFlags::HOST_ACCESS,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..32 {
+        blocks.push(unsafe { allocator.alloc(&device, request) }?);
+    }
+    let chunks = device.live_allocations();
+    assert!(chunks > 2);
+
+    // Keep every fourth block, fragmenting all memory objects.
+    let mut kept = Vec::new();
+    for (index, block) in blocks.into_iter().enumerate() {
+        if index % 4 == 0 {
+            kept.push(block);
+        } else {
+            unsafe { allocator.dealloc(&device, block) };
+        }
+    }
+    assert_eq!(device.live_allocations(), chunks);
+
+    for (index, block) in kept.iter_mut().enumerate() {
+        unsafe { block.write_bytes(&device, 0, &[index as u8; 16]) }?;
+    }
+
+    let mut relocations = unsafe { allocator.defragment(&device, kept) };
+    assert!(relocations
+        .iter()
+        .any(|relocation| relocation.dst().is_some()));
+
+    // Copy content of moved blocks, as device would do.
+    for relocation in &mut relocations {
+        if let Relocation::Move { src, dst } = relocation {
+            let mut content = [0; 16];
+            unsafe {
+                src.read_bytes(&device, 0, &mut content)?;
+                dst.write_bytes(&device, 0, &content)?;
+            }
+        }
+    }
+
+    let kept = unsafe { allocator.commit_relocations(&device, relocations) };
+    assert!(device.live_allocations() < chunks);
+
+    for (index, mut block) in kept.into_iter().enumerate() {
+        let mut content = [0; 16];
+        unsafe { block.read_bytes(&device, 0, &mut content) }?;
+        assert_eq!(content, [index as u8; 16]);
+        unsafe { allocator.dealloc(&device, block) };
+    }
+
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, PlannedAllocation, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: true,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = |usage: UsageFlags, size: u64| Request {
+        size,
+        align_mask: 255,
+        usage,
+        ..Request::default()
+    };
+
+    let regular = request(UsageFlags::FAST_DEVICE_ACCESS, 1024);
+    let addressable = request(
+        UsageFlags::FAST_DEVICE_ACCESS | UsageFlags::DEVICE_ADDRESS,
+        1024,
+    );
+
+    let mut blocks = Vec::new();
+    blocks.push(unsafe { allocator.alloc(&device, regular) }?);
+    let flags = device.memory_allocation_flags(*blocks[0].memory());
+    assert!(!flags.contains(AllocationFlags::DEVICE_ADDRESS));
+
+    // Regular chunk has room, yet block with device address needs its own.
+    assert!(matches!(
+        allocator.plan(&[addressable]).allocations[0],
+        PlannedAllocation::NewMemory { .. }
+    ));
+    blocks.push(unsafe { allocator.alloc(&device, addressable) }?);
+    assert_eq!(device.live_allocations(), 2);
+    assert_ne!(blocks[0].memory(), blocks[1].memory());
+    let flags = device.memory_allocation_flags(*blocks[1].memory());
+    assert!(flags.contains(AllocationFlags::DEVICE_ADDRESS));
+
+    // Both kinds share memory objects with blocks of the same kind.
+    blocks.push(unsafe { allocator.alloc(&device, regular) }?);
+    blocks.push(unsafe { allocator.alloc(&device, addressable) }?);
+    assert_eq!(device.live_allocations(), 2);
+    assert_eq!(blocks[0].memory(), blocks[2].memory());
+    assert_eq!(blocks[1].memory(), blocks[3].memory());
+
+    // Transient and dedicated blocks are segregated as well.
+    for &size in &[1024, 1024 * 1024] {
+        for &usage in &[UsageFlags::empty(), UsageFlags::DEVICE_ADDRESS] {
+            let transient = request(usage | UsageFlags::TRANSIENT, size);
+            let block = unsafe { allocator.alloc(&device, transient) }?;
+            let flags = device.memory_allocation_flags(*block.memory());
+            assert_eq!(
+                flags.contains(AllocationFlags::DEVICE_ADDRESS),
+                usage.contains(UsageFlags::DEVICE_ADDRESS)
+            );
+            blocks.push(block);
+        }
+    }
+
+    let snapshot = allocator.snapshot();
+    for chunk in &snapshot.chunks {
+        for block in snapshot.blocks.iter().filter(|block| block.is_in(chunk)) {
+            assert_eq!(
+                block.usage.contains(UsageFlags::DEVICE_ADDRESS),
+                chunk.device_address
+            );
+        }
+    }
+    assert_eq!(
+        snapshot.chunks.iter().filter(|c| c.device_address).count(),
+        2
+    );
+    assert_eq!(allocator.stats().total().block_count, blocks.len() as u64);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Memory objects with device address are kept apart");
+    Ok(())
+}
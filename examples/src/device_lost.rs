@@ -0,0 +1,120 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{
+        borrow::Cow,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    },
+    tracing::{Event, Level, Subscriber},
+    tracing_subscriber::{
+        layer::{Context, Layer, SubscriberExt as _},
+        registry::Registry,
+    },
+};
+
+/// Layer that counts error events.
+struct CountErrors(Arc<AtomicUsize>);
+
+impl<S: Subscriber> Layer<S> for CountErrors {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() == Level::ERROR {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let errors = Arc::new(AtomicUsize::new(0));
+    let subscriber = Registry::default().with(CountErrors(errors.clone()));
+    tracing::subscriber::set_global_default(subscriber)?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.frame_ring_size = 8192;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD,
+        ..Request::default()
+    };
+
+    let block = unsafe { allocator.alloc(&device, request) }?;
+    let transient = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                usage: UsageFlags::UPLOAD | UsageFlags::TRANSIENT,
+                ..request
+            },
+        )
+    }?;
+    let dedicated = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 4 * 1024 * 1024,
+                ..request
+            },
+        )
+    }?;
+    allocator.begin_frame(0);
+    let frame_block = unsafe { allocator.alloc_in_frame(&device, request) }?;
+    unsafe { allocator.dealloc_deferred(frame_block, 0) };
+
+    let live = device.live_allocations();
+    assert!(live >= 3);
+
+    allocator.mark_device_lost();
+    assert!(allocator.is_device_lost());
+
+    // New allocations fail without touching the device.
+    let total = device.total_allocations();
+    assert_eq!(
+        unsafe { allocator.alloc(&device, request) }.err(),
+        Some(AllocationError::DeviceLost)
+    );
+    assert_eq!(
+        unsafe { allocator.alloc_in_frame(&device, request) }.err(),
+        Some(AllocationError::DeviceLost)
+    );
+    assert_eq!(device.total_allocations(), total);
+
+    // Deallocations and cleanup are accepted and skipped.
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.dealloc(&device, transient);
+        allocator.dealloc(&device, dedicated);
+        allocator.flush_retired(&device, 0);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.total_deallocations(), 0);
+    assert_eq!(device.live_allocations(), live);
+    assert!(allocator.report_leaks().is_empty());
+
+    // Frame was never ended and nothing complains on teardown.
+    drop(allocator);
+    assert_eq!(errors.load(Ordering::Relaxed), 0);
+    Ok(())
+}
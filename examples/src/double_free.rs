@@ -0,0 +1,65 @@
+use {
+    gpu_alloc::{
+        Config, DeallocError, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.validate_deallocations = true;
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 256,
+        ..Request::default()
+    };
+
+    for &dedicated in &[None, Some(Dedicated::Required)] {
+        let block = match dedicated {
+            None => unsafe { allocator.alloc(&device, request) }?,
+            Some(dedicated) => {
+                unsafe { allocator.alloc_with_dedicated(&device, request, dedicated) }?
+            }
+        };
+        let neighbor = unsafe { allocator.alloc(&device, request) }?;
+
+        // Simulate handle duplicated by a bug.
+        let duplicate = unsafe { std::ptr::read(&block) };
+
+        unsafe { allocator.try_dealloc(&device, block) }?;
+        assert_eq!(
+            unsafe { allocator.try_dealloc(&device, duplicate) },
+            Err(DeallocError::NotAllocated)
+        );
+
+        // Allocator state is intact.
+        let block = unsafe { allocator.alloc(&device, request) }?;
+        unsafe {
+            allocator.try_dealloc(&device, block)?;
+            allocator.try_dealloc(&device, neighbor)?;
+        }
+
+        assert_eq!(device.live_allocations(), 0);
+    }
+
+    Ok(())
+}
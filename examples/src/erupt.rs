@@ -1,6 +1,8 @@
 use {
     erupt::{vk1_0, DeviceLoader, EntryLoader, InstanceLoader},
-    gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+    gpu_alloc::{
+        AllocationFlags, Config, GpuAllocator, Request, RequestFlags, ResourceKind, UsageFlags,
+    },
     gpu_alloc_erupt::{device_properties, EruptMemoryDevice},
     std::ffi::CStr,
 };
@@ -58,6 +60,15 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                memory_type: None,
+                access: None,
+                label: None,
+                dedicated: None,
+                dedicated_resource: None,
+                export: AllocationFlags::empty(),
+                priority: None,
+                flags: RequestFlags::empty(),
+                resource_kind: ResourceKind::Linear,
             },
         )
     }?;
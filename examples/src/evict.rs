@@ -0,0 +1,81 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 256 * 1024,
+        ..Request::default()
+    };
+
+    // Cache of evictable blocks fills the heap.
+    let mut cache = Vec::new();
+    for _ in 0..4 {
+        cache.push(unsafe { allocator.alloc(&device, request)? });
+    }
+    assert!(matches!(
+        unsafe { allocator.alloc(&device, request) },
+        Err(AllocationError::OutOfDeviceMemory(_))
+    ));
+
+    // Handler that frees nothing surfaces the error.
+    let mut calls = 0;
+    let result = unsafe {
+        allocator.alloc_or_evict(&device, request, |_, _| {
+            calls += 1;
+            false
+        })
+    };
+    assert!(matches!(result, Err(AllocationError::OutOfDeviceMemory(_))));
+    assert_eq!(calls, 1);
+
+    // Handler that evicts cached block lets retry succeed.
+    let block = unsafe {
+        allocator.alloc_or_evict(&device, request, |allocator, device| match cache.pop() {
+            Some(evicted) => {
+                allocator.dealloc(device, evicted);
+                true
+            }
+            None => false,
+        })?
+    };
+    assert_eq!(cache.len(), 3);
+
+    // Handler is not called when allocation succeeds.
+    unsafe { allocator.dealloc(&device, block) };
+    let block = unsafe {
+        allocator.alloc_or_evict(&device, request, |_, _| unreachable!("Memory is available"))?
+    };
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        for block in cache {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, PlannedAllocation, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        ..Request::default()
+    };
+
+    // Implied fast device access selects device-local memory.
+    let selected = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(selected.memory_type(), 0);
+
+    // Explicit memory type bypasses selection.
+    let explicit = Request {
+        memory_type: Some(1),
+        ..request
+    };
+    assert_eq!(
+        allocator.strategy_for(&explicit, None).map(|(t, _)| t),
+        Some(1)
+    );
+    assert!(matches!(
+        allocator.plan(&[explicit]).allocations[..],
+        [PlannedAllocation::NewMemory { memory_type: 1, .. }]
+    ));
+    let overridden = unsafe { allocator.alloc(&device, explicit) }?;
+    assert_eq!(overridden.memory_type(), 1);
+
+    // But is still validated against memory types bitset.
+    let masked = Request {
+        memory_types: 0b01,
+        ..explicit
+    };
+    assert!(matches!(
+        unsafe { allocator.alloc(&device, masked) },
+        Err(AllocationError::NoCompatibleMemoryTypes)
+    ));
+    assert_eq!(allocator.strategy_for(&masked, None), None);
+
+    unsafe {
+        allocator.dealloc(&device, selected);
+        allocator.dealloc(&device, overridden);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
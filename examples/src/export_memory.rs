@@ -0,0 +1,86 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, PlannedAllocation, Relocation, Request, Strategy, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let shared = Request {
+        size: 256,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    };
+
+    let exported = Request {
+        export: AllocationFlags::EXPORT_OPAQUE_FD,
+        ..shared
+    };
+
+    // Small exportable request is still served by dedicated memory object.
+    assert_eq!(
+        allocator.strategy_for(&exported, None),
+        Some((0, Strategy::Dedicated))
+    );
+    assert!(matches!(
+        allocator.plan(&[exported]).allocations[..],
+        [PlannedAllocation::NewMemory {
+            strategy: Strategy::Dedicated,
+            ..
+        }]
+    ));
+
+    let a = unsafe { allocator.alloc(&device, shared) }?;
+    let b = unsafe { allocator.alloc(&device, exported) }?;
+    let c = unsafe { allocator.alloc(&device, shared) }?;
+
+    // Exported memory object is not shared with other blocks.
+    assert_ne!(a.memory(), b.memory());
+    assert_ne!(c.memory(), b.memory());
+    assert_eq!(
+        device.memory_allocation_flags(*b.memory()),
+        AllocationFlags::EXPORT_OPAQUE_FD
+    );
+    assert_eq!(
+        device.memory_allocation_flags(*a.memory()),
+        AllocationFlags::empty()
+    );
+
+    // Exported memory object is never relocated.
+    let relocations = unsafe { allocator.compact_dedicated(&device, vec![b]) };
+    assert!(matches!(relocations[..], [Relocation::Keep(_)]));
+
+    unsafe {
+        for block in allocator.commit_relocations(&device, relocations) {
+            allocator.dealloc(&device, block);
+        }
+        allocator.dealloc(&device, a);
+        allocator.dealloc(&device, c);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, DedicatedResource, DeviceProperties, ExternalHandleType,
+        ExternalMemory, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType, Request,
+        ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 1000,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    };
+
+    // Exportable block comes with what `cudaImportExternalMemory` needs.
+    let (block, external) =
+        unsafe { allocator.alloc_exportable(&device, request, ExternalHandleType::OpaqueFd) }?;
+    assert_eq!(
+        device.memory_allocation_flags(*block.memory()),
+        AllocationFlags::EXPORT_OPAQUE_FD
+    );
+    assert_eq!(
+        external,
+        ExternalMemory {
+            handle_type: ExternalHandleType::OpaqueFd,
+            memory_size: block.size(),
+            offset: 0,
+            size: block.size(),
+            dedicated: false,
+            resource_kind: ResourceKind::Linear,
+        }
+    );
+    assert!(external.size >= 1000);
+    assert_eq!(
+        block.external_memory(ExternalHandleType::OpaqueFd),
+        Some(external)
+    );
+    assert_eq!(block.external_memory(ExternalHandleType::OpaqueWin32), None);
+
+    // Memory dedicated to resource must be imported as dedicated.
+    let (dedicated, external) = unsafe {
+        allocator.alloc_exportable(
+            &device,
+            Request {
+                dedicated_resource: Some(DedicatedResource::Buffer(1)),
+                ..request
+            },
+            ExternalHandleType::OpaqueWin32,
+        )
+    }?;
+    assert!(external.dedicated);
+    assert_eq!(external.handle_type, ExternalHandleType::OpaqueWin32);
+
+    // Blocks of shared memory objects are not exportable.
+    let shared = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(shared.external_memory(ExternalHandleType::OpaqueFd), None);
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.dealloc(&device, dedicated);
+        allocator.dealloc(&device, shared);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use {
+    gpu_alloc::{
+        AllocationError, AllocationFlags, Config, DeviceMapError, DeviceProperties, GpuAllocator,
+        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory, Request, RequestFlags,
+        ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, mem::MaybeUninit},
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 100,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: !0,
+        memory_type: None,
+        access: None,
+        label: None,
+        dedicated: None,
+        dedicated_resource: None,
+        export: AllocationFlags::empty(),
+        priority: None,
+        flags: RequestFlags::empty(),
+        resource_kind: ResourceKind::Linear,
+    };
+
+    device.fail_allocation(1, OutOfMemory::OutOfHostMemory);
+    let result = unsafe { allocator.alloc(&device, request) };
+    assert_eq!(result.err(), Some(AllocationError::OutOfHostMemory));
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(device.heap_usage(0), 0);
+
+    device.fail_map(1, DeviceMapError::MapFailed);
+    let result = unsafe { allocator.alloc(&device, request) };
+    assert!(result.is_err());
+
+    let mut block = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(device.live_allocations(), 1);
+    assert!(device.heap_usage(0) >= block.size());
+
+    unsafe { block.write_bytes(&device, 10, &[1, 2, 3, 4]) }?;
+
+    let mut data = [0; 4];
+    unsafe { block.read_bytes(&device, 10, &mut data) }?;
+    assert_eq!(data, [1, 2, 3, 4]);
+
+    let mut uninit = [MaybeUninit::uninit(); 4];
+    let data = unsafe { block.read_bytes_uninit(&device, 10, &mut uninit) }?;
+    assert_eq!(data, [1, 2, 3, 4]);
+
+    unsafe { allocator.dealloc(&device, block) }
+    unsafe { allocator.cleanup(&device) }
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, RequestFlags, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 1000,
+        align_mask: 63,
+        usage: UsageFlags::UPLOAD | UsageFlags::DOWNLOAD,
+        memory_type: Some(0),
+        dedicated: Some(Dedicated::Required),
+        flags: RequestFlags::PERSISTENTLY_MAPPED,
+        ..Request::default()
+    };
+
+    let mut block = unsafe { allocator.alloc(&device, request) }?;
+
+    // Unaligned ranges are extended to atom boundaries.
+    // Mock device asserts that flushed ranges are aligned and mapped.
+    unsafe {
+        block.mapped_slice().unwrap()[10..20].copy_from_slice(&[1; 10]);
+        block.flush_range(&device, 10, 10)?;
+        block.invalidate_range(&device, 990, 10)?;
+    }
+    assert_eq!(device.total_flushes(), 1);
+    assert_eq!(device.total_invalidations(), 1);
+
+    // Ranges in host-coherent memory are never flushed.
+    let coherent = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                memory_type: Some(1),
+                ..request
+            },
+        )
+    }?;
+    unsafe {
+        coherent.flush_range(&device, 0, 1000)?;
+        coherent.invalidate_range(&device, 0, 1000)?;
+    }
+    assert_eq!(device.total_flushes(), 1);
+    assert_eq!(device.total_invalidations(), 1);
+
+    // Ranges can be flushed while block is mapped with `map`.
+    let mut shared = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                dedicated: None,
+                flags: RequestFlags::empty(),
+                ..request
+            },
+        )
+    }?;
+    unsafe {
+        let ptr = shared.map(&device, 100, 28)?;
+        ptr.as_ptr().write_bytes(7, 28);
+        shared.flush_range(&device, 100, 28)?;
+        shared.unmap(&device);
+    }
+    assert_eq!(device.total_flushes(), 2);
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.dealloc(&device, coherent);
+        allocator.dealloc(&device, shared);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::from_bits_truncate(
+                MemoryPropertyFlags::HOST_VISIBLE.bits()
+                    | MemoryPropertyFlags::HOST_COHERENT.bits(),
+            ),
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.frame_ring_size = 8192;
+    config.validate_deallocations = true;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 1000,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD,
+        ..Request::default()
+    };
+
+    allocator.begin_frame(0);
+    let mut first = unsafe { allocator.alloc_in_frame(&device, request) }?;
+    let second = unsafe { allocator.alloc_in_frame(&device, request) }?;
+    assert_eq!((first.offset(), second.offset()), (0, 1024));
+    unsafe { first.write_bytes(&device, 0, &[42; 16]) }?;
+
+    // Frame blocks may be passed to `dealloc` or simply dropped.
+    unsafe { allocator.dealloc(&device, first) };
+    drop(second);
+
+    allocator.begin_frame(1);
+    let mut blocks = Vec::new();
+    for i in 2..8 {
+        let block = unsafe { allocator.alloc_in_frame(&device, request) }?;
+        assert_eq!(block.offset(), i * 1024);
+        blocks.push(block);
+    }
+
+    // Ring is full until frame 0 is retired.
+    assert!(matches!(
+        unsafe { allocator.alloc_in_frame(&device, request) },
+        Err(AllocationError::OutOfDeviceMemory(_))
+    ));
+
+    allocator.end_frame(0);
+    let wrapped = unsafe { allocator.alloc_in_frame(&device, request) }?;
+    assert_eq!(wrapped.offset(), 0);
+    drop((blocks, wrapped));
+    allocator.end_frame(1);
+
+    // Many frames reuse single memory object.
+    for frame in 2..100 {
+        allocator.begin_frame(frame);
+        for _ in 0..3 {
+            unsafe { allocator.alloc_in_frame(&device, request) }?;
+        }
+        allocator.end_frame(frame - 1);
+    }
+    allocator.end_frame(99);
+    assert_eq!(device.live_allocations(), 1);
+
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    Ok(())
+}
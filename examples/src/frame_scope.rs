@@ -0,0 +1,67 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, FrameScope, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 32 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 32 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let mut scope = FrameScope::new();
+
+    let request = Request {
+        size: 256,
+        usage: UsageFlags::UPLOAD,
+        ..Request::default()
+    };
+
+    let mut kept = None;
+
+    for frame in 0..100 {
+        for _ in 0..16 {
+            let block = unsafe { scope.alloc(&mut allocator, &device, request) }?;
+            unsafe { block.write_bytes(&device, 0, &[frame as u8; 16]) }?;
+        }
+        assert_eq!(scope.blocks().len(), 16);
+
+        if frame == 50 {
+            kept = Some(scope.promote(3));
+        }
+
+        unsafe { scope.reset(&mut allocator, &device) };
+        assert!(scope.blocks().is_empty());
+    }
+
+    // Memory objects are reused across frames.
+    assert_eq!(device.total_allocations(), 1);
+
+    let mut kept = kept.unwrap();
+    let mut data = [0; 16];
+    unsafe { kept.read_bytes(&device, 0, &mut data) }?;
+    assert_eq!(data, [50; 16]);
+
+    unsafe { allocator.dealloc(&device, kept) };
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    Ok(())
+}
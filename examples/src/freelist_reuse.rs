@@ -0,0 +1,85 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let config = Config {
+        starting_free_list_chunk: 64 * 1024,
+        ..Config::i_am_potato()
+    };
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 4096,
+        align_mask: 255,
+        usage: UsageFlags::TRANSIENT,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..8 {
+        blocks.push(unsafe { allocator.alloc(&device, request) }?);
+    }
+    assert_eq!(device.total_allocations(), 1);
+
+    // Block freed in the middle of the chunk is reused right away.
+    let freed = blocks.remove(3);
+    let offset = freed.offset();
+    unsafe { allocator.dealloc(&device, freed) };
+
+    let block = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(block.offset(), offset);
+    blocks.insert(3, block);
+
+    // Neighbouring freed blocks are coalesced into a range
+    // that fits larger block.
+    let freed = blocks.drain(2..5).collect::<Vec<_>>();
+    let start = freed.iter().map(|block| block.offset()).min().unwrap();
+    for block in freed {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+
+    let large = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 3 * 4096,
+                ..request
+            },
+        )
+    }?;
+    assert_eq!(large.offset(), start);
+    assert_eq!(device.total_allocations(), 1);
+    blocks.push(large);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Freed ranges of free-list chunks are reused immediately");
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 256,
+        ..Request::default()
+    };
+
+    let first = unsafe { allocator.alloc(&device, request) }?;
+    let second = unsafe { allocator.alloc(&device, request) }?;
+    assert!(second.generation() > first.generation());
+    assert!(allocator.is_current(&first));
+    assert!(allocator.is_current(&second));
+
+    let (memory, offset, generation) = (*first.memory(), first.offset(), first.generation());
+    unsafe { allocator.dealloc(&device, first) };
+
+    // Range of deallocated block is reused by new block with new generation.
+    let reused = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!((*reused.memory(), reused.offset()), (memory, offset));
+    assert!(reused.generation() > second.generation());
+    assert_ne!(reused.generation(), generation);
+    assert!(allocator.is_current(&reused));
+
+    unsafe {
+        allocator.dealloc(&device, second);
+        allocator.dealloc(&device, reused);
+    }
+
+    Ok(())
+}
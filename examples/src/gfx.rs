@@ -6,7 +6,9 @@ use {
         queue::QueueFamily as _,
         Features, Instance as _,
     },
-    gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+    gpu_alloc::{
+        AllocationFlags, Config, GpuAllocator, Request, RequestFlags, ResourceKind, UsageFlags,
+    },
     gpu_alloc_gfx::{gfx_device_properties, GfxMemoryDevice},
 };
 
@@ -66,6 +68,15 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                memory_type: None,
+                access: None,
+                label: None,
+                dedicated: None,
+                dedicated_resource: None,
+                export: AllocationFlags::empty(),
+                priority: None,
+                flags: RequestFlags::empty(),
+                resource_kind: ResourceKind::Linear,
             },
         )
     }?;
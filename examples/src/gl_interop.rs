@@ -0,0 +1,93 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, DeviceProperties, ExternalHandleType, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, PoolConfig, PoolStrategy, Request, ResourceKind,
+        UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1024,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Memory objects of exportable pool are shared with OpenGL as a whole.
+    let pool = allocator.create_pool(PoolConfig {
+        label: Some("gl shared"),
+        strategy: PoolStrategy::Tlsf,
+        chunk_size: 1024 * 1024,
+        export: AllocationFlags::EXPORT_OPAQUE_FD,
+        ..PoolConfig::default()
+    });
+
+    let image = Request {
+        size: 64 * 1024,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        resource_kind: ResourceKind::NonLinear,
+        ..Request::default()
+    };
+
+    let a = unsafe { allocator.alloc_in_pool(&device, pool, image) }?;
+    let b = unsafe { allocator.alloc_in_pool(&device, pool, image) }?;
+    assert_eq!(a.memory(), b.memory());
+    assert_eq!(
+        device.memory_allocation_flags(*a.memory()),
+        AllocationFlags::EXPORT_OPAQUE_FD
+    );
+
+    // Both textures are created in the same imported memory object at their offsets.
+    let external_a = a.external_memory(ExternalHandleType::OpaqueFd).unwrap();
+    let external_b = b.external_memory(ExternalHandleType::OpaqueFd).unwrap();
+    assert_eq!(external_a.memory_size, 1024 * 1024);
+    assert_eq!(external_b.memory_size, 1024 * 1024);
+    assert_eq!(external_a.offset, a.offset());
+    assert_eq!(external_b.offset, b.offset());
+    assert_ne!(external_a.offset, external_b.offset);
+    assert!(!external_a.dedicated);
+
+    // Texture tiling must match tiling of Vulkan image.
+    assert_eq!(external_a.resource_kind, ResourceKind::NonLinear);
+    assert_eq!(a.external_memory(ExternalHandleType::OpaqueWin32), None);
+
+    // Blocks of regular pools are not exportable.
+    let regular = allocator.create_pool(PoolConfig {
+        chunk_size: 1024 * 1024,
+        ..PoolConfig::default()
+    });
+    let c = unsafe { allocator.alloc_in_pool(&device, regular, image) }?;
+    assert_eq!(c.external_memory(ExternalHandleType::OpaqueFd), None);
+    assert_eq!(
+        device.memory_allocation_flags(*c.memory()),
+        AllocationFlags::empty()
+    );
+
+    unsafe {
+        allocator.dealloc(&device, a);
+        allocator.dealloc(&device, b);
+        allocator.dealloc(&device, c);
+        assert!(allocator.destroy_pool(&device, pool));
+        assert!(allocator.destroy_pool(&device, regular));
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
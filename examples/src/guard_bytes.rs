@@ -0,0 +1,71 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.guard_size = 16;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = |size, usage, label| Request {
+        size,
+        align_mask: 63,
+        usage: UsageFlags::HOST_ACCESS | usage,
+        label: Some(label),
+        ..Request::default()
+    };
+
+    // Writes within the block leave guards intact.
+    let mut good = unsafe { allocator.alloc(&device, request(1000, UsageFlags::empty(), "good")) }?;
+    assert_eq!(good.offset() % 64, 0);
+    assert!(good.size() >= 1000);
+    unsafe { good.write_bytes(&device, 0, &vec![0xFF; good.size() as usize]) }?;
+    unsafe { allocator.dealloc(&device, good) };
+    assert!(allocator.take_guard_corruptions().is_empty());
+
+    // Writes past the end of the block are detected.
+    for &usage in &[UsageFlags::empty(), UsageFlags::TRANSIENT] {
+        let mut bad = unsafe { allocator.alloc(&device, request(100, usage, "overrun")) }?;
+        let size = bad.size();
+        unsafe {
+            let ptr = bad.map(&device, 0, size as usize)?;
+            // Simulate off-by-one write.
+            ptr.as_ptr().add(size as usize).write(0);
+            bad.unmap(&device);
+            allocator.dealloc(&device, bad);
+        }
+
+        let corruptions = allocator.take_guard_corruptions();
+        assert_eq!(corruptions.len(), 1);
+        assert_eq!(corruptions[0].label, Some("overrun"));
+        assert_eq!(corruptions[0].size, size);
+    }
+
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Guard corruption detected");
+    Ok(())
+}
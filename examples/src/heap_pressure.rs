@@ -0,0 +1,94 @@
+use {
+    gpu_alloc::{
+        AllocationObserver, Config, Dedicated, DeviceProperties, GpuAllocator, HeapPressure,
+        MemoryHeap, MemoryPropertyFlags, MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{
+        borrow::Cow,
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Default)]
+struct Pressure {
+    events: Mutex<Vec<HeapPressure>>,
+}
+
+impl AllocationObserver for Pressure {
+    fn on_heap_pressure(&self, pressure: &HeapPressure) {
+        self.events.lock().unwrap().push(*pressure);
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+    device.set_memory_budget(0, 1024 * 1024);
+
+    let pressure = Arc::new(Pressure::default());
+
+    let mut config = Config::i_am_potato();
+    config.observer = Some(pressure.clone());
+    config.pressure_thresholds = vec![95, 80];
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 128 * 1024,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..8 {
+        blocks.push(unsafe {
+            allocator.alloc_with_dedicated(&device, request, Dedicated::Required)?
+        });
+    }
+
+    // Thresholds are reported as budget usage rises: 7 blocks reach 87.5%, 8 blocks reach 100%.
+    let events = pressure.events.lock().unwrap().clone();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].threshold, Some(80));
+    assert_eq!(events[0].previous, None);
+    assert_eq!(events[0].used, 7 * 128 * 1024);
+    assert_eq!(events[0].budget, 1024 * 1024);
+    assert_eq!(events[1].threshold, Some(95));
+    assert_eq!(events[1].previous, Some(80));
+
+    // And as it falls.
+    unsafe {
+        for block in blocks.drain(..4) {
+            allocator.dealloc(&device, block);
+        }
+    }
+    let events = pressure.events.lock().unwrap().clone();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[2].threshold, Some(80));
+    assert_eq!(events[2].previous, Some(95));
+    assert_eq!(events[3].threshold, None);
+    assert_eq!(events[3].previous, Some(80));
+    assert!(events.iter().all(|event| event.heap == 0));
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+    assert_eq!(pressure.events.lock().unwrap().len(), 4);
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+const SPARSE_ALIGN: u64 = 64 * 1024;
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    // Buddy, free-list and TLSF, each with alignment exceeding their chunks.
+    for &(usage, tlsf) in &[
+        (UsageFlags::HOST_ACCESS, false),
+        (UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT, false),
+        (UsageFlags::HOST_ACCESS, true),
+    ] {
+        let mut config = Config::i_am_potato();
+        config.tlsf_memory_types = if tlsf { 1 } else { 0 };
+        let mut allocator = GpuAllocator::new(config, device.props());
+
+        let mut blocks = Vec::new();
+        for _ in 0..8 {
+            let block = unsafe {
+                allocator.alloc(
+                    &device,
+                    Request {
+                        size: 4000,
+                        align_mask: SPARSE_ALIGN - 1,
+                        usage,
+                        ..Request::default()
+                    },
+                )
+            }?;
+            assert_eq!(block.offset() % SPARSE_ALIGN, 0);
+            assert!(block.size() >= 4000);
+            if usage.contains(UsageFlags::TRANSIENT) || tlsf {
+                // Linear allocators don't hand padding out with blocks.
+                assert!(block.size() < SPARSE_ALIGN);
+            }
+            blocks.push(block);
+        }
+
+        // Padding is trimmed, so blocks don't take more than aligned stride.
+        assert!(device.heap_usage(0) <= 8 * SPARSE_ALIGN);
+
+        for block in blocks {
+            unsafe { allocator.dealloc(&device, block) };
+        }
+        unsafe { allocator.cleanup(&device) };
+        assert_eq!(device.live_allocations(), 0);
+    }
+
+    // Global minimum alignment applies to every request.
+    let mut config = Config::i_am_potato();
+    config.min_alignment = 4096;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let mut blocks = Vec::new();
+    for &usage in &[
+        UsageFlags::HOST_ACCESS,
+        UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT,
+    ] {
+        for &size in &[1, 100, 5000] {
+            let block = unsafe {
+                allocator.alloc(
+                    &device,
+                    Request {
+                        size,
+                        align_mask: 0,
+                        usage,
+                        ..Request::default()
+                    },
+                )
+            }?;
+            assert_eq!(block.offset() % 4096, 0);
+            blocks.push(block);
+        }
+    }
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Blocks are aligned beyond alignment of chunks");
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, ImportError, MemoryHeap, MemoryPropertyFlags,
+        MemoryType,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{alloc::Layout, borrow::Cow, ptr::NonNull},
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap { size: 1024 * 1024 },
+            MemoryHeap { size: 1024 * 1024 },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let layout = Layout::from_size_align(16384, 4096)?;
+    let arena = NonNull::new(unsafe { std::alloc::alloc_zeroed(layout) }).unwrap();
+
+    let misaligned = NonNull::new(unsafe { arena.as_ptr().add(64) }).unwrap();
+    let result = unsafe { allocator.import_host_ptr(&device, misaligned, 8192, 1) };
+    assert_eq!(result.err(), Some(ImportError::Misaligned));
+
+    let result = unsafe { allocator.import_host_ptr(&device, arena, 1000, 1) };
+    assert_eq!(result.err(), Some(ImportError::Misaligned));
+
+    let result = unsafe { allocator.import_host_ptr(&device, arena, 8192, 0) };
+    assert_eq!(result.err(), Some(ImportError::IncompatibleMemoryType));
+    assert_eq!(device.live_allocations(), 0);
+
+    let mut block = unsafe { allocator.import_host_ptr(&device, arena, 8192, 1) }?;
+    assert_eq!(device.live_allocations(), 1);
+    assert_eq!(allocator.allocation_count(), 1);
+
+    // Mapping returns original host pointer.
+    let ptr = unsafe { block.map(&device, 128, 16) }?;
+    assert_eq!(ptr.as_ptr(), unsafe { arena.as_ptr().add(128) });
+    unsafe { block.unmap(&device) };
+
+    unsafe { block.write_bytes(&device, 0, &[7; 32]) }?;
+    assert_eq!(unsafe { *arena.as_ptr().add(31) }, 7);
+
+    unsafe { allocator.dealloc(&device, block) };
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(allocator.allocation_count(), 0);
+
+    unsafe { std::alloc::dealloc(arena.as_ptr(), layout) };
+
+    Ok(())
+}
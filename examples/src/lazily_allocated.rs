@@ -0,0 +1,86 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::DEVICE_LOCAL | MemoryPropertyFlags::LAZILY_ALLOCATED,
+            },
+            MemoryType {
+                heap: 2,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap { size: 1024 * 1024 },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let normal = Request {
+        size: 256 * 1024,
+        align_mask: 255,
+        ..Request::default()
+    };
+
+    let attachment = Request {
+        usage: UsageFlags::TRANSIENT_ATTACHMENT,
+        ..normal
+    };
+
+    // Lazily allocated memory type is skipped for other requests.
+    let a = unsafe { allocator.alloc(&device, normal) }?;
+    assert_eq!(a.memory_type(), 0);
+
+    // Transient attachments prefer lazily allocated memory type.
+    let b = unsafe { allocator.alloc(&device, attachment) }?;
+    assert_eq!(b.memory_type(), 1);
+
+    // And fall back to device-local memory type when it is exhausted.
+    let c = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 2 * 1024 * 1024,
+                ..attachment
+            },
+        )
+    }?;
+    assert_eq!(c.memory_type(), 0);
+
+    unsafe {
+        allocator.dealloc(&device, a);
+        allocator.dealloc(&device, b);
+        allocator.dealloc(&device, c);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, Strategy, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let vertices = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1024,
+                label: Some("vertices"),
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let staging = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 256,
+                usage: UsageFlags::TRANSIENT,
+                label: Some("staging"),
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let anonymous = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 512,
+                ..Request::default()
+            },
+        )
+    }?;
+
+    unsafe { allocator.dealloc(&device, anonymous) };
+
+    let leaks = allocator.report_leaks();
+    assert_eq!(leaks.len(), 2);
+    assert_eq!(leaks[0].label, Some("vertices"));
+    assert_eq!(leaks[0].strategy, Strategy::Buddy);
+    assert_eq!(leaks[0].size, vertices.size());
+    assert_eq!(leaks[1].label, Some("staging"));
+    assert_eq!(leaks[1].strategy, Strategy::FreeList);
+    assert_eq!(leaks[1].memory_type, staging.memory_type());
+
+    for leak in &leaks {
+        println!(
+            "Leaked `{}` block of {} bytes",
+            leak.label.unwrap_or("<unnamed>"),
+            leak.size
+        );
+    }
+
+    unsafe {
+        allocator.dealloc(&device, vertices);
+        allocator.dealloc(&device, staging);
+        allocator.cleanup(&device);
+    }
+
+    assert!(allocator.report_leaks().is_empty());
+    assert_eq!(device.live_allocations(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! Allocator over fake device that carves memory objects out of single host buffer,
+//! the way wasm linear memory backs WebGPU buffers in browser builds.
+//! Handles are 32-bit offsets, so it also checks mapping math on 32-bit hosts.
+
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, Dedicated, DeviceMapError, DeviceProperties, GpuAllocator,
+        MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        Request, UsageFlags,
+    },
+    std::{borrow::Cow, cell::RefCell, convert::TryFrom as _, ptr::NonNull},
+};
+
+/// Memory object within linear memory.
+#[derive(Debug, PartialEq, Eq)]
+struct LinearMemory {
+    offset: u32,
+    size: u32,
+}
+
+struct LinearMemoryDevice {
+    memory: Box<[u8]>,
+    free: RefCell<Vec<(u32, u32)>>,
+}
+
+const OBJECT_ALIGN: u32 = 256;
+
+impl LinearMemoryDevice {
+    fn new(capacity: u32) -> Self {
+        LinearMemoryDevice {
+            memory: vec![0; capacity as usize].into_boxed_slice(),
+            free: RefCell::new(vec![(0, capacity)]),
+        }
+    }
+
+    fn capacity(&self) -> u32 {
+        self.memory.len() as u32
+    }
+
+    fn base(&self) -> *mut u8 {
+        self.memory.as_ptr() as *mut u8
+    }
+
+    fn props(&self) -> DeviceProperties<'static> {
+        DeviceProperties {
+            memory_types: Cow::Owned(vec![MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT,
+            }]),
+            memory_heaps: Cow::Owned(vec![MemoryHeap {
+                size: u64::from(self.capacity()),
+            }]),
+            max_memory_allocation_count: 1024,
+            max_memory_allocation_size: u64::from(self.capacity()),
+            non_coherent_atom_size: 64,
+            buffer_device_address: false,
+            buffer_image_granularity: 1,
+        }
+    }
+}
+
+impl MemoryDevice<LinearMemory> for LinearMemoryDevice {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        _memory_type: u32,
+        _flags: AllocationFlags,
+    ) -> Result<LinearMemory, OutOfMemory> {
+        let size = u32::try_from(size).map_err(|_| OutOfMemory::OutOfDeviceMemory)?;
+        let aligned = size
+            .checked_add(OBJECT_ALIGN - 1)
+            .ok_or(OutOfMemory::OutOfDeviceMemory)?
+            & !(OBJECT_ALIGN - 1);
+
+        let mut free = self.free.borrow_mut();
+        let index = free
+            .iter()
+            .position(|&(_, len)| len >= aligned)
+            .ok_or(OutOfMemory::OutOfDeviceMemory)?;
+
+        let (offset, len) = free[index];
+        if len == aligned {
+            free.remove(index);
+        } else {
+            free[index] = (offset + aligned, len - aligned);
+        }
+
+        Ok(LinearMemory { offset, size })
+    }
+
+    unsafe fn deallocate_memory(&self, memory: LinearMemory) {
+        let aligned = (memory.size + OBJECT_ALIGN - 1) & !(OBJECT_ALIGN - 1);
+
+        let mut free = self.free.borrow_mut();
+        free.push((memory.offset, aligned));
+        free.sort_unstable();
+
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(free.len());
+        for &(offset, len) in free.iter() {
+            match merged.last_mut() {
+                Some((last, last_len)) if *last + *last_len == offset => *last_len += len,
+                _ => merged.push((offset, len)),
+            }
+        }
+        *free = merged;
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut LinearMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        assert!(
+            offset + size <= u64::from(memory.size),
+            "Mapped range is out of memory object bounds"
+        );
+
+        let offset = memory.offset as usize + offset as usize;
+        Ok(NonNull::new_unchecked(self.base().add(offset)))
+    }
+
+    unsafe fn unmap_memory(&self, _memory: &mut LinearMemory) {}
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, LinearMemory>],
+    ) -> Result<(), OutOfMemory> {
+        Ok(())
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, LinearMemory>],
+    ) -> Result<(), OutOfMemory> {
+        Ok(())
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = LinearMemoryDevice::new(64 * 1024 * 1024);
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let sizes = [
+        (16, None),
+        (100, None),
+        (3000, None),
+        (5000, None),
+        (20000, None),
+        (70000, None),
+        (1024 * 1024, None),
+        (2000, Some(Dedicated::Required)),
+        (300, Some(Dedicated::Preferred)),
+    ];
+
+    let mut blocks = Vec::new();
+    for (round, &(size, dedicated)) in sizes.iter().cycle().take(64).enumerate() {
+        let request = Request {
+            size,
+            align_mask: 15,
+            usage: if round % 3 == 0 {
+                UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT
+            } else {
+                UsageFlags::HOST_ACCESS
+            },
+            ..Request::default()
+        };
+
+        let mut block = match dedicated {
+            None => unsafe { allocator.alloc(&device, request) }?,
+            Some(dedicated) => {
+                unsafe { allocator.alloc_with_dedicated(&device, request, dedicated) }?
+            }
+        };
+
+        // Pointer returned by mapping must match block placement in linear memory.
+        let map_offset = block.size() - 1;
+        let ptr = unsafe { block.map(&device, map_offset, 1) }?;
+        let expected = device.base() as usize
+            + block.memory().offset as usize
+            + (block.offset() + map_offset) as usize;
+        assert_eq!(ptr.as_ptr() as usize, expected);
+        unsafe { block.unmap(&device) };
+
+        let (shared, guard) = unsafe { block.map_shared(&device, 0, 1) }?;
+        assert_eq!(
+            shared.as_ptr() as usize,
+            expected - map_offset as usize,
+            "Shared mapping must point to block start"
+        );
+        drop(guard);
+
+        let fill = vec![round as u8; size as usize];
+        unsafe { block.write_bytes(&device, 0, &fill) }?;
+
+        blocks.push(block);
+    }
+
+    for (round, block) in blocks.iter_mut().enumerate() {
+        let mut data = vec![0; block.size() as usize];
+        unsafe { block.read_bytes(&device, 0, &mut data[..]) }?;
+        let size = sizes[round % sizes.len()].0 as usize;
+        assert!(
+            data[..size].iter().all(|&byte| byte == round as u8),
+            "Block {} was overwritten by neighbor",
+            round
+        );
+    }
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+
+    assert_eq!(*device.free.borrow(), [(0, device.capacity())]);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, Strategy, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let mut blocks = Vec::new();
+    for &(label, size, usage) in &[
+        ("vertices", 1000, UsageFlags::FAST_DEVICE_ACCESS),
+        ("staging", 256, UsageFlags::TRANSIENT),
+        (
+            "shadow map",
+            4 * 1024 * 1024,
+            UsageFlags::FAST_DEVICE_ACCESS,
+        ),
+    ] {
+        blocks.push(unsafe {
+            allocator.alloc(
+                &device,
+                Request {
+                    size,
+                    usage,
+                    label: Some(label),
+                    ..Request::default()
+                },
+            )
+        }?);
+    }
+
+    let live = allocator.report_live_allocations();
+    assert_eq!(live.len(), 3);
+    assert_eq!(live[0].label, Some("vertices"));
+    assert_eq!(live[0].strategy, Strategy::Buddy);
+    assert_eq!(live[0].size, blocks[0].size());
+    assert_eq!(live[0].padding, blocks[0].size() - 1000);
+    assert_eq!(live[1].strategy, Strategy::FreeList);
+    assert!(live[1].usage.contains(UsageFlags::TRANSIENT));
+    assert_eq!(live[2].strategy, Strategy::Dedicated);
+    assert_eq!(live[2].offset, blocks[2].offset());
+
+    for allocation in &live {
+        println!(
+            "`{}`: {} bytes of memory type {} from {:?}",
+            allocation.label.unwrap_or("<unnamed>"),
+            allocation.size,
+            allocation.memory_type,
+            allocation.strategy,
+        );
+    }
+
+    // Listing follows deallocations.
+    let block = blocks.remove(1);
+    unsafe { allocator.dealloc(&device, block) };
+    let live = allocator.report_live_allocations();
+    assert_eq!(live.len(), 2);
+    assert_eq!(live[1].label, Some("shadow map"));
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert!(allocator.report_live_allocations().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let mut block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1024,
+                align_mask: 63,
+                usage: UsageFlags::UPLOAD | UsageFlags::DOWNLOAD,
+                dedicated: Some(Dedicated::Required),
+                ..Request::default()
+            },
+        )
+    }?;
+
+    // Written guard is flushed and unmapped on drop.
+    let flushes = device.total_flushes();
+    {
+        let mut guard = unsafe { block.map_guard(&device, 16, 32) }?;
+        assert_eq!(device.live_mappings(), 1);
+        guard.copy_from_slice(&[7; 32]);
+    }
+    assert_eq!(device.live_mappings(), 0);
+    assert_eq!(device.total_flushes(), flushes + 1);
+
+    // Guard that was only read from is not flushed.
+    {
+        let guard = unsafe { block.map_guard(&device, 0, 64) }?;
+        assert_eq!(guard[..16], [0; 16]);
+        assert_eq!(guard[16..48], [7; 32]);
+    }
+    assert_eq!(device.live_mappings(), 0);
+    assert_eq!(device.total_flushes(), flushes + 1);
+
+    // Flush errors can be observed with `finish`.
+    let mut guard = unsafe { block.map_guard(&device, 512, 8) }?;
+    guard[0] = 42;
+    guard.finish()?;
+    assert_eq!(device.total_flushes(), flushes + 2);
+
+    let mut byte = [0];
+    unsafe { block.read_bytes(&device, 512, &mut byte) }?;
+    assert_eq!(byte, [42]);
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use {
+    gpu_alloc::{
+        AllocationError, BudgetPolicy, Config, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn device() -> MockMemoryDevice {
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 32 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 32 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 32 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    // Heap is not exhausted, but its budget is.
+    device.set_memory_budget(0, 1024);
+    device
+}
+
+fn alloc_with(policy: BudgetPolicy) -> Result<u32, AllocationError> {
+    let device = device();
+    let mut config = Config::i_am_potato();
+    config.budget_policy = policy;
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+    let block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 4096,
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let memory_type = block.memory_type();
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+    Ok(memory_type)
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    // Over-budget heap is skipped when budget must be respected.
+    assert_eq!(alloc_with(BudgetPolicy::Fail)?, 1);
+
+    // Otherwise heap is still used.
+    assert_eq!(alloc_with(BudgetPolicy::Warn)?, 0);
+    assert_eq!(alloc_with(BudgetPolicy::Ignore)?, 0);
+
+    // No heap within budget.
+    let device = device();
+    device.set_memory_budget(1, 1024);
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let result = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 4096,
+                ..Request::default()
+            },
+        )
+    };
+    assert!(matches!(result, Err(AllocationError::OutOfDeviceMemory(_))));
+    assert_eq!(device.total_allocations(), 0);
+
+    // Budget tracks usage reported by device.
+    device.set_memory_budget(1, 64 * 1024 * 1024);
+    let block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 4096,
+                ..Request::default()
+            },
+        )
+    }?;
+    assert_eq!(block.memory_type(), 1);
+    unsafe { allocator.dealloc(&device, block) };
+
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Budget is respected");
+    Ok(())
+}
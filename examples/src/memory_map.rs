@@ -0,0 +1,84 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let mut blocks = Vec::new();
+    for i in 0..48u64 {
+        let usage = if i % 4 == 0 {
+            UsageFlags::TRANSIENT
+        } else {
+            UsageFlags::empty()
+        };
+        blocks.push(unsafe {
+            allocator.alloc(
+                &device,
+                Request {
+                    size: 300 + i * 41,
+                    usage,
+                    ..Request::default()
+                },
+            )
+        }?);
+    }
+
+    // Scatter free memory.
+    let mut kept = Vec::new();
+    for (i, block) in blocks.into_iter().enumerate() {
+        if i % 3 == 0 {
+            kept.push(block);
+        } else {
+            unsafe { allocator.dealloc(&device, block) };
+        }
+    }
+
+    let svg = allocator.dump_memory_map();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+
+    let snapshot = allocator.snapshot();
+    assert_eq!(svg.matches(">block ").count(), snapshot.blocks.len());
+    assert_eq!(
+        svg.matches(">free ").count(),
+        snapshot
+            .chunks
+            .iter()
+            .map(|chunk| chunk.free_regions.len())
+            .sum::<usize>()
+    );
+
+    let path = std::env::temp_dir().join("gpu-alloc-memory-map.svg");
+    std::fs::write(&path, &svg)?;
+    println!("Memory map written to {}", path.display());
+
+    for block in kept {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+
+    Ok(())
+}
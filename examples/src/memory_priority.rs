@@ -0,0 +1,130 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let plain = Request {
+        size: 256,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    };
+
+    // Requests without priority never use priority.
+    let a = unsafe { allocator.alloc_with_dedicated(&device, plain, Dedicated::Required) }?;
+    assert_eq!(device.memory_priority(*a.memory()), None);
+
+    // Dedicated memory object gets priority of its request.
+    let streaming = Request {
+        priority: Some(0.25),
+        dedicated: Some(Dedicated::Required),
+        ..plain
+    };
+    let b = unsafe { allocator.alloc(&device, streaming) }?;
+    assert_eq!(device.memory_priority(*b.memory()), Some(0.25));
+
+    let render_target = Request {
+        priority: Some(1.0),
+        ..streaming
+    };
+    let c = unsafe { allocator.alloc(&device, render_target) }?;
+    assert_eq!(device.memory_priority(*c.memory()), Some(1.0));
+
+    // Shared memory objects get highest priority of their class.
+    let high = Request {
+        size: 3 * 1024,
+        priority: Some(0.7),
+        ..plain
+    };
+    let d = unsafe { allocator.alloc(&device, high) }?;
+    assert_eq!(device.memory_priority(*d.memory()), Some(0.75));
+
+    // Blocks of the same class share memory objects.
+    let similar = Request {
+        priority: Some(0.6),
+        ..high
+    };
+    let e = unsafe { allocator.alloc(&device, similar) }?;
+    assert_eq!(e.memory(), d.memory());
+
+    // Blocks of lower class don't raise priority of shared memory objects
+    // and don't get higher priority from them.
+    let low = Request {
+        priority: Some(0.0),
+        ..high
+    };
+    let f = unsafe { allocator.alloc(&device, low) }?;
+    assert_ne!(f.memory(), d.memory());
+    assert_eq!(device.memory_priority(*f.memory()), Some(0.0));
+
+    // Neither do blocks without priority.
+    let g = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                priority: None,
+                ..high
+            },
+        )
+    }?;
+    assert_ne!(g.memory(), d.memory());
+    assert_ne!(g.memory(), f.memory());
+    assert_eq!(device.memory_priority(*g.memory()), None);
+
+    // Failed request doesn't change priority of memory objects allocated later.
+    let too_large = Request {
+        size: 1024 * 1024 * 1024,
+        priority: Some(1.0),
+        ..high
+    };
+    assert!(unsafe { allocator.alloc(&device, too_large) }.is_err());
+    let h = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 8 * 1024,
+                ..low
+            },
+        )
+    }?;
+    assert_eq!(device.memory_priority(*h.memory()), Some(0.0));
+
+    unsafe {
+        allocator.dealloc(&device, a);
+        allocator.dealloc(&device, b);
+        allocator.dealloc(&device, c);
+        allocator.dealloc(&device, d);
+        allocator.dealloc(&device, e);
+        allocator.dealloc(&device, f);
+        allocator.dealloc(&device, g);
+        allocator.dealloc(&device, h);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
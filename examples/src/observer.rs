@@ -0,0 +1,120 @@
+use {
+    gpu_alloc::{
+        AllocationObserver, ChunkInfo, Config, Dedicated, DeviceProperties, GpuAllocator,
+        MemoryBlockInfo, MemoryHeap, MemoryPropertyFlags, MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, sync::Arc, sync::Mutex},
+};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Alloc(Option<String>, u64, u64),
+    Dealloc(Option<String>, u64, u64),
+    ChunkCreate(u64),
+    ChunkDestroy(u64),
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<Event>>,
+}
+
+impl AllocationObserver for Recorder {
+    fn on_alloc(&self, info: &MemoryBlockInfo<'_>) {
+        let label = info.label.map(String::from);
+        self.events
+            .lock()
+            .unwrap()
+            .push(Event::Alloc(label, info.size, info.generation));
+    }
+
+    fn on_dealloc(&self, info: &MemoryBlockInfo<'_>) {
+        let label = info.label.map(String::from);
+        self.events
+            .lock()
+            .unwrap()
+            .push(Event::Dealloc(label, info.size, info.generation));
+    }
+
+    fn on_chunk_create(&self, info: &ChunkInfo) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(Event::ChunkCreate(info.size));
+    }
+
+    fn on_chunk_destroy(&self, info: &ChunkInfo) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(Event::ChunkDestroy(info.size));
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let recorder = Arc::new(Recorder::default());
+
+    let mut config = Config::i_am_potato();
+    config.observer = Some(recorder.clone());
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 256,
+        label: Some("mesh"),
+        ..Request::default()
+    };
+
+    let shared = unsafe { allocator.alloc(&device, request) }?;
+    let dedicated = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                label: None,
+                ..request
+            },
+            Dedicated::Required,
+        )
+    }?;
+
+    // Generations pair allocation and deallocation events of one block.
+    let generations = (shared.generation(), dedicated.generation());
+    assert_ne!(generations.0, generations.1);
+
+    unsafe {
+        allocator.dealloc(&device, shared);
+        allocator.dealloc(&device, dedicated);
+    }
+
+    let chunk_size = Config::i_am_potato().initial_buddy_dedicated_size;
+
+    assert_eq!(
+        *recorder.events.lock().unwrap(),
+        [
+            Event::ChunkCreate(chunk_size),
+            Event::Alloc(Some("mesh".into()), 256, generations.0),
+            Event::Alloc(None, 256, generations.1),
+            Event::Dealloc(Some("mesh".into()), 256, generations.0),
+            Event::ChunkDestroy(chunk_size),
+            Event::Dealloc(None, 256, generations.1),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, OutOfMemoryDetails, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+const MB: u64 = 1024 * 1024;
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 16 * MB }, MemoryHeap { size: 8 * MB }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * MB,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 12 * MB,
+        ..Request::default()
+    };
+    let block = unsafe { allocator.alloc_with_dedicated(&device, request, Dedicated::Required) }?;
+    assert_eq!(block.memory_type(), 0);
+
+    // Neither heap can fit another block of that size.
+    let err = unsafe { allocator.alloc_with_dedicated(&device, request, Dedicated::Required) }
+        .unwrap_err();
+    assert_eq!(
+        err,
+        AllocationError::OutOfDeviceMemory(Some(OutOfMemoryDetails {
+            memory_type: 1,
+            heap: 1,
+            size: 12 * MB,
+            heap_usage: 0,
+            heap_size: 8 * MB,
+            fallbacks_attempted: true,
+        }))
+    );
+    println!("{}", err);
+
+    // Only requested memory type is tried.
+    let err = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                memory_type: Some(0),
+                ..request
+            },
+            Dedicated::Required,
+        )
+    }
+    .unwrap_err();
+    assert_eq!(
+        err,
+        AllocationError::OutOfDeviceMemory(Some(OutOfMemoryDetails {
+            memory_type: 0,
+            heap: 0,
+            size: 12 * MB,
+            heap_usage: 12 * MB,
+            heap_size: 16 * MB,
+            fallbacks_attempted: false,
+        }))
+    );
+    println!("{}", err);
+
+    unsafe { allocator.dealloc(&device, block) };
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, DeviceMapError, DeviceProperties, GpuAllocator, MapError,
+        MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, cell::RefCell, collections::HashMap, ptr::NonNull},
+};
+
+const ATOM: u64 = 64;
+
+/// Wraps mock device to model non-coherent memory.
+///
+/// Host writes reach device copy of memory object only when flushed,
+/// and invalidation overwrites host copy with device copy.
+struct NonCoherentDevice {
+    inner: MockMemoryDevice,
+    /// Device copy of each memory object and host pointer to its start while mapped.
+    copies: RefCell<HashMap<usize, (Vec<u8>, *mut u8)>>,
+}
+
+impl NonCoherentDevice {
+    /// Writes `byte` to device copy of memory, as if written by a command.
+    fn device_write(&self, memory: usize, offset: u64, byte: u8) {
+        self.copies.borrow_mut().get_mut(&memory).unwrap().0[offset as usize] = byte;
+    }
+
+    fn device_read(&self, memory: usize, offset: u64) -> u8 {
+        self.copies.borrow()[&memory].0[offset as usize]
+    }
+}
+
+impl MemoryDevice<usize> for NonCoherentDevice {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<usize, OutOfMemory> {
+        let memory = self.inner.allocate_memory(size, memory_type, flags)?;
+        self.copies
+            .borrow_mut()
+            .insert(memory, (vec![0; size as usize], std::ptr::null_mut()));
+        Ok(memory)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: usize) {
+        self.copies.borrow_mut().remove(&memory);
+        self.inner.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut usize,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        let ptr = self.inner.map_memory(memory, offset, size)?;
+        self.copies.borrow_mut().get_mut(memory).unwrap().1 = ptr.as_ptr().sub(offset as usize);
+        Ok(ptr)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut usize) {
+        self.copies.borrow_mut().get_mut(memory).unwrap().1 = std::ptr::null_mut();
+        self.inner.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        let copies = self.copies.borrow();
+        for range in ranges {
+            let (copy, host) = &copies[range.memory];
+            let offset = range.offset as usize;
+            std::ptr::copy_nonoverlapping(
+                copy[offset..].as_ptr(),
+                host.add(offset),
+                range.size as usize,
+            );
+        }
+        self.inner.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        let mut copies = self.copies.borrow_mut();
+        for range in ranges {
+            let (copy, host) = copies.get_mut(range.memory).unwrap();
+            let offset = range.offset as usize;
+            std::ptr::copy_nonoverlapping(
+                host.add(offset),
+                copy[offset..].as_mut_ptr(),
+                range.size as usize,
+            );
+        }
+        self.inner.flush_memory_ranges(ranges)
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = NonCoherentDevice {
+        inner: MockMemoryDevice::new(DeviceProperties {
+            memory_types: Cow::Borrowed(&[MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            }]),
+            memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+            max_memory_allocation_count: 32,
+            max_memory_allocation_size: 1024 * 1024,
+            non_coherent_atom_size: ATOM,
+            buffer_device_address: false,
+            buffer_image_granularity: 1,
+        }),
+        copies: RefCell::new(HashMap::new()),
+    };
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.inner.props());
+
+    let mut block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 4 * ATOM,
+                usage: UsageFlags::HOST_ACCESS,
+                ..Request::default()
+            },
+        )
+    }?;
+    let memory = *block.memory();
+    let base = block.offset();
+    let ptr = block
+        .mapped_ptr()
+        .expect("Sub-allocated host-visible block is persistently mapped");
+
+    // Another user of the block writes through persistent pointer and flushes later.
+    // Plain write to the same atom flushes that write along.
+    unsafe { ptr.as_ptr().write(1) };
+    unsafe { block.write_bytes(&device, 8, &[2; 4]) }?;
+    assert_eq!(device.device_read(memory, base), 1);
+    assert_eq!(device.device_read(memory, base + 8), 2);
+
+    // Plain write overwrites device writes to the rest of partially written atom
+    // with stale host copy.
+    device.device_write(memory, base + ATOM + 6, 7);
+    unsafe { block.write_bytes(&device, ATOM + 8, &[3; 4]) }?;
+    assert_eq!(device.device_read(memory, base + ATOM + 6), 0);
+
+    // Preserving write invalidates the atom first and keeps device writes.
+    device.device_write(memory, base + ATOM + 6, 7);
+    unsafe { block.write_bytes_preserving(&device, ATOM + 8, &[3; 4]) }?;
+    assert_eq!(device.device_read(memory, base + ATOM + 6), 7);
+    assert_eq!(device.device_read(memory, base + ATOM + 8), 3);
+
+    // But it discards unflushed host writes to the atom made through other pointers.
+    unsafe { ptr.as_ptr().add(2 * ATOM as usize + 2).write(5) };
+    unsafe { block.write_bytes_preserving(&device, 2 * ATOM + 8, &[4; 4]) }?;
+    assert_eq!(device.device_read(memory, base + 2 * ATOM + 2), 0);
+    assert_eq!(unsafe { ptr.as_ptr().add(2 * ATOM as usize + 2).read() }, 0);
+
+    // Range mapped with `map` that shares partially written atom is not discarded,
+    // preserving write is refused instead.
+    unsafe { block.map(&device, 3 * ATOM, 4) }?;
+    assert_eq!(
+        unsafe { block.write_bytes_preserving(&device, 3 * ATOM + 8, &[5; 4]) }.err(),
+        Some(MapError::AlreadyMapped)
+    );
+    unsafe { block.unmap(&device) };
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.inner.live_allocations(), 0);
+    Ok(())
+}
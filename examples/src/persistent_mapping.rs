@@ -0,0 +1,118 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DeviceMapError, DeviceProperties, GpuAllocator,
+        MemoryHeap, MemoryPropertyFlags, MemoryType, Request, RequestFlags, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Persistent mapping implies host access.
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        dedicated: Some(Dedicated::Required),
+        flags: RequestFlags::PERSISTENTLY_MAPPED,
+        ..Request::default()
+    };
+
+    // Dedicated memory object is mapped once on allocation.
+    let mut block = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(block.memory_type(), 1);
+    assert_eq!(device.total_maps(), 1);
+    assert_eq!(device.live_mappings(), 1);
+    assert!(block.mapped_ptr().is_some());
+
+    unsafe {
+        let slice = block.mapped_slice().unwrap();
+        assert_eq!(slice.len(), 1024);
+        slice[..4].copy_from_slice(&[1, 2, 3, 4]);
+    }
+    assert_eq!(&device.memory_content(*block.memory())[..4], &[1, 2, 3, 4]);
+
+    // Transient mapping reuses persistent one.
+    unsafe { block.write_bytes(&device, 8, &[5, 6, 7, 8]) }?;
+    assert_eq!(
+        &device.memory_content(*block.memory())[8..12],
+        &[5, 6, 7, 8]
+    );
+    assert_eq!(device.total_maps(), 1);
+    assert_eq!(device.live_mappings(), 1);
+
+    // Dedicated memory object is not mapped without the flag.
+    let unmapped = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                usage: UsageFlags::HOST_ACCESS,
+                flags: RequestFlags::empty(),
+                ..request
+            },
+        )
+    }?;
+    assert!(unmapped.mapped_ptr().is_none());
+    assert_eq!(device.total_maps(), 1);
+
+    // Memory object is released if it cannot be mapped.
+    device.fail_map(2, DeviceMapError::MapFailed);
+    assert!(matches!(
+        unsafe { allocator.alloc(&device, request) },
+        Err(AllocationError::AddressSpaceExhausted)
+    ));
+    assert_eq!(device.live_allocations(), 2);
+
+    // Explicitly chosen memory type that can't be mapped is refused.
+    for &dedicated in &[Some(Dedicated::Required), None] {
+        let request = Request {
+            memory_type: Some(0),
+            dedicated,
+            ..request
+        };
+        assert_eq!(
+            unsafe { allocator.alloc(&device, request) }.unwrap_err(),
+            AllocationError::NoCompatibleMemoryTypes
+        );
+    }
+    assert_eq!(device.live_allocations(), 2);
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.dealloc(&device, unmapped);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_mappings(), 0);
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
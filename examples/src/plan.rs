@@ -0,0 +1,112 @@
+use {
+    gpu_alloc::{
+        AllocationError, AllocationFlags, Config, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, PlannedAllocation, Request, RequestFlags, ResourceKind,
+        Strategy, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = |size| Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        memory_type: None,
+        access: None,
+        label: None,
+        dedicated: None,
+        dedicated_resource: None,
+        export: AllocationFlags::empty(),
+        priority: None,
+        flags: RequestFlags::empty(),
+        resource_kind: ResourceKind::Linear,
+    };
+
+    let requests = [
+        request(256),
+        request(256),
+        request(512 * 1024),
+        request(768 * 1024),
+    ];
+
+    let plan = allocator.plan(&requests);
+    assert_eq!(device.total_allocations(), 0, "Planning must not allocate");
+    assert!(!plan.fits());
+
+    let chunk_size = match plan.allocations[0] {
+        PlannedAllocation::NewMemory {
+            memory_type: 0,
+            strategy: Strategy::Buddy,
+            size,
+        } => size,
+        other => eyre::bail!("Unexpected plan {:?}", other),
+    };
+    assert_eq!(
+        plan.allocations[1],
+        PlannedAllocation::Existing {
+            memory_type: 0,
+            strategy: Strategy::Buddy,
+        }
+    );
+    assert_eq!(
+        plan.allocations[2],
+        PlannedAllocation::NewMemory {
+            memory_type: 0,
+            strategy: Strategy::Dedicated,
+            size: 512 * 1024,
+        }
+    );
+    assert_eq!(
+        plan.allocations[3],
+        PlannedAllocation::Failed(AllocationError::OutOfDeviceMemory(None))
+    );
+    assert_eq!(plan.additional_memory, chunk_size + 512 * 1024);
+
+    // Actual allocations follow the plan.
+    let mut blocks = Vec::new();
+    for request in &requests[..3] {
+        blocks.push(unsafe { allocator.alloc(&device, *request) }?);
+    }
+    assert_eq!(device.total_allocations(), 2);
+    assert_eq!(device.heap_usage(0), plan.additional_memory);
+    assert!(unsafe { allocator.alloc(&device, requests[3]) }.is_err());
+
+    // Plan accounts for current state.
+    let plan = allocator.plan(&requests[..1]);
+    assert_eq!(
+        plan.allocations[0],
+        PlannedAllocation::Existing {
+            memory_type: 0,
+            strategy: Strategy::Buddy,
+        }
+    );
+    assert_eq!(plan.additional_memory, 0);
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let mut block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 256,
+                align_mask: 63,
+                usage: UsageFlags::UPLOAD | UsageFlags::DOWNLOAD,
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let flushes = device.total_flushes();
+
+    // Values are written byte-wise, even at offsets unaligned for their type.
+    unsafe {
+        block.write_pod(&device, 3, &0x0403_0201u32)?;
+        block.write_slice(&device, 100, &[1.5f32, -2.0, 4.25])?;
+    }
+
+    // Each write is flushed once as non-coherent memory.
+    assert_eq!(device.total_flushes(), flushes + 2);
+
+    let mut bytes = [0; 4];
+    unsafe { block.read_bytes(&device, 3, &mut bytes) }?;
+    assert_eq!(bytes, 0x0403_0201u32.to_ne_bytes());
+
+    let value: u32 = unsafe { block.read_pod(&device, 3) }?;
+    assert_eq!(value, 0x0403_0201);
+
+    let values: [f32; 3] = unsafe { block.read_pod(&device, 100) }?;
+    assert_eq!(values, [1.5, -2.0, 4.25]);
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
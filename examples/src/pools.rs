@@ -0,0 +1,114 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, PoolConfig, PoolStrategy, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let render_targets = allocator.create_pool(PoolConfig {
+        label: Some("render targets"),
+        strategy: PoolStrategy::Buddy,
+        chunk_size: 64 * 1024,
+        max_size: Some(128 * 1024),
+        ..PoolConfig::default()
+    });
+
+    let streaming = allocator.create_pool(PoolConfig {
+        label: Some("streaming"),
+        strategy: PoolStrategy::FreeList,
+        chunk_size: 32 * 1024,
+        memory_types: 0b10,
+        ..PoolConfig::default()
+    });
+
+    let request = Request {
+        size: 16 * 1024,
+        align_mask: 255,
+        usage: UsageFlags::HOST_ACCESS,
+        ..Request::default()
+    };
+
+    let shared = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(shared.pool(), None);
+    assert_eq!(shared.memory_type(), 0);
+
+    // Pool blocks never share memory objects with other blocks.
+    let mut targets = Vec::new();
+    for _ in 0..8 {
+        let block = unsafe { allocator.alloc_in_pool(&device, render_targets, request) }?;
+        assert_eq!(block.pool(), Some(render_targets));
+        assert_ne!(*block.memory(), *shared.memory());
+        targets.push(block);
+    }
+
+    // Pool cannot grow beyond its maximum size.
+    assert!(matches!(
+        unsafe { allocator.alloc_in_pool(&device, render_targets, request) },
+        Err(AllocationError::OutOfDeviceMemory(_))
+    ));
+
+    let stats = allocator.pool_stats(render_targets);
+    assert_eq!(stats.memory_objects, 2);
+    assert_eq!(stats.device_bytes, 128 * 1024);
+    assert_eq!(stats.block_count, 8);
+    assert_eq!(allocator.stats().total().block_count, 9);
+
+    // Pool memory types restrict memory type selection.
+    let streamed = unsafe { allocator.alloc_in_pool(&device, streaming, request) }?;
+    assert_eq!(streamed.memory_type(), 1);
+    assert_eq!(allocator.pool_stats(streaming).device_bytes, 32 * 1024);
+
+    let snapshot = allocator.snapshot();
+    let pool_chunks = snapshot
+        .chunks
+        .iter()
+        .filter(|chunk| chunk.pool == Some(render_targets))
+        .count();
+    assert_eq!(pool_chunks, 2);
+
+    // Pool with live blocks is kept.
+    assert!(!unsafe { allocator.destroy_pool(&device, streaming) });
+
+    unsafe {
+        for block in targets {
+            allocator.dealloc(&device, block);
+        }
+        allocator.dealloc(&device, streamed);
+        allocator.dealloc(&device, shared);
+
+        assert!(allocator.destroy_pool(&device, render_targets));
+        assert!(allocator.destroy_pool(&device, streaming));
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
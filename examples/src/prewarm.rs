@@ -0,0 +1,85 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Loading screen allocates memory objects of 8 KiB used by buddy allocator.
+    unsafe {
+        allocator.prewarm(&device, 0, 40 * 1024)?;
+        allocator.prewarm(&device, 1, 8 * 1024)?;
+    }
+    assert_eq!(device.total_allocations(), 6);
+    assert_eq!(device.heap_usage(0), 40 * 1024);
+    assert_eq!(allocator.allocation_count(), 6);
+
+    let request = Request {
+        size: 2048,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    };
+
+    // Gameplay doesn't allocate memory objects until prewarmed ones are used up.
+    let mut blocks = Vec::new();
+    for _ in 0..20 {
+        blocks.push(unsafe { allocator.alloc(&device, request) }?);
+    }
+    assert!(blocks.iter().all(|block| block.memory_type() == 0));
+    assert_eq!(device.total_allocations(), 6);
+    assert_eq!(allocator.allocation_count(), 6);
+
+    blocks.push(unsafe { allocator.alloc(&device, request) }?);
+    assert_eq!(device.total_allocations(), 7);
+    assert_eq!(allocator.allocation_count(), 7);
+
+    // Unused prewarmed memory objects are released by trimming.
+    unsafe { allocator.trim(&device) };
+    assert_eq!(device.heap_usage(1), 0);
+    assert_eq!(allocator.allocation_count(), 6);
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(allocator.allocation_count(), 0);
+    Ok(())
+}
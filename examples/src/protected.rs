@@ -0,0 +1,91 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL | MemoryPropertyFlags::PROTECTED,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let normal = Request {
+        size: 256,
+        align_mask: 255,
+        ..Request::default()
+    };
+
+    let protected = Request {
+        usage: UsageFlags::PROTECTED,
+        ..normal
+    };
+
+    // Protected memory type is skipped for normal requests.
+    let a = unsafe { allocator.alloc(&device, normal) }?;
+    assert_eq!(a.memory_type(), 1);
+
+    // Protected requests use only protected memory types.
+    let b = unsafe { allocator.alloc(&device, protected) }?;
+    assert_eq!(b.memory_type(), 0);
+    assert_ne!(a.memory(), b.memory());
+
+    let host = Request {
+        usage: UsageFlags::PROTECTED | UsageFlags::HOST_ACCESS,
+        ..normal
+    };
+    assert!(matches!(
+        unsafe { allocator.alloc(&device, host) },
+        Err(AllocationError::NoCompatibleMemoryTypes)
+    ));
+
+    let unprotected_types = Request {
+        memory_types: 0b110,
+        ..protected
+    };
+    assert!(matches!(
+        unsafe { allocator.alloc(&device, unprotected_types) },
+        Err(AllocationError::NoCompatibleMemoryTypes)
+    ));
+
+    unsafe {
+        allocator.dealloc(&device, a);
+        allocator.dealloc(&device, b);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
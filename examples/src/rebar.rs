@@ -0,0 +1,95 @@
+use {
+    gpu_alloc::{
+        AccessPattern, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::DEVICE_LOCAL
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 2,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap { size: 1024 * 1024 },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let uniforms = Request {
+        size: 512 * 1024,
+        align_mask: 255,
+        access: Some(AccessPattern::FrequentWrite),
+        ..Request::default()
+    };
+
+    // Staging memory stays out of BAR heap.
+    let staging = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                access: Some(AccessPattern::SequentialWrite),
+                ..uniforms
+            },
+        )
+    }?;
+    assert_eq!(staging.memory_type(), 2);
+
+    // Frequently written memory goes to BAR heap.
+    let a = unsafe { allocator.alloc(&device, uniforms) }?;
+    assert_eq!(a.memory_type(), 1);
+    assert_eq!(allocator.stats().bar_device_bytes, 512 * 1024);
+
+    // And falls back to host-visible memory once BAR heap is exhausted.
+    let b = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 768 * 1024,
+                ..uniforms
+            },
+        )
+    }?;
+    assert_eq!(b.memory_type(), 2);
+    assert_eq!(allocator.stats().bar_device_bytes, 512 * 1024);
+
+    unsafe {
+        allocator.dealloc(&device, staging);
+        allocator.dealloc(&device, a);
+        allocator.dealloc(&device, b);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(allocator.stats().bar_device_bytes, 0);
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
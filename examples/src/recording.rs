@@ -0,0 +1,175 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        RecordedEvent, Recording, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn props() -> DeviceProperties<'static> {
+    DeviceProperties {
+        memory_types: Cow::Owned(vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Owned(vec![
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(props());
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Blocks allocated before recording started are not recorded.
+    let early = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 256,
+                usage: UsageFlags::UPLOAD,
+                ..Request::default()
+            },
+        )?
+    };
+
+    allocator.start_recording();
+
+    let mut blocks = Vec::new();
+    for index in 0..40u64 {
+        let request = Request {
+            size: 512 + index * 100,
+            align_mask: 255,
+            usage: if index % 3 == 0 {
+                UsageFlags::UPLOAD
+            } else {
+                UsageFlags::FAST_DEVICE_ACCESS
+            },
+            label: if index % 5 == 0 {
+                Some("labeled")
+            } else {
+                None
+            },
+            ..Request::default()
+        };
+        blocks.push(unsafe { allocator.alloc(&device, request)? });
+    }
+
+    // Free every other block, leaving holes behind.
+    let mut kept = Vec::new();
+    for (index, block) in blocks.into_iter().enumerate() {
+        if index % 2 == 0 {
+            unsafe { allocator.dealloc(&device, block) };
+        } else {
+            kept.push(block);
+        }
+    }
+
+    // Failed request is recorded with its error.
+    let failed = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 256,
+                memory_types: 0,
+                ..Request::default()
+            },
+        )
+    };
+    assert!(failed.is_err());
+
+    unsafe { allocator.dealloc(&device, early) };
+
+    let recording = allocator.take_recording().unwrap();
+    assert!(allocator.take_recording().is_none());
+
+    let events = recording.events();
+    let allocs = events
+        .iter()
+        .filter(|event| matches!(event, RecordedEvent::Alloc { .. }))
+        .count();
+    assert_eq!(allocs, 41);
+    assert_eq!(events.len(), 41 + 20);
+    assert!(events
+        .windows(2)
+        .all(|pair| timestamp(&pair[0]) <= timestamp(&pair[1])));
+
+    let recorded_report = allocator.report();
+    let recorded_stats = allocator.stats();
+
+    // Replay reproduces the same state on fresh allocator.
+    let replay_device = MockMemoryDevice::new(props());
+    let mut replay_allocator = GpuAllocator::new(Config::i_am_potato(), replay_device.props());
+    let replay = unsafe { recording.replay(&mut replay_allocator, &replay_device) };
+
+    assert_eq!(replay.allocations, 41);
+    assert_eq!(replay.failures, 1);
+    assert_eq!(replay.mismatches, 0);
+    assert_eq!(replay.report, recorded_report);
+    assert_eq!(replay.stats, recorded_stats);
+
+    // Blocks never deallocated in recording are freed after replay.
+    assert!(replay_allocator.report_leaks().is_empty());
+
+    // Encoded recording decodes to the same events, with owned labels.
+    let encoded = recording.encode();
+    let decoded = Recording::decode(&encoded).unwrap();
+    assert_eq!(decoded.events(), recording.events());
+    let labels = decoded
+        .events()
+        .iter()
+        .filter(|event| match event {
+            RecordedEvent::Alloc { label, .. } => label.as_deref() == Some("labeled"),
+            RecordedEvent::Dealloc { .. } => false,
+        })
+        .count();
+    assert_eq!(labels, 8);
+    assert!(Recording::decode(&encoded[..encoded.len() - 1]).is_none());
+
+    // Decoded recording replays the same way.
+    let decoded_device = MockMemoryDevice::new(props());
+    let mut decoded_allocator = GpuAllocator::new(Config::i_am_potato(), decoded_device.props());
+    let decoded_replay = unsafe { decoded.replay(&mut decoded_allocator, &decoded_device) };
+    assert_eq!(decoded_replay, replay);
+
+    unsafe {
+        for block in kept {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+        replay_allocator.cleanup(&replay_device);
+        decoded_allocator.cleanup(&decoded_device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(replay_device.live_allocations(), 0);
+    assert_eq!(decoded_device.live_allocations(), 0);
+    Ok(())
+}
+
+fn timestamp(event: &RecordedEvent) -> u64 {
+    match *event {
+        RecordedEvent::Alloc { timestamp, .. } => timestamp,
+        RecordedEvent::Dealloc { timestamp, .. } => timestamp,
+    }
+}
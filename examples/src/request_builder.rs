@@ -0,0 +1,78 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, RequestError, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request::builder()
+        .size(1000)
+        .align(256)
+        .usage(UsageFlags::UPLOAD)
+        .label("uniforms")
+        .build()?;
+    assert_eq!(request.align_mask, 255);
+
+    let block = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(block.offset() % 256, 0);
+    assert_eq!(block.memory_type(), 1);
+    unsafe { allocator.dealloc(&device, block) };
+
+    // Invalid requests are rejected before reaching allocator.
+    let builder = Request::builder().size(1000);
+    assert_eq!(
+        Request::builder().build().unwrap_err(),
+        RequestError::ZeroSize
+    );
+    assert_eq!(
+        builder.align(48).build().unwrap_err(),
+        RequestError::AlignmentNotPowerOfTwo { align: 48 }
+    );
+    assert_eq!(
+        builder.align(0).build().unwrap_err(),
+        RequestError::AlignmentNotPowerOfTwo { align: 0 }
+    );
+    assert_eq!(
+        builder.memory_types(0).build().unwrap_err(),
+        RequestError::NoMemoryTypes
+    );
+    let err = builder
+        .memory_types(0b01)
+        .memory_type(1)
+        .build()
+        .unwrap_err();
+    assert_eq!(err, RequestError::MemoryTypeNotAllowed { memory_type: 1 });
+    println!("{}", err);
+
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
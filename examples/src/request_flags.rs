@@ -0,0 +1,110 @@
+use {
+    gpu_alloc::{
+        AllocationError, BudgetPolicy, Config, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, PlannedAllocation, Request, RequestFlags, Strategy,
+        UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.budget_policy = BudgetPolicy::Warn;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let small = Request {
+        size: 256,
+        align_mask: 255,
+        usage: UsageFlags::HOST_ACCESS,
+        ..Request::default()
+    };
+
+    let first = unsafe { allocator.alloc(&device, small) }?;
+    assert_eq!(device.live_allocations(), 1);
+
+    // Free space in existing memory object can be reused.
+    let reuse = Request {
+        flags: RequestFlags::NEVER_ALLOCATE,
+        ..small
+    };
+    assert!(matches!(
+        allocator.plan(&[reuse]).allocations[..],
+        [PlannedAllocation::Existing { .. }]
+    ));
+    let reused = unsafe { allocator.alloc(&device, reuse) }?;
+    assert_eq!(device.live_allocations(), 1);
+    assert_eq!(*reused.memory(), *first.memory());
+
+    // But no new memory object is allocated.
+    let large = Request {
+        size: 4 * 1024 * 1024,
+        ..reuse
+    };
+    assert!(matches!(
+        allocator.plan(&[large]).allocations[..],
+        [PlannedAllocation::Failed(
+            AllocationError::OutOfDeviceMemory(None)
+        )]
+    ));
+    assert!(matches!(
+        unsafe { allocator.alloc(&device, large) },
+        Err(AllocationError::OutOfDeviceMemory(_))
+    ));
+    assert_eq!(device.live_allocations(), 1);
+
+    // Request that is never suballocated gets its own memory object.
+    let own = Request {
+        flags: RequestFlags::DONT_SUBALLOCATE,
+        ..small
+    };
+    assert_eq!(
+        allocator.strategy_for(&own, None),
+        Some((0, Strategy::Dedicated))
+    );
+    let dedicated = unsafe { allocator.alloc(&device, own) }?;
+    assert_eq!(device.live_allocations(), 2);
+    assert_ne!(*dedicated.memory(), *first.memory());
+
+    // Budget is only warned about by configured policy,
+    // unless request must stay within budget.
+    device.set_memory_budget(0, 1024);
+    let within = Request {
+        flags: RequestFlags::DONT_SUBALLOCATE | RequestFlags::WITHIN_BUDGET,
+        ..small
+    };
+    assert!(matches!(
+        unsafe { allocator.alloc(&device, within) },
+        Err(AllocationError::OutOfDeviceMemory(_))
+    ));
+    let over_budget = unsafe { allocator.alloc(&device, own) }?;
+    assert_eq!(device.live_allocations(), 3);
+
+    unsafe {
+        allocator.dealloc(&device, first);
+        allocator.dealloc(&device, reused);
+        allocator.dealloc(&device, dedicated);
+        allocator.dealloc(&device, over_budget);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, SharedGpuAllocator, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let allocator = unsafe { SharedGpuAllocator::new(allocator, device) };
+
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD,
+        ..Request::default()
+    };
+
+    let mut block = allocator.alloc(request)?;
+    let dedicated = allocator.alloc_with_dedicated(request, Dedicated::Required)?;
+    assert_eq!(allocator.device().live_allocations(), 2);
+    assert_eq!(allocator.lock().allocation_count(), 2);
+
+    // Blocks are mapped with device owned by the allocator.
+    unsafe {
+        block
+            .block_mut()
+            .write_bytes(allocator.device(), 0, &[1, 2, 3, 4])?;
+    }
+    let content = allocator.device().memory_content(*block.memory());
+    let offset = block.offset() as usize;
+    assert_eq!(content[offset..offset + 4], [1, 2, 3, 4]);
+
+    // Dropped dedicated block frees its memory object immediately.
+    drop(dedicated);
+    assert_eq!(allocator.device().live_allocations(), 1);
+
+    // Blocks may be dropped through any handle, or none at all.
+    let handle = block.allocator().unwrap();
+    drop(block);
+    handle.cleanup();
+    assert_eq!(handle.device().live_allocations(), 0);
+    assert_eq!(allocator.lock().allocation_count(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MapError, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 1024,
+        usage: UsageFlags::HOST_ACCESS,
+        ..Request::default()
+    };
+
+    for &dedicated in &[None, Some(Dedicated::Required)] {
+        let mut block = match dedicated {
+            None => unsafe { allocator.alloc(&device, request) }?,
+            Some(dedicated) => {
+                unsafe { allocator.alloc_with_dedicated(&device, request, dedicated) }?
+            }
+        };
+
+        let (first, first_guard) = unsafe { block.map_shared(&device, 0, 16) }?;
+        let (second, second_guard) = unsafe { block.map_shared(&device, 256, 16) }?;
+        assert_eq!(second.as_ptr() as usize - first.as_ptr() as usize, 256);
+
+        unsafe { first.as_ptr().write(42) };
+        drop(first_guard);
+
+        // Mapping stays valid while any guard is held.
+        let (again, again_guard) = unsafe { block.map_shared(&device, 0, 1) }?;
+        assert_eq!(again, first);
+        assert_eq!(unsafe { again.as_ptr().read() }, 42);
+        drop(again_guard);
+
+        // Guards may be held by different users of the block at once.
+        let guards = (0..4u64)
+            .map(|index| unsafe { block.map_shared(&device, 512 + index, 1) })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (index, (ptr, _)) in guards.iter().enumerate() {
+            unsafe { ptr.as_ptr().write(index as u8) };
+        }
+        drop(guards);
+        drop(second_guard);
+
+        // Exclusive mapping is allowed after last guard is released.
+        let mut data = [0; 1];
+        unsafe { block.read_bytes(&device, 0, &mut data) }?;
+        assert_eq!(data, [42]);
+
+        // Shared mapping is not allowed while exclusively mapped.
+        unsafe { block.map(&device, 0, 16) }?;
+        assert_eq!(
+            unsafe { block.map_shared(&device, 0, 16) }.err(),
+            Some(MapError::AlreadyMapped)
+        );
+        unsafe { block.unmap(&device) };
+
+        unsafe { allocator.dealloc(&device, block) };
+    }
+
+    Ok(())
+}
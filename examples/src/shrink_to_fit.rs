@@ -0,0 +1,93 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let heap_usage = || device.heap_usage(0) + device.heap_usage(1);
+
+    // Memory left from the previous level.
+    unsafe { allocator.prewarm(&device, 0, 32 * 1024) }?;
+
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD | UsageFlags::TRANSIENT,
+        ..Request::default()
+    };
+    let mut transient = Vec::new();
+    for _ in 0..4 {
+        transient.push(unsafe { allocator.alloc(&device, request) }?);
+    }
+
+    let kept = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                usage: UsageFlags::FAST_DEVICE_ACCESS,
+                ..request
+            },
+        )
+    }?;
+
+    unsafe {
+        for block in transient {
+            allocator.dealloc(&device, block);
+        }
+    }
+
+    let before = heap_usage();
+    let released = unsafe { allocator.shrink_to_fit(&device) };
+    assert!(released > 0);
+    assert_eq!(released, before - heap_usage());
+
+    // Only memory object of the live block is left.
+    assert_eq!(device.live_allocations(), 1);
+    assert_eq!(device.heap_usage(1), 0);
+    assert_eq!(unsafe { allocator.shrink_to_fit(&device) }, 0);
+
+    // Allocator keeps working after shrinking.
+    let block = unsafe { allocator.alloc(&device, request) }?;
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.dealloc(&device, kept);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Released {} bytes", released);
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use {
+    gpu_alloc::{
+        AllocatorSnapshot, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, Strategy, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+/// Checks that free regions and blocks of each chunk don't overlap.
+/// Returns bytes of chunks of the strategy covered by neither.
+fn uncovered(snapshot: &AllocatorSnapshot, strategy: Strategy) -> u64 {
+    let mut uncovered = 0;
+    for chunk in snapshot.chunks.iter().filter(|c| c.strategy == strategy) {
+        let mut ranges: Vec<(u64, u64)> = chunk
+            .free_regions
+            .iter()
+            .map(|region| (region.offset, region.size))
+            .chain(
+                snapshot
+                    .blocks
+                    .iter()
+                    .filter(|block| block.is_in(chunk))
+                    .map(|block| (block.offset, block.size)),
+            )
+            .collect();
+        ranges.sort_unstable();
+
+        let mut end = 0;
+        for (offset, size) in ranges {
+            assert!(offset >= end, "Ranges overlap in {:?}", chunk);
+            uncovered += offset - end;
+            end = offset + size;
+        }
+        assert!(end <= chunk.size);
+        uncovered += chunk.size - end;
+    }
+    uncovered
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.tlsf_memory_types = 0b10;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let mut blocks = Vec::new();
+    for i in 0..64u64 {
+        let usage = match i % 3 {
+            0 => UsageFlags::empty(),
+            1 => UsageFlags::TRANSIENT,
+            _ => UsageFlags::HOST_ACCESS,
+        };
+        blocks.push(unsafe {
+            allocator.alloc(
+                &device,
+                Request {
+                    size: 100 + i * 37,
+                    align_mask: 63,
+                    usage,
+                    ..Request::default()
+                },
+            )
+        }?);
+    }
+
+    // Leave holes.
+    let mut kept = Vec::new();
+    for (i, block) in blocks.into_iter().enumerate() {
+        if i % 2 == 0 {
+            unsafe { allocator.dealloc(&device, block) };
+        } else {
+            kept.push(block);
+        }
+    }
+
+    let snapshot = allocator.snapshot();
+    assert_eq!(snapshot.blocks.len(), kept.len());
+    assert_eq!(
+        snapshot.chunks.len(),
+        device.live_allocations(),
+        "All memory objects are suballocated"
+    );
+    assert!(snapshot
+        .chunks
+        .iter()
+        .any(|chunk| !chunk.free_regions.is_empty()));
+
+    // Buddy and TLSF allocators account every byte of their chunks.
+    assert_eq!(uncovered(&snapshot, Strategy::Buddy), 0);
+    assert_eq!(uncovered(&snapshot, Strategy::Tlsf), 0);
+    uncovered(&snapshot, Strategy::FreeList);
+
+    for block in kept {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+
+    let snapshot = allocator.snapshot();
+    assert!(snapshot.chunks.is_empty());
+    assert!(snapshot.blocks.is_empty());
+
+    println!("Snapshot covers all chunks");
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, SparseResource, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Virtual texture of 1000 KiB with 64 KiB sparse blocks.
+    let mut texture = SparseResource::new(Request {
+        size: 1000 * 1024,
+        align_mask: 64 * 1024 - 1,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    });
+    assert_eq!(texture.page_size(), 64 * 1024);
+    assert_eq!(texture.page_count(), 16);
+
+    unsafe {
+        for &page in &[0, 3, 5] {
+            assert!(texture.make_resident(&mut allocator, &device, page)?);
+        }
+        assert!(!texture.make_resident(&mut allocator, &device, 3)?);
+    }
+    assert_eq!(texture.resident_pages().collect::<Vec<_>>(), [0, 3, 5]);
+
+    let binds = texture.pending_binds();
+    assert_eq!(
+        binds
+            .iter()
+            .map(|bind| bind.resource_offset)
+            .collect::<Vec<_>>(),
+        [0, 3 * 64 * 1024, 5 * 64 * 1024],
+    );
+    for bind in &binds {
+        assert_eq!(bind.size, 64 * 1024);
+        let (_, offset) = bind.memory.unwrap();
+        assert_eq!(offset % (64 * 1024), 0);
+    }
+    unsafe { texture.flush_binds(&mut allocator, 0) };
+    assert!(texture.pending_binds().is_empty());
+
+    // Evicted page is unbound and its memory is freed once the bind is completed.
+    assert!(texture.evict(3));
+    assert!(!texture.evict(4));
+    let binds = texture.pending_binds();
+    assert_eq!(binds.len(), 1);
+    assert_eq!(binds[0].resource_offset, 3 * 64 * 1024);
+    assert!(binds[0].memory.is_none());
+    unsafe { texture.flush_binds(&mut allocator, 1) };
+    assert_eq!(allocator.deferred_count(), 1);
+    assert_eq!(allocator.report_leaks().len(), 3);
+
+    unsafe { allocator.flush_retired(&device, 1) };
+    assert_eq!(allocator.deferred_count(), 0);
+    assert_eq!(allocator.report_leaks().len(), 2);
+    assert!(!texture.is_resident(3));
+    assert_eq!(texture.resident_count(), 2);
+
+    unsafe {
+        texture.destroy(&mut allocator, &device);
+        allocator.cleanup(&device);
+    }
+
+    assert!(allocator.report_leaks().is_empty());
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 32 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 32 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.buddy_spillover_memory_types = 1;
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    // Leave free memory in free-list allocator.
+    let transient = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 256,
+                usage: UsageFlags::TRANSIENT,
+                ..Request::default()
+            },
+        )
+    }?;
+    unsafe { allocator.dealloc(&device, transient) };
+    assert_eq!(device.live_allocations(), 1);
+
+    let request = Request {
+        size: 2048,
+        ..Request::default()
+    };
+
+    // Requests are served from free-list memory object while it lasts.
+    let mut blocks = Vec::new();
+    while device.total_allocations() == 1 {
+        blocks.push(unsafe { allocator.alloc(&device, request) }?);
+        assert!(
+            blocks.len() < 64,
+            "Buddy allocator never allocated memory object"
+        );
+    }
+
+    let spilled = blocks.len() as u64 - 1;
+    assert!(spilled > 0);
+    assert_eq!(allocator.spillover_count(0), spilled);
+    assert!(blocks[..spilled as usize]
+        .iter()
+        .all(|block| block.memory() == blocks[0].memory()));
+    assert!(blocks.last().unwrap().memory() != blocks[0].memory());
+
+    // Free blocks of buddy memory object are used before spilling over.
+    blocks.push(unsafe { allocator.alloc(&device, request) }?);
+    assert_eq!(allocator.spillover_count(0), spilled);
+    assert_eq!(device.total_allocations(), 2);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    Ok(())
+}
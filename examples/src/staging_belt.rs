@@ -0,0 +1,83 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        StagingBelt,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 32 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 32 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 32 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let mut belt = StagingBelt::new(4096, 15);
+
+    // Two frames in flight, each writing more than one block.
+    for frame in 0..20u64 {
+        for i in 0..8 {
+            let mut slice = unsafe { belt.alloc(&mut allocator, &device, 1000) }?;
+            assert_eq!(slice.offset() % 16, 0);
+            assert_eq!(slice.block().memory_type(), 1);
+            unsafe { slice.write_bytes(&device, 0, &[i as u8; 1000]) }?;
+        }
+        belt.finish(frame);
+
+        if frame >= 2 {
+            unsafe { belt.retire(frame - 2) };
+        }
+    }
+
+    // Retired blocks are reused, so number of blocks doesn't grow.
+    let blocks = belt.block_count();
+    assert_eq!(blocks, 3 * 2);
+    assert_eq!(belt.in_flight_count(), 2 * 2);
+    assert_eq!(device.total_allocations(), 1);
+
+    // Slice larger than chunk size gets its own block.
+    let slice = unsafe { belt.alloc(&mut allocator, &device, 10000) }?;
+    assert_eq!(slice.offset(), 0);
+    assert!(slice.block().size() >= 10000);
+    assert_eq!(belt.block_count(), blocks + 1);
+
+    for frame in 18..20 {
+        unsafe { belt.retire(frame) };
+    }
+    assert_eq!(belt.in_flight_count(), 0);
+
+    unsafe { belt.trim(&mut allocator, &device) };
+    assert_eq!(belt.block_count(), 1);
+
+    unsafe { belt.destroy(&mut allocator, &device) };
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Staging blocks are recycled once submissions complete");
+    Ok(())
+}
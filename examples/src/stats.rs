@@ -0,0 +1,124 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL | MemoryPropertyFlags::HOST_VISIBLE,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let buddy = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1100,
+                usage: UsageFlags::HOST_ACCESS,
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let transient = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 300,
+                usage: UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT,
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let dedicated = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                size: 4096,
+                usage: UsageFlags::HOST_ACCESS,
+                ..Request::default()
+            },
+            Dedicated::Required,
+        )
+    }?;
+
+    allocator.begin_frame(0);
+    let frame = unsafe {
+        allocator.alloc_in_frame(
+            &device,
+            Request {
+                size: 100,
+                usage: UsageFlags::HOST_ACCESS,
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let stats = allocator.stats();
+    let memory_type = &stats.memory_types[0];
+
+    assert_eq!(memory_type.buddy.block_count, 1);
+    assert_eq!(memory_type.buddy.memory_objects, 1);
+    assert_eq!(memory_type.buddy.user_bytes, 2048);
+    assert_eq!(memory_type.buddy.internal_fragmentation, 2048 - 1100);
+    assert!(memory_type.buddy.free_bytes() > 0);
+
+    assert_eq!(memory_type.free_list.block_count, 1);
+    assert_eq!(memory_type.free_list.memory_objects, 1);
+    assert_eq!(memory_type.free_list.user_bytes, 320);
+    assert_eq!(memory_type.free_list.internal_fragmentation, 20);
+
+    assert_eq!(memory_type.dedicated.block_count, 1);
+    assert_eq!(memory_type.dedicated.memory_objects, 1);
+    assert_eq!(memory_type.dedicated.device_bytes, 4096);
+    assert_eq!(memory_type.dedicated.free_bytes(), 0);
+
+    assert_eq!(memory_type.frame_ring.memory_objects, 1);
+    assert_eq!(memory_type.frame_ring.user_bytes, frame.size());
+
+    // Every memory object allocated from device is accounted.
+    let total = stats.total();
+    assert_eq!(total.memory_objects, device.live_allocations() as u64);
+    assert_eq!(total.device_bytes, device.heap_usage(0));
+    assert_eq!(total.block_count, 3);
+
+    allocator.end_frame(0);
+    unsafe {
+        allocator.dealloc(&device, frame);
+        allocator.dealloc(&device, buddy);
+        allocator.dealloc(&device, transient);
+        allocator.dealloc(&device, dedicated);
+    }
+
+    let total = allocator.stats().total();
+    assert_eq!(total.block_count, 0);
+    assert_eq!(total.user_bytes, 0);
+    assert_eq!(total.memory_objects, device.live_allocations() as u64);
+    assert_eq!(total.device_bytes, device.heap_usage(0));
+
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(allocator.stats().total(), Default::default());
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Stats account all memory");
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, Strategy, StrategyThresholds, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let request = |size, usage| Request {
+        size,
+        usage,
+        ..Request::default()
+    };
+
+    let mut config = Config::i_am_potato();
+    config.usage_thresholds.push((
+        UsageFlags::FAST_DEVICE_ACCESS,
+        StrategyThresholds {
+            dedicated: Some(256 * 1024),
+            large_object: Some(16 * 1024),
+            ..StrategyThresholds::default()
+        },
+    ));
+    config.memory_type_thresholds.push((
+        1,
+        StrategyThresholds {
+            dedicated: Some(8 * 1024),
+            transient_dedicated: Some(16 * 1024),
+            ..StrategyThresholds::default()
+        },
+    ));
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    // Device-local memory suballocates larger blocks and keeps more of them in small-object allocator.
+    let device_local = UsageFlags::FAST_DEVICE_ACCESS;
+    assert_eq!(
+        allocator.strategy_for(&request(8 * 1024, device_local), None),
+        Some((0, Strategy::Buddy))
+    );
+    assert_eq!(
+        allocator.strategy_for(&request(64 * 1024, device_local), None),
+        Some((0, Strategy::LargeBuddy))
+    );
+    assert_eq!(
+        allocator.strategy_for(&request(256 * 1024, device_local), None),
+        Some((0, Strategy::Dedicated))
+    );
+
+    // Upload memory dedicates memory objects much earlier.
+    assert_eq!(
+        allocator.strategy_for(&request(4 * 1024, UsageFlags::UPLOAD), None),
+        Some((1, Strategy::LargeBuddy))
+    );
+    assert_eq!(
+        allocator.strategy_for(&request(8 * 1024, UsageFlags::UPLOAD), None),
+        Some((1, Strategy::Dedicated))
+    );
+    let transient = UsageFlags::UPLOAD | UsageFlags::TRANSIENT;
+    assert_eq!(
+        allocator.strategy_for(&request(12 * 1024, transient), None),
+        Some((1, Strategy::FreeList))
+    );
+    assert_eq!(
+        allocator.strategy_for(&request(16 * 1024, transient), None),
+        Some((1, Strategy::Dedicated))
+    );
+
+    // Allocation follows selected strategies.
+    let mut blocks = Vec::new();
+    for &(size, usage) in &[
+        (64 * 1024, device_local),
+        (8 * 1024, UsageFlags::UPLOAD),
+        (12 * 1024, transient),
+    ] {
+        blocks.push(unsafe { allocator.alloc(&device, request(size, usage)) }?);
+    }
+    let live = allocator.report_live_allocations();
+    assert_eq!(live[0].strategy, Strategy::LargeBuddy);
+    assert_eq!(live[1].strategy, Strategy::Dedicated);
+    assert_eq!(live[2].strategy, Strategy::FreeList);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Strategy thresholds are tuned per usage and memory type");
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MapError, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, slice::from_raw_parts_mut, thread},
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 4096,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD,
+        memory_types: 0b01,
+        ..Request::default()
+    };
+
+    let mut block = unsafe { allocator.alloc(&device, request) }?;
+
+    // Non-overlapping ranges can be mapped at the same time.
+    let mut slices = Vec::new();
+    for index in 0..4 {
+        let ptr = unsafe { block.map(&device, index * 1024, 1024) }?;
+        slices.push(unsafe { from_raw_parts_mut(ptr.as_ptr(), 1024) });
+    }
+
+    // Overlapping ranges can't.
+    assert!(matches!(
+        unsafe { block.map(&device, 1000, 100) },
+        Err(MapError::AlreadyMapped)
+    ));
+
+    // Mapped ranges are filled from different threads.
+    thread::scope(|scope| {
+        for (index, slice) in slices.into_iter().enumerate() {
+            scope.spawn(move || slice.fill(index as u8 + 1));
+        }
+    });
+
+    // Each range is unmapped separately.
+    unsafe {
+        assert!(block.unmap_range(&device, 1024));
+        assert!(!block.unmap_range(&device, 1024));
+
+        // Unmapped range can be accessed while others are still mapped.
+        let mut bytes = [0; 4];
+        block.read_bytes(&device, 1100, &mut bytes)?;
+        assert_eq!(bytes, [2; 4]);
+
+        assert!(block.unmap(&device));
+        assert!(!block.unmap(&device));
+    }
+
+    let content = device.memory_content(*block.memory());
+    let offset = block.offset() as usize;
+    for index in 0..4 {
+        let range = offset + index * 1024..offset + (index + 1) * 1024;
+        assert!(content[range].iter().all(|&byte| byte == index as u8 + 1));
+    }
+
+    // Only one range of dedicated memory object can be mapped.
+    let mut dedicated = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                dedicated: Some(Dedicated::Required),
+                ..request
+            },
+        )
+    }?;
+    unsafe {
+        dedicated.map(&device, 0, 1024)?;
+        assert!(matches!(
+            dedicated.map(&device, 2048, 1024),
+            Err(MapError::AlreadyMapped)
+        ));
+        dedicated.unmap(&device);
+    }
+
+    // Ranges of non-coherent memory must not share atoms,
+    // as flushing one of them would flush bytes of the other.
+    let mut non_coherent = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                memory_types: 0b10,
+                ..request
+            },
+        )
+    }?;
+    unsafe {
+        non_coherent.map(&device, 0, 10)?;
+        assert!(matches!(
+            non_coherent.map(&device, 20, 10),
+            Err(MapError::AlreadyMapped)
+        ));
+
+        // Ranges within separate atoms can be mapped at the same time.
+        non_coherent.map(&device, 64, 10)?;
+
+        // Range is unmapped by offset it was mapped at.
+        assert!(!non_coherent.unmap_range(&device, 20));
+        assert!(non_coherent.unmap_range(&device, 0));
+        non_coherent.map(&device, 20, 10)?;
+        assert!(non_coherent.unmap(&device));
+    }
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.dealloc(&device, dedicated);
+        allocator.dealloc(&device, non_coherent);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DeviceProperties, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, OutOfMemoryDetails, Request, SyncGpuAllocator, UsageFallback, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, sync::Mutex, thread},
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    // Mock device is not thread-safe, so threads share it under a lock.
+    let device = Mutex::new(MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    }));
+
+    let mut config = Config::i_am_potato();
+    config.max_memory_allocation_count = Some(8);
+
+    let allocator = SyncGpuAllocator::new(config, device.lock().unwrap().props());
+
+    // Threads allocate from both memory types through shared reference.
+    let blocks = thread::scope(|scope| {
+        let threads: Vec<_> = (0..4)
+            .map(|index| {
+                let allocator = &allocator;
+                let device = &device;
+                scope.spawn(move || {
+                    let usage = if index % 2 == 0 {
+                        UsageFlags::FAST_DEVICE_ACCESS
+                    } else {
+                        UsageFlags::UPLOAD
+                    };
+                    (0..16)
+                        .map(|_| {
+                            let device = device.lock().unwrap();
+                            let request = Request {
+                                size: 1024,
+                                align_mask: 255,
+                                usage,
+                                ..Request::default()
+                            };
+                            unsafe { allocator.alloc(&*device, request) }.unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    assert_eq!(blocks.len(), 64);
+    assert!(blocks.iter().any(|block| block.memory_type() == 0));
+    assert!(blocks.iter().any(|block| block.memory_type() == 1));
+
+    let device = device.into_inner().unwrap();
+    assert_eq!(
+        allocator.allocation_count(),
+        device.live_allocations() as u32
+    );
+
+    // Limit on memory objects count is shared by all memory types.
+    let mut dedicated = Vec::new();
+    let error = loop {
+        match unsafe {
+            allocator.alloc_with_dedicated(
+                &device,
+                Request {
+                    size: 1024,
+                    align_mask: 255,
+                    usage: UsageFlags::UPLOAD,
+                    ..Request::default()
+                },
+                Dedicated::Required,
+            )
+        } {
+            Ok(block) => dedicated.push(block),
+            Err(err) => break err,
+        }
+    };
+    assert_eq!(error, AllocationError::TooManyObjects);
+    assert_eq!(allocator.allocation_count(), 8);
+    assert_eq!(device.live_allocations(), 8);
+
+    unsafe {
+        for block in blocks.into_iter().chain(dedicated) {
+            allocator.dealloc(&device, block);
+        }
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(allocator.allocation_count(), 0);
+    assert_eq!(device.live_allocations(), 0);
+
+    shared_heap()
+}
+
+/// Memory types of one heap account its usage together.
+fn shared_heap() -> eyre::Result<()> {
+    const MB: u64 = 1024 * 1024;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL | MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 16 * MB }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * MB,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let allocator = SyncGpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let request = Request {
+        size: 12 * MB,
+        ..Request::default()
+    };
+    let block = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                memory_type: Some(0),
+                ..request
+            },
+            Dedicated::Required,
+        )
+    }?;
+
+    // Other memory type sees usage of the heap by the first one.
+    let error = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                memory_type: Some(1),
+                ..request
+            },
+            Dedicated::Required,
+        )
+    }
+    .unwrap_err();
+    assert_eq!(
+        error,
+        AllocationError::OutOfDeviceMemory(Some(OutOfMemoryDetails {
+            memory_type: 1,
+            heap: 0,
+            size: 12 * MB,
+            heap_usage: 12 * MB,
+            heap_size: 16 * MB,
+            fallbacks_attempted: false,
+        }))
+    );
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.live_allocations(), 0);
+
+    usage_fallbacks()
+}
+
+/// Usage fallback chains are followed tier by tier.
+fn usage_fallbacks() -> eyre::Result<()> {
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            // Small BAR heap.
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::DEVICE_LOCAL
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 2,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap { size: 64 * 1024 },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.usage_fallbacks = vec![
+        // Uploads go to BAR heap, then to host memory.
+        UsageFallback {
+            usage: UsageFlags::UPLOAD,
+            tiers: vec![1 << 1, 1 << 2],
+        },
+        // Device-local host access never falls back to host memory.
+        UsageFallback {
+            usage: UsageFlags::HOST_ACCESS | UsageFlags::FAST_DEVICE_ACCESS,
+            tiers: vec![1 << 1],
+        },
+    ];
+    let allocator = SyncGpuAllocator::new(config, device.props());
+
+    let upload = Request {
+        size: 16 * 1024,
+        usage: UsageFlags::UPLOAD,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..6 {
+        blocks
+            .push(unsafe { allocator.alloc_with_dedicated(&device, upload, Dedicated::Required)? });
+    }
+
+    // First blocks fill BAR heap, the rest are served by the next tier.
+    let tiers: Vec<_> = blocks.iter().map(|block| block.fallback_tier()).collect();
+    assert_eq!(
+        tiers,
+        [Some(0), Some(0), Some(0), Some(0), Some(1), Some(1)]
+    );
+    assert!(blocks[..4].iter().all(|block| block.memory_type() == 1));
+    assert!(blocks[4..].iter().all(|block| block.memory_type() == 2));
+
+    // Chain ending with BAR heap fails once it is exhausted.
+    let result = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                usage: UsageFlags::HOST_ACCESS | UsageFlags::FAST_DEVICE_ACCESS,
+                ..upload
+            },
+            Dedicated::Required,
+        )
+    };
+    assert!(matches!(result, Err(AllocationError::OutOfDeviceMemory(_))));
+
+    // Requests of other usages are not affected.
+    let other = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1024,
+                usage: UsageFlags::FAST_DEVICE_ACCESS,
+                ..Request::default()
+            },
+        )?
+    };
+    assert_eq!(other.fallback_tier(), None);
+    assert_eq!(other.memory_type(), 0);
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.dealloc(&device, other);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, Strategy,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 1024 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 1024 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_prototyping();
+    config.tlsf_memory_types = 0b10;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = |size, memory_types| Request {
+        size,
+        align_mask: 255,
+        memory_types,
+        ..Request::default()
+    };
+
+    // Awkwardly sized request is rounded to power of two by buddy allocator
+    // and is packed tightly by TLSF allocator.
+    let awkward = 1100 * 1024;
+    assert_eq!(
+        allocator.strategy_for(&request(awkward, 0b10), None),
+        Some((1, Strategy::Tlsf))
+    );
+
+    let buddy = unsafe { allocator.alloc(&device, request(awkward, 0b01)) }?;
+    let tlsf = unsafe { allocator.alloc(&device, request(awkward, 0b10)) }?;
+    assert_eq!(buddy.size(), 2 * 1024 * 1024);
+    assert_eq!(tlsf.size(), awkward);
+
+    let neighbour = unsafe { allocator.alloc(&device, request(awkward, 0b10)) }?;
+    assert_eq!(*neighbour.memory(), *tlsf.memory());
+    assert_eq!(neighbour.offset(), awkward);
+
+    unsafe {
+        allocator.dealloc(&device, buddy);
+        allocator.dealloc(&device, tlsf);
+        allocator.dealloc(&device, neighbour);
+    }
+    assert_eq!(device.live_allocations(), 0, "Free chunks are deallocated");
+
+    // Random sizes and alignments never overlap.
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut random = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..2000 {
+        if blocks.len() > 10 && random() % 2 == 0 {
+            let index = random() as usize % blocks.len();
+            let block = blocks.swap_remove(index);
+            unsafe { allocator.dealloc(&device, block) };
+            continue;
+        }
+
+        let size = 1 + random() % (256 * 1024);
+        let align_mask = (1u64 << (random() % 12)) - 1;
+        let block = unsafe {
+            allocator.alloc(
+                &device,
+                Request {
+                    size,
+                    align_mask,
+                    memory_types: 0b10,
+                    ..Request::default()
+                },
+            )
+        }?;
+        assert_eq!(block.offset() & align_mask, 0);
+        assert!(block.size() >= size);
+
+        for other in &blocks {
+            if other.memory() == block.memory() {
+                assert!(
+                    other.offset() + other.size() <= block.offset()
+                        || block.offset() + block.size() <= other.offset(),
+                    "Blocks overlap"
+                );
+            }
+        }
+        blocks.push(block);
+    }
+
+    let plan = allocator.plan(&[request(awkward, 0b10)]);
+    assert_eq!(plan.allocations.len(), 1);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+    assert_eq!(device.live_allocations(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{
+        borrow::Cow,
+        collections::BTreeMap,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    },
+    tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Subscriber,
+    },
+    tracing_subscriber::{
+        layer::{Context, Layer, SubscriberExt as _},
+        registry::Registry,
+    },
+};
+
+type Fields = BTreeMap<String, String>;
+
+#[derive(Default)]
+struct Captured {
+    spans: BTreeMap<u64, (String, Fields)>,
+    events: Vec<Fields>,
+}
+
+struct Collect(Fields);
+
+impl Visit for Collect {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_owned(), format!("{:?}", value));
+    }
+}
+
+/// Layer that keeps fields of gpu-alloc spans and events.
+struct Capture(Arc<Mutex<Captured>>);
+
+impl<S: Subscriber> Layer<S> for Capture {
+    fn new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = Collect(Fields::new());
+        attrs.record(&mut fields);
+        self.0.lock().unwrap().spans.insert(
+            id.into_u64(),
+            (attrs.metadata().name().to_owned(), fields.0),
+        );
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut captured = self.0.lock().unwrap();
+        if let Some((_, fields)) = captured.spans.get_mut(&id.into_u64()) {
+            let mut collect = Collect(std::mem::take(fields));
+            values.record(&mut collect);
+            *fields = collect.0;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = Collect(Fields::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().events.push(fields.0);
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let captured = Arc::new(Mutex::new(Captured::default()));
+    let subscriber = Registry::default().with(Capture(captured.clone()));
+    tracing::subscriber::set_global_default(subscriber)?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1000,
+                align_mask: 255,
+                usage: UsageFlags::FAST_DEVICE_ACCESS,
+                label: Some("vertices"),
+                ..Request::default()
+            },
+        )?
+    };
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+
+    let captured = captured.lock().unwrap();
+
+    // Request span carries requirements and chosen memory type and strategy.
+    let (_, request) = captured
+        .spans
+        .values()
+        .find(|(name, _)| name == "request")
+        .expect("Request span is missing");
+    assert_eq!(request["size"], "1000");
+    assert_eq!(request["align_mask"], "255");
+    assert_eq!(request["label"], "Some(\"vertices\")");
+    assert!(request["usage"].contains("FAST_DEVICE_ACCESS"));
+    assert_eq!(request["memory_type"], "0");
+    assert_eq!(request["strategy"], "Buddy");
+
+    // Allocation and deallocation events carry block placement.
+    let message = |text: &str| {
+        captured
+            .events
+            .iter()
+            .find(|fields| fields.get("message").map(String::as_str) == Some(text))
+            .unwrap_or_else(|| panic!("Event {:?} is missing", text))
+    };
+    for fields in &[message("Block allocated"), message("Block deallocated")] {
+        assert_eq!(fields["memory_type"], "0");
+        assert_eq!(fields["strategy"], "Buddy");
+        assert_eq!(fields["size"], "1024");
+        assert!(fields.contains_key("offset"));
+    }
+    Ok(())
+}
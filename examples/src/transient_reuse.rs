@@ -1,7 +1,7 @@
 use {
     gpu_alloc::{
-        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
-        Request, UsageFlags,
+        AllocationFlags, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, RequestFlags, ResourceKind, UsageFlags,
     },
     gpu_alloc_mock::MockMemoryDevice,
     std::{borrow::Cow, collections::VecDeque},
@@ -31,6 +31,7 @@ fn main() -> eyre::Result<()> {
         max_memory_allocation_size: 1024 * 1024,
         non_coherent_atom_size: 8,
         buffer_device_address: false,
+        buffer_image_granularity: 1,
     });
 
     let config = Config::i_am_potato();
@@ -58,6 +59,15 @@ fn main() -> eyre::Result<()> {
                     align_mask: 0,
                     usage: UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT,
                     memory_types: !0,
+                    memory_type: None,
+                    access: None,
+                    label: None,
+                    dedicated: None,
+                    dedicated_resource: None,
+                    export: AllocationFlags::empty(),
+                    priority: None,
+                    flags: RequestFlags::empty(),
+                    resource_kind: ResourceKind::Linear,
                 },
             )
         }?;
@@ -0,0 +1,85 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.frame_ring_size = 8192;
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let request = Request {
+        size: 1024,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD,
+        ..Request::default()
+    };
+
+    // Transient blocks are served by free-list allocator.
+    let mut blocks = Vec::new();
+    for _ in 0..16 {
+        let block = unsafe {
+            allocator.alloc(
+                &device,
+                Request {
+                    usage: UsageFlags::UPLOAD | UsageFlags::TRANSIENT,
+                    ..request
+                },
+            )
+        }?;
+        blocks.push(block);
+    }
+
+    allocator.begin_frame(0);
+    let frame_block = unsafe { allocator.alloc_in_frame(&device, request) }?;
+
+    // Free-list allocator keeps its last memory object when all blocks are freed.
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+    }
+    assert_eq!(device.live_allocations(), 2);
+
+    // Trimming releases it, ring is kept while frame is not retired.
+    unsafe { allocator.trim(&device) };
+    assert_eq!(device.live_allocations(), 1);
+
+    drop(frame_block);
+    allocator.end_frame(0);
+
+    unsafe { allocator.trim(&device) };
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(allocator.allocation_count(), 0);
+
+    // Allocator keeps working after trimming.
+    let block = unsafe { allocator.alloc(&device, request) }?;
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
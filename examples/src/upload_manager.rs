@@ -0,0 +1,130 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UploadCopy, UploadManager, UploadPath, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+/// Returns device with device-local memory type
+/// and host-visible memory type with `host_visible` properties in `host_visible_heap`.
+fn mock_device(host_visible: MemoryPropertyFlags, host_visible_heap: u32) -> MockMemoryDevice {
+    MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Owned(vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: host_visible_heap,
+                props: host_visible | MemoryPropertyFlags::HOST_VISIBLE,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 32 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 32 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 32 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    })
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let request = Request {
+        size: 4096,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        ..Request::default()
+    };
+    let data = [7u8; 1000];
+
+    // Data is written to staging memory and copied on discrete device.
+    let device = mock_device(MemoryPropertyFlags::empty(), 1);
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let mut uploads = UploadManager::new(64 * 1024, 3);
+
+    let mut dst = unsafe { allocator.alloc(&device, request) }?;
+    assert_eq!(dst.memory_type(), 0);
+
+    let mut copies = Vec::new();
+    let mut recorder = |copy: UploadCopy<'_, usize>| {
+        assert_eq!(copy.src.memory_type(), 1);
+        assert_eq!(copy.src_offset % 4, 0);
+        copies.push((copy.dst_offset, copy.size));
+    };
+
+    for i in 0..4 {
+        let path = unsafe {
+            uploads.upload(
+                &mut allocator,
+                &device,
+                &mut dst,
+                i * 1000,
+                &data,
+                &mut recorder,
+            )
+        }?;
+        assert_eq!(path, UploadPath::Staged);
+    }
+    assert_eq!(
+        copies,
+        [(0, 1000), (1000, 1000), (2000, 1000), (3000, 1000)]
+    );
+    assert_eq!(uploads.staging_belt().block_count(), 1);
+
+    uploads.finish(0);
+    unsafe { uploads.retire(0) };
+
+    unsafe {
+        uploads.destroy(&mut allocator, &device);
+        allocator.dealloc(&device, dst);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.live_allocations(), 0);
+
+    // Device with resizable BAR writes device-local memory directly.
+    let device = mock_device(MemoryPropertyFlags::DEVICE_LOCAL, 0);
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let mut uploads = UploadManager::new(64 * 1024, 3);
+
+    let mut dst = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                usage: UsageFlags::FAST_DEVICE_ACCESS | UsageFlags::UPLOAD,
+                ..request
+            },
+        )
+    }?;
+    assert_eq!(dst.memory_type(), 1);
+
+    let mut recorder = |_: UploadCopy<'_, usize>| panic!("Direct upload must not record copies");
+    let path =
+        unsafe { uploads.upload(&mut allocator, &device, &mut dst, 100, &data, &mut recorder) }?;
+    assert_eq!(path, UploadPath::Direct);
+    assert_eq!(uploads.staging_belt().block_count(), 0);
+
+    let mut read = [0; 1000];
+    unsafe { dst.read_bytes(&device, 100, &mut read) }?;
+    assert_eq!(read[..], data[..]);
+
+    unsafe {
+        uploads.destroy(&mut allocator, &device);
+        allocator.dealloc(&device, dst);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.live_allocations(), 0);
+
+    println!("Uploads pick direct writes or staging copies");
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use {
+    gpu_alloc::{
+        ChunkSizes, Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    // Large chunks for device-local memory, small ones for upload memory.
+    let mut config = Config::i_am_potato();
+    config.usage_chunk_sizes = vec![
+        (
+            UsageFlags::FAST_DEVICE_ACCESS,
+            ChunkSizes {
+                buddy: Some(256 * 1024),
+                ..ChunkSizes::default()
+            },
+        ),
+        (
+            UsageFlags::UPLOAD,
+            ChunkSizes {
+                buddy: Some(16 * 1024),
+                free_list: Some(16 * 1024),
+                ..ChunkSizes::default()
+            },
+        ),
+    ];
+
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let mut blocks = Vec::new();
+    let mut alloc = |usage: UsageFlags| -> eyre::Result<u64> {
+        let heap = if usage.contains(UsageFlags::UPLOAD) {
+            1
+        } else {
+            0
+        };
+        let used = device.heap_usage(heap);
+        blocks.push(unsafe {
+            allocator.alloc(
+                &device,
+                Request {
+                    size: 1024,
+                    align_mask: 255,
+                    usage,
+                    ..Request::default()
+                },
+            )
+        }?);
+        Ok(device.heap_usage(heap) - used)
+    };
+
+    assert_eq!(alloc(UsageFlags::FAST_DEVICE_ACCESS)?, 256 * 1024);
+    assert_eq!(alloc(UsageFlags::UPLOAD)?, 16 * 1024);
+    assert_eq!(
+        alloc(UsageFlags::UPLOAD | UsageFlags::TRANSIENT)?,
+        16 * 1024
+    );
+
+    // Free-list chunks of device-local memory keep configured size.
+    assert_eq!(
+        alloc(UsageFlags::FAST_DEVICE_ACCESS | UsageFlags::TRANSIENT)?,
+        8 * 1024
+    );
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+
+    println!("Chunk sizes follow usage of memory types");
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, UsageFallback, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            // Small BAR heap.
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::DEVICE_LOCAL
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 2,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Borrowed(&[
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+            MemoryHeap { size: 64 * 1024 },
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+            },
+        ]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut config = Config::i_am_potato();
+    config.usage_fallbacks = vec![
+        // Uploads go to BAR heap, then to host memory.
+        UsageFallback {
+            usage: UsageFlags::UPLOAD,
+            tiers: vec![1 << 1, 1 << 2],
+        },
+        // Device-local host access never falls back to host memory.
+        UsageFallback {
+            usage: UsageFlags::HOST_ACCESS | UsageFlags::FAST_DEVICE_ACCESS,
+            tiers: vec![1 << 1],
+        },
+    ];
+    let mut allocator = GpuAllocator::new(config, device.props());
+
+    let upload = Request {
+        size: 16 * 1024,
+        usage: UsageFlags::UPLOAD,
+        ..Request::default()
+    };
+
+    let mut blocks = Vec::new();
+    for _ in 0..6 {
+        blocks
+            .push(unsafe { allocator.alloc_with_dedicated(&device, upload, Dedicated::Required)? });
+    }
+
+    // First blocks fill BAR heap, the rest are served by the next tier.
+    let tiers: Vec<_> = blocks.iter().map(|block| block.fallback_tier()).collect();
+    assert_eq!(
+        tiers,
+        [Some(0), Some(0), Some(0), Some(0), Some(1), Some(1)]
+    );
+    assert!(blocks[..4].iter().all(|block| block.memory_type() == 1));
+    assert!(blocks[4..].iter().all(|block| block.memory_type() == 2));
+
+    // Chain ending with BAR heap fails once it is exhausted.
+    let result = unsafe {
+        allocator.alloc_with_dedicated(
+            &device,
+            Request {
+                usage: UsageFlags::HOST_ACCESS | UsageFlags::FAST_DEVICE_ACCESS,
+                ..upload
+            },
+            Dedicated::Required,
+        )
+    };
+    assert!(matches!(result, Err(AllocationError::OutOfDeviceMemory(_))));
+
+    // Requests of other usages are not affected.
+    let other = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1024,
+                usage: UsageFlags::FAST_DEVICE_ACCESS,
+                ..Request::default()
+            },
+        )?
+    };
+    assert_eq!(other.fallback_tier(), None);
+    assert_eq!(other.memory_type(), 0);
+
+    unsafe {
+        for block in blocks {
+            allocator.dealloc(&device, block);
+        }
+        allocator.dealloc(&device, other);
+        allocator.cleanup(&device);
+    }
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
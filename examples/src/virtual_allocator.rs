@@ -0,0 +1,50 @@
+use gpu_alloc::VirtualAllocator;
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    // Descriptor buffer of 4 KiB with descriptors aligned to 64 bytes.
+    let mut allocator = VirtualAllocator::new(4096);
+
+    let a = allocator.alloc(100, 63).unwrap();
+    let b = allocator.alloc(1000, 63).unwrap();
+    let c = allocator.alloc(64, 255).unwrap();
+    for block in [&a, &b, &c].iter() {
+        assert_eq!(block.offset() % 64, 0);
+        assert!(block.offset() + block.size() <= 4096);
+    }
+    assert_eq!(c.offset() % 256, 0);
+    assert!(a.offset() + a.size() <= b.offset() || b.offset() + b.size() <= a.offset());
+    assert_eq!(allocator.used(), 1164);
+    assert_eq!(allocator.block_count(), 3);
+
+    // Range is exhausted.
+    assert!(allocator.alloc(4096, 0).is_none());
+
+    // Freed blocks merge back into whole range.
+    allocator.dealloc(b);
+    allocator.dealloc(a);
+    allocator.dealloc(c);
+    assert_eq!(allocator.used(), 0);
+
+    let whole = allocator.alloc(4096, 0).unwrap();
+    assert_eq!(whole.offset(), 0);
+    assert!(allocator.alloc(1, 0).is_none());
+    allocator.dealloc(whole);
+
+    // Free range that fits exactly is found too.
+    let mut exact = VirtualAllocator::new(1000);
+    let whole = exact.alloc(1000, 7).unwrap();
+    assert_eq!(whole.offset(), 0);
+    exact.dealloc(whole);
+
+    // Reset frees all blocks at once.
+    let blocks: Vec<_> = (0..64).map(|_| allocator.alloc(64, 63).unwrap()).collect();
+    assert!(allocator.alloc(1, 0).is_none());
+    drop(blocks);
+    allocator.reset();
+    assert_eq!(allocator.block_count(), 0);
+    assert!(allocator.alloc(4096, 0).is_some());
+
+    Ok(())
+}
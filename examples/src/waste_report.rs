@@ -0,0 +1,76 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    // Buddy allocator rounds size up to power of two.
+    let buddy = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1100,
+                ..Request::default()
+            },
+        )
+    }?;
+    assert_eq!(buddy.size(), 2048);
+
+    let report = allocator.report();
+    assert_eq!(report.memory_types[0].block_count, 1);
+    assert_eq!(report.memory_types[0].live_bytes, 2048);
+    assert_eq!(report.memory_types[0].wasted_bytes, 2048 - 1100);
+
+    // Free-list allocator pads block to alignment.
+    let transient = |size| Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::TRANSIENT,
+        ..Request::default()
+    };
+    let first = unsafe { allocator.alloc(&device, transient(256)) }?;
+    let second = unsafe { allocator.alloc(&device, transient(100)) }?;
+    assert_eq!(second.size(), 256);
+
+    let report = allocator.report();
+    assert_eq!(report.memory_types[0].block_count, 3);
+    assert_eq!(report.live_bytes(), 2048 + 256 + 256);
+    assert_eq!(report.wasted_bytes(), 2048 - 1100 + 256 - 100);
+
+    unsafe {
+        allocator.dealloc(&device, buddy);
+        allocator.dealloc(&device, first);
+        allocator.dealloc(&device, second);
+    }
+
+    let report = allocator.report();
+    assert_eq!(report.live_bytes(), 0);
+    assert_eq!(report.wasted_bytes(), 0);
+
+    unsafe { allocator.cleanup(&device) };
+
+    Ok(())
+}
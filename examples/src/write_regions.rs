@@ -0,0 +1,78 @@
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 16 * 1024 * 1024,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    });
+
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let mut block = unsafe {
+        allocator.alloc(
+            &device,
+            Request {
+                size: 1024,
+                align_mask: 63,
+                usage: UsageFlags::UPLOAD,
+                dedicated: Some(Dedicated::Required),
+                ..Request::default()
+            },
+        )
+    }?;
+
+    let maps = device.total_maps();
+    let flushes = device.total_flushes();
+    let invalidations = device.total_invalidations();
+
+    // Regions may come in any order and share atoms.
+    unsafe {
+        block.write_regions(
+            &device,
+            &[(512, &[3; 64]), (10, &[1; 20]), (40, &[2; 8]), (700, &[])],
+        )
+    }?;
+
+    // Block is mapped once, for all regions.
+    assert_eq!(device.total_maps(), maps + 1);
+    assert_eq!(device.live_mappings(), 0);
+
+    // Each non-empty region is flushed in single call,
+    // partial atoms are flushed whole without invalidation.
+    assert_eq!(device.total_flushes(), flushes + 3);
+    assert_eq!(device.total_invalidations(), invalidations);
+
+    let content = device.memory_content(*block.memory());
+    assert_eq!(content[..10], [0; 10]);
+    assert_eq!(content[10..30], [1; 20]);
+    assert_eq!(content[30..40], [0; 10]);
+    assert_eq!(content[40..48], [2; 8]);
+    assert_eq!(content[512..576], [3; 64]);
+
+    unsafe {
+        allocator.dealloc(&device, block);
+        allocator.cleanup(&device);
+    }
+
+    assert_eq!(device.live_allocations(), 0);
+    Ok(())
+}
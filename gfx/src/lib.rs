@@ -13,7 +13,9 @@
 //!         queue::QueueFamily as _,
 //!         Features, Instance as _,
 //!     },
-//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc::{
+//!         AllocationFlags, Config, GpuAllocator, Request, RequestFlags, ResourceKind, UsageFlags,
+//!     },
 //!     gpu_alloc_gfx::{gfx_device_properties, GfxMemoryDevice},
 //! };
 //!
@@ -73,6 +75,15 @@
 //!                 align_mask: 1,
 //!                 usage: UsageFlags::HOST_ACCESS,
 //!                 memory_types: !0,
+//!                 memory_type: None,
+//!                 access: None,
+//!                 label: None,
+//!                 dedicated: None,
+//!                 dedicated_resource: None,
+//!                 export: AllocationFlags::empty(),
+//!                 priority: None,
+//!                 flags: RequestFlags::empty(),
+//!                 resource_kind: ResourceKind::Linear,
 //!             },
 //!         )
 //!     }?;
@@ -202,7 +213,7 @@ where
         self.device
             .invalidate_mapped_memory_ranges(ranges.iter().map(|range| {
                 (
-                    &*range.memory,
+                    range.memory,
                     Segment {
                         offset: range.offset,
                         size: Some(range.size),
@@ -223,7 +234,7 @@ where
         self.device
             .flush_mapped_memory_ranges(ranges.iter().map(|range| {
                 (
-                    &*range.memory,
+                    range.memory,
                     Segment {
                         offset: range.offset,
                         size: Some(range.size),
@@ -246,10 +257,9 @@ where
     let memory_properties = adapter.physical_device.memory_properties();
     DeviceProperties {
         max_memory_allocation_count: u32::try_from(limits.max_memory_allocation_count)
-            .unwrap_or(u32::max_value()),
-        max_memory_allocation_size: u64::max_value(),
-        non_coherent_atom_size: u64::try_from(limits.non_coherent_atom_size)
-            .unwrap_or(u64::max_value()),
+            .unwrap_or(u32::MAX),
+        max_memory_allocation_size: u64::MAX,
+        non_coherent_atom_size: u64::try_from(limits.non_coherent_atom_size).unwrap_or(u64::MAX),
         memory_types: memory_properties
             .memory_types
             .iter()
@@ -267,6 +277,7 @@ where
             })
             .collect(),
         buffer_device_address: false,
+        buffer_image_granularity: limits.buffer_image_granularity,
     }
 }
 
@@ -1,29 +1,69 @@
 use {
     crate::{
-        align_down,
+        align_down, align_up,
         block::{MemoryBlock, MemoryBlockFlavor},
-        buddy::{BuddyAllocator, BuddyBlock},
-        config::Config,
-        error::AllocationError,
-        freelist::{FreeListAllocator, FreeListBlock},
+        buddy::{BuddyAllocator, BuddyBlock, BuddyPlan},
+        budget::BudgetedDevice,
+        config::{BudgetPolicy, ChunkGrowth, ChunkSizes, Config, StrategyThresholds},
+        error::{
+            AllocationError, BindError, DeallocError, DeallocRangeError, ImportError,
+            OutOfMemoryDetails,
+        },
+        external::{ExternalHandleType, ExternalMemory},
+        freelist::{FreeListAllocator, FreeListBlock, FreeListPlan},
         heap::Heap,
-        usage::{MemoryForUsage, UsageFlags},
+        memory_map,
+        observer::{AllocationObserver, ChunkInfo, HeapPressure},
+        plan::{reserve, PlanResult, PlannedAllocation},
+        pool::{Pool, PoolAllocator, PoolConfig, PoolId},
+        preallocated::{Preallocated, PreallocatedDevice},
+        recording::Recording,
+        relocation::Relocation,
+        report::{
+            AllocatorReport, AllocatorStats, GuardCorruption, LeakedBlock, LiveAllocation,
+            MemoryTypeReport, MemoryTypeStats, StrategyStats,
+        },
+        ring::RingAllocator,
+        snapshot::{AllocatorSnapshot, BlockSnapshot},
+        tlsf::{TlsfAllocator, TlsfBlock, TlsfPlan},
+        tuning::SizeHistogram,
+        usage::{MemoryForUsage, RequestFlags, ResourceKind, UsageFallback, UsageFlags},
         MemoryBounds, Request,
     },
-    alloc::boxed::Box,
-    core::convert::TryFrom as _,
+    alloc::{
+        boxed::Box,
+        collections::{BTreeMap, BTreeSet},
+        string::String,
+        sync::Arc,
+        vec::Vec,
+    },
+    core::{
+        cell::{RefCell, UnsafeCell},
+        convert::TryFrom as _,
+        ptr::NonNull,
+    },
     gpu_alloc_types::{
-        AllocationFlags, DeviceProperties, MemoryDevice, MemoryPropertyFlags, MemoryType,
-        OutOfMemory,
+        AllocationFlags, DeviceMapError, DeviceProperties, MemoryDevice, MemoryPropertyFlags,
+        MemoryType,
     },
 };
 
+#[cfg(feature = "async")]
+use gpu_alloc_types::AsyncMemoryDevice;
+
+#[cfg(feature = "std")]
+use crate::heap::SharedHeap;
+
+#[cfg(feature = "backtrace")]
+use crate::report::AllocationBacktrace;
+
 /// Memory allocator for Vulkan-like APIs.
 #[derive(Debug)]
 pub struct GpuAllocator<M> {
-    dedicated_threshold: u64,
-    preferred_dedicated_threshold: u64,
-    transient_dedicated_threshold: u64,
+    /// Thresholds of strategy selection of each memory type.
+    dedicated_threshold: Box<[u64]>,
+    preferred_dedicated_threshold: Box<[u64]>,
+    transient_dedicated_threshold: Box<[u64]>,
     max_memory_allocation_size: u64,
     memory_for_usage: MemoryForUsage,
     memory_types: Box<[MemoryType]>,
@@ -31,14 +71,52 @@ pub struct GpuAllocator<M> {
     max_allocation_count: u32,
     allocations_remains: u32,
     non_coherent_atom_mask: u64,
-    starting_free_list_chunk: u64,
-    final_free_list_chunk: u64,
-    minimal_buddy_size: u64,
-    initial_buddy_dedicated_size: u64,
+    min_imported_host_pointer_alignment: u64,
+    min_alignment_mask: u64,
+    /// Starting and final free-list chunk sizes of each memory type.
+    free_list_chunks: Box<[(u64, u64)]>,
+    minimal_buddy_size: Box<[u64]>,
+    /// Buddy and TLSF chunk size of each memory type.
+    initial_buddy_dedicated_size: Box<[u64]>,
+    large_object_threshold: Box<[u64]>,
+    adaptive_chunk_size: bool,
+    chunk_growth: Box<[ChunkGrowth]>,
     buffer_device_address: bool,
+    buffer_image_granularity_mask: u64,
+    observer: Option<Arc<dyn AllocationObserver>>,
+    validate_deallocations: bool,
+    last_block_id: u64,
+    live_blocks: BTreeMap<u64, LiveBlock>,
+    /// Blocks of each aliasing group by generation of memory range they share.
+    alias_groups: BTreeMap<u64, BTreeMap<u64, LeakedBlock>>,
 
+    /// Allocators of shared memory objects are indexed by `allocator_slot`.
     buddy_allocators: Box<[Option<BuddyAllocator<M>>]>,
+    large_buddy_allocators: Box<[Option<BuddyAllocator<M>>]>,
     freelist_allocators: Box<[Option<FreeListAllocator<M>>]>,
+    tlsf_allocators: Box<[Option<TlsfAllocator<M>>]>,
+    best_fit_allocators: Box<[Option<FreeListAllocator<M>>]>,
+    ring_allocators: Box<[Option<RingAllocator<M>>]>,
+    frame_ring_size: u64,
+    current_frame: Option<u64>,
+    budget_policy: BudgetPolicy,
+    /// Sorted thresholds, number of them reached is kept by each heap.
+    pressure_thresholds: Box<[u8]>,
+    /// Heap usage at last pressure check.
+    pressure_used: Box<[u64]>,
+    guard_size: u64,
+    guard_corruptions: Vec<GuardCorruption>,
+    size_histograms: Box<[SizeHistogram]>,
+    buddy_spillover_memory_types: u32,
+    tlsf_memory_types: u32,
+    best_fit_memory_types: u32,
+    usage_fallbacks: Box<[UsageFallback]>,
+    spillover_counts: Box<[u64]>,
+    pools: Vec<Option<Pool<M>>>,
+    deferred: BTreeMap<u64, Vec<MemoryBlock<M>>>,
+    prewarmed: Box<[Vec<Preallocated<M>>]>,
+    recording: Option<Recording>,
+    device_lost: bool,
 }
 
 /// Hints for allocator to decide on allocation strategy.
@@ -59,6 +137,39 @@ pub enum Dedicated {
     Preferred,
 }
 
+/// Allocation strategy chosen by allocator to serve a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+#[non_exhaustive]
+pub enum Strategy {
+    /// Request is served by dedicated memory object.
+    Dedicated,
+
+    /// Request is served by buddy allocator of small objects.
+    Buddy,
+
+    /// Request is served by buddy allocator of large objects.\
+    /// Large objects are kept in separate chunks from small ones.
+    LargeBuddy,
+
+    /// Request is served by free-list allocator of transient objects.
+    FreeList,
+
+    /// Request is served by two-level segregated fit allocator.\
+    /// Used instead of buddy allocators for memory types
+    /// selected by `Config::tlsf_memory_types`.
+    Tlsf,
+
+    /// Request is served by best-fit free-list allocator of mid-size objects.\
+    /// Used instead of large-object buddy allocator for memory types
+    /// selected by `Config::best_fit_memory_types`.
+    BestFit,
+}
+
 impl<M> GpuAllocator<M>
 where
     M: MemoryBounds + 'static,
@@ -78,19 +189,119 @@ where
             "`non_coherent_atom_size` must fit host address space"
         );
 
+        assert!(
+            props.buffer_image_granularity.is_power_of_two(),
+            "`buffer_image_granularity` must be power of two"
+        );
+
+        let max_allocation_count = match config.max_memory_allocation_count {
+            Some(count) => count.min(props.max_memory_allocation_count),
+            None => props.max_memory_allocation_count,
+        };
+
+        assert!(
+            config.min_imported_host_pointer_alignment.is_power_of_two(),
+            "`min_imported_host_pointer_alignment` must be power of two"
+        );
+
+        assert!(
+            config.min_alignment.is_power_of_two(),
+            "`min_alignment` must be power of two"
+        );
+
+        let memory_for_usage = MemoryForUsage::new(props.memory_types.as_ref());
+
+        // Memory objects of blocks with device address and of different priority classes
+        // are kept apart from others.
+        let slots = props.memory_types.len() * 2 * PRIORITY_CLASSES;
+
+        let chunk_sizes: Vec<ChunkSizes> = (0..props.memory_types.len() as u32)
+            .map(|index| {
+                let mut sizes = ChunkSizes::default();
+                for &(usage, overrides) in &config.usage_chunk_sizes {
+                    if memory_for_usage.types(usage, None).first() == Some(&index) {
+                        sizes.buddy = overrides.buddy.or(sizes.buddy);
+                        sizes.free_list = overrides.free_list.or(sizes.free_list);
+                        sizes.minimal_buddy = overrides.minimal_buddy.or(sizes.minimal_buddy);
+                    }
+                }
+                sizes
+            })
+            .collect();
+
+        let thresholds: Vec<StrategyThresholds> = (0..props.memory_types.len() as u32)
+            .map(|index| {
+                let usage_overrides = config
+                    .usage_thresholds
+                    .iter()
+                    .filter(|&&(usage, _)| {
+                        memory_for_usage.types(usage, None).first() == Some(&index)
+                    })
+                    .map(|(_, overrides)| overrides);
+
+                let memory_type_overrides = config
+                    .memory_type_thresholds
+                    .iter()
+                    .filter(|&&(memory_type, _)| memory_type == index)
+                    .map(|(_, overrides)| overrides);
+
+                let mut thresholds = StrategyThresholds::default();
+                for overrides in usage_overrides.chain(memory_type_overrides) {
+                    thresholds.dedicated = overrides.dedicated.or(thresholds.dedicated);
+                    thresholds.preferred_dedicated = overrides
+                        .preferred_dedicated
+                        .or(thresholds.preferred_dedicated);
+                    thresholds.transient_dedicated = overrides
+                        .transient_dedicated
+                        .or(thresholds.transient_dedicated);
+                    thresholds.large_object = overrides.large_object.or(thresholds.large_object);
+                }
+                thresholds
+            })
+            .collect();
+
+        let dedicated_threshold: Box<[u64]> = thresholds
+            .iter()
+            .map(|thresholds| thresholds.dedicated.unwrap_or(config.dedicated_threshold))
+            .collect();
+
         GpuAllocator {
-            dedicated_threshold: config.dedicated_threshold,
-            preferred_dedicated_threshold: config
-                .preferred_dedicated_threshold
-                .min(config.dedicated_threshold),
+            preferred_dedicated_threshold: thresholds
+                .iter()
+                .zip(&*dedicated_threshold)
+                .map(|(thresholds, &dedicated)| {
+                    thresholds
+                        .preferred_dedicated
+                        .unwrap_or(config.preferred_dedicated_threshold)
+                        .min(dedicated)
+                })
+                .collect(),
+
+            transient_dedicated_threshold: thresholds
+                .iter()
+                .zip(&*dedicated_threshold)
+                .map(|(thresholds, &dedicated)| {
+                    thresholds
+                        .transient_dedicated
+                        .unwrap_or(config.transient_dedicated_threshold)
+                        .max(dedicated)
+                })
+                .collect(),
+
+            large_object_threshold: thresholds
+                .iter()
+                .map(|thresholds| {
+                    thresholds
+                        .large_object
+                        .unwrap_or(config.large_object_threshold)
+                })
+                .collect(),
 
-            transient_dedicated_threshold: config
-                .transient_dedicated_threshold
-                .max(config.dedicated_threshold),
+            dedicated_threshold,
 
             max_memory_allocation_size: props.max_memory_allocation_size,
 
-            memory_for_usage: MemoryForUsage::new(props.memory_types.as_ref()),
+            memory_for_usage,
 
             memory_types: props.memory_types.as_ref().iter().copied().collect(),
             memory_heaps: props
@@ -101,18 +312,87 @@ where
                 .collect(),
 
             buffer_device_address: props.buffer_device_address,
+            buffer_image_granularity_mask: props.buffer_image_granularity - 1,
 
-            max_allocation_count: props.max_memory_allocation_count,
-            allocations_remains: props.max_memory_allocation_count,
+            max_allocation_count,
+            allocations_remains: max_allocation_count,
             non_coherent_atom_mask: props.non_coherent_atom_size - 1,
+            min_imported_host_pointer_alignment: config.min_imported_host_pointer_alignment,
+            min_alignment_mask: config.min_alignment - 1,
 
-            starting_free_list_chunk: config.starting_free_list_chunk,
-            final_free_list_chunk: config.final_free_list_chunk,
-            minimal_buddy_size: config.minimal_buddy_size,
-            initial_buddy_dedicated_size: config.initial_buddy_dedicated_size,
+            free_list_chunks: chunk_sizes
+                .iter()
+                .map(|sizes| match sizes.free_list {
+                    Some(size) => (size, size),
+                    None => (
+                        config.starting_free_list_chunk,
+                        config.final_free_list_chunk,
+                    ),
+                })
+                .collect(),
+            minimal_buddy_size: chunk_sizes
+                .iter()
+                .map(|sizes| sizes.minimal_buddy.unwrap_or(config.minimal_buddy_size))
+                .collect(),
+            initial_buddy_dedicated_size: chunk_sizes
+                .iter()
+                .map(|sizes| sizes.buddy.unwrap_or(config.initial_buddy_dedicated_size))
+                .collect(),
+            adaptive_chunk_size: config.adaptive_chunk_size,
+            chunk_growth: (0..props.memory_types.len() as u32)
+                .map(|index| {
+                    config
+                        .memory_type_chunk_growth
+                        .iter()
+                        .rev()
+                        .find(|&&(memory_type, _)| memory_type == index)
+                        .map_or(config.chunk_growth, |&(_, growth)| growth)
+                })
+                .collect(),
+            observer: config.observer,
+            validate_deallocations: config.validate_deallocations && cfg!(debug_assertions),
+            last_block_id: 0,
+            live_blocks: BTreeMap::new(),
+            alias_groups: BTreeMap::new(),
 
-            buddy_allocators: props.memory_types.as_ref().iter().map(|_| None).collect(),
-            freelist_allocators: props.memory_types.as_ref().iter().map(|_| None).collect(),
+            buddy_allocators: (0..slots).map(|_| None).collect(),
+            large_buddy_allocators: (0..slots).map(|_| None).collect(),
+            freelist_allocators: (0..slots).map(|_| None).collect(),
+            tlsf_allocators: (0..slots).map(|_| None).collect(),
+            best_fit_allocators: (0..slots).map(|_| None).collect(),
+            ring_allocators: props.memory_types.as_ref().iter().map(|_| None).collect(),
+            frame_ring_size: config.frame_ring_size,
+            current_frame: None,
+            budget_policy: config.budget_policy,
+            pressure_thresholds: {
+                let mut thresholds = config.pressure_thresholds;
+                thresholds.sort_unstable();
+                thresholds.into_boxed_slice()
+            },
+            pressure_used: props.memory_heaps.as_ref().iter().map(|_| 0).collect(),
+            guard_size: config.guard_size,
+            guard_corruptions: Vec::new(),
+            size_histograms: props
+                .memory_types
+                .as_ref()
+                .iter()
+                .map(|_| SizeHistogram::new())
+                .collect(),
+            buddy_spillover_memory_types: config.buddy_spillover_memory_types,
+            tlsf_memory_types: config.tlsf_memory_types,
+            best_fit_memory_types: config.best_fit_memory_types,
+            usage_fallbacks: config.usage_fallbacks.into_boxed_slice(),
+            spillover_counts: props.memory_types.as_ref().iter().map(|_| 0).collect(),
+            pools: Vec::new(),
+            deferred: BTreeMap::new(),
+            prewarmed: props
+                .memory_types
+                .as_ref()
+                .iter()
+                .map(|_| Vec::new())
+                .collect(),
+            recording: None,
+            device_lost: false,
         }
     }
 
@@ -129,7 +409,45 @@ where
         device: &impl MemoryDevice<M>,
         request: Request,
     ) -> Result<MemoryBlock<M>, AllocationError> {
-        self.alloc_internal(device, request, None)
+        self.alloc_internal(device, request, None, None)
+    }
+
+    /// Allocates memory block from specified `device` according to the `request`,
+    /// like `GpuAllocator::alloc`.
+    /// If allocation fails with `AllocationError::OutOfDeviceMemory`,
+    /// `evict` is called to free memory, e.g. by deallocating cached blocks,
+    /// destroying pools or calling `GpuAllocator::cleanup`.
+    /// Allocation is retried once if `evict` returns `true`,
+    /// otherwise the error is returned.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, evict)))]
+    pub unsafe fn alloc_or_evict<D>(
+        &mut self,
+        device: &D,
+        request: Request,
+        evict: impl FnOnce(&mut Self, &D) -> bool,
+    ) -> Result<MemoryBlock<M>, AllocationError>
+    where
+        D: MemoryDevice<M>,
+    {
+        match self.alloc_internal(device, request, None, None) {
+            Err(err @ AllocationError::OutOfDeviceMemory(_)) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Out of device memory, evicting");
+
+                if evict(self, device) {
+                    self.alloc_internal(device, request, None, None)
+                } else {
+                    Err(err)
+                }
+            }
+            result => result,
+        }
     }
 
     /// Allocates memory block from specified `device` according to the `request`.
@@ -149,330 +467,3421 @@ where
         request: Request,
         dedicated: Dedicated,
     ) -> Result<MemoryBlock<M>, AllocationError> {
-        self.alloc_internal(device, request, Some(dedicated))
+        self.alloc_internal(device, request, Some(dedicated), None)
     }
 
-    unsafe fn alloc_internal(
+    /// Allocates memory block from specified `device` according to the `request`.
+    /// If new memory object has to be allocated to serve the `request`,
+    /// it is allocated with size of `chunk_size` bytes instead of size chosen according to `Config`.
+    /// Existing memory objects are reused as usual,
+    /// and sizes of memory objects allocated later are not affected.
+    ///
+    /// `chunk_size` is rounded up to power of two for buddy allocator
+    /// and to `non_coherent_atom_size` for free-list allocator.
+    /// Memory object is made large enough to serve the `request` even if `chunk_size` is smaller.
+    /// Requests served by dedicated memory objects ignore `chunk_size`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_in_chunk_of(
         &mut self,
         device: &impl MemoryDevice<M>,
-        mut request: Request,
-        dedicated: Option<Dedicated>,
+        request: Request,
+        chunk_size: u64,
     ) -> Result<MemoryBlock<M>, AllocationError> {
-        enum Strategy {
-            Buddy,
-            Dedicated,
-            FreeList,
-        }
+        self.alloc_internal(device, request, None, Some(chunk_size))
+    }
+
+    /// Allocates memory block according to the `request`
+    /// in memory object exportable as `handle_type`,
+    /// e.g. to share it with CUDA.
+    ///
+    /// Returns block together with description required to import its memory object,
+    /// see `MemoryBlock::external_memory`.
+    /// Memory type must support export of `handle_type`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_exportable(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: Request,
+        handle_type: ExternalHandleType,
+    ) -> Result<(MemoryBlock<M>, ExternalMemory), AllocationError> {
+        let request = Request {
+            export: request.export | handle_type.allocation_flag(),
+            ..request
+        };
 
-        request.usage = with_implicit_usage_flags(request.usage);
+        let block = self.alloc_internal(device, request, None, None)?;
+        let external = block
+            .external_memory(handle_type)
+            .expect("Exportable request must be served by exportable memory object");
+        Ok((block, external))
+    }
 
-        if request.usage.contains(UsageFlags::DEVICE_ADDRESS) {
-            assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
+    /// Allocates memory blocks for all `requests` at once.
+    /// Returned blocks are in the same order as requests.
+    ///
+    /// Requests are served from largest to smallest,
+    /// which packs blocks into fewer memory objects than serving them in arbitrary order.
+    /// Memory objects the batch needs are found with `GpuAllocator::plan`
+    /// and allocated up front, grouped by memory type, before blocks are suballocated from them.
+    /// If any request fails, blocks allocated for the batch are deallocated and error is returned.
+    /// Memory objects allocated up front for the failed batch stay unused,
+    /// like ones allocated by `GpuAllocator::prewarm`, until `trim` or `cleanup`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_many(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        requests: &[Request],
+    ) -> Result<Vec<MemoryBlock<M>>, AllocationError> {
+        if self.device_lost {
+            return Err(AllocationError::DeviceLost);
         }
 
-        if request.size > self.max_memory_allocation_size {
-            return Err(AllocationError::OutOfDeviceMemory);
+        let mut order: Vec<usize> = (0..requests.len()).collect();
+        order.sort_by_key(|&index| core::cmp::Reverse(requests[index].size));
+
+        let sorted: Vec<Request> = order.iter().map(|&index| requests[index]).collect();
+        let plan = self.plan(&sorted);
+
+        // Batch that is not going to fit is not worth allocating memory objects for,
+        // its requests report the actual error.
+        if plan.fits() {
+            let mut chunks: Vec<(u32, u64)> = sorted
+                .iter()
+                .zip(&plan.allocations)
+                .filter_map(|(request, planned)| match *planned {
+                    PlannedAllocation::NewMemory {
+                        memory_type,
+                        strategy,
+                        size,
+                    } if strategy != Strategy::Dedicated && is_preallocatable(request) => {
+                        Some((memory_type, size))
+                    }
+                    _ => None,
+                })
+                .collect();
+            chunks.sort_by_key(|&(memory_type, _)| memory_type);
+
+            for &(memory_type, size) in &chunks {
+                // Request that needs the memory object allocates it itself and reports the error.
+                if self.preallocate(device, memory_type, size).is_err() {
+                    break;
+                }
+            }
+
+            if !chunks.is_empty() {
+                self.update_pressure(device);
+            }
         }
 
-        if let Some(Dedicated::Required) = dedicated {
-            if self.allocations_remains == 0 {
-                return Err(AllocationError::TooManyObjects);
+        let mut blocks: Vec<Option<MemoryBlock<M>>> = requests.iter().map(|_| None).collect();
+        for index in order {
+            match self.alloc(device, requests[index]) {
+                Ok(block) => blocks[index] = Some(block),
+                Err(err) => {
+                    for block in blocks.into_iter().flatten() {
+                        self.dealloc(device, block);
+                    }
+                    return Err(err);
+                }
             }
         }
 
-        if 0 == self.memory_for_usage.mask(request.usage) & request.memory_types {
-            #[cfg(feature = "tracing")]
-            tracing::error!(
-                "Cannot serve request {:?}, no memory among bitset `{}` support usage {:?}",
-                request,
-                request.memory_types,
-                request.usage
+        Ok(blocks.into_iter().flatten().collect())
+    }
+
+    /// Allocates memory blocks for all `requests` that alias the same memory range.
+    /// Returned blocks are in the same order as requests and start at the same offset.
+    ///
+    /// Use it for resources that are never used at the same time,
+    /// e.g. transient render targets of different passes.
+    /// Memory range fits the largest request and satisfies alignment,
+    /// memory types and usage of all of them.
+    /// It is deallocated when all blocks of the group are deallocated.
+    ///
+    /// Memory range counts once in `stats` and `report`,
+    /// while `report_leaks` lists blocks of the group individually.
+    /// Blocks of the group can be mapped only if memory range is persistently mapped,
+    /// see `MemoryBlock::mapped_ptr`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * Device and host must not access blocks of the group
+    ///   without synchronization between uses of different blocks.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `requests` is empty,
+    /// or if any request has `dedicated_resource` or non-empty `export`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_aliased(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        requests: &[Request],
+    ) -> Result<Vec<MemoryBlock<M>>, AllocationError> {
+        assert!(
+            !requests.is_empty(),
+            "Aliasing group must have at least one request"
+        );
+
+        let requests = requests
+            .iter()
+            .map(|request| {
+                assert!(
+                    request.dedicated_resource.is_none(),
+                    "Memory dedicated to resource cannot be aliased"
+                );
+                assert!(
+                    request.export.is_empty(),
+                    "Exportable memory cannot be aliased"
+                );
+                self.granular_request(request)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Requests are already padded for their resource kinds.
+        let mut combined = Request {
+            usage: with_implicit_usage_flags(&requests[0]),
+            resource_kind: ResourceKind::Linear,
+            ..requests[0]
+        };
+        for request in &requests[1..] {
+            combined.size = combined.size.max(request.size);
+            combined.align_mask |= request.align_mask;
+            combined.usage |= with_implicit_usage_flags(request);
+            combined.memory_types &= request.memory_types;
+            combined.memory_type = combined.memory_type.or(request.memory_type);
+            combined.access = combined.access.or(request.access);
+            combined.dedicated = combined.dedicated.or(request.dedicated);
+            combined.priority = max_priority(combined.priority, request.priority);
+            combined.flags |= request.flags;
+        }
+
+        let backing = Arc::new(self.alloc(device, combined)?);
+        #[cfg(feature = "backtrace")]
+        let backtrace = self.live_blocks[&backing.generation()].backtrace.clone();
+
+        let mut aliases = BTreeMap::new();
+        let mut blocks = Vec::with_capacity(requests.len());
+        for request in &requests {
+            let mut block = MemoryBlock::alias(&backing, request.size, request);
+            self.last_block_id += 1;
+            block.id = self.last_block_id;
+
+            aliases.insert(
+                block.id,
+                LeakedBlock {
+                    label: block.label(),
+                    memory_type: block.memory_type(),
+                    strategy: block.strategy(),
+                    offset: block.offset(),
+                    size: block.size(),
+                    #[cfg(feature = "backtrace")]
+                    backtrace: backtrace.clone(),
+                },
             );
+            blocks.push(block);
+        }
 
-            return Err(AllocationError::NoCompatibleMemoryTypes);
+        self.alias_groups.insert(backing.generation(), aliases);
+        Ok(blocks)
+    }
+
+    /// Allocates memory objects for memory type with specified index ahead of time,
+    /// until at least `size` bytes are allocated.
+    ///
+    /// Memory objects are sized as ones that allocator of small requests
+    /// of this memory type would allocate next, growing according to `Config::chunk_growth`,
+    /// and are used by it instead of allocating new memory objects.
+    /// Call this function during loading to avoid allocating memory objects later.
+    ///
+    /// Prewarmed memory objects count towards memory object limit and heap usage.
+    /// Unused ones are deallocated by `trim` and `cleanup`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `memory_type` is not valid memory type index.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn prewarm(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        memory_type: u32,
+        size: u64,
+    ) -> Result<(), AllocationError> {
+        if self.device_lost {
+            return Err(AllocationError::DeviceLost);
         }
 
-        let transient = request.usage.contains(UsageFlags::TRANSIENT);
+        let index = memory_type;
+        let heap = self.memory_types[index as usize].heap;
+        let heap_size = self.memory_heaps[heap as usize].size();
+        let growth = self.chunk_growth[index as usize];
+        let tlsf = self.tlsf_memory_types & (1 << index) != 0;
 
-        for &index in self.memory_for_usage.types(request.usage) {
-            if 0 == request.memory_types & (1 << index) {
-                // Skip memory type incompatible with the request.
-                continue;
+        // Continue after memory objects that are already prewarmed.
+        let mut chunk_size = match self.prewarmed[index as usize].last() {
+            Some(last) if self.adaptive_chunk_size && !tlsf => last.size,
+            Some(last) => growth.next(last.size, heap_size),
+            None => self.next_chunk_size(index),
+        };
+
+        let mut allocated = 0;
+        while allocated < size {
+            self.preallocate(device, index, chunk_size)?;
+
+            allocated += chunk_size;
+            if !self.adaptive_chunk_size || tlsf {
+                chunk_size = growth.next(chunk_size, heap_size);
             }
+        }
 
-            let memory_type = &self.memory_types[index as usize];
-            let heap = memory_type.heap;
-            let heap = &mut self.memory_heaps[heap as usize];
+        self.update_pressure(device);
+        Ok(())
+    }
 
-            let atom_mask = if host_visible_non_coherent(memory_type.props) {
-                self.non_coherent_atom_mask
-            } else {
-                0
-            };
+    /// Allocates memory object of `size` bytes for memory type with specified index,
+    /// claimed by the first allocator of shared memory objects
+    /// that allocates memory object of the same size for blocks without device address.
+    unsafe fn preallocate(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        index: u32,
+        size: u64,
+    ) -> Result<(), AllocationError> {
+        if self.allocations_remains == 0 {
+            return Err(AllocationError::TooManyObjects);
+        }
 
-            let flags = if self.buffer_device_address {
-                AllocationFlags::DEVICE_ADDRESS
-            } else {
-                AllocationFlags::empty()
-            };
+        let heap = self.memory_types[index as usize].heap;
 
-            let strategy = match (dedicated, transient) {
-                (Some(Dedicated::Required), _) => Strategy::Dedicated,
-                (Some(Dedicated::Preferred), _)
-                    if request.size >= self.preferred_dedicated_threshold =>
-                {
-                    Strategy::Dedicated
-                }
-                (_, true) => {
-                    let threshold = self.transient_dedicated_threshold.min(heap.size() / 32);
+        // Preallocated memory objects are claimed by blocks without device address.
+        let flags = AllocationFlags::empty();
 
-                    if request.size < threshold {
-                        Strategy::FreeList
-                    } else {
-                        Strategy::Dedicated
-                    }
-                }
-                (_, false) => {
-                    let threshold = self.dedicated_threshold.min(heap.size() / 32);
+        let device = BudgetedDevice {
+            device,
+            heap,
+            policy: self.budget_policy,
+            priority: None,
+            never_allocate: false,
+            max_size: None,
+        };
 
-                    if request.size < threshold {
-                        Strategy::Buddy
-                    } else {
-                        Strategy::Dedicated
-                    }
-                }
-            };
+        let memory = device.allocate_memory(size, index, flags)?;
+        self.allocations_remains -= 1;
+        self.memory_heaps[heap as usize].alloc(size);
 
-            match strategy {
-                Strategy::Dedicated => {
-                    #[cfg(feature = "tracing")]
-                    tracing::debug!(
-                        "Allocating memory object `{}@{:?}`",
-                        request.size,
-                        memory_type
-                    );
+        self.prewarmed[index as usize].push(Preallocated {
+            memory,
+            size,
+            flags,
+        });
+        Ok(())
+    }
 
-                    match device.allocate_memory(request.size, index, flags) {
-                        Ok(memory) => {
-                            self.allocations_remains -= 1;
-                            heap.alloc(request.size);
-
-                            return Ok(MemoryBlock::new(
-                                index,
-                                memory_type.props,
-                                0,
-                                request.size,
-                                atom_mask,
-                                MemoryBlockFlavor::Dedicated { memory },
-                            ));
-                        }
-                        Err(OutOfMemory::OutOfDeviceMemory) => continue,
-                        Err(OutOfMemory::OutOfHostMemory) => {
-                            return Err(AllocationError::OutOfHostMemory)
-                        }
-                    }
-                }
-                Strategy::FreeList => {
-                    let allocator = match &mut self.freelist_allocators[index as usize] {
-                        Some(allocator) => allocator,
-                        slot => {
-                            let starting_free_list_chunk = match align_down(
-                                self.starting_free_list_chunk.min(heap.size() / 32),
-                                atom_mask,
-                            ) {
-                                0 => atom_mask,
-                                other => other,
-                            };
-
-                            let final_free_list_chunk = match align_down(
-                                self.final_free_list_chunk
-                                    .max(self.starting_free_list_chunk)
-                                    .max(self.transient_dedicated_threshold)
-                                    .min(heap.size() / 32),
-                                atom_mask,
-                            ) {
-                                0 => atom_mask,
-                                other => other,
-                            };
-
-                            slot.get_or_insert(FreeListAllocator::new(
-                                starting_free_list_chunk,
-                                final_free_list_chunk,
-                                index,
-                                memory_type.props,
-                                if host_visible_non_coherent(memory_type.props) {
-                                    self.non_coherent_atom_mask
-                                } else {
-                                    0
-                                },
-                            ))
-                        }
-                    };
-                    let result = allocator.alloc(
-                        device,
-                        request.size,
-                        request.align_mask,
-                        flags,
-                        heap,
-                        &mut self.allocations_remains,
-                    );
+    /// Returns index of allocators of shared memory objects of memory type with specified index,
+    /// separate for blocks with `UsageFlags::DEVICE_ADDRESS` and for each priority class,
+    /// see `priority_class`.
+    fn allocator_slot(
+        &self,
+        memory_type: u32,
+        device_address: bool,
+        priority: Option<f32>,
+    ) -> usize {
+        let group = priority_class(priority) * 2 + device_address as usize;
+        group * self.memory_types.len() + memory_type as usize
+    }
 
-                    match result {
-                        Ok(block) => {
-                            return Ok(MemoryBlock::new(
-                                index,
-                                memory_type.props,
-                                block.offset,
-                                block.size,
-                                atom_mask,
-                                MemoryBlockFlavor::FreeList {
-                                    chunk: block.chunk,
-                                    ptr: block.ptr,
-                                    memory: block.memory,
-                                },
-                            ))
-                        }
-                        Err(AllocationError::OutOfDeviceMemory) => continue,
-                        Err(err) => return Err(err),
-                    }
-                }
+    /// Returns size of memory object that allocator of small requests
+    /// of memory type with specified index would allocate next.
+    fn next_chunk_size(&self, index: u32) -> u64 {
+        let memory_type = &self.memory_types[index as usize];
+        let heap_size = self.memory_heaps[memory_type.heap as usize].size();
 
-                Strategy::Buddy => {
-                    let allocator = match &mut self.buddy_allocators[index as usize] {
-                        Some(allocator) => allocator,
-                        slot => {
-                            let minimal_buddy_size = self
-                                .minimal_buddy_size
-                                .min(heap.size() / 1024)
-                                .next_power_of_two();
-
-                            let initial_buddy_dedicated_size = self
-                                .initial_buddy_dedicated_size
-                                .min(heap.size() / 32)
-                                .next_power_of_two();
-
-                            slot.get_or_insert(BuddyAllocator::new(
-                                minimal_buddy_size,
-                                initial_buddy_dedicated_size,
-                                index,
-                                memory_type.props,
-                                if host_visible_non_coherent(memory_type.props) {
-                                    self.non_coherent_atom_mask
-                                } else {
-                                    0
-                                },
-                            ))
-                        }
-                    };
-                    let result = allocator.alloc(
-                        device,
-                        request.size,
-                        request.align_mask,
-                        flags,
-                        heap,
-                        &mut self.allocations_remains,
-                    );
+        if self.tlsf_memory_types & (1 << index) != 0 {
+            return match &self.tlsf_allocators[index as usize] {
+                Some(allocator) => allocator.chunk_size(),
+                None => tlsf_chunk_size(
+                    self.initial_buddy_dedicated_size[index as usize],
+                    self.dedicated_threshold[index as usize],
+                    heap_size,
+                ),
+            };
+        }
 
-                    match result {
-                        Ok(block) => {
-                            return Ok(MemoryBlock::new(
-                                index,
-                                memory_type.props,
-                                block.offset,
-                                block.size,
-                                atom_mask,
-                                MemoryBlockFlavor::Buddy {
-                                    chunk: block.chunk,
-                                    ptr: block.ptr,
-                                    index: block.index,
-                                    memory: block.memory,
-                                },
-                            ))
-                        }
-                        Err(AllocationError::OutOfDeviceMemory) => continue,
-                        Err(err) => return Err(err),
-                    }
-                }
-            }
+        if self.adaptive_chunk_size {
+            return self.recommended_chunk_size(index);
         }
 
-        Err(AllocationError::OutOfDeviceMemory)
+        match &self.buddy_allocators[index as usize] {
+            Some(allocator) => allocator.chunk_size(),
+            None => {
+                let (minimal_buddy_size, initial_buddy_dedicated_size) = buddy_chunk_sizes(
+                    self.minimal_buddy_size[index as usize],
+                    self.initial_buddy_dedicated_size[index as usize],
+                    heap_size,
+                );
+                BuddyPlan::new(minimal_buddy_size, initial_buddy_dedicated_size, 0).chunk_size()
+            }
+        }
     }
 
-    /// Deallocates memory block previously allocated from this `GpuAllocator` instance.
+    /// Allocates memory block from specified `device` according to the `request`,
+    /// awaiting new memory object instead of blocking when one has to be allocated.
+    ///
+    /// Requests served from existing memory objects complete without awaiting.
+    /// Memory objects allocated with priority or dedicated to a resource
+    /// are allocated synchronously.
     ///
     /// # Safety
     ///
-    /// * Memory block must have been allocated by this `GpuAllocator` instance
-    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
     /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
-    ///   and memory blocks allocated from it
+    ///   and memory blocks allocated from it.
+    #[cfg(feature = "async")]
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
-    pub unsafe fn dealloc(&mut self, device: &impl MemoryDevice<M>, block: MemoryBlock<M>) {
-        let memory_type = block.memory_type();
-        let offset = block.offset();
-        let size = block.size();
-        let flavor = block.deallocate();
-        match flavor {
-            MemoryBlockFlavor::Dedicated { memory } => {
-                let heap = self.memory_types[memory_type as usize].heap;
-                device.deallocate_memory(memory);
-                self.allocations_remains += 1;
-                self.memory_heaps[heap as usize].dealloc(size);
-            }
-            MemoryBlockFlavor::Buddy {
+    pub async unsafe fn alloc_async<D>(
+        &mut self,
+        device: &D,
+        request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError>
+    where
+        D: AsyncMemoryDevice<M>,
+    {
+        if self.device_lost {
+            return Err(AllocationError::DeviceLost);
+        }
+
+        let planned = self.plan(core::slice::from_ref(&request)).allocations.pop();
+        let (memory_type, strategy, size) = match planned {
+            Some(PlannedAllocation::NewMemory {
+                memory_type,
+                strategy,
+                size,
+            }) => (memory_type, strategy, size),
+            _ => return self.alloc(device, request),
+        };
+
+        let dedicated = strategy == Strategy::Dedicated;
+        let priority = if dedicated {
+            request.priority
+        } else {
+            class_priority(request.priority)
+        };
+
+        if priority.is_some() || (dedicated && request.dedicated_resource.is_some()) {
+            return self.alloc(device, request);
+        }
+
+        let mut flags = device_address_flags(request.usage);
+        if dedicated {
+            flags |= request.export;
+        }
+
+        let memory = match device.allocate_memory_async(size, memory_type, flags).await {
+            Ok(memory) => memory,
+            // Let synchronous path choose another memory type.
+            Err(_) => return self.alloc(device, request),
+        };
+
+        // Allocator claims the memory object if it still needs one of the same kind.
+        let preallocated = PreallocatedDevice {
+            device,
+            memory_type,
+            memory: RefCell::new(alloc::vec![Preallocated {
+                memory,
+                size,
+                flags,
+            }]),
+        };
+
+        let result = self.alloc(&preallocated, request);
+        for unused in preallocated.into_unused() {
+            device.deallocate_memory(unused.memory);
+        }
+        result
+    }
+
+    /// Allocates memory block of `size` bytes aligned by `align_mask`
+    /// from memory type with specified index.
+    /// This function bypasses memory type selection based on `UsageFlags`,
+    /// but still chooses allocation strategy by request size as `GpuAllocator::alloc` does.
+    ///
+    /// Use this function when memory type was already chosen by other means,
+    /// for example to satisfy requirements of imported resource.
+    /// Set `Request::memory_type` instead to keep other options of the request.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `memory_type` is not valid memory type index.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_from_type(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        memory_type: u32,
+        size: u64,
+        align_mask: u64,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        assert!(
+            (memory_type as usize) < self.memory_types.len(),
+            "Invalid memory type index"
+        );
+
+        if self.device_lost {
+            return Err(AllocationError::DeviceLost);
+        }
+
+        if size > self.max_memory_allocation_size {
+            return Err(AllocationError::OutOfDeviceMemory(None));
+        }
+
+        let request = Request {
+            size,
+            align_mask,
+            memory_types: 1 << memory_type,
+            ..Request::default()
+        };
+
+        let result = match self.alloc_from_type_internal(device, &request, None, None, memory_type)
+        {
+            Err(AllocationError::OutOfDeviceMemory(_)) => {
+                Err(self.out_of_device_memory(Some((memory_type, false)), size))
+            }
+            result => result,
+        };
+        self.update_pressure(device);
+        result
+    }
+
+    /// Creates memory pool with its own memory objects, configured by `config`.
+    ///
+    /// Blocks allocated from the pool with `GpuAllocator::alloc_in_pool`
+    /// never share memory objects with blocks allocated otherwise or from other pools.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `config.chunk_size` is zero
+    /// or `config.priority` is not in range `[0.0, 1.0]`.
+    pub fn create_pool(&mut self, config: PoolConfig) -> PoolId {
+        assert_ne!(config.chunk_size, 0, "Pool chunk size must not be zero");
+        assert!(
+            (config.export & !EXPORT_FLAGS).is_empty(),
+            "`PoolConfig::export` may contain only export flags"
+        );
+
+        if let Some(priority) = config.priority {
+            assert!(
+                (0.0..=1.0).contains(&priority),
+                "`PoolConfig::priority` must be in range [0.0, 1.0]"
+            );
+        }
+
+        let pool = Pool {
+            config,
+            allocators: self.memory_types.iter().map(|_| None).collect(),
+        };
+
+        match self.pools.iter().position(Option::is_none) {
+            Some(index) => {
+                self.pools[index] = Some(pool);
+                PoolId(index as u32)
+            }
+            None => {
+                self.pools.push(Some(pool));
+                PoolId(self.pools.len() as u32 - 1)
+            }
+        }
+    }
+
+    /// Deallocates free memory objects of the pool and destroys it.
+    /// Returns `false` if the pool is not destroyed.
+    ///
+    /// Pool with blocks still allocated is not destroyed,
+    /// error is reported and the pool can be used further.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` was not created by this allocator or was destroyed.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn destroy_pool(&mut self, device: &impl MemoryDevice<M>, pool: PoolId) -> bool {
+        let live = self
+            .live_blocks
+            .values()
+            .any(|live| live.pool == Some(pool.0));
+
+        let slot = &mut self.pools[pool.0 as usize];
+        let entry = slot.as_mut().expect("Pool does not exist");
+
+        for (index, allocator) in entry.allocators.iter_mut().enumerate() {
+            if self.device_lost {
+                break;
+            }
+            if let Some(PoolAllocator::FreeList(allocator)) = allocator {
+                let heap = self.memory_types[index].heap;
+                let heap = &mut self.memory_heaps[heap as usize];
+                allocator.cleanup(device, heap, &mut self.allocations_remains);
+            }
+        }
+
+        let destroyed = !live || self.device_lost;
+        if destroyed {
+            *slot = None;
+        } else {
+            report_error!(
+                "Blocks of pool {:?} are alive on destruction",
+                entry.config.label
+            );
+        }
+
+        self.update_pressure(device);
+        destroyed
+    }
+
+    /// Allocates memory block from the `pool` according to the `request`.
+    ///
+    /// Memory type is chosen among types allowed by both `request` and `PoolConfig::memory_types`.
+    /// Block is always sub-allocated by strategy of the pool,
+    /// so `Request::dedicated` and `Request::priority` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` was not created by this allocator or was destroyed,
+    /// or if the request is exportable or has `RequestFlags::DONT_SUBALLOCATE` flag.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_in_pool(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        pool: PoolId,
+        request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        if self.device_lost {
+            return Err(AllocationError::DeviceLost);
+        }
+
+        let result = self.alloc_in_pool_inner(device, pool, request);
+        self.update_pressure(device);
+        result
+    }
+
+    unsafe fn alloc_in_pool_inner(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        pool: PoolId,
+        mut request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        assert!(
+            request.export.is_empty(),
+            "Exportable memory cannot be allocated in pool"
+        );
+
+        assert!(
+            !request.flags.contains(RequestFlags::DONT_SUBALLOCATE),
+            "Memory allocated in pool is always suballocated"
+        );
+
+        let pool_memory_types = self.pools[pool.0 as usize]
+            .as_ref()
+            .expect("Pool does not exist")
+            .config
+            .memory_types;
+
+        request.usage = with_implicit_usage_flags(&request);
+        request.memory_types &= pool_memory_types;
+
+        if request.usage.contains(UsageFlags::DEVICE_ADDRESS) {
+            assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
+        }
+
+        if request.size > self.max_memory_allocation_size {
+            return Err(AllocationError::OutOfDeviceMemory(None));
+        }
+
+        if let Some(index) = self.explicit_memory_type(&request) {
+            let index = index?;
+            return match self.alloc_in_pool_from_type(device, &request, pool, index) {
+                Err(AllocationError::OutOfDeviceMemory(_)) => {
+                    Err(self.out_of_device_memory(Some((index, false)), request.size))
+                }
+                result => result,
+            };
+        }
+
+        if 0 == self.memory_for_usage.mask(request.usage, request.access) & request.memory_types {
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let types_count = self
+            .memory_for_usage
+            .types(request.usage, request.access)
+            .len();
+
+        let mut failed = None;
+        for i in 0..types_count {
+            let index = self.memory_for_usage.types(request.usage, request.access)[i];
+
+            if 0 == request.memory_types & (1 << index) {
+                continue;
+            }
+
+            match self.alloc_in_pool_from_type(device, &request, pool, index) {
+                Err(AllocationError::OutOfDeviceMemory(_)) => {
+                    failed = Some((index, failed.is_some()));
+                    continue;
+                }
+                result => return result,
+            }
+        }
+
+        Err(self.out_of_device_memory(failed, request.size))
+    }
+
+    /// Returns counters of memory objects and live blocks of the `pool`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` was not created by this allocator or was destroyed.
+    pub fn pool_stats(&self, pool: PoolId) -> StrategyStats {
+        let (memory_objects, device_bytes) = self.pools[pool.0 as usize]
+            .as_ref()
+            .expect("Pool does not exist")
+            .memory_objects();
+
+        let mut stats = StrategyStats {
+            memory_objects,
+            device_bytes,
+            ..StrategyStats::default()
+        };
+
+        for live in self
+            .live_blocks
+            .values()
+            .filter(|live| live.pool == Some(pool.0))
+        {
+            stats.block_count += 1;
+            stats.user_bytes += live.size;
+            stats.internal_fragmentation += live.padding;
+        }
+
+        stats
+    }
+
+    /// Allocates memory block according to the `request`
+    /// and binds `buffer` to it with `MemoryDevice::bind_buffer_memory`.
+    /// Block is deallocated if binding fails.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * `buffer` must be valid raw handle of buffer created from `device`
+    ///   that is not bound to memory yet.
+    /// * `request` must contain memory requirements of the buffer.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_and_bind_buffer(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        buffer: u64,
+        request: Request,
+    ) -> Result<MemoryBlock<M>, BindError> {
+        let block = self.alloc(device, request)?;
+
+        match device.bind_buffer_memory(buffer, block.memory(), block.offset()) {
+            Ok(()) => Ok(block),
+            Err(err) => {
+                self.dealloc(device, block);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Allocates memory block according to the `request`
+    /// and binds `image` to it with `MemoryDevice::bind_image_memory`.
+    /// Block is deallocated if binding fails.
+    ///
+    /// Request is treated as one for `ResourceKind::NonLinear` resource,
+    /// so that image never shares memory with buffers and linear images
+    /// at finer granularity than `DeviceProperties::buffer_image_granularity`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * `image` must be valid raw handle of image created from `device`
+    ///   that is not bound to memory yet.
+    /// * `request` must contain memory requirements of the image.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_and_bind_image(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        image: u64,
+        request: Request,
+    ) -> Result<MemoryBlock<M>, BindError> {
+        let request = Request {
+            resource_kind: ResourceKind::NonLinear,
+            ..request
+        };
+
+        let block = self.alloc(device, request)?;
+
+        match device.bind_image_memory(image, block.memory(), block.offset()) {
+            Ok(()) => Ok(block),
+            Err(err) => {
+                self.dealloc(device, block);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Returns index of the memory type and strategy that would be tried first
+    /// to serve the `request`.
+    /// Actual allocation may fall back to other memory types
+    /// if preferred one is exhausted.
+    ///
+    /// Returns `None` if no memory type is compatible with the `request`.
+    ///
+    /// This function is intended for debugging and tuning `Config`.
+    pub fn strategy_for(
+        &self,
+        request: &Request,
+        dedicated: Option<Dedicated>,
+    ) -> Option<(u32, Strategy)> {
+        let usage = with_implicit_usage_flags(request);
+
+        let index = match self.explicit_memory_type(request) {
+            Some(index) => index.ok()?,
+            None => self
+                .memory_for_usage
+                .types(usage, request.access)
+                .iter()
+                .copied()
+                .find(|&index| request.memory_types & (1 << index) != 0)?,
+        };
+
+        let heap = self.memory_types[index as usize].heap;
+        let heap = &self.memory_heaps[heap as usize];
+
+        Some((
+            index,
+            self.strategy(
+                index,
+                request.size,
+                usage.contains(UsageFlags::TRANSIENT),
+                with_implied_dedicated(request, dedicated),
+                heap,
+            ),
+        ))
+    }
+
+    /// Simulates allocation of `requests` in order without allocating any memory.
+    /// No device calls are made.
+    ///
+    /// Outcome of each request accounts for blocks planned for preceding requests,
+    /// current fragmentation of memory objects and limit on memory objects count.
+    /// Heap budget is estimated as heap size reported in `DeviceProperties`
+    /// minus memory allocated by this allocator,
+    /// so actual allocation may still fail if heap is shared with other allocators.
+    ///
+    /// Requests are planned as if they were passed to `GpuAllocator::alloc`.
+    pub fn plan(&self, requests: &[Request]) -> PlanResult {
+        let mut state = PlanState {
+            heaps: self.memory_heaps.iter().map(Heap::available).collect(),
+            allocations_remains: self.allocations_remains,
+            buddy: self
+                .buddy_allocators
+                .iter()
+                .map(|allocator| allocator.as_ref().map(BuddyAllocator::plan))
+                .collect(),
+            large_buddy: self
+                .large_buddy_allocators
+                .iter()
+                .map(|allocator| allocator.as_ref().map(BuddyAllocator::plan))
+                .collect(),
+            freelist: self
+                .freelist_allocators
+                .iter()
+                .map(|allocator| allocator.as_ref().map(FreeListAllocator::plan))
+                .collect(),
+            tlsf: self
+                .tlsf_allocators
+                .iter()
+                .map(|allocator| allocator.as_ref().map(TlsfAllocator::plan))
+                .collect(),
+            best_fit: self
+                .best_fit_allocators
+                .iter()
+                .map(|allocator| allocator.as_ref().map(FreeListAllocator::plan))
+                .collect(),
+        };
+
+        let mut result = PlanResult {
+            allocations: Vec::with_capacity(requests.len()),
+            additional_memory: 0,
+        };
+
+        for request in requests {
+            let planned = self.plan_request(&mut state, request);
+            if let PlannedAllocation::NewMemory { size, .. } = planned {
+                result.additional_memory += size;
+            }
+            result.allocations.push(planned);
+        }
+
+        result
+    }
+
+    fn plan_request(&self, state: &mut PlanState, request: &Request) -> PlannedAllocation {
+        let request = &match self.granular_request(request) {
+            Ok(request) => request,
+            Err(err) => return PlannedAllocation::Failed(err),
+        };
+        let usage = with_implicit_usage_flags(request);
+
+        if request.size > self.max_memory_allocation_size {
+            return PlannedAllocation::Failed(AllocationError::OutOfDeviceMemory(None));
+        }
+
+        let explicit;
+        let types = match self.explicit_memory_type(request) {
+            Some(Ok(index)) => {
+                explicit = [index];
+                &explicit[..]
+            }
+            Some(Err(err)) => return PlannedAllocation::Failed(err),
+            None => {
+                if 0 == self.memory_for_usage.mask(usage, request.access) & request.memory_types {
+                    return PlannedAllocation::Failed(AllocationError::NoCompatibleMemoryTypes);
+                }
+                self.memory_for_usage.types(usage, request.access)
+            }
+        };
+
+        let never_allocate = request.flags.contains(RequestFlags::NEVER_ALLOCATE);
+
+        for &index in types {
+            if 0 == request.memory_types & (1 << index) {
+                continue;
+            }
+
+            // Planning new memory object mutates state, so keep a copy to roll back.
+            let rollback = if never_allocate {
+                Some(state.clone())
+            } else {
+                None
+            };
+
+            match self.plan_from_type(
+                state,
+                request,
+                usage,
+                with_implied_dedicated(request, None),
+                index,
+            ) {
+                Err(AllocationError::OutOfDeviceMemory(_)) => continue,
+                Err(err) => return PlannedAllocation::Failed(err),
+                Ok((strategy, None)) => {
+                    return PlannedAllocation::Existing {
+                        memory_type: index,
+                        strategy,
+                    }
+                }
+                Ok((strategy, Some(size))) => {
+                    if let Some(rollback) = rollback {
+                        *state = rollback;
+                        continue;
+                    }
+
+                    return PlannedAllocation::NewMemory {
+                        memory_type: index,
+                        strategy,
+                        size,
+                    };
+                }
+            }
+        }
+
+        PlannedAllocation::Failed(AllocationError::OutOfDeviceMemory(None))
+    }
+
+    fn plan_from_type(
+        &self,
+        state: &mut PlanState,
+        request: &Request,
+        usage: UsageFlags,
+        dedicated: Option<Dedicated>,
+        index: u32,
+    ) -> Result<(Strategy, Option<u64>), AllocationError> {
+        let size = request.size;
+        let align_mask = request.align_mask;
+        let transient = usage.contains(UsageFlags::TRANSIENT);
+        let slot = self.allocator_slot(
+            index,
+            usage.contains(UsageFlags::DEVICE_ADDRESS),
+            request.priority,
+        );
+        let memory_type = &self.memory_types[index as usize];
+        let heap = &self.memory_heaps[memory_type.heap as usize];
+        let strategy = self.strategy(index, size, transient, dedicated, heap);
+
+        let atom_mask = if host_visible_non_coherent(memory_type.props) {
+            self.non_coherent_atom_mask
+        } else {
+            0
+        };
+
+        let heap_available = &mut state.heaps[memory_type.heap as usize];
+        let allocations_remains = &mut state.allocations_remains;
+        let growth = self.chunk_growth[index as usize];
+
+        let new_memory = match strategy {
+            Strategy::Dedicated => {
+                reserve(size, heap_available, allocations_remains)?;
+                Some(size)
+            }
+            Strategy::FreeList | Strategy::BestFit => {
+                let best_fit = strategy == Strategy::BestFit;
+
+                let slot = if best_fit {
+                    &mut state.best_fit[slot]
+                } else {
+                    &mut state.freelist[slot]
+                };
+
+                slot.get_or_insert_with(|| {
+                    let (starting_chunk_size, final_chunk_size) = if best_fit {
+                        let chunk_size = tlsf_chunk_size(
+                            self.initial_buddy_dedicated_size[index as usize],
+                            self.dedicated_threshold[index as usize],
+                            heap.size(),
+                        );
+                        free_list_chunk_sizes(
+                            chunk_size,
+                            chunk_size,
+                            self.dedicated_threshold[index as usize],
+                            heap.size(),
+                            atom_mask,
+                        )
+                    } else {
+                        free_list_chunk_sizes(
+                            self.free_list_chunks[index as usize].0,
+                            self.free_list_chunks[index as usize].1,
+                            self.transient_dedicated_threshold[index as usize],
+                            heap.size(),
+                            atom_mask,
+                        )
+                    };
+                    FreeListPlan::new(starting_chunk_size, final_chunk_size, atom_mask, best_fit)
+                })
+                .alloc(size, align_mask, heap_available, allocations_remains)?
+            }
+            Strategy::Tlsf => {
+                let plan = state.tlsf[slot].get_or_insert_with(|| {
+                    TlsfPlan::new(
+                        tlsf_chunk_size(
+                            self.initial_buddy_dedicated_size[index as usize],
+                            self.dedicated_threshold[index as usize],
+                            heap.size(),
+                        ),
+                        atom_mask,
+                    )
+                });
+
+                let chunk_size = plan.chunk_size();
+                let new_memory =
+                    plan.alloc(size, align_mask, heap_available, allocations_remains)?;
+                if new_memory == Some(chunk_size) {
+                    plan.set_chunk_size(growth.next(chunk_size, heap.size()));
+                }
+                new_memory
+            }
+            Strategy::Buddy | Strategy::LargeBuddy => {
+                let large = strategy == Strategy::LargeBuddy;
+
+                let slot = if large {
+                    &mut state.large_buddy[slot]
+                } else {
+                    &mut state.buddy[slot]
+                };
+
+                let plan = slot.get_or_insert_with(|| {
+                    let (minimal_buddy_size, initial_buddy_dedicated_size) = if large {
+                        buddy_chunk_sizes(
+                            self.large_object_threshold[index as usize],
+                            self.initial_buddy_dedicated_size[index as usize]
+                                .max(self.dedicated_threshold[index as usize]),
+                            heap.size(),
+                        )
+                    } else {
+                        buddy_chunk_sizes(
+                            self.minimal_buddy_size[index as usize],
+                            self.initial_buddy_dedicated_size[index as usize],
+                            heap.size(),
+                        )
+                    };
+                    BuddyPlan::new(minimal_buddy_size, initial_buddy_dedicated_size, atom_mask)
+                });
+
+                let adaptive = self.adaptive_chunk_size && !large;
+                if adaptive {
+                    plan.set_chunk_size(self.recommended_chunk_size(index));
+                }
+
+                let chunk_size = plan.chunk_size();
+                let new_memory =
+                    plan.alloc(size, align_mask, heap_available, allocations_remains)?;
+                if !adaptive && new_memory == Some(chunk_size) {
+                    plan.set_chunk_size(growth.next(chunk_size, heap.size()));
+                }
+                new_memory
+            }
+        };
+
+        Ok((strategy, new_memory))
+    }
+
+    /// Returns size of memory objects for buddy allocator of small objects
+    /// recommended for memory type with specified index.
+    ///
+    /// Recommendation is based on sizes of recent requests served from this memory type.
+    /// It is large enough to fit several requests of 95th percentile size
+    /// and is capped by size of the heap.
+    /// Without gathered statistics configured `initial_buddy_dedicated_size` is returned.
+    ///
+    /// Statistics are gathered only if `Config::adaptive_chunk_size` is enabled,
+    /// in which case recommended size is used when new memory object is allocated.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `memory_type` is not valid memory type index.
+    pub fn recommended_chunk_size(&self, memory_type: u32) -> u64 {
+        let heap = self.memory_types[memory_type as usize].heap;
+        let cap = self.memory_heaps[heap as usize].size() / 32;
+
+        let chunk_size = match self.size_histograms[memory_type as usize].percentile(95) {
+            Some(p95) => p95.saturating_mul(8),
+            None => self.initial_buddy_dedicated_size[memory_type as usize],
+        };
+
+        chunk_size.min(cap).next_power_of_two()
+    }
+
+    /// Returns number of memory objects currently allocated by this allocator.
+    ///
+    /// This includes dedicated memory objects and chunks of shared memory objects.
+    pub fn allocation_count(&self) -> u32 {
+        self.max_allocation_count - self.allocations_remains
+    }
+
+    /// Returns maximum number of memory objects this allocator may allocate at once.
+    pub fn max_allocation_count(&self) -> u32 {
+        self.max_allocation_count
+    }
+
+    /// Makes heaps of this allocator account usage together with other allocators
+    /// sharing `heaps`, one for each heap.
+    #[cfg(feature = "std")]
+    pub(crate) fn share_heaps(&mut self, heaps: &[Arc<SharedHeap>]) {
+        for (heap, shared) in self.memory_heaps.iter_mut().zip(heaps) {
+            heap.share(shared.clone());
+        }
+    }
+
+    /// Returns number of requests for specified memory type served by free-list allocator
+    /// instead of allocating new buddy allocator memory object.
+    ///
+    /// Frequent spillover suggests that buddy allocator memory objects are too small.
+    /// See `Config::buddy_spillover_memory_types`.
+    pub fn spillover_count(&self, memory_type: u32) -> u64 {
+        self.spillover_counts[memory_type as usize]
+    }
+
+    /// Returns usage of memory types by live blocks.
+    ///
+    /// `MemoryTypeReport::wasted_bytes` shows how much of allocated memory
+    /// is lost to padding, which may be reduced by relaxing alignment requirements
+    /// or tuning buddy allocator sizes.
+    pub fn report(&self) -> AllocatorReport {
+        let mut memory_types = alloc::vec![MemoryTypeReport::default(); self.memory_types.len()];
+
+        for live in self.live_blocks.values() {
+            let report = &mut memory_types[live.memory_type as usize];
+            report.block_count += 1;
+            report.live_bytes += live.size;
+            report.wasted_bytes += live.padding;
+        }
+
+        AllocatorReport { memory_types }
+    }
+
+    /// Returns counters of memory objects and live blocks
+    /// for each memory type and allocation strategy.
+    pub fn stats(&self) -> AllocatorStats {
+        let mut memory_types = alloc::vec![MemoryTypeStats::default(); self.memory_types.len()];
+
+        for live in self.live_blocks.values() {
+            let stats = memory_types[live.memory_type as usize].strategy_mut(live.strategy);
+            stats.block_count += 1;
+            stats.user_bytes += live.size;
+            stats.internal_fragmentation += live.padding;
+
+            if live.strategy == Strategy::Dedicated {
+                // Each dedicated block owns whole memory object.
+                stats.memory_objects += 1;
+                stats.device_bytes += live.size;
+            }
+        }
+
+        for (index, stats) in memory_types.iter_mut().enumerate() {
+            let mut chunks = Vec::new();
+            for slot in (index..self.buddy_allocators.len()).step_by(self.memory_types.len()) {
+                chunks.extend_from_slice(&[
+                    (
+                        Strategy::Buddy,
+                        self.buddy_allocators[slot]
+                            .as_ref()
+                            .map(BuddyAllocator::memory_objects),
+                    ),
+                    (
+                        Strategy::LargeBuddy,
+                        self.large_buddy_allocators[slot]
+                            .as_ref()
+                            .map(BuddyAllocator::memory_objects),
+                    ),
+                    (
+                        Strategy::FreeList,
+                        self.freelist_allocators[slot]
+                            .as_ref()
+                            .map(FreeListAllocator::memory_objects),
+                    ),
+                    (
+                        Strategy::Tlsf,
+                        self.tlsf_allocators[slot]
+                            .as_ref()
+                            .map(TlsfAllocator::memory_objects),
+                    ),
+                    (
+                        Strategy::BestFit,
+                        self.best_fit_allocators[slot]
+                            .as_ref()
+                            .map(FreeListAllocator::memory_objects),
+                    ),
+                ]);
+            }
+
+            let pools = self
+                .pools
+                .iter()
+                .flatten()
+                .filter_map(|pool| pool.allocators[index].as_ref())
+                .map(|allocator| (allocator.strategy(), Some(allocator.memory_objects())));
+
+            for (strategy, chunks) in chunks.iter().copied().chain(pools) {
+                if let Some((count, bytes)) = chunks {
+                    let stats = stats.strategy_mut(strategy);
+                    stats.memory_objects += count;
+                    stats.device_bytes += bytes;
+                }
+            }
+
+            if let Some(ring) = &self.ring_allocators[index] {
+                stats.frame_ring.memory_objects = 1;
+                stats.frame_ring.device_bytes = ring.size();
+                stats.frame_ring.user_bytes = ring.used();
+            }
+        }
+
+        let bar_device_bytes = memory_types
+            .iter()
+            .zip(&*self.memory_types)
+            .filter(|(_, memory_type)| {
+                memory_type
+                    .props
+                    .contains(MemoryPropertyFlags::DEVICE_LOCAL | MemoryPropertyFlags::HOST_VISIBLE)
+            })
+            .map(|(stats, _)| stats.total().device_bytes)
+            .sum();
+
+        AllocatorStats {
+            memory_types,
+            bar_device_bytes,
+        }
+    }
+
+    /// Returns state of memory objects, their free regions and live blocks.
+    ///
+    /// Unlike `stats` this walks all internal structures
+    /// and is meant for occasional inspection rather than every frame.
+    /// Snapshot can be attached to crash report with `AllocatorSnapshot::encode`
+    /// and examined offline.
+    pub fn snapshot(&self) -> AllocatorSnapshot {
+        let mut chunks = Vec::new();
+
+        for slot in 0..self.buddy_allocators.len() {
+            let first = chunks.len();
+            if let Some(allocator) = &self.buddy_allocators[slot] {
+                allocator.snapshot(Strategy::Buddy, &mut chunks);
+            }
+            if let Some(allocator) = &self.large_buddy_allocators[slot] {
+                allocator.snapshot(Strategy::LargeBuddy, &mut chunks);
+            }
+            if let Some(allocator) = &self.freelist_allocators[slot] {
+                allocator.snapshot(&mut chunks);
+            }
+            if let Some(allocator) = &self.tlsf_allocators[slot] {
+                allocator.snapshot(&mut chunks);
+            }
+            if let Some(allocator) = &self.best_fit_allocators[slot] {
+                allocator.snapshot(&mut chunks);
+            }
+            if (slot / self.memory_types.len()) % 2 == 1 {
+                for chunk in &mut chunks[first..] {
+                    chunk.device_address = true;
+                }
+            }
+        }
+
+        for (id, pool) in self.pools.iter().enumerate() {
+            let allocators = pool
+                .iter()
+                .flat_map(|pool| pool.allocators.iter().flatten());
+            for allocator in allocators {
+                let first = chunks.len();
+                match allocator {
+                    PoolAllocator::Buddy(allocator) => {
+                        allocator.snapshot(Strategy::Buddy, &mut chunks)
+                    }
+                    PoolAllocator::FreeList(allocator) => allocator.snapshot(&mut chunks),
+                    PoolAllocator::Tlsf(allocator) => allocator.snapshot(&mut chunks),
+                }
+                for chunk in &mut chunks[first..] {
+                    chunk.pool = Some(PoolId(id as u32));
+                }
+            }
+        }
+
+        let blocks = self
+            .live_blocks
+            .values()
+            .map(|live| BlockSnapshot {
+                memory_type: live.memory_type,
+                pool: live.pool.map(PoolId),
+                device_address: live.pool.is_none()
+                    && live.usage.contains(UsageFlags::DEVICE_ADDRESS),
+                strategy: live.strategy,
+                chunk: live.chunk,
+                offset: live.offset,
+                size: live.size,
+                padding: live.padding,
+                usage: live.usage,
+                label: live.label.map(Into::into),
+            })
+            .collect();
+
+        AllocatorSnapshot { chunks, blocks }
+    }
+
+    /// Returns SVG image of memory objects with occupied and free ranges.
+    ///
+    /// Each memory object shared by blocks is drawn as separate row
+    /// scaled to full width, so it is easy to see how free memory is scattered.
+    /// Occupied ranges are blue with requested size padding in yellow,
+    /// free ranges are green and ranges that are neither are red.
+    /// Dedicated memory objects and frame rings are not drawn.
+    pub fn dump_memory_map(&self) -> String {
+        memory_map::render_svg(&self.snapshot())
+    }
+
+    /// Returns `true` when few memory objects can be allocated before reaching the limit.
+    fn allocations_nearly_exhausted(&self) -> bool {
+        // Keep last eighth of memory objects for requests that cannot be suballocated.
+        self.allocations_remains <= self.max_allocation_count / 8
+    }
+
+    fn strategy(
+        &self,
+        index: u32,
+        size: u64,
+        transient: bool,
+        dedicated: Option<Dedicated>,
+        heap: &Heap,
+    ) -> Strategy {
+        match (dedicated, transient) {
+            (Some(Dedicated::Required), _) => Strategy::Dedicated,
+            (Some(Dedicated::Preferred), _)
+                if size >= self.preferred_dedicated_threshold[index as usize]
+                    && !self.allocations_nearly_exhausted() =>
+            {
+                Strategy::Dedicated
+            }
+            (_, true) => {
+                let threshold =
+                    self.transient_dedicated_threshold[index as usize].min(heap.size() / 32);
+
+                if size < threshold {
+                    Strategy::FreeList
+                } else {
+                    Strategy::Dedicated
+                }
+            }
+            (_, false) => {
+                let threshold = self.dedicated_threshold[index as usize].min(heap.size() / 32);
+
+                if size >= threshold {
+                    Strategy::Dedicated
+                } else if self.tlsf_memory_types & (1 << index) != 0 {
+                    Strategy::Tlsf
+                } else if size >= self.large_object_threshold[index as usize]
+                    && self.best_fit_memory_types & (1 << index) != 0
+                {
+                    Strategy::BestFit
+                } else if size >= self.large_object_threshold[index as usize] {
+                    Strategy::LargeBuddy
+                } else {
+                    Strategy::Buddy
+                }
+            }
+        }
+    }
+
+    /// Span of each request carries its requirements,
+    /// memory type and strategy are recorded once block is allocated.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "request",
+            skip(self, device, request),
+            fields(
+                size = request.size,
+                align_mask = request.align_mask,
+                usage = ?request.usage,
+                label = ?request.label,
+                memory_type = tracing::field::Empty,
+                strategy = tracing::field::Empty,
+            )
+        )
+    )]
+    pub(crate) unsafe fn alloc_internal(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: Request,
+        dedicated: Option<Dedicated>,
+        chunk_size: Option<u64>,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        if self.device_lost {
+            return Err(AllocationError::DeviceLost);
+        }
+
+        let mut result = self.alloc_inner(device, request, dedicated, chunk_size);
+
+        if let Err(AllocationError::AddressSpaceExhausted) = result {
+            // Unmapping idle memory objects frees host address space for another attempt.
+            let released = self.release_idle_chunks(device, true);
+            if released > 0 {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    "Host address space exhausted, retrying after {} idle mapped memory objects are released",
+                    released
+                );
+
+                result = self.alloc_inner(device, request, dedicated, chunk_size);
+            }
+        }
+
+        self.update_pressure(device);
+
+        if let Some(recording) = &mut self.recording {
+            let generation = result.as_ref().map(|block| block.id).map_err(|err| *err);
+            recording.record_alloc(request, dedicated, chunk_size, generation);
+        }
+
+        result
+    }
+
+    unsafe fn alloc_inner(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        mut request: Request,
+        dedicated: Option<Dedicated>,
+        chunk_size: Option<u64>,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        let fallback = self
+            .usage_fallbacks
+            .iter()
+            .position(|fallback| fallback.usage == request.usage);
+
+        request.usage = with_implicit_usage_flags(&request);
+        let dedicated = with_implied_dedicated(&request, dedicated);
+
+        debug_assert!(
+            (request.export & !EXPORT_FLAGS).is_empty(),
+            "`Request::export` may contain only export flags"
+        );
+
+        if let Some(priority) = request.priority {
+            assert!(
+                (0.0..=1.0).contains(&priority),
+                "`Request::priority` must be in range [0.0, 1.0]"
+            );
+        }
+
+        if request.usage.contains(UsageFlags::DEVICE_ADDRESS) {
+            assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
+        }
+
+        if request.size > self.max_memory_allocation_size {
+            return Err(AllocationError::OutOfDeviceMemory(None));
+        }
+
+        if let Some(Dedicated::Required) = dedicated {
+            if self.allocations_remains == 0 {
+                return Err(AllocationError::TooManyObjects);
+            }
+        }
+
+        if let Some(index) = self.explicit_memory_type(&request) {
+            let index = index?;
+            return match self
+                .alloc_from_type_internal(device, &request, dedicated, chunk_size, index)
+            {
+                Err(AllocationError::OutOfDeviceMemory(_)) => {
+                    Err(self.out_of_device_memory(Some((index, false)), request.size))
+                }
+                result => result,
+            };
+        }
+
+        if 0 == self.memory_for_usage.mask(request.usage, request.access) & request.memory_types {
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                "Cannot serve request {:?}, no memory among bitset `{}` support usage {:?}",
+                request,
+                request.memory_types,
+                request.usage
+            );
+
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let types_count = self
+            .memory_for_usage
+            .types(request.usage, request.access)
+            .len();
+
+        let mut failed = None;
+
+        if let Some(fallback) = fallback {
+            let tiers_count = self.usage_fallbacks[fallback].tiers.len();
+            for tier in 0..tiers_count {
+                let tier_types = self.usage_fallbacks[fallback].tiers[tier] & request.memory_types;
+
+                for i in 0..types_count {
+                    let index = self.memory_for_usage.types(request.usage, request.access)[i];
+
+                    if 0 == tier_types & (1 << index) {
+                        continue;
+                    }
+
+                    match self
+                        .alloc_from_type_internal(device, &request, dedicated, chunk_size, index)
+                    {
+                        Err(AllocationError::OutOfDeviceMemory(_)) => {
+                            failed = Some((index, failed.is_some()));
+                            continue;
+                        }
+                        Ok(mut block) => {
+                            block.fallback_tier = Some(tier as u32);
+                            return Ok(block);
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+
+            return Err(self.out_of_device_memory(failed, request.size));
+        }
+
+        for i in 0..types_count {
+            let index = self.memory_for_usage.types(request.usage, request.access)[i];
+
+            if 0 == request.memory_types & (1 << index) {
+                // Skip memory type incompatible with the request.
+                continue;
+            }
+
+            match self.alloc_from_type_internal(device, &request, dedicated, chunk_size, index) {
+                Err(AllocationError::OutOfDeviceMemory(_)) => {
+                    failed = Some((index, failed.is_some()));
+                    continue;
+                }
+                result => return result,
+            }
+        }
+
+        Err(self.out_of_device_memory(failed, request.size))
+    }
+
+    /// Returns `AllocationError::OutOfDeviceMemory` with details of the last memory type
+    /// that `failed` to serve request of `size` bytes,
+    /// flagged if other memory types failed before it.
+    fn out_of_device_memory(&self, failed: Option<(u32, bool)>, size: u64) -> AllocationError {
+        AllocationError::OutOfDeviceMemory(failed.map(|(index, fallbacks_attempted)| {
+            let heap = self.memory_types[index as usize].heap;
+            OutOfMemoryDetails {
+                memory_type: index,
+                heap,
+                size,
+                heap_usage: self.memory_heaps[heap as usize].used(),
+                heap_size: self.memory_heaps[heap as usize].size(),
+                fallbacks_attempted,
+            }
+        }))
+    }
+
+    unsafe fn alloc_from_type_internal(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: &Request,
+        dedicated: Option<Dedicated>,
+        chunk_size: Option<u64>,
+        index: u32,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        let request = &self.granular_request(request)?;
+
+        if self.prewarmed[index as usize].is_empty() {
+            return self.alloc_from_type_inner(device, request, dedicated, chunk_size, index);
+        }
+
+        // Memory objects allocated by `prewarm` are not counted while they may be claimed,
+        // as allocators count memory objects they claim.
+        let prewarmed = core::mem::take(&mut self.prewarmed[index as usize]);
+        self.uncount_prewarmed(index, &prewarmed);
+
+        let device = PreallocatedDevice {
+            device,
+            memory_type: index,
+            memory: RefCell::new(prewarmed),
+        };
+
+        let result = self.alloc_from_type_inner(&device, request, dedicated, chunk_size, index);
+
+        let prewarmed = device.into_unused();
+        self.count_prewarmed(index, &prewarmed);
+        self.prewarmed[index as usize] = prewarmed;
+        result
+    }
+
+    fn count_prewarmed(&mut self, index: u32, prewarmed: &[Preallocated<M>]) {
+        let heap = &mut self.memory_heaps[self.memory_types[index as usize].heap as usize];
+        for memory in prewarmed {
+            self.allocations_remains -= 1;
+            heap.alloc(memory.size);
+        }
+    }
+
+    fn uncount_prewarmed(&mut self, index: u32, prewarmed: &[Preallocated<M>]) {
+        let heap = &mut self.memory_heaps[self.memory_types[index as usize].heap as usize];
+        for memory in prewarmed {
+            self.allocations_remains += 1;
+            heap.dealloc(memory.size);
+        }
+    }
+
+    unsafe fn alloc_from_type_inner(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: &Request,
+        dedicated: Option<Dedicated>,
+        chunk_size: Option<u64>,
+        index: u32,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        let size = request.size;
+        let align_mask = request.align_mask;
+        let transient = request.usage.contains(UsageFlags::TRANSIENT);
+
+        let memory_type = &self.memory_types[index as usize];
+
+        // Explicitly chosen memory type may be unable to keep the block mapped.
+        if request.flags.contains(RequestFlags::PERSISTENTLY_MAPPED)
+            && !memory_type
+                .props
+                .contains(MemoryPropertyFlags::HOST_VISIBLE)
+        {
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let heap = memory_type.heap;
+        let heap_size = self.memory_heaps[heap as usize].size();
+
+        let strategy = self.strategy(
+            index,
+            size,
+            transient,
+            dedicated,
+            &self.memory_heaps[heap as usize],
+        );
+
+        // Shared memory objects get highest priority of their priority class.
+        let priority = if strategy == Strategy::Dedicated {
+            request.priority
+        } else {
+            class_priority(request.priority)
+        };
+
+        let device = &BudgetedDevice {
+            device,
+            heap,
+            policy: budget_policy(self.budget_policy, request),
+            priority,
+            never_allocate: request.flags.contains(RequestFlags::NEVER_ALLOCATE),
+            max_size: None,
+        };
+
+        let adaptive_chunk_size = if self.adaptive_chunk_size && strategy == Strategy::Buddy {
+            self.size_histograms[index as usize].record(size);
+            Some(self.recommended_chunk_size(index))
+        } else {
+            None
+        };
+
+        let slot = self.allocator_slot(
+            index,
+            request.usage.contains(UsageFlags::DEVICE_ADDRESS),
+            request.priority,
+        );
+
+        let heap = &mut self.memory_heaps[heap as usize];
+        let growth = self.chunk_growth[index as usize];
+
+        let atom_mask = if host_visible_non_coherent(memory_type.props) {
+            self.non_coherent_atom_mask
+        } else {
+            0
+        };
+
+        let flags = device_address_flags(request.usage);
+
+        // Only dedicated memory objects are exportable.
+        let flags = if strategy == Strategy::Dedicated {
+            flags | request.export
+        } else {
+            flags
+        };
+
+        let guard = if self.guard_size != 0
+            && strategy != Strategy::Dedicated
+            && memory_type
+                .props
+                .contains(MemoryPropertyFlags::HOST_VISIBLE)
+        {
+            // Block after the guard stays aligned.
+            align_up(self.guard_size, align_mask | atom_mask)
+                .ok_or(AllocationError::OutOfDeviceMemory(None))?
+        } else {
+            0
+        };
+
+        let size = guard
+            .checked_mul(2)
+            .and_then(|guards| size.checked_add(guards))
+            .ok_or(AllocationError::OutOfDeviceMemory(None))?;
+
+        let mut block = match strategy {
+            Strategy::Dedicated => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Allocating memory object `{}@{:?}`", size, memory_type);
+
+                if self.allocations_remains == 0 {
+                    return Err(AllocationError::TooManyObjects);
+                }
+
+                let memory = match request.dedicated_resource {
+                    Some(resource) => {
+                        device.allocate_dedicated_memory(size, index, flags, resource)?
+                    }
+                    None => device.allocate_memory(size, index, flags)?,
+                };
+
+                let (memory, ptr) = if request.flags.contains(RequestFlags::PERSISTENTLY_MAPPED) {
+                    let (memory, ptr) = map_dedicated(device, memory, size)?;
+                    (memory, Some(ptr))
+                } else {
+                    (memory, None)
+                };
+
+                self.allocations_remains -= 1;
+                heap.alloc(size);
+
+                let mut block = MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    0,
+                    size,
+                    atom_mask,
+                    request,
+                    MemoryBlockFlavor::Dedicated {
+                        memory: UnsafeCell::new(memory),
+                        ptr,
+                        required: dedicated == Some(Dedicated::Required)
+                            || request.dedicated_resource.is_some(),
+                    },
+                );
+                block.export = request.export;
+                block.dedicated_resource = request.dedicated_resource.is_some();
+                block.memory_size = size;
+                block
+            }
+            Strategy::FreeList | Strategy::BestFit => {
+                let best_fit = strategy == Strategy::BestFit;
+                let (starting_chunk_size, final_chunk_size) = if best_fit {
+                    // Chunks fit any request below dedicated threshold.
+                    let chunk_size = tlsf_chunk_size(
+                        self.initial_buddy_dedicated_size[index as usize],
+                        self.dedicated_threshold[index as usize],
+                        heap_size,
+                    );
+                    free_list_chunk_sizes(
+                        chunk_size,
+                        chunk_size,
+                        self.dedicated_threshold[index as usize],
+                        heap_size,
+                        atom_mask,
+                    )
+                } else {
+                    free_list_chunk_sizes(
+                        self.free_list_chunks[index as usize].0,
+                        self.free_list_chunks[index as usize].1,
+                        self.transient_dedicated_threshold[index as usize],
+                        heap_size,
+                        atom_mask,
+                    )
+                };
+
+                let slot = if best_fit {
+                    &mut self.best_fit_allocators[slot]
+                } else {
+                    &mut self.freelist_allocators[slot]
+                };
+
+                let allocator = match slot {
+                    Some(allocator) => allocator,
+                    slot => slot.get_or_insert(FreeListAllocator::new(
+                        starting_chunk_size,
+                        final_chunk_size,
+                        index,
+                        memory_type.props,
+                        atom_mask,
+                        best_fit,
+                        self.observer.clone(),
+                    )),
+                };
+                let block = match chunk_size {
+                    None => allocator.alloc(
+                        device,
+                        size,
+                        align_mask,
+                        flags,
+                        heap,
+                        &mut self.allocations_remains,
+                    ),
+                    Some(chunk_size) => {
+                        let previous = allocator.chunk_size();
+                        allocator.set_chunk_size(chunk_size);
+                        let result = allocator.alloc(
+                            device,
+                            size,
+                            align_mask,
+                            flags,
+                            heap,
+                            &mut self.allocations_remains,
+                        );
+                        allocator.set_chunk_size(previous);
+                        result
+                    }
+                }?;
+
+                MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    atom_mask,
+                    request,
+                    MemoryBlockFlavor::FreeList {
+                        chunk: block.chunk,
+                        ptr: block.ptr,
+                        memory: block.memory,
+                        best_fit,
+                    },
+                )
+            }
+
+            Strategy::Tlsf => {
+                let allocator = match &mut self.tlsf_allocators[slot] {
+                    Some(allocator) => allocator,
+                    slot => slot.get_or_insert(TlsfAllocator::new(
+                        tlsf_chunk_size(
+                            self.initial_buddy_dedicated_size[index as usize],
+                            self.dedicated_threshold[index as usize],
+                            heap_size,
+                        ),
+                        index,
+                        memory_type.props,
+                        atom_mask,
+                        self.observer.clone(),
+                    )),
+                };
+
+                let block = match chunk_size {
+                    None => {
+                        let chunk_size = allocator.chunk_size();
+                        let used = heap.own_used();
+                        let result = allocator.alloc(
+                            device,
+                            size,
+                            align_mask,
+                            flags,
+                            heap,
+                            &mut self.allocations_remains,
+                        );
+                        // Grow after memory object of current size is allocated.
+                        if heap.own_used() - used == chunk_size {
+                            allocator.set_chunk_size(growth.next(chunk_size, heap_size));
+                        }
+                        result
+                    }
+                    Some(chunk_size) => {
+                        let previous = allocator.chunk_size();
+                        allocator.set_chunk_size(chunk_size);
+                        let result = allocator.alloc(
+                            device,
+                            size,
+                            align_mask,
+                            flags,
+                            heap,
+                            &mut self.allocations_remains,
+                        );
+                        allocator.set_chunk_size(previous);
+                        result
+                    }
+                }?;
+
+                MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    atom_mask,
+                    request,
+                    MemoryBlockFlavor::Tlsf {
+                        chunk: block.chunk,
+                        region: block.region,
+                        ptr: block.ptr,
+                        memory: block.memory,
+                    },
+                )
+            }
+
+            Strategy::Buddy | Strategy::LargeBuddy => {
+                let large = strategy == Strategy::LargeBuddy;
+
+                let entry = if large {
+                    &mut self.large_buddy_allocators[slot]
+                } else {
+                    &mut self.buddy_allocators[slot]
+                };
+
+                let allocator = match entry {
+                    Some(allocator) => allocator,
+                    slot => {
+                        let (minimal_buddy_size, initial_buddy_dedicated_size) = if large {
+                            buddy_chunk_sizes(
+                                self.large_object_threshold[index as usize],
+                                self.initial_buddy_dedicated_size[index as usize]
+                                    .max(self.dedicated_threshold[index as usize]),
+                                heap_size,
+                            )
+                        } else {
+                            buddy_chunk_sizes(
+                                self.minimal_buddy_size[index as usize],
+                                self.initial_buddy_dedicated_size[index as usize],
+                                heap_size,
+                            )
+                        };
+
+                        slot.get_or_insert(BuddyAllocator::new(
+                            minimal_buddy_size,
+                            initial_buddy_dedicated_size,
+                            index,
+                            memory_type.props,
+                            if host_visible_non_coherent(memory_type.props) {
+                                self.non_coherent_atom_mask
+                            } else {
+                                0
+                            },
+                            self.observer.clone(),
+                        ))
+                    }
+                };
+
+                if let Some(adaptive_chunk_size) = adaptive_chunk_size {
+                    allocator.set_chunk_size(adaptive_chunk_size);
+                }
+
+                if chunk_size.is_none()
+                    && self.buddy_spillover_memory_types & (1 << index) != 0
+                    && !allocator.has_free_block(size, align_mask)
+                {
+                    let spilled = self.freelist_allocators[slot]
+                        .as_mut()
+                        .and_then(|allocator| allocator.alloc_existing(size, align_mask));
+
+                    if let Some(block) = spilled {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(
+                            "Request `{}@{:?}` spilled over to free-list allocator",
+                            size,
+                            memory_type
+                        );
+
+                        self.spillover_counts[index as usize] += 1;
+
+                        let mut block = MemoryBlock::new(
+                            index,
+                            memory_type.props,
+                            block.offset,
+                            block.size,
+                            atom_mask,
+                            request,
+                            MemoryBlockFlavor::FreeList {
+                                chunk: block.chunk,
+                                ptr: block.ptr,
+                                memory: block.memory,
+                                best_fit: false,
+                            },
+                        );
+
+                        self.register_block(&mut block, request.size, guard);
+                        return Ok(block);
+                    }
+                }
+
+                let block = match chunk_size {
+                    None => {
+                        let chunk_size = allocator.chunk_size();
+                        let used = heap.own_used();
+                        let result = allocator.alloc(
+                            device,
+                            size,
+                            align_mask,
+                            flags,
+                            heap,
+                            &mut self.allocations_remains,
+                        );
+                        // Grow after memory object of current size is allocated.
+                        if adaptive_chunk_size.is_none() && heap.own_used() - used == chunk_size {
+                            allocator.set_chunk_size(growth.next(chunk_size, heap_size));
+                        }
+                        result
+                    }
+                    Some(chunk_size) => {
+                        let previous = allocator.chunk_size();
+                        allocator.set_chunk_size(chunk_size);
+                        let result = allocator.alloc(
+                            device,
+                            size,
+                            align_mask,
+                            flags,
+                            heap,
+                            &mut self.allocations_remains,
+                        );
+                        allocator.set_chunk_size(previous);
+                        result
+                    }
+                }?;
+
+                MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    atom_mask,
+                    request,
+                    MemoryBlockFlavor::Buddy {
+                        chunk: block.chunk,
+                        ptr: block.ptr,
+                        index: block.index,
+                        memory: block.memory,
+                        large,
+                    },
+                )
+            }
+        };
+
+        self.register_block(&mut block, request.size, guard);
+        Ok(block)
+    }
+
+    unsafe fn alloc_in_pool_from_type(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: &Request,
+        pool: PoolId,
+        index: u32,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        let request = &self.granular_request(request)?;
+        let memory_type = &self.memory_types[index as usize];
+        let entry = self.pools[pool.0 as usize]
+            .as_mut()
+            .expect("Pool does not exist");
+
+        let max_size = entry
+            .config
+            .max_size
+            .map(|max_size| max_size.saturating_sub(entry.memory_objects().1));
+
+        let device = &BudgetedDevice {
+            device,
+            heap: memory_type.heap,
+            policy: budget_policy(self.budget_policy, request),
+            priority: entry.config.priority,
+            never_allocate: request.flags.contains(RequestFlags::NEVER_ALLOCATE),
+            max_size,
+        };
+
+        let heap = &mut self.memory_heaps[memory_type.heap as usize];
+
+        let atom_mask = if host_visible_non_coherent(memory_type.props) {
+            self.non_coherent_atom_mask
+        } else {
+            0
+        };
+
+        let export = entry.config.export;
+        let flags = if self.buffer_device_address {
+            AllocationFlags::DEVICE_ADDRESS | export
+        } else {
+            export
+        };
+
+        let guard = if self.guard_size != 0
+            && memory_type
+                .props
+                .contains(MemoryPropertyFlags::HOST_VISIBLE)
+        {
+            align_up(self.guard_size, request.align_mask | atom_mask)
+                .ok_or(AllocationError::OutOfDeviceMemory(None))?
+        } else {
+            0
+        };
+
+        let size = guard
+            .checked_mul(2)
+            .and_then(|guards| request.size.checked_add(guards))
+            .ok_or(AllocationError::OutOfDeviceMemory(None))?;
+
+        let config = &entry.config;
+        let minimal_buddy_size = self.minimal_buddy_size[index as usize];
+        let observer = &self.observer;
+        let allocator = entry.allocators[index as usize].get_or_insert_with(|| {
+            PoolAllocator::new(
+                config,
+                minimal_buddy_size,
+                index,
+                memory_type.props,
+                atom_mask,
+                observer.clone(),
+            )
+        });
+
+        let allocations_remains = &mut self.allocations_remains;
+        let align_mask = request.align_mask;
+
+        let memory_size;
+        let mut block = match allocator {
+            PoolAllocator::Buddy(allocator) => {
+                let block =
+                    allocator.alloc(device, size, align_mask, flags, heap, allocations_remains)?;
+                memory_size = allocator.memory_size(block.chunk);
+
+                MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    atom_mask,
+                    request,
+                    MemoryBlockFlavor::Buddy {
+                        chunk: block.chunk,
+                        ptr: block.ptr,
+                        index: block.index,
+                        memory: block.memory,
+                        large: false,
+                    },
+                )
+            }
+            PoolAllocator::FreeList(allocator) => {
+                let block =
+                    allocator.alloc(device, size, align_mask, flags, heap, allocations_remains)?;
+                memory_size = allocator.memory_size(block.chunk);
+
+                MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    atom_mask,
+                    request,
+                    MemoryBlockFlavor::FreeList {
+                        chunk: block.chunk,
+                        ptr: block.ptr,
+                        memory: block.memory,
+                        best_fit: false,
+                    },
+                )
+            }
+            PoolAllocator::Tlsf(allocator) => {
+                let block =
+                    allocator.alloc(device, size, align_mask, flags, heap, allocations_remains)?;
+                memory_size = allocator.memory_size(block.chunk);
+
+                MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    atom_mask,
+                    request,
+                    MemoryBlockFlavor::Tlsf {
+                        chunk: block.chunk,
+                        region: block.region,
+                        ptr: block.ptr,
+                        memory: block.memory,
+                    },
+                )
+            }
+        };
+
+        if !export.is_empty() {
+            block.export = export;
+            block.memory_size = memory_size;
+        }
+        block.pool = Some(pool.0);
+        self.register_block(&mut block, request.size, guard);
+        Ok(block)
+    }
+
+    /// Returns memory type chosen with `Request::memory_type`, if any.
+    fn explicit_memory_type(&self, request: &Request) -> Option<Result<u32, AllocationError>> {
+        let index = request.memory_type?;
+
+        assert!(
+            (index as usize) < self.memory_types.len(),
+            "Invalid memory type index"
+        );
+
+        if 0 == request.memory_types & (1 << index) {
+            Some(Err(AllocationError::NoCompatibleMemoryTypes))
+        } else {
+            Some(Ok(index))
+        }
+    }
+
+    /// Returns `request` with alignment raised to `Config::min_alignment`,
+    /// and with size and alignment padded to `buffer_image_granularity`
+    /// if it is for non-linear resource.
+    /// Linear resources never share a page with padded blocks,
+    /// so their sizes are kept as is.
+    fn granular_request(&self, request: &Request) -> Result<Request, AllocationError> {
+        let align_mask = request.align_mask | self.min_alignment_mask;
+        match request.resource_kind {
+            ResourceKind::Linear => Ok(Request {
+                align_mask,
+                ..*request
+            }),
+            ResourceKind::NonLinear => {
+                let granularity_mask = self.buffer_image_granularity_mask;
+                Ok(Request {
+                    size: align_up(request.size, granularity_mask)
+                        .ok_or(AllocationError::OutOfDeviceMemory(None))?,
+                    align_mask: align_mask | granularity_mask,
+                    ..*request
+                })
+            }
+        }
+    }
+
+    /// Starts tracking of newly allocated block.
+    /// `requested` is size from the request the block was allocated for.
+    /// Non-zero `guard` is size of guard regions to leave
+    /// at both ends of the block.
+    fn register_block(&mut self, block: &mut MemoryBlock<M>, requested: u64, guard: u64) {
+        if guard != 0 {
+            // Block was allocated `guard * 2` bytes larger.
+            unsafe { block.apply_guard(guard) };
+        }
+
+        self.last_block_id += 1;
+        block.id = self.last_block_id;
+
+        let strategy = block.strategy();
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            span.record("memory_type", block.memory_type());
+            span.record("strategy", tracing::field::debug(strategy));
+            tracing::debug!(
+                memory_type = block.memory_type(),
+                strategy = ?strategy,
+                offset = block.offset(),
+                size = block.size(),
+                "Block allocated"
+            );
+        }
+
+        self.live_blocks.insert(
+            block.id,
+            LiveBlock {
+                memory_type: block.memory_type(),
+                pool: block.pool,
+                strategy,
+                chunk: block.chunk(),
+                usage: block.usage(),
+                label: block.label(),
+                offset: block.offset(),
+                size: block.size(),
+                padding: block.size() - requested.min(block.size()),
+                guard,
+                #[cfg(feature = "backtrace")]
+                backtrace: AllocationBacktrace::capture(),
+            },
+        );
+
+        if let Some(observer) = &self.observer {
+            observer.on_alloc(&block.info());
+        }
+
+        #[cfg(feature = "tracy")]
+        crate::tracy::alloc(&block.info());
+    }
+
+    /// Starts frame `index`.
+    /// Following `alloc_in_frame` calls allocate blocks tagged with this frame.
+    ///
+    /// Frames may overlap, i.e. next frame may begin before previous one ends.
+    pub fn begin_frame(&mut self, index: u64) {
+        self.current_frame = Some(index);
+        for ring in self.ring_allocators.iter_mut().flatten() {
+            ring.begin_frame(index);
+        }
+    }
+
+    /// Retires frame `index`.
+    /// All blocks allocated in this frame by `alloc_in_frame` are reclaimed.
+    /// Their memory is reused once all older frames are retired too.
+    ///
+    /// Should be called when device finished using blocks of the frame.
+    /// Blocks don't have to be deallocated, but must not be used afterwards.
+    /// No new blocks can be allocated in the frame after it is retired.
+    pub fn end_frame(&mut self, index: u64) {
+        if self.current_frame == Some(index) {
+            self.current_frame = None;
+        }
+        for ring in self.ring_allocators.iter_mut().flatten() {
+            ring.retire_frame(index);
+        }
+    }
+
+    /// Allocates memory block from ring memory object
+    /// tagged with current frame started by `begin_frame`.
+    ///
+    /// Blocks are allocated one after another and reclaimed in bulk with `end_frame`.
+    /// They may be simply dropped or passed to `GpuAllocator::dealloc` which ignores them.
+    /// Ring of `Config::frame_ring_size` bytes is allocated
+    /// for each memory type on first use.
+    /// Fails with `AllocationError::OutOfDeviceMemory`
+    /// if no ring has space for the request until older frames are retired.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * Block must not be used after its frame is retired.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if no frame is started,
+    /// if `request.export` is not empty
+    /// or `request.flags` contains `RequestFlags::DONT_SUBALLOCATE`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_in_frame(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        if self.device_lost {
+            return Err(AllocationError::DeviceLost);
+        }
+
+        let result = self.alloc_in_frame_inner(device, request);
+        self.update_pressure(device);
+        result
+    }
+
+    unsafe fn alloc_in_frame_inner(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        mut request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        let frame = self
+            .current_frame
+            .expect("`alloc_in_frame` must be called after `begin_frame`");
+
+        assert!(
+            request.export.is_empty(),
+            "Exportable memory cannot be allocated in frame"
+        );
+
+        assert!(
+            !request.flags.contains(RequestFlags::DONT_SUBALLOCATE),
+            "Memory allocated in frame is always suballocated"
+        );
+
+        request.usage = with_implicit_usage_flags(&request);
+
+        if let Some(index) = self.explicit_memory_type(&request) {
+            let index = index?;
+            return match self.alloc_in_frame_from_type(device, &request, frame, index) {
+                Err(AllocationError::OutOfDeviceMemory(_)) => {
+                    Err(self.out_of_device_memory(Some((index, false)), request.size))
+                }
+                result => result,
+            };
+        }
+
+        if 0 == self.memory_for_usage.mask(request.usage, request.access) & request.memory_types {
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let types_count = self
+            .memory_for_usage
+            .types(request.usage, request.access)
+            .len();
+
+        let mut failed = None;
+        for i in 0..types_count {
+            let index = self.memory_for_usage.types(request.usage, request.access)[i];
+
+            if 0 == request.memory_types & (1 << index) {
+                // Skip memory type incompatible with the request.
+                continue;
+            }
+
+            match self.alloc_in_frame_from_type(device, &request, frame, index) {
+                Err(AllocationError::OutOfDeviceMemory(_)) => {
+                    failed = Some((index, failed.is_some()));
+                    continue;
+                }
+                result => return result,
+            }
+        }
+
+        Err(self.out_of_device_memory(failed, request.size))
+    }
+
+    unsafe fn alloc_in_frame_from_type(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: &Request,
+        frame: u64,
+        index: u32,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        let request = &self.granular_request(request)?;
+        let memory_type = &self.memory_types[index as usize];
+
+        // Frame ring is allocated with priority of request that needs it first.
+        let device = &BudgetedDevice {
+            device,
+            heap: memory_type.heap,
+            policy: budget_policy(self.budget_policy, request),
+            priority: request.priority,
+            never_allocate: request.flags.contains(RequestFlags::NEVER_ALLOCATE),
+            max_size: None,
+        };
+
+        let atom_mask = if host_visible_non_coherent(memory_type.props) {
+            self.non_coherent_atom_mask
+        } else {
+            0
+        };
+
+        let ring = match &mut self.ring_allocators[index as usize] {
+            Some(ring) => ring,
+            slot => {
+                if self.frame_ring_size == 0 || request.size > self.frame_ring_size {
+                    return Err(AllocationError::OutOfDeviceMemory(None));
+                }
+
+                if self.allocations_remains == 0 {
+                    return Err(AllocationError::TooManyObjects);
+                }
+
+                let flags = if self.buffer_device_address {
+                    AllocationFlags::DEVICE_ADDRESS
+                } else {
+                    AllocationFlags::empty()
+                };
+
+                let mut ring = RingAllocator::new(
+                    device,
+                    self.frame_ring_size,
+                    index,
+                    memory_type.props,
+                    atom_mask,
+                    flags,
+                    &mut self.memory_heaps[memory_type.heap as usize],
+                )?;
+                self.allocations_remains -= 1;
+
+                if let Some(observer) = &self.observer {
+                    observer.on_chunk_create(&ChunkInfo {
+                        memory_type: index,
+                        size: ring.size(),
+                    });
+                }
+
+                ring.begin_frame(frame);
+                slot.get_or_insert(ring)
+            }
+        };
+
+        let block = ring
+            .alloc(request.size, request.align_mask)
+            .ok_or(AllocationError::OutOfDeviceMemory(None))?;
+
+        let mut block = MemoryBlock::new(
+            index,
+            memory_type.props,
+            block.offset,
+            block.size,
+            atom_mask,
+            request,
+            MemoryBlockFlavor::Ring {
+                ptr: block.ptr,
+                memory: block.memory,
+            },
+        );
+
+        // Frame blocks are not tracked individually.
+        self.last_block_id += 1;
+        block.id = self.last_block_id;
+        Ok(block)
+    }
+
+    /// Imports host memory range as memory block.
+    /// Requires "VK_EXT_external_memory_host" extension
+    /// or equivalent support by `device`.
+    ///
+    /// Returned block is mapped to `ptr` directly.
+    /// Mapping it doesn't touch `device` and returns pointer within imported range.
+    ///
+    /// `ptr` and `size` must be multiple of `Config::min_imported_host_pointer_alignment`,
+    /// otherwise `ImportError::Misaligned` is returned.
+    /// Memory type must be `HOST_VISIBLE` and `HOST_COHERENT`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * `ptr` must point to host memory range of `size` bytes
+    ///   that outlives returned block.
+    /// * `memory_type` must support importing `ptr`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn import_host_ptr(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<MemoryBlock<M>, ImportError> {
+        if self.device_lost {
+            return Err(ImportError::DeviceLost);
+        }
+
+        let alignment_mask = self.min_imported_host_pointer_alignment - 1;
+        if (ptr.as_ptr() as usize as u64) & alignment_mask != 0 || size & alignment_mask != 0 {
+            return Err(ImportError::Misaligned);
+        }
+
+        let props = self.memory_types[memory_type as usize].props;
+        if !props.contains(MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT) {
+            return Err(ImportError::IncompatibleMemoryType);
+        }
+
+        if self.allocations_remains == 0 {
+            return Err(ImportError::TooManyObjects);
+        }
+
+        let memory = device.import_host_memory(ptr, size, memory_type)?;
+        self.allocations_remains -= 1;
+
+        let heap = self.memory_types[memory_type as usize].heap;
+        self.memory_heaps[heap as usize].alloc(size);
+
+        let mut block = MemoryBlock::new(
+            memory_type,
+            props,
+            0,
+            size,
+            0,
+            &Request {
+                size,
+                usage: UsageFlags::HOST_ACCESS,
+                memory_types: 1 << memory_type,
+                ..Request::default()
+            },
+            MemoryBlockFlavor::Imported { memory, ptr },
+        );
+
+        self.register_block(&mut block, size, 0);
+        self.update_pressure(device);
+        Ok(block)
+    }
+
+    /// Deallocates memory block previously allocated from this `GpuAllocator` instance.
+    ///
+    /// If block cannot be deallocated, error is reported and its memory is not released.
+    /// Use `try_dealloc` to handle `DeallocError` instead.
+    ///
+    /// # Safety
+    ///
+    /// * Memory block must have been allocated by this `GpuAllocator` instance
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn dealloc(&mut self, device: &impl MemoryDevice<M>, block: MemoryBlock<M>) {
+        if let Err(err) = self.try_dealloc(device, block) {
+            report_error!("Memory block wasn't deallocated: {}", err);
+        }
+    }
+
+    /// Schedules memory block previously allocated from this `GpuAllocator` instance
+    /// to be deallocated once device completes frame `frame`.
+    ///
+    /// Use it for blocks that may still be used by submitted commands.
+    /// Block is deallocated by `flush_retired` call with `completed_frame` not less than `frame`.
+    ///
+    /// # Safety
+    ///
+    /// * Memory block must have been allocated by this `GpuAllocator` instance
+    pub unsafe fn dealloc_deferred(&mut self, block: MemoryBlock<M>, frame: u64) {
+        self.deferred.entry(frame).or_default().push(block);
+    }
+
+    /// Deallocates all memory blocks scheduled with `dealloc_deferred`
+    /// for frames up to and including `completed_frame`.
+    ///
+    /// Should be called when device finished executing commands of `completed_frame`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn flush_retired(&mut self, device: &impl MemoryDevice<M>, completed_frame: u64) {
+        let pending = match completed_frame.checked_add(1) {
+            Some(next) => self.deferred.split_off(&next),
+            None => BTreeMap::new(),
+        };
+        let retired = core::mem::replace(&mut self.deferred, pending);
+
+        for (_, blocks) in retired {
+            for block in blocks {
+                self.dealloc(device, block);
+            }
+        }
+    }
+
+    /// Returns number of memory blocks scheduled with `dealloc_deferred`
+    /// and not deallocated yet.
+    pub fn deferred_count(&self) -> usize {
+        self.deferred.values().map(Vec::len).sum()
+    }
+
+    /// Deallocates memory block previously allocated from this `GpuAllocator` instance.
+    ///
+    /// If `Config::validate_deallocations` is enabled,
+    /// this function checks that block is currently allocated by this allocator.
+    /// Otherwise error is returned and block is leaked without touching allocator state.
+    /// Without validation this function never fails.
+    ///
+    /// # Safety
+    ///
+    /// * Memory block must have been allocated by this `GpuAllocator` instance,
+    ///   unless deallocation validation is enabled
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn try_dealloc(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        block: MemoryBlock<M>,
+    ) -> Result<(), DeallocError> {
+        let generation = block.id;
+        let result = self.try_dealloc_inner(device, block);
+        self.update_pressure(device);
+
+        if let (Ok(()), Some(recording)) = (&result, &mut self.recording) {
+            recording.record_dealloc(generation);
+        }
+
+        result
+    }
+
+    unsafe fn try_dealloc_inner(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        block: MemoryBlock<M>,
+    ) -> Result<(), DeallocError> {
+        if let Some(group) = block.alias_group() {
+            return self.dealloc_alias(device, block, group);
+        }
+
+        if block.is_frame_block() {
+            // Reclaimed when its frame is retired.
+            block.deallocate();
+            return Ok(());
+        }
+
+        if self.device_lost {
+            // Memory objects are gone with the device.
+            self.live_blocks.remove(&block.id);
+            block.deallocate();
+            return Ok(());
+        }
+
+        if self.validate_deallocations {
+            let error = match self.live_blocks.get(&block.id) {
+                None => Some(DeallocError::NotAllocated),
+                Some(live)
+                    if live.memory_type != block.memory_type()
+                        || live.offset != block.offset()
+                        || live.size != block.size() =>
+                {
+                    Some(DeallocError::RangeMismatch)
+                }
+                Some(_) => None,
+            };
+
+            if let Some(error) = error {
+                // Block may be a bitwise copy of another block,
+                // so it must not touch memory object reference counter.
+                core::mem::forget(block);
+                return Err(error);
+            }
+        }
+
+        let live = self.live_blocks.remove(&block.id);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            memory_type = block.memory_type(),
+            strategy = ?block.strategy(),
+            offset = block.offset(),
+            size = block.size(),
+            "Block deallocated"
+        );
+
+        if let Some(observer) = &self.observer {
+            observer.on_dealloc(&block.info());
+        }
+
+        #[cfg(feature = "tracy")]
+        crate::tracy::free(&block.info());
+
+        let mut block = block;
+        if let Some(live) = live.filter(|live| live.guard != 0) {
+            if !block.remove_guard(live.guard) {
+                #[cfg(feature = "tracing")]
+                tracing::error!(
+                    "Guard bytes around block {:?} of `{}` bytes at `{}` in memory type `{}` are overwritten",
+                    live.label,
+                    live.size,
+                    live.offset,
+                    live.memory_type,
+                );
+
+                self.guard_corruptions.push(GuardCorruption {
+                    label: live.label,
+                    memory_type: live.memory_type,
+                    offset: live.offset,
+                    size: live.size,
+                });
+            }
+        }
+
+        let memory_type = block.memory_type();
+        let offset = block.offset();
+        let size = block.size();
+        let pool = block.pool;
+        let slot = self.allocator_slot(
+            memory_type,
+            block.usage().contains(UsageFlags::DEVICE_ADDRESS),
+            block.priority,
+        );
+        let flavor = block.deallocate();
+        match flavor {
+            MemoryBlockFlavor::Dedicated { memory, ptr, .. } => {
+                let mut memory = memory.into_inner();
+                let heap = self.memory_types[memory_type as usize].heap;
+                if ptr.is_some() {
+                    device.unmap_memory(&mut memory);
+                }
+                device.deallocate_memory(memory);
+                self.allocations_remains += 1;
+                self.memory_heaps[heap as usize].dealloc(size);
+            }
+            MemoryBlockFlavor::Imported { memory, .. } => {
+                let heap = self.memory_types[memory_type as usize].heap;
+                device.deallocate_memory(memory);
+                self.allocations_remains += 1;
+                self.memory_heaps[heap as usize].dealloc(size);
+            }
+            MemoryBlockFlavor::Buddy {
                 chunk,
                 ptr,
                 index,
                 memory,
+                large,
+            } => {
+                let heap = self.memory_types[memory_type as usize].heap;
+                let heap = &mut self.memory_heaps[heap as usize];
+
+                let allocator = match pool {
+                    Some(pool) => match pool_allocator(&mut self.pools, pool, memory_type) {
+                        PoolAllocator::Buddy(allocator) => Some(allocator),
+                        _ => None,
+                    },
+                    None if large => self.large_buddy_allocators[slot].as_mut(),
+                    None => self.buddy_allocators[slot].as_mut(),
+                }
+                .expect("Allocator should exist");
+
+                allocator.dealloc(
+                    device,
+                    BuddyBlock {
+                        memory,
+                        ptr,
+                        offset,
+                        size,
+                        chunk,
+                        index,
+                    },
+                    heap,
+                    &mut self.allocations_remains,
+                );
+            }
+            MemoryBlockFlavor::Tlsf {
+                chunk,
+                region,
+                ptr,
+                memory,
+            } => {
+                let heap = self.memory_types[memory_type as usize].heap;
+                let heap = &mut self.memory_heaps[heap as usize];
+
+                let allocator = match pool {
+                    Some(pool) => match pool_allocator(&mut self.pools, pool, memory_type) {
+                        PoolAllocator::Tlsf(allocator) => Some(allocator),
+                        _ => None,
+                    },
+                    None => self.tlsf_allocators[slot].as_mut(),
+                }
+                .expect("Allocator should exist");
+
+                allocator.dealloc(
+                    device,
+                    TlsfBlock {
+                        memory,
+                        ptr,
+                        offset,
+                        size,
+                        chunk,
+                        region,
+                    },
+                    heap,
+                    &mut self.allocations_remains,
+                );
+            }
+            MemoryBlockFlavor::Ring { .. } | MemoryBlockFlavor::Aliased { .. } => unreachable!(),
+            MemoryBlockFlavor::FreeList {
+                chunk,
+                ptr,
+                memory,
+                best_fit,
             } => {
                 let heap = self.memory_types[memory_type as usize].heap;
                 let heap = &mut self.memory_heaps[heap as usize];
 
-                let allocator = self.buddy_allocators[memory_type as usize]
-                    .as_mut()
-                    .expect("Allocator should exist");
+                let allocator = match pool {
+                    Some(pool) => match pool_allocator(&mut self.pools, pool, memory_type) {
+                        PoolAllocator::FreeList(allocator) => Some(allocator),
+                        _ => None,
+                    },
+                    None if best_fit => self.best_fit_allocators[slot].as_mut(),
+                    None => self.freelist_allocators[slot].as_mut(),
+                }
+                .expect("Allocator should exist");
+
+                allocator.dealloc(
+                    device,
+                    FreeListBlock {
+                        memory,
+                        ptr,
+                        chunk,
+                        offset,
+                        size,
+                    },
+                    heap,
+                    &mut self.allocations_remains,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deallocates block of aliasing group `group`,
+    /// and memory range of the group with its last block.
+    unsafe fn dealloc_alias(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        block: MemoryBlock<M>,
+        group: u64,
+    ) -> Result<(), DeallocError> {
+        let aliases = match self.alias_groups.get_mut(&group) {
+            Some(aliases) if aliases.contains_key(&block.id) => aliases,
+            _ if self.validate_deallocations => {
+                // Block may be a bitwise copy of another block,
+                // so it must not touch reference counter of memory range.
+                core::mem::forget(block);
+                return Err(DeallocError::NotAllocated);
+            }
+            aliases => aliases.expect("Aliasing group should exist"),
+        };
+
+        aliases.remove(&block.id);
+        if !aliases.is_empty() {
+            block.deallocate();
+            return Ok(());
+        }
+        self.alias_groups.remove(&group);
+
+        match block.deallocate() {
+            MemoryBlockFlavor::Aliased { backing, .. } => {
+                let backing = Arc::try_unwrap(backing)
+                    .expect("All blocks of aliasing group should be deallocated");
+                self.try_dealloc(device, backing)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Deallocates range `offset..offset + size` of memory block,
+    /// keeping the rest of the block allocated.
+    ///
+    /// Only blocks sub-allocated from free-list memory objects support this,
+    /// for other blocks `DeallocRangeError::Unsupported` is returned.
+    /// Range must not cover whole block and block must not be mapped.
+    /// For host-visible non-coherent memory types
+    /// range must be aligned to `non_coherent_atom_size`.
+    /// On error block is left untouched.
+    ///
+    /// Deallocated range returns to free list of the memory object and can be reused
+    /// by following allocations. Memory object itself is deallocated only
+    /// when all blocks in it are deallocated.
+    ///
+    /// If range is at the start or at the end of the block, the block shrinks
+    /// and `None` is returned.
+    /// Otherwise the block keeps part before the range and new block is returned
+    /// for part after the range. New block has its own `MemoryBlock::generation`
+    /// and must be deallocated separately.
+    ///
+    /// # Safety
+    ///
+    /// * Memory block must have been allocated by this `GpuAllocator` instance,
+    ///   unless deallocation validation is enabled
+    /// * Deallocated range must not be used afterwards
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, block)))]
+    pub unsafe fn dealloc_range(
+        &mut self,
+        block: &mut MemoryBlock<M>,
+        offset: u64,
+        size: u64,
+    ) -> Result<Option<MemoryBlock<M>>, DeallocRangeError> {
+        if self.validate_deallocations && !self.is_current(block) {
+            return Err(DeallocRangeError::NotAllocated);
+        }
+
+        if matches!(self.live_blocks.get(&block.id), Some(live) if live.guard != 0) {
+            return Err(DeallocRangeError::Unsupported);
+        }
+
+        let before = block.info();
+        let (range, tail) = block.cut_free_list_range(offset, size)?;
+
+        if let Some(live) = self.live_blocks.get_mut(&block.id) {
+            live.offset = block.offset();
+            live.size = block.size();
+            live.padding = live.padding.min(live.size);
+        }
+
+        if let Some(observer) = &self.observer {
+            observer.on_dealloc(&before);
+            observer.on_alloc(&block.info());
+        }
+
+        #[cfg(feature = "tracy")]
+        {
+            crate::tracy::free(&before);
+            crate::tracy::alloc(&block.info());
+        }
+
+        let memory_type = block.memory_type();
+        let slot = self.allocator_slot(
+            memory_type,
+            block.usage().contains(UsageFlags::DEVICE_ADDRESS),
+            block.priority,
+        );
+        let allocator = match block.pool {
+            Some(pool) => match pool_allocator(&mut self.pools, pool, memory_type) {
+                PoolAllocator::FreeList(allocator) => Some(allocator),
+                _ => None,
+            },
+            None if block.strategy() == Strategy::BestFit => {
+                self.best_fit_allocators[slot].as_mut()
+            }
+            None => self.freelist_allocators[slot].as_mut(),
+        }
+        .expect("Allocator should exist");
+
+        allocator.dealloc_range(range, tail.is_some());
+
+        Ok(tail.map(|mut tail| {
+            let size = tail.size();
+            self.register_block(&mut tail, size, 0);
+            tail
+        }))
+    }
+
+    /// Plans moving dedicated memory blocks into shared memory objects.
+    ///
+    /// Blocks that are dedicated memory objects smaller than current dedicated threshold
+    /// get new block allocated in shared memory object of the same memory type.
+    /// Blocks allocated with `Dedicated::Required` or `Request::dedicated_resource`,
+    /// blocks in shared memory objects and blocks for which new block cannot be allocated are kept.
+    ///
+    /// Allocator does not own allocated blocks, so they must be provided by the caller.
+    /// Returned relocations are in the same order as `blocks`.
+    ///
+    /// Caller should copy content of each moved block and rebind resources,
+    /// then finish with `commit_relocations` that frees dedicated memory objects,
+    /// or with `abort_relocations`.
+    ///
+    /// # Safety
+    ///
+    /// * Memory blocks must have been allocated by this `GpuAllocator` instance
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, blocks)))]
+    pub unsafe fn compact_dedicated(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        blocks: impl IntoIterator<Item = MemoryBlock<M>>,
+    ) -> Vec<Relocation<M>> {
+        blocks
+            .into_iter()
+            .map(|block| {
+                if !block.is_relocatable_dedicated() {
+                    return Relocation::Keep(block);
+                }
+
+                let request = block.relocation_request();
+                let heap = self.memory_types[block.memory_type() as usize].heap;
+                let strategy = self.strategy(
+                    block.memory_type(),
+                    request.size,
+                    request.usage.contains(UsageFlags::TRANSIENT),
+                    None,
+                    &self.memory_heaps[heap as usize],
+                );
+
+                if strategy == Strategy::Dedicated {
+                    return Relocation::Keep(block);
+                }
 
-                allocator.dealloc(
+                match self.alloc_from_type_internal(
                     device,
-                    BuddyBlock {
-                        memory,
-                        ptr,
-                        offset,
-                        size,
-                        chunk,
-                        index,
-                    },
-                    heap,
-                    &mut self.allocations_remains,
-                );
+                    &request,
+                    None,
+                    None,
+                    block.memory_type(),
+                ) {
+                    Ok(dst) => Relocation::Move { src: block, dst },
+                    Err(_err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!("Dedicated block is kept: {}", _err);
+                        Relocation::Keep(block)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Plans moving sub-allocated blocks out of sparsely used memory objects.
+    ///
+    /// Memory objects of each memory type are ordered by total size of provided blocks in them.
+    /// Blocks from the sparser half of memory objects get new blocks,
+    /// allocated from free space of other memory objects of the same memory type.
+    /// New memory objects are never allocated.
+    /// Blocks that cannot be moved this way, dedicated and frame blocks are kept.
+    /// Use `compact_dedicated` to move dedicated blocks.
+    ///
+    /// Allocator does not own allocated blocks, so they must be provided by the caller.
+    /// Only provided blocks are considered, so for best result all live blocks should be provided.
+    /// Returned relocations are in the same order as `blocks`.
+    ///
+    /// Caller should copy content of each moved block and rebind resources,
+    /// for example with its own command buffer,
+    /// then finish with `commit_relocations` that frees vacated memory objects,
+    /// or with `abort_relocations`.
+    ///
+    /// # Safety
+    ///
+    /// * Memory blocks must have been allocated by this `GpuAllocator` instance
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, blocks)))]
+    pub unsafe fn defragment(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        blocks: impl IntoIterator<Item = MemoryBlock<M>>,
+    ) -> Vec<Relocation<M>> {
+        let blocks: Vec<_> = blocks.into_iter().collect();
+
+        // Memory objects are identified by address of memory object handle shared by blocks.
+        let chunk_of =
+            |block: &MemoryBlock<M>| (block.memory_type(), block.memory() as *const M as usize);
+
+        // Blocks in pools stay in their pools.
+        let movable = |block: &MemoryBlock<M>| block.is_suballocated() && block.pool.is_none();
+
+        let mut usage = BTreeMap::new();
+        for block in blocks.iter().filter(|block| movable(block)) {
+            *usage.entry(chunk_of(block)).or_insert(0u64) += block.size();
+        }
+
+        let mut vacated = BTreeSet::new();
+        for memory_type in 0..self.memory_types.len() as u32 {
+            let mut chunks: Vec<_> = usage
+                .iter()
+                .filter(|((chunk_type, _), _)| *chunk_type == memory_type)
+                .map(|(&chunk, &size)| (size, chunk))
+                .collect();
+            chunks.sort_unstable();
+            let sparse = chunks.len() / 2;
+            vacated.extend(chunks[..sparse].iter().map(|&(_, chunk)| chunk));
+        }
+
+        // Blocks allocated in vacated memory objects are held until the end
+        // so that following attempts use other free space.
+        let mut rejected = Vec::new();
+
+        // No memory object can be allocated while limit is zero.
+        let allocations_remains = core::mem::replace(&mut self.allocations_remains, 0);
+
+        let relocations = blocks
+            .into_iter()
+            .map(|block| {
+                if !movable(&block) || !vacated.contains(&chunk_of(&block)) {
+                    return Relocation::Keep(block);
+                }
+
+                let request = block.relocation_request();
+
+                loop {
+                    match self.alloc_from_type_internal(
+                        device,
+                        &request,
+                        None,
+                        None,
+                        block.memory_type(),
+                    ) {
+                        Ok(dst) if vacated.contains(&chunk_of(&dst)) => rejected.push(dst),
+                        Ok(dst) => return Relocation::Move { src: block, dst },
+                        Err(_err) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!("Block is kept: {}", _err);
+                            return Relocation::Keep(block);
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        self.allocations_remains = allocations_remains;
+
+        for block in rejected {
+            self.dealloc(device, block);
+        }
+
+        relocations
+    }
+
+    /// Finishes relocations planned by `compact_dedicated` or `defragment`.
+    /// Deallocates source of each moved block.
+    /// Returns blocks holding content in the same order as `relocations`.
+    ///
+    /// # Safety
+    ///
+    /// * Relocations must have been planned by this `GpuAllocator` instance
+    /// * Source blocks must not be used by device anymore
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, device, relocations))
+    )]
+    pub unsafe fn commit_relocations(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        relocations: Vec<Relocation<M>>,
+    ) -> Vec<MemoryBlock<M>> {
+        relocations
+            .into_iter()
+            .map(|relocation| match relocation {
+                Relocation::Keep(block) => block,
+                Relocation::Move { src, dst } => {
+                    self.dealloc(device, src);
+                    dst
+                }
+            })
+            .collect()
+    }
+
+    /// Cancels relocations planned by `compact_dedicated` or `defragment`.
+    /// Deallocates destination of each moved block.
+    /// Returns original blocks in the same order as `relocations`.
+    ///
+    /// # Safety
+    ///
+    /// * Relocations must have been planned by this `GpuAllocator` instance
+    /// * Destination blocks must not be used by device anymore
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, device, relocations))
+    )]
+    pub unsafe fn abort_relocations(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        relocations: Vec<Relocation<M>>,
+    ) -> Vec<MemoryBlock<M>> {
+        relocations
+            .into_iter()
+            .map(|relocation| match relocation {
+                Relocation::Keep(block) => block,
+                Relocation::Move { src, dst } => {
+                    self.dealloc(device, dst);
+                    src
+                }
+            })
+            .collect()
+    }
+
+    /// Returns `true` if specified block is currently allocated by this allocator.
+    ///
+    /// Block is matched by its `MemoryBlock::generation`,
+    /// so block that was deallocated is never current again,
+    /// even if its memory range was reused by another block.
+    /// Result is meaningless for blocks allocated by other allocators.
+    pub fn is_current(&self, block: &MemoryBlock<M>) -> bool {
+        if let Some(group) = block.alias_group() {
+            return match self.alias_groups.get(&group) {
+                Some(aliases) => aliases.contains_key(&block.generation()),
+                None => false,
+            };
+        }
+
+        match self.live_blocks.get(&block.generation()) {
+            Some(live) => {
+                live.memory_type == block.memory_type()
+                    && live.offset == block.offset()
+                    && live.size == block.size()
             }
-            MemoryBlockFlavor::FreeList { chunk, ptr, memory } => {
-                let heap = self.memory_types[memory_type as usize].heap;
-                let heap = &mut self.memory_heaps[heap as usize];
+            None => false,
+        }
+    }
 
-                let allocator = self.freelist_allocators[memory_type as usize]
-                    .as_mut()
-                    .expect("Allocator should exist");
+    /// Returns blocks found with overwritten guard regions
+    /// on deallocation since last call.
+    ///
+    /// See `Config::guard_size`.
+    pub fn take_guard_corruptions(&mut self) -> Vec<GuardCorruption> {
+        core::mem::take(&mut self.guard_corruptions)
+    }
 
-                allocator.dealloc(
-                    device,
-                    FreeListBlock {
-                        memory,
-                        ptr,
-                        chunk,
-                        offset,
-                        size,
-                    },
-                    heap,
-                    &mut self.allocations_remains,
-                );
+    /// Starts recording allocations and deallocations.
+    /// Recording in progress is discarded.
+    ///
+    /// See `Recording`.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::new());
+    }
+
+    /// Stops recording and returns recorded events,
+    /// or `None` if recording was not started.
+    pub fn take_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    /// Marks device as lost, e.g. after backend reported `VK_ERROR_DEVICE_LOST`.
+    ///
+    /// Following allocations fail with `AllocationError::DeviceLost`.
+    /// Deallocations are accepted without calling into `device`,
+    /// as well as `cleanup`, `trim` and `destroy_pool`,
+    /// and errors about blocks or frames left alive are not reported.
+    /// Blocks must still be passed to `dealloc` to not be reported on drop.
+    /// Allocator can only be torn down after this call.
+    pub fn mark_device_lost(&mut self) {
+        self.device_lost = true;
+
+        for allocator in self
+            .freelist_allocators
+            .iter_mut()
+            .chain(self.best_fit_allocators.iter_mut())
+            .flatten()
+        {
+            allocator.device_lost();
+        }
+
+        for pool in self.pools.iter_mut().flatten() {
+            for allocator in pool.allocators.iter_mut().flatten() {
+                if let PoolAllocator::FreeList(allocator) = allocator {
+                    allocator.device_lost();
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if device was marked as lost with `mark_device_lost`.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost
+    }
+
+    /// Returns blocks that are not deallocated yet, in order of allocation.
+    ///
+    /// Should be empty on shutdown,
+    /// otherwise labels of the blocks help to find the leak.
+    /// Frame blocks are not included.
+    /// Blocks of aliasing groups are listed instead of memory ranges they share.
+    pub fn report_leaks(&self) -> Vec<LeakedBlock> {
+        let mut leaks = Vec::new();
+        for (id, live) in &self.live_blocks {
+            match self.alias_groups.get(id) {
+                Some(aliases) => leaks.extend(aliases.values().cloned()),
+                None => leaks.push(LeakedBlock {
+                    label: live.label,
+                    memory_type: live.memory_type,
+                    strategy: live.strategy,
+                    offset: live.offset,
+                    size: live.size,
+                    #[cfg(feature = "backtrace")]
+                    backtrace: live.backtrace.clone(),
+                }),
             }
         }
+        leaks
+    }
+
+    /// Returns blocks that are not deallocated yet, in order of allocation.
+    ///
+    /// Unlike `report_leaks` it is meant to be called at any time,
+    /// e.g. to list what holds memory in debug overlay.
+    /// Frame blocks are not included.
+    /// Memory ranges shared by aliasing groups are listed once.
+    pub fn report_live_allocations(&self) -> Vec<LiveAllocation> {
+        self.live_blocks
+            .values()
+            .map(|live| LiveAllocation {
+                label: live.label,
+                memory_type: live.memory_type,
+                pool: live.pool.map(PoolId),
+                strategy: live.strategy,
+                usage: live.usage,
+                offset: live.offset,
+                size: live.size,
+                padding: live.padding,
+                #[cfg(feature = "backtrace")]
+                backtrace: live.backtrace.clone(),
+            })
+            .collect()
     }
 
     /// Deallocates leftover memory objects.
     /// Should be used before dropping.
+    /// Blocks still allocated are listed by `report_leaks`.
+    /// Frame rings with live frame blocks are kept and reported as error.
     ///
     /// # Safety
     ///
@@ -481,18 +3890,299 @@ where
     ///   and memory blocks allocated from it
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
     pub unsafe fn cleanup(&mut self, device: &impl MemoryDevice<M>) {
+        self.release_unused(device, true);
+    }
+
+    /// Deallocates memory objects that serve no blocks,
+    /// returning their memory to the device.
+    ///
+    /// Buddy and TLSF allocators deallocate empty memory objects immediately,
+    /// while free-list allocators keep one empty memory object for following requests.
+    /// Call this function after freeing many blocks, e.g. when level is unloaded,
+    /// to release those too, along with frame rings without live frame blocks
+    /// and memory objects left unused after `prewarm`.
+    /// Unlike `cleanup` it is fine to have rings in use.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn trim(&mut self, device: &impl MemoryDevice<M>) {
+        self.release_unused(device, false);
+    }
+
+    /// Releases as much memory as possible while keeping live blocks,
+    /// returning number of bytes deallocated.
+    ///
+    /// Deallocates everything `trim` does and drops orders buddy allocators
+    /// keep empty after chunks sized for large requests were deallocated.
+    /// Memory objects cannot be resized,
+    /// so unused tails of chunks that still serve blocks are kept.
+    /// Call this function after level transition, when blocks of the previous levels are freed.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn shrink_to_fit(&mut self, device: &impl MemoryDevice<M>) -> u64 {
+        let used = |heaps: &[Heap]| heaps.iter().map(Heap::own_used).sum::<u64>();
+        let before = used(&self.memory_heaps);
+
+        self.release_unused(device, false);
+
+        for allocator in self
+            .buddy_allocators
+            .iter_mut()
+            .chain(self.large_buddy_allocators.iter_mut())
+            .flatten()
+        {
+            allocator.shrink_to_fit();
+        }
+
+        for pool in self.pools.iter_mut().flatten() {
+            for allocator in pool.allocators.iter_mut().flatten() {
+                if let PoolAllocator::Buddy(allocator) = allocator {
+                    allocator.shrink_to_fit();
+                }
+            }
+        }
+
+        before - used(&self.memory_heaps)
+    }
+
+    unsafe fn release_unused(&mut self, device: &impl MemoryDevice<M>, report_live_rings: bool) {
+        if self.device_lost {
+            // Memory objects are gone with the device.
+            for prewarmed in self.prewarmed.iter_mut() {
+                prewarmed.clear();
+            }
+            return;
+        }
+
+        for index in 0..self.prewarmed.len() {
+            let prewarmed = core::mem::take(&mut self.prewarmed[index]);
+            self.uncount_prewarmed(index as u32, &prewarmed);
+            for unused in prewarmed {
+                device.deallocate_memory(unused.memory);
+            }
+        }
+
+        self.release_idle_chunks(device, false);
+
+        // Rings left are those with live frame blocks.
+        if report_live_rings && self.ring_allocators.iter().any(Option::is_some) {
+            report_error!("Frame blocks are alive on cleanup");
+        }
+
+        self.update_pressure(device);
+    }
+
+    /// Deallocates memory objects without blocks kept by free-list allocators
+    /// and frame rings without live frame blocks.
+    /// Only host-visible ones, which are persistently mapped, if `mapped_only` is `true`.
+    ///
+    /// Returns number of deallocated memory objects.
+    unsafe fn release_idle_chunks(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        mapped_only: bool,
+    ) -> u32 {
+        let allocations_remains = self.allocations_remains;
+        let memory_types = &self.memory_types;
+        // Allocators of blocks with device address follow ones of other blocks.
+        let skip = |index: usize| {
+            mapped_only
+                && !memory_types[index % memory_types.len()]
+                    .props
+                    .contains(MemoryPropertyFlags::HOST_VISIBLE)
+        };
+
+        let best_fit_allocators = self.best_fit_allocators.iter_mut().enumerate();
         for (index, allocator) in self
             .freelist_allocators
             .iter_mut()
             .enumerate()
+            .chain(best_fit_allocators)
+            .filter(|&(index, _)| !skip(index))
             .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
         {
-            let memory_type = &self.memory_types[index];
+            let memory_type = &self.memory_types[index % self.memory_types.len()];
             let heap = memory_type.heap;
             let heap = &mut self.memory_heaps[heap as usize];
 
             allocator.cleanup(device, heap, &mut self.allocations_remains);
         }
+
+        for pool in self.pools.iter_mut().flatten() {
+            for (index, allocator) in pool.allocators.iter_mut().enumerate() {
+                if skip(index) {
+                    continue;
+                }
+                if let Some(PoolAllocator::FreeList(allocator)) = allocator {
+                    let heap = self.memory_types[index].heap;
+                    let heap = &mut self.memory_heaps[heap as usize];
+                    allocator.cleanup(device, heap, &mut self.allocations_remains);
+                }
+            }
+        }
+
+        for (index, slot) in self.ring_allocators.iter_mut().enumerate() {
+            if skip(index) {
+                continue;
+            }
+            if let Some(ring) = slot.take() {
+                let memory_type = &self.memory_types[index];
+                let heap = &mut self.memory_heaps[memory_type.heap as usize];
+                let size = ring.size();
+
+                match ring.destroy(device, heap) {
+                    Ok(()) => {
+                        self.allocations_remains += 1;
+                        if let Some(observer) = &self.observer {
+                            observer.on_chunk_destroy(&ChunkInfo {
+                                memory_type: index as u32,
+                                size,
+                            });
+                        }
+                    }
+                    Err(ring) => *slot = Some(ring),
+                }
+            }
+        }
+
+        self.allocations_remains - allocations_remains
+    }
+
+    /// Notifies observer about heaps which utilization crossed pressure thresholds
+    /// since last check.
+    fn update_pressure(&mut self, device: &impl MemoryDevice<M>) {
+        let observer = match &self.observer {
+            Some(observer) if !self.pressure_thresholds.is_empty() => observer,
+            _ => return,
+        };
+        let thresholds = &self.pressure_thresholds;
+
+        for (index, heap) in self.memory_heaps.iter_mut().enumerate() {
+            let used = heap.used();
+            if used == self.pressure_used[index] {
+                continue;
+            }
+            self.pressure_used[index] = used;
+
+            let budget = match device.memory_budget(index as u32) {
+                Some(budget) => budget.budget,
+                None => heap.size(),
+            };
+
+            let level = thresholds
+                .iter()
+                .take_while(|&&threshold| {
+                    u128::from(used) * 100 >= u128::from(budget) * u128::from(threshold)
+                })
+                .count();
+
+            let previous = heap.swap_pressure_level(level);
+            if level != previous {
+                let threshold = |level: usize| level.checked_sub(1).map(|l| thresholds[l]);
+                observer.on_heap_pressure(&HeapPressure {
+                    heap: index as u32,
+                    used,
+                    budget,
+                    threshold: threshold(level),
+                    previous: threshold(previous),
+                });
+            }
+        }
+    }
+}
+
+/// Block tracked to validate deallocations, detect stale blocks and report usage.
+#[derive(Debug)]
+struct LiveBlock {
+    memory_type: u32,
+    pool: Option<u32>,
+    strategy: Strategy,
+    chunk: Option<u64>,
+    usage: UsageFlags,
+    label: Option<&'static str>,
+    offset: u64,
+    size: u64,
+    /// Bytes of the block beyond requested size.
+    padding: u64,
+    /// Size of guard regions around the block.
+    guard: u64,
+    #[cfg(feature = "backtrace")]
+    backtrace: AllocationBacktrace,
+}
+
+/// Copy of allocator state used by `GpuAllocator::plan`.
+#[derive(Clone)]
+struct PlanState {
+    heaps: Vec<u64>,
+    allocations_remains: u32,
+    buddy: Vec<Option<BuddyPlan>>,
+    large_buddy: Vec<Option<BuddyPlan>>,
+    freelist: Vec<Option<FreeListPlan>>,
+    tlsf: Vec<Option<TlsfPlan>>,
+    best_fit: Vec<Option<FreeListPlan>>,
+}
+
+/// Returns starting and final chunk sizes for free-list allocator.
+fn free_list_chunk_sizes(
+    starting_free_list_chunk: u64,
+    final_free_list_chunk: u64,
+    transient_dedicated_threshold: u64,
+    heap_size: u64,
+    atom_mask: u64,
+) -> (u64, u64) {
+    let starting = match align_down(starting_free_list_chunk.min(heap_size / 32), atom_mask) {
+        0 => atom_mask + 1,
+        other => other,
+    };
+
+    let final_ = match align_down(
+        final_free_list_chunk
+            .max(starting_free_list_chunk)
+            .max(transient_dedicated_threshold)
+            .min(heap_size / 32),
+        atom_mask,
+    ) {
+        0 => atom_mask + 1,
+        other => other,
+    };
+
+    (starting, final_)
+}
+
+/// Returns chunk size for TLSF allocator.
+/// Chunk fits any request below dedicated threshold, like large-object buddy allocator chunk.
+fn tlsf_chunk_size(initial_dedicated_size: u64, dedicated_threshold: u64, heap_size: u64) -> u64 {
+    initial_dedicated_size
+        .max(dedicated_threshold)
+        .min(heap_size / 32)
+        .next_power_of_two()
+}
+
+/// Returns minimal block size and initial chunk size for buddy allocator.
+fn buddy_chunk_sizes(minimal_size: u64, initial_dedicated_size: u64, heap_size: u64) -> (u64, u64) {
+    (
+        minimal_size.min(heap_size / 1024).next_power_of_two(),
+        initial_dedicated_size
+            .min(heap_size / 32)
+            .next_power_of_two(),
+    )
+}
+
+/// Returns flags of memory objects allocated for blocks with specified usage.
+fn device_address_flags(usage: UsageFlags) -> AllocationFlags {
+    if usage.contains(UsageFlags::DEVICE_ADDRESS) {
+        AllocationFlags::DEVICE_ADDRESS
+    } else {
+        AllocationFlags::empty()
     }
 }
 
@@ -501,9 +4191,123 @@ fn host_visible_non_coherent(props: MemoryPropertyFlags) -> bool {
         == MemoryPropertyFlags::HOST_VISIBLE
 }
 
-fn with_implicit_usage_flags(usage: UsageFlags) -> UsageFlags {
-    if usage.is_empty() {
-        UsageFlags::FAST_DEVICE_ACCESS
+const EXPORT_FLAGS: AllocationFlags = AllocationFlags::from_bits_truncate(
+    AllocationFlags::EXPORT_OPAQUE_FD.bits() | AllocationFlags::EXPORT_OPAQUE_WIN32.bits(),
+);
+
+/// Returns dedicated hint for the `request`.
+/// Exportable memory can't be shared between blocks, so it is always dedicated.
+/// So is memory requested with `RequestFlags::DONT_SUBALLOCATE`.
+fn with_implied_dedicated(request: &Request, dedicated: Option<Dedicated>) -> Option<Dedicated> {
+    if request.export.is_empty() && !request.flags.contains(RequestFlags::DONT_SUBALLOCATE) {
+        dedicated.or(request.dedicated)
+    } else {
+        Some(Dedicated::Required)
+    }
+}
+
+/// Returns `true` if memory object for the `request` may be allocated with `GpuAllocator::preallocate`.
+/// Memory objects for blocks with device address or priority are allocated with other flags,
+/// and ones for requests within budget must be checked against it.
+fn is_preallocatable(request: &Request) -> bool {
+    !request.usage.contains(UsageFlags::DEVICE_ADDRESS)
+        && request.priority.is_none()
+        && !request.flags.contains(RequestFlags::WITHIN_BUDGET)
+}
+
+fn budget_policy(policy: BudgetPolicy, request: &Request) -> BudgetPolicy {
+    if request.flags.contains(RequestFlags::WITHIN_BUDGET) {
+        BudgetPolicy::Fail
+    } else {
+        policy
+    }
+}
+
+/// Maps whole dedicated memory object to keep it persistently mapped.
+/// Memory object is deallocated if mapping fails.
+unsafe fn map_dedicated<M>(
+    device: &impl MemoryDevice<M>,
+    mut memory: M,
+    size: u64,
+) -> Result<(M, NonNull<u8>), AllocationError> {
+    let result = match isize::try_from(size) {
+        Ok(_) => device.map_memory(&mut memory, 0, size),
+        Err(_) => Err(DeviceMapError::MapFailed),
+    };
+
+    match result {
+        Ok(ptr) => Ok((memory, ptr)),
+        Err(err) => {
+            #[cfg(feature = "tracing")]
+            tracing::error!("Failed to map persistently mapped dedicated memory object");
+
+            device.deallocate_memory(memory);
+            Err(match err {
+                DeviceMapError::OutOfDeviceMemory => AllocationError::OutOfDeviceMemory(None),
+                DeviceMapError::OutOfHostMemory => AllocationError::OutOfHostMemory,
+                DeviceMapError::MapFailed => AllocationError::AddressSpaceExhausted,
+            })
+        }
+    }
+}
+
+/// Returns allocator of the pool for memory type of block being deallocated.
+fn pool_allocator<M>(
+    pools: &mut [Option<Pool<M>>],
+    pool: u32,
+    memory_type: u32,
+) -> &mut PoolAllocator<M> {
+    pools[pool as usize]
+        .as_mut()
+        .and_then(|pool| pool.allocators[memory_type as usize].as_mut())
+        .expect("Allocator should exist")
+}
+
+/// Number of priority classes of shared memory objects, see `priority_class`.
+const PRIORITY_CLASSES: usize = 6;
+
+/// Returns class of shared memory objects for blocks with `priority`.
+///
+/// Blocks without priority get class 0.
+/// Others are classed by priority rounded up to quarter,
+/// from class 1 for priority `0.0` to class 5 for priorities above `0.75`.
+fn priority_class(priority: Option<f32>) -> usize {
+    match priority {
+        None => 0,
+        Some(priority) => {
+            let quarters = priority * 4.0;
+            let whole = quarters as usize;
+            if (whole as f32) < quarters {
+                whole + 2
+            } else {
+                whole + 1
+            }
+        }
+    }
+}
+
+/// Returns priority of shared memory objects for blocks with `priority`,
+/// which is highest priority of its class.
+fn class_priority(priority: Option<f32>) -> Option<f32> {
+    match priority_class(priority) {
+        0 => None,
+        class => Some((class - 1) as f32 / 4.0),
+    }
+}
+
+fn max_priority(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+pub(crate) fn with_implicit_usage_flags(request: &Request) -> UsageFlags {
+    let usage = request.usage;
+    if request.access.is_some() || request.flags.contains(RequestFlags::PERSISTENTLY_MAPPED) {
+        usage | UsageFlags::HOST_ACCESS
+    } else if (usage & !(UsageFlags::PROTECTED | UsageFlags::TRANSIENT_ATTACHMENT)).is_empty() {
+        usage | UsageFlags::FAST_DEVICE_ACCESS
     } else if usage.intersects(UsageFlags::DOWNLOAD | UsageFlags::UPLOAD) {
         usage | UsageFlags::HOST_ACCESS
     } else {
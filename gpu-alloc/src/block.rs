@@ -1,14 +1,44 @@
 use {
-    crate::{align_down, align_up, error::MapError},
-    alloc::sync::Arc,
+    crate::{
+        align_down, align_up,
+        allocator::Strategy,
+        error::{DeallocRangeError, MapError},
+        external::{ExternalHandleType, ExternalMemory},
+        freelist::FreeListBlock,
+        observer::MemoryBlockInfo,
+        pool::PoolId,
+        usage::{AccessPattern, RequestFlags, ResourceKind, UsageFlags},
+        Request,
+    },
+    alloc::{sync::Arc, vec::Vec},
     core::{
+        cell::UnsafeCell,
         convert::TryFrom as _,
-        ptr::{copy_nonoverlapping, NonNull},
-        // sync::atomic::{AtomicU8, Ordering::*},
+        fmt::{self, Debug},
+        mem::MaybeUninit,
+        ops::{Deref, DerefMut},
+        ptr::{copy_nonoverlapping, null_mut, NonNull},
+        slice::{from_raw_parts, from_raw_parts_mut},
+        sync::atomic::{AtomicPtr, AtomicUsize, Ordering::*},
+    },
+    gpu_alloc_types::{
+        AllocationFlags, MappedMemoryRange, MemoryDevice, MemoryPropertyFlags, OutOfMemory,
     },
-    gpu_alloc_types::{MappedMemoryRange, MemoryDevice, MemoryPropertyFlags},
 };
 
+/// Byte pattern filling guard regions around blocks.
+pub(crate) const GUARD_PATTERN: u8 = 0xA5;
+
+unsafe fn fill_guard(ptr: *mut u8, guard: u64) {
+    core::ptr::write_bytes(ptr, GUARD_PATTERN, guard as usize);
+}
+
+unsafe fn check_guard(ptr: *const u8, guard: u64) -> bool {
+    core::slice::from_raw_parts(ptr, guard as usize)
+        .iter()
+        .all(|&byte| byte == GUARD_PATTERN)
+}
+
 #[derive(Debug)]
 struct Relevant;
 
@@ -18,6 +48,163 @@ impl Drop for Relevant {
     }
 }
 
+/// Share of memory block mapping acquired with `MemoryBlock::map_shared`.
+///
+/// Guard borrows the block, so it can't be mapped with `map` or deallocated
+/// while any share is alive.
+/// Share is released on drop, dedicated memory object is unmapped with the last one.
+#[must_use = "Shared mapping is released when guard is dropped"]
+pub struct MapGuard<'a, M, D: MemoryDevice<M>> {
+    block: &'a MemoryBlock<M>,
+    device: &'a D,
+}
+
+impl<M, D> Drop for MapGuard<'_, M, D>
+where
+    D: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        unsafe { self.block.release_shared(self.device) }
+    }
+}
+
+impl<M, D> Debug for MapGuard<'_, M, D>
+where
+    M: Debug,
+    D: MemoryDevice<M>,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("MapGuard")
+            .field("block", &self.block)
+            .finish()
+    }
+}
+
+/// Value of `SharedMapping::count` while memory object is being mapped or unmapped.
+const SHARED_BUSY: usize = usize::MAX;
+
+/// State of mapping shared with `MemoryBlock::map_shared`.
+#[derive(Debug, Default)]
+struct SharedMapping {
+    /// Number of live guards, or `SHARED_BUSY`.
+    count: AtomicUsize,
+    /// Pointer to start of the block, valid while there are live guards.
+    ptr: AtomicPtr<u8>,
+}
+
+/// Mapped range of memory block acquired with `MemoryBlock::map_guard`.
+///
+/// Dereferences to mapped bytes.
+/// Range is unmapped on drop, flushing it first if it was written to
+/// and memory is not `HOST_COHERENT`.
+/// Use `MappedBlock::finish` to observe flush errors.
+pub struct MappedBlock<'a, M, D: MemoryDevice<M>> {
+    block: &'a mut MemoryBlock<M>,
+    device: &'a D,
+    offset: u64,
+    ptr: NonNull<u8>,
+    size: usize,
+    written: bool,
+}
+
+impl<M, D> MappedBlock<'_, M, D>
+where
+    D: MemoryDevice<M>,
+{
+    /// Returns offset of mapped range in the block.
+    #[inline(always)]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns pointer to start of mapped range.
+    /// Writes through the pointer are not tracked,
+    /// mutable dereference marks range as written.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> NonNull<u8> {
+        self.ptr
+    }
+
+    /// Unmaps the range, flushing it first if it was written to
+    /// and memory is not `HOST_COHERENT`.
+    pub fn finish(mut self) -> Result<(), MapError> {
+        let result = unsafe { self.release() };
+        core::mem::forget(self);
+        result
+    }
+
+    unsafe fn release(&mut self) -> Result<(), MapError> {
+        let result = if self.written {
+            self.block
+                .flush_range(self.device, self.offset, self.size as u64)
+        } else {
+            Ok(())
+        };
+        self.block.unmap_range(self.device, self.offset);
+        result
+    }
+}
+
+impl<M, D> Deref for MappedBlock<'_, M, D>
+where
+    D: MemoryDevice<M>,
+{
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &[u8] {
+        unsafe { from_raw_parts(self.ptr.as_ptr(), self.size) }
+    }
+}
+
+impl<M, D> DerefMut for MappedBlock<'_, M, D>
+where
+    D: MemoryDevice<M>,
+{
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.written = true;
+        unsafe { from_raw_parts_mut(self.ptr.as_ptr(), self.size) }
+    }
+}
+
+impl<M, D> Drop for MappedBlock<'_, M, D>
+where
+    D: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        if unsafe { self.release() }.is_err() {
+            report_error_on_drop!("Failed to flush mapped memory block range");
+        }
+    }
+}
+
+impl<M, D> Debug for MappedBlock<'_, M, D>
+where
+    M: Debug,
+    D: MemoryDevice<M>,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("MappedBlock")
+            .field("block", &self.block)
+            .field("offset", &self.offset)
+            .field("size", &self.size)
+            .field("written", &self.written)
+            .finish()
+    }
+}
+
+/// Range of memory block mapped with `MemoryBlock::map`.
+#[derive(Clone, Copy, Debug)]
+struct MappedRange {
+    /// Offset passed to `map`, identifies the range for `unmap_range`.
+    offset: u64,
+    /// Bounds of the range,
+    /// extended to `non_coherent_atom_size` boundaries for non-coherent memory.
+    start: u64,
+    end: u64,
+}
+
 /// Memory block allocated by `GpuAllocator`.
 #[derive(Debug)]
 pub struct MemoryBlock<M> {
@@ -26,9 +213,27 @@ pub struct MemoryBlock<M> {
     offset: u64,
     size: u64,
     atom_mask: u64,
-    mapped: bool,
+    align_mask: u64,
+    usage: UsageFlags,
+    pub(crate) priority: Option<f32>,
+    access: Option<AccessPattern>,
+    label: Option<&'static str>,
+    pub(crate) id: u64,
+    pub(crate) pool: Option<u32>,
+    pub(crate) fallback_tier: Option<u32>,
+    /// Handle types dedicated memory object is exportable as.
+    pub(crate) export: AllocationFlags,
+    /// Dedicated memory object was allocated for resource.
+    pub(crate) dedicated_resource: bool,
+    /// Size of exportable memory object.
+    pub(crate) memory_size: u64,
+    resource_kind: ResourceKind,
+    /// Ranges mapped with `map`, sorted by offset.
+    mapped: Vec<MappedRange>,
+    shared: SharedMapping,
     flavor: MemoryBlockFlavor<M>,
-    relevant: Relevant,
+    /// `None` for blocks that are reclaimed in bulk.
+    relevant: Option<Relevant>,
 }
 
 impl<M> MemoryBlock<M> {
@@ -38,6 +243,7 @@ impl<M> MemoryBlock<M> {
         offset: u64,
         size: u64,
         atom_mask: u64,
+        request: &Request,
         flavor: MemoryBlockFlavor<M>,
     ) -> Self {
         isize::try_from(atom_mask).expect("`atom_mask` is too large");
@@ -47,12 +253,293 @@ impl<M> MemoryBlock<M> {
             offset,
             size,
             atom_mask,
+            align_mask: request.align_mask,
+            usage: request.usage,
+            priority: request.priority,
+            access: request.access,
+            label: request.label,
+            id: 0,
+            pool: None,
+            fallback_tier: None,
+            export: AllocationFlags::empty(),
+            dedicated_resource: false,
+            memory_size: 0,
+            resource_kind: request.resource_kind,
+            relevant: match flavor {
+                MemoryBlockFlavor::Ring { .. } => None,
+                _ => Some(Relevant),
+            },
             flavor,
-            mapped: false,
-            relevant: Relevant,
+            mapped: Vec::new(),
+            shared: SharedMapping::default(),
+        }
+    }
+
+    /// Returns request that would allocate block suitable to replace this one
+    /// in memory type of this block.
+    pub(crate) fn relocation_request(&self) -> Request {
+        Request {
+            size: self.size,
+            align_mask: self.align_mask,
+            usage: self.usage,
+            memory_types: 1 << self.memory_type,
+            memory_type: Some(self.memory_type),
+            access: self.access,
+            label: self.label,
+            dedicated: None,
+            dedicated_resource: None,
+            export: AllocationFlags::empty(),
+            priority: self.priority,
+            flags: RequestFlags::empty(),
+            // Size and alignment of the block are already padded for its resource kind.
+            resource_kind: ResourceKind::Linear,
+        }
+    }
+
+    /// Returns block of `size` bytes at the start of `backing` that aliases it.
+    pub(crate) fn alias(backing: &Arc<MemoryBlock<M>>, size: u64, request: &Request) -> Self {
+        debug_assert!(size <= backing.size);
+        MemoryBlock::new(
+            backing.memory_type,
+            backing.props,
+            backing.offset,
+            size,
+            backing.atom_mask,
+            request,
+            MemoryBlockFlavor::Aliased {
+                ptr: backing.mapped_ptr(),
+                backing: backing.clone(),
+            },
+        )
+    }
+
+    /// Returns generation of block this block aliases,
+    /// if it was allocated by `GpuAllocator::alloc_aliased`.
+    pub(crate) fn alias_group(&self) -> Option<u64> {
+        match &self.flavor {
+            MemoryBlockFlavor::Aliased { backing, .. } => Some(backing.id),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this block is dedicated memory object
+    /// that may be replaced by block in shared memory object.
+    pub(crate) fn is_relocatable_dedicated(&self) -> bool {
+        match self.flavor {
+            MemoryBlockFlavor::Dedicated { required, .. } => !required,
+            _ => false,
+        }
+    }
+
+    /// Cuts range `offset..offset + size` of this block out.
+    /// This block keeps part of the range before cut range,
+    /// or part after cut range if nothing is before it.
+    ///
+    /// Returns cut range and block for part after cut range
+    /// if there are parts on both sides.
+    /// Returned block is not registered in allocator.
+    pub(crate) fn cut_free_list_range(
+        &mut self,
+        offset: u64,
+        size: u64,
+    ) -> Result<(FreeListBlock<M>, Option<MemoryBlock<M>>), DeallocRangeError> {
+        let (chunk, ptr, memory, best_fit) = match &mut self.flavor {
+            MemoryBlockFlavor::FreeList {
+                chunk,
+                ptr,
+                memory,
+                best_fit,
+            } => (*chunk, ptr, memory, *best_fit),
+            _ => return Err(DeallocRangeError::Unsupported),
+        };
+
+        if !self.mapped.is_empty() {
+            return Err(DeallocRangeError::Mapped);
+        }
+
+        if size == 0
+            || size >= self.size
+            || offset > self.size - size
+            || offset & self.atom_mask != 0
+            || size & self.atom_mask != 0
+        {
+            return Err(DeallocRangeError::InvalidRange);
+        }
+
+        let end = offset + size;
+
+        // Range is within this block, and so within its mapped memory object.
+        let ptr_add = |ptr: Option<NonNull<u8>>, offset: u64| unsafe {
+            ptr.map(|ptr| NonNull::new_unchecked(ptr.as_ptr().offset(offset as isize)))
+        };
+
+        let cut = FreeListBlock {
+            memory: memory.clone(),
+            ptr: ptr_add(*ptr, offset),
+            chunk,
+            offset: self.offset + offset,
+            size,
+        };
+
+        if offset == 0 {
+            *ptr = ptr_add(*ptr, size);
+            self.offset += size;
+            self.size -= size;
+            return Ok((cut, None));
+        }
+
+        if end == self.size {
+            self.size = offset;
+            return Ok((cut, None));
+        }
+
+        let tail = MemoryBlock {
+            memory_type: self.memory_type,
+            props: self.props,
+            offset: self.offset + end,
+            size: self.size - end,
+            atom_mask: self.atom_mask,
+            align_mask: self.align_mask,
+            usage: self.usage,
+            priority: self.priority,
+            access: self.access,
+            label: self.label,
+            id: 0,
+            pool: self.pool,
+            fallback_tier: self.fallback_tier,
+            export: AllocationFlags::empty(),
+            dedicated_resource: false,
+            memory_size: 0,
+            resource_kind: self.resource_kind,
+            mapped: Vec::new(),
+            shared: SharedMapping::default(),
+            flavor: MemoryBlockFlavor::FreeList {
+                chunk,
+                ptr: ptr_add(*ptr, end),
+                memory: memory.clone(),
+                best_fit,
+            },
+            relevant: Some(Relevant),
+        };
+
+        self.size = offset;
+        Ok((cut, Some(tail)))
+    }
+
+    /// Returns `true` if this block is sub-allocated from memory object
+    /// shared with other blocks and deallocated individually.
+    pub(crate) fn is_suballocated(&self) -> bool {
+        matches!(
+            self.flavor,
+            MemoryBlockFlavor::Buddy { .. }
+                | MemoryBlockFlavor::FreeList { .. }
+                | MemoryBlockFlavor::Tlsf { .. }
+        )
+    }
+
+    /// Returns strategy that served this block.
+    /// Blocks that own whole memory object, including imported ones,
+    /// are reported as dedicated.
+    /// Frame blocks are not served by any strategy and must not be queried.
+    pub(crate) fn strategy(&self) -> Strategy {
+        match &self.flavor {
+            MemoryBlockFlavor::Buddy { large: false, .. } => Strategy::Buddy,
+            MemoryBlockFlavor::Buddy { large: true, .. } => Strategy::LargeBuddy,
+            MemoryBlockFlavor::FreeList {
+                best_fit: false, ..
+            } => Strategy::FreeList,
+            MemoryBlockFlavor::FreeList { best_fit: true, .. } => Strategy::BestFit,
+            MemoryBlockFlavor::Tlsf { .. } => Strategy::Tlsf,
+            MemoryBlockFlavor::Dedicated { .. } | MemoryBlockFlavor::Imported { .. } => {
+                Strategy::Dedicated
+            }
+            MemoryBlockFlavor::Aliased { backing, .. } => backing.strategy(),
+            MemoryBlockFlavor::Ring { .. } => unreachable!("Frame blocks are not tracked"),
+        }
+    }
+
+    /// Returns identifier of memory object this block is suballocated from
+    /// among memory objects of the same strategy.
+    pub(crate) fn chunk(&self) -> Option<u64> {
+        match &self.flavor {
+            MemoryBlockFlavor::Buddy { chunk, .. } | MemoryBlockFlavor::Tlsf { chunk, .. } => {
+                Some(*chunk as u64)
+            }
+            MemoryBlockFlavor::FreeList { chunk, .. } => Some(*chunk),
+            MemoryBlockFlavor::Aliased { backing, .. } => backing.chunk(),
+            MemoryBlockFlavor::Dedicated { .. }
+            | MemoryBlockFlavor::Imported { .. }
+            | MemoryBlockFlavor::Ring { .. } => None,
+        }
+    }
+
+    /// Moves start of this block `guard` bytes forward
+    /// and end `guard` bytes backward, leaving guard regions outside the block.
+    /// Guard regions are filled with `GUARD_PATTERN` when block is mapped.
+    ///
+    /// # Safety
+    ///
+    /// Block must be larger than both guard regions.
+    pub(crate) unsafe fn apply_guard(&mut self, guard: u64) {
+        debug_assert!(self.size > guard * 2);
+        self.offset += guard;
+        self.size -= guard * 2;
+
+        if let Some(ptr) = self.suballocated_ptr() {
+            // Guards are within block that fits host address space.
+            let start = ptr.as_ptr();
+            let ptr = start.offset(guard as isize);
+            fill_guard(start, guard);
+            fill_guard(ptr.offset(self.size as isize), guard);
+            self.set_suballocated_ptr(NonNull::new_unchecked(ptr));
+        }
+    }
+
+    /// Restores range of this block including guard regions.
+    /// Returns `true` if guard regions are intact.
+    ///
+    /// # Safety
+    ///
+    /// `guard` must be the value passed to `apply_guard`.
+    pub(crate) unsafe fn remove_guard(&mut self, guard: u64) -> bool {
+        self.offset -= guard;
+        self.size += guard * 2;
+
+        match self.suballocated_ptr() {
+            Some(ptr) => {
+                let start = ptr.as_ptr().offset(-(guard as isize));
+                self.set_suballocated_ptr(NonNull::new_unchecked(start));
+                check_guard(start, guard)
+                    && check_guard(start.offset((self.size - guard) as isize), guard)
+            }
+            None => true,
+        }
+    }
+
+    fn suballocated_ptr(&self) -> Option<NonNull<u8>> {
+        match self.flavor {
+            MemoryBlockFlavor::Buddy { ptr, .. }
+            | MemoryBlockFlavor::FreeList { ptr, .. }
+            | MemoryBlockFlavor::Tlsf { ptr, .. } => ptr,
+            _ => None,
+        }
+    }
+
+    fn set_suballocated_ptr(&mut self, value: NonNull<u8>) {
+        match &mut self.flavor {
+            MemoryBlockFlavor::Buddy { ptr, .. }
+            | MemoryBlockFlavor::FreeList { ptr, .. }
+            | MemoryBlockFlavor::Tlsf { ptr, .. } => *ptr = Some(value),
+            _ => unreachable!("Guard regions are used only for suballocated blocks"),
         }
     }
 
+    /// Returns `true` if this block is allocated from frame ring
+    /// and so is reclaimed when its frame is retired.
+    pub(crate) fn is_frame_block(&self) -> bool {
+        matches!(self.flavor, MemoryBlockFlavor::Ring { .. })
+    }
+
     pub(crate) fn deallocate(self) -> MemoryBlockFlavor<M> {
         core::mem::forget(self.relevant);
         self.flavor
@@ -65,18 +552,47 @@ unsafe impl<M> Send for MemoryBlock<M> where M: Send {}
 #[derive(Debug)]
 pub(crate) enum MemoryBlockFlavor<M> {
     Dedicated {
-        memory: M,
+        /// Mutated only through `&mut` block,
+        /// or by `map_shared` while it holds `SharedMapping` busy.
+        memory: UnsafeCell<M>,
+        /// Pointer to persistently mapped memory object.
+        ptr: Option<NonNull<u8>>,
+        /// Dedicated memory object was required by the request
+        /// or is dedicated to resource.
+        required: bool,
     },
     Buddy {
         chunk: usize,
         index: usize,
         ptr: Option<NonNull<u8>>,
         memory: Arc<M>,
+        large: bool,
     },
     FreeList {
         chunk: u64,
         ptr: Option<NonNull<u8>>,
         memory: Arc<M>,
+        best_fit: bool,
+    },
+    Imported {
+        memory: M,
+        ptr: NonNull<u8>,
+    },
+    Tlsf {
+        chunk: usize,
+        region: usize,
+        ptr: Option<NonNull<u8>>,
+        memory: Arc<M>,
+    },
+    Ring {
+        ptr: Option<NonNull<u8>>,
+        memory: Arc<M>,
+    },
+    /// Block sharing memory range with other blocks of aliasing group.
+    /// `backing` is deallocated with the last of them.
+    Aliased {
+        ptr: Option<NonNull<u8>>,
+        backing: Arc<MemoryBlock<M>>,
     },
 }
 
@@ -85,9 +601,13 @@ impl<M> MemoryBlock<M> {
     #[inline(always)]
     pub fn memory(&self) -> &M {
         match &self.flavor {
-            MemoryBlockFlavor::Dedicated { memory } => memory,
-            MemoryBlockFlavor::Buddy { memory, .. } => &**memory,
-            MemoryBlockFlavor::FreeList { memory, .. } => &**memory,
+            MemoryBlockFlavor::Dedicated { memory, .. } => unsafe { &*memory.get() },
+            MemoryBlockFlavor::Buddy { memory, .. } => memory,
+            MemoryBlockFlavor::FreeList { memory, .. } => memory,
+            MemoryBlockFlavor::Imported { memory, .. } => memory,
+            MemoryBlockFlavor::Tlsf { memory, .. } => memory,
+            MemoryBlockFlavor::Ring { memory, .. } => memory,
+            MemoryBlockFlavor::Aliased { backing, .. } => backing.memory(),
         }
     }
 
@@ -115,8 +635,220 @@ impl<M> MemoryBlock<M> {
         self.memory_type
     }
 
+    /// Returns access pattern hint this block was requested with.
+    #[inline(always)]
+    pub fn access(&self) -> Option<AccessPattern> {
+        self.access
+    }
+
+    /// Returns usage this block was requested with.
+    #[inline(always)]
+    pub fn usage(&self) -> UsageFlags {
+        self.usage
+    }
+
+    /// Returns generation of this block.
+    ///
+    /// Each block allocated by `GpuAllocator` gets generation greater
+    /// than all blocks allocated before it by the same allocator.
+    /// Can be used to detect stale handles with `GpuAllocator::is_current`.
+    #[inline(always)]
+    pub fn generation(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns index of tier of `Config::usage_fallbacks` chain this block was allocated from,
+    /// or `None` if no chain applies to the request.
+    #[inline(always)]
+    pub fn fallback_tier(&self) -> Option<u32> {
+        self.fallback_tier
+    }
+
+    /// Returns pool this block was allocated from with `GpuAllocator::alloc_in_pool`.
+    #[inline(always)]
+    pub fn pool(&self) -> Option<PoolId> {
+        self.pool.map(PoolId)
+    }
+
+    /// Returns label this block was requested with.
+    #[inline(always)]
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// Returns description required to import memory object of this block
+    /// into another API as `handle_type`.
+    ///
+    /// Returns `None` if memory object is not exportable as `handle_type`,
+    /// see `Request::export` and `PoolConfig::export`.
+    pub fn external_memory(&self, handle_type: ExternalHandleType) -> Option<ExternalMemory> {
+        if !self.export.contains(handle_type.allocation_flag()) {
+            return None;
+        }
+
+        Some(ExternalMemory {
+            handle_type,
+            memory_size: self.memory_size,
+            offset: self.offset,
+            size: self.size,
+            dedicated: self.dedicated_resource,
+            resource_kind: self.resource_kind,
+        })
+    }
+
+    /// Returns description of this block reported to `AllocationObserver`.
+    pub fn info(&self) -> MemoryBlockInfo<'static> {
+        MemoryBlockInfo {
+            generation: self.id,
+            memory_type: self.memory_type,
+            offset: self.offset,
+            size: self.size,
+            label: self.label,
+            dedicated: match &self.flavor {
+                MemoryBlockFlavor::Dedicated { .. } | MemoryBlockFlavor::Imported { .. } => true,
+                MemoryBlockFlavor::Aliased { backing, .. } => backing.info().dedicated,
+                _ => false,
+            },
+        }
+    }
+
+    /// Returns range of parent memory object to flush or invalidate
+    /// after host access to range `offset..offset + size` of this block.
+    ///
+    /// Returned range is extended to `non_coherent_atom_size` boundaries
+    /// and clamped to this block,
+    /// so it can be passed to `MemoryDevice::flush_memory_ranges`
+    /// together with ranges of other blocks.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    #[inline(always)]
+    pub fn mapped_range(&self, offset: u64, size: u64) -> MappedMemoryRange<'_, M> {
+        assert!(
+            offset <= self.size,
+            "`offset` is out of memory block bounds"
+        );
+        assert!(
+            size <= self.size - offset,
+            "`offset + size` is out of memory block bounds"
+        );
+
+        let (aligned_offset, aligned_size) =
+            atom_aligned_range(offset, size, self.size, self.atom_mask);
+
+        MappedMemoryRange {
+            memory: self.memory(),
+            offset: self.offset + aligned_offset,
+            size: aligned_size,
+        }
+    }
+
+    /// Returns pointer to start of this block if it is persistently mapped.
+    ///
+    /// Blocks requested with `RequestFlags::PERSISTENTLY_MAPPED` are always persistently mapped.
+    /// Other blocks are if they are sub-allocated from host-visible memory objects,
+    /// which allocator keeps mapped anyway.
+    ///
+    /// Unlike `map` this doesn't change mapping state of the block.
+    /// Same guarantees are required from the user of returned pointer,
+    /// including flushing and invalidating non-coherent memory, see `mapped_range`.
+    #[inline(always)]
+    pub fn mapped_ptr(&self) -> Option<NonNull<u8>> {
+        match &self.flavor {
+            MemoryBlockFlavor::Dedicated { ptr, .. }
+            | MemoryBlockFlavor::Buddy { ptr, .. }
+            | MemoryBlockFlavor::FreeList { ptr, .. }
+            | MemoryBlockFlavor::Tlsf { ptr, .. }
+            | MemoryBlockFlavor::Ring { ptr, .. }
+            | MemoryBlockFlavor::Aliased { ptr, .. } => *ptr,
+            MemoryBlockFlavor::Imported { ptr, .. } => Some(*ptr),
+        }
+    }
+
+    /// Returns whole memory of this block as slice if it is persistently mapped.
+    /// See `mapped_ptr`.
+    ///
+    /// # Safety
+    ///
+    /// * The user of returned slice must guarantee that no previously submitted command accesses this block
+    ///   while the slice is used, as with pointer returned by `map`.
+    /// * Memory of this block must not be accessed by other means while the slice is used.
+    #[inline(always)]
+    pub unsafe fn mapped_slice(&mut self) -> Option<&mut [u8]> {
+        let ptr = self.mapped_ptr()?;
+        // Mapped block fits host address space.
+        Some(from_raw_parts_mut(ptr.as_ptr(), self.size as usize))
+    }
+
+    /// Flushes host writes to range `offset..offset + size` of this block,
+    /// making them available to the device.
+    ///
+    /// Range is extended to `non_coherent_atom_size` boundaries, see `mapped_range`.
+    /// Does nothing if memory is `HOST_COHERENT`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// * `block` must have been allocated from specified `device`.
+    /// * Range must be mapped, with `map`, `map_shared` or persistently.
+    #[inline(always)]
+    pub unsafe fn flush_range(
+        &self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), MapError> {
+        if self.coherent() {
+            return Ok(());
+        }
+        device
+            .flush_memory_ranges(&[self.mapped_range(offset, size)])
+            .map_err(Into::into)
+    }
+
+    /// Invalidates host copy of range `offset..offset + size` of this block,
+    /// making device writes to it visible to the host.
+    ///
+    /// Range is extended to `non_coherent_atom_size` boundaries, see `mapped_range`.
+    /// Does nothing if memory is `HOST_COHERENT`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// * `block` must have been allocated from specified `device`.
+    /// * Range must be mapped, with `map`, `map_shared` or persistently.
+    /// * Unflushed host writes to extended range are discarded.
+    #[inline(always)]
+    pub unsafe fn invalidate_range(
+        &self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), MapError> {
+        if self.coherent() {
+            return Ok(());
+        }
+        device
+            .invalidate_memory_ranges(&[self.mapped_range(offset, size)])
+            .map_err(Into::into)
+    }
+
     /// Returns pointer to mapped memory range of this block.
-    /// This blocks becomes mapped.
+    /// This range becomes mapped until `unmap_range` or `unmap` is called.
+    ///
+    /// Any number of non-overlapping ranges of a block can be mapped at once,
+    /// for example to fill them from different threads.
+    /// Ranges of non-coherent memory must not overlap after extension to `non_coherent_atom_size` boundaries,
+    /// because flushing or invalidating one of them would affect the other.
+    /// Only one range of dedicated memory object can be mapped at a time,
+    /// unless it is persistently mapped, see `RequestFlags::PERSISTENTLY_MAPPED`.
     ///
     /// The user of returned pointer must guarantee that any previously submitted command that writes to this range has completed
     /// before the host reads from or writes to that range,
@@ -127,9 +859,12 @@ impl<M> MemoryBlock<M> {
     /// the user must round down the start of the range to the nearest multiple of `non_coherent_atom_size`,
     /// and round the end of the range up to the nearest multiple of `non_coherent_atom_size`.
     ///
+    /// Fails with `MapError::AlreadyMapped` if range overlaps range already mapped with `map`,
+    /// or shares an atom with it if memory is not `HOST_COHERENT`.
+    ///
     /// # Panics
     ///
-    /// This function panics if block is currently mapped.
+    /// This function panics if range is out of memory block bounds.
     ///
     /// # Safety
     ///
@@ -141,24 +876,31 @@ impl<M> MemoryBlock<M> {
         offset: u64,
         size: usize,
     ) -> Result<NonNull<u8>, MapError> {
-        let size_u64 = u64::try_from(size).expect("`size` doesn't fit device address space");
+        let size_u64 = u64::try_from(size).map_err(|_| MapError::AddressSpaceOverflow)?;
         assert!(offset < self.size, "`offset` is out of memory block bounds");
         assert!(
             size_u64 <= self.size - offset,
             "`offset + size` is out of memory block bounds"
         );
 
+        let range = self.mapped_bounds(offset, size_u64);
         let ptr = match &mut self.flavor {
-            MemoryBlockFlavor::Dedicated { memory } => {
-                let end = align_up(offset + size_u64, self.atom_mask)
-                    .expect("mapping end doesn't fit device address space");
-                let aligned_offset = align_down(offset, self.atom_mask);
+            MemoryBlockFlavor::Dedicated {
+                memory, ptr: None, ..
+            } => {
+                let (aligned_offset, aligned_size) =
+                    atom_aligned_range(offset, size_u64, self.size, self.atom_mask);
+
+                // Range extended to atom boundaries must fit host address space too.
+                isize::try_from(aligned_size).map_err(|_| MapError::AddressSpaceOverflow)?;
 
-                if !acquire_mapping(&mut self.mapped) {
+                // Memory object can be mapped only once.
+                if !self.mapped.is_empty() {
                     return Err(MapError::AlreadyMapped);
                 }
+                acquire_mapping(&mut self.mapped, range);
                 let result =
-                    device.map_memory(memory, self.offset + aligned_offset, end - aligned_offset);
+                    device.map_memory(memory.get_mut(), self.offset + aligned_offset, aligned_size);
 
                 match result {
                     // the overflow is checked in `Self::new()`
@@ -167,18 +909,24 @@ impl<M> MemoryBlock<M> {
                         ptr.as_ptr().offset(ptr_offset)
                     }
                     Err(err) => {
-                        release_mapping(&mut self.mapped);
+                        release_mapping(&mut self.mapped, offset);
                         return Err(err.into());
                     }
                 }
             }
-            MemoryBlockFlavor::FreeList { ptr: Some(ptr), .. }
-            | MemoryBlockFlavor::Buddy { ptr: Some(ptr), .. } => {
-                if !acquire_mapping(&mut self.mapped) {
+            MemoryBlockFlavor::Dedicated { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::FreeList { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Buddy { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Tlsf { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Ring { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Aliased { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Imported { ptr, .. } => {
+                let offset_isize =
+                    isize::try_from(offset).map_err(|_| MapError::AddressSpaceOverflow)?;
+
+                if !acquire_mapping(&mut self.mapped, range) {
                     return Err(MapError::AlreadyMapped);
                 }
-                let offset_isize = isize::try_from(offset)
-                    .expect("Buddy and linear block should fit host address space");
                 ptr.as_ptr().offset(offset_isize)
             }
             _ => return Err(MapError::NonHostVisible),
@@ -187,29 +935,256 @@ impl<M> MemoryBlock<M> {
         Ok(NonNull::new_unchecked(ptr))
     }
 
-    /// Unmaps memory range of this block that was previously mapped with `Block::map`.
-    /// This block becomes unmapped.
+    /// Returns pointer to memory range of this block,
+    /// mapping it if this block isn't mapped yet.
+    ///
+    /// Unlike `map` this function can be called any number of times,
+    /// including from different threads,
+    /// each call returns a guard which releases its share of mapping on drop.
+    /// Dedicated memory object is mapped on first call
+    /// and unmapped when last guard is dropped.
+    ///
+    /// Fails with `MapError::AlreadyMapped` only if block is mapped with `map`.
     ///
     /// # Panics
     ///
-    /// This function panics if this block is not currently mapped.
+    /// This function panics if range is out of memory block bounds.
     ///
     /// # Safety
     ///
-    /// `block` must have been allocated from specified `device`.
+    /// * `block` must have been allocated from specified `device`.
+    /// * Reference returned by `memory` of dedicated block that is not persistently mapped
+    ///   must not be used while this function maps it or last guard unmaps it.
+    #[inline(always)]
+    pub unsafe fn map_shared<'a, D>(
+        &'a self,
+        device: &'a D,
+        offset: u64,
+        size: usize,
+    ) -> Result<(NonNull<u8>, MapGuard<'a, M, D>), MapError>
+    where
+        D: MemoryDevice<M>,
+    {
+        let size_u64 = u64::try_from(size).map_err(|_| MapError::AddressSpaceOverflow)?;
+        assert!(offset < self.size, "`offset` is out of memory block bounds");
+        assert!(
+            size_u64 <= self.size - offset,
+            "`offset + size` is out of memory block bounds"
+        );
+
+        if !self.mapped.is_empty() {
+            return Err(MapError::AlreadyMapped);
+        }
+
+        let offset_isize = isize::try_from(offset).map_err(|_| MapError::AddressSpaceOverflow)?;
+        let ptr = self.acquire_shared(device)?;
+
+        Ok((
+            NonNull::new_unchecked(ptr.as_ptr().offset(offset_isize)),
+            MapGuard {
+                block: self,
+                device,
+            },
+        ))
+    }
+
+    /// Acquires share of mapping, mapping dedicated memory object with first share.
+    unsafe fn acquire_shared(
+        &self,
+        device: &impl MemoryDevice<M>,
+    ) -> Result<NonNull<u8>, MapError> {
+        loop {
+            match self.shared.count.load(Acquire) {
+                SHARED_BUSY => {}
+                0 => {
+                    if self
+                        .shared
+                        .count
+                        .compare_exchange_weak(0, SHARED_BUSY, Acquire, Relaxed)
+                        .is_err()
+                    {
+                        continue;
+                    }
+
+                    match self.map_whole(device) {
+                        Ok(ptr) => {
+                            self.shared.ptr.store(ptr.as_ptr(), Relaxed);
+                            self.shared.count.store(1, Release);
+                            return Ok(ptr);
+                        }
+                        Err(err) => {
+                            self.shared.count.store(0, Release);
+                            return Err(err);
+                        }
+                    }
+                }
+                count => {
+                    if self
+                        .shared
+                        .count
+                        .compare_exchange_weak(count, count + 1, Acquire, Relaxed)
+                        .is_ok()
+                    {
+                        return Ok(NonNull::new_unchecked(self.shared.ptr.load(Relaxed)));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Releases share of mapping, unmapping dedicated memory object with last share.
+    unsafe fn release_shared(&self, device: &impl MemoryDevice<M>) {
+        loop {
+            match self.shared.count.load(Acquire) {
+                0 => unreachable!("Block has no shared mapping"),
+                SHARED_BUSY => {}
+                1 => {
+                    if self
+                        .shared
+                        .count
+                        .compare_exchange_weak(1, SHARED_BUSY, Acquire, Relaxed)
+                        .is_err()
+                    {
+                        continue;
+                    }
+
+                    self.shared.ptr.store(null_mut(), Relaxed);
+                    if let MemoryBlockFlavor::Dedicated {
+                        memory, ptr: None, ..
+                    } = &self.flavor
+                    {
+                        device.unmap_memory(&mut *memory.get());
+                    }
+                    self.shared.count.store(0, Release);
+                    return;
+                }
+                count => {
+                    if self
+                        .shared
+                        .count
+                        .compare_exchange_weak(count, count - 1, Release, Relaxed)
+                        .is_ok()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Maps whole block for shared mapping.
+    /// Must be called only while `SharedMapping` is busy.
+    unsafe fn map_whole(&self, device: &impl MemoryDevice<M>) -> Result<NonNull<u8>, MapError> {
+        match &self.flavor {
+            MemoryBlockFlavor::Dedicated {
+                memory, ptr: None, ..
+            } => {
+                // Whole block is mapped and must fit host address space.
+                isize::try_from(self.size).map_err(|_| MapError::AddressSpaceOverflow)?;
+                Ok(device.map_memory(&mut *memory.get(), self.offset, self.size)?)
+            }
+            _ => self.mapped_ptr().ok_or(MapError::NonHostVisible),
+        }
+    }
+
+    /// Unmaps memory range of this block at `offset` that was previously mapped with `map`.
+    /// Other ranges mapped with `map` stay mapped.
+    ///
+    /// Returns `false` if no range at `offset` is mapped.
+    ///
+    /// # Safety
+    ///
+    /// * `block` must have been allocated from specified `device`.
+    /// * Pointer returned by `map` for this range must not be used anymore.
+    #[inline(always)]
+    pub unsafe fn unmap_range(&mut self, device: &impl MemoryDevice<M>, offset: u64) -> bool {
+        if !release_mapping(&mut self.mapped, offset) {
+            return false;
+        }
+        self.unmap_memory(device);
+        true
+    }
+
+    /// Unmaps all memory ranges of this block that were previously mapped with `map`.
+    /// This block becomes unmapped.
+    ///
+    /// Returns `false` if block is not mapped with `map`.
+    ///
+    /// # Safety
+    ///
+    /// * `block` must have been allocated from specified `device`.
+    /// * Pointers returned by `map` must not be used anymore.
     #[inline(always)]
     pub unsafe fn unmap(&mut self, device: &impl MemoryDevice<M>) -> bool {
-        if !release_mapping(&mut self.mapped) {
+        if self.mapped.is_empty() {
             return false;
         }
+        self.mapped.clear();
+        self.unmap_memory(device);
+        true
+    }
+
+    /// Unmaps dedicated memory object if no range of it is mapped anymore.
+    unsafe fn unmap_memory(&mut self, device: &impl MemoryDevice<M>) {
+        if !self.mapped.is_empty() {
+            return;
+        }
         match &mut self.flavor {
-            MemoryBlockFlavor::Dedicated { memory } => {
-                device.unmap_memory(memory);
+            MemoryBlockFlavor::Dedicated {
+                memory, ptr: None, ..
+            } => {
+                device.unmap_memory(memory.get_mut());
             }
+            MemoryBlockFlavor::Dedicated { .. } => {}
             MemoryBlockFlavor::Buddy { .. } => {}
             MemoryBlockFlavor::FreeList { .. } => {}
+            MemoryBlockFlavor::Imported { .. } => {}
+            MemoryBlockFlavor::Tlsf { .. } => {}
+            MemoryBlockFlavor::Ring { .. } => {}
+            MemoryBlockFlavor::Aliased { .. } => {}
         }
-        true
+    }
+
+    /// Maps memory range of this block and returns guard that unmaps it on drop.
+    /// See `MappedBlock`.
+    ///
+    /// Range of non-coherent memory is invalidated after mapping,
+    /// so guard can be used both to read and to write.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    /// The caller must guarantee that any previously submitted command that reads or writes to this range
+    /// has completed before guard is created and that no command accesses it until guard is dropped.
+    #[inline(always)]
+    pub unsafe fn map_guard<'a, D>(
+        &'a mut self,
+        device: &'a D,
+        offset: u64,
+        size: usize,
+    ) -> Result<MappedBlock<'a, M, D>, MapError>
+    where
+        D: MemoryDevice<M>,
+    {
+        let ptr = self.map(device, offset, size)?;
+
+        if let Err(err) = self.invalidate_range(device, offset, size as u64) {
+            self.unmap_range(device, offset);
+            return Err(err);
+        }
+
+        Ok(MappedBlock {
+            block: self,
+            device,
+            offset,
+            ptr,
+            size,
+            written: false,
+        })
     }
 
     /// Transiently maps block memory range and copies specified data
@@ -217,37 +1192,152 @@ impl<M> MemoryBlock<M> {
     ///
     /// # Panics
     ///
-    /// This function panics if block is currently mapped.
+    /// This function panics if range is out of memory block bounds.
     ///
     /// # Safety
     ///
     /// `block` must have been allocated from specified `device`.
     /// The caller must guarantee that any previously submitted command that reads or writes to this range has completed.
+    ///
+    /// # Non-coherent memory
+    ///
+    /// Flushed range is extended to `non_coherent_atom_size` boundaries,
+    /// so bytes of this block around written range that share atoms with it are flushed as well.
+    /// Host copy of those bytes is written back to the device,
+    /// overwriting bytes written there by the device since last invalidation.
+    /// Use `write_bytes_preserving` if device may have written to those atoms.
     #[inline(always)]
     pub unsafe fn write_bytes(
         &mut self,
         device: &impl MemoryDevice<M>,
         offset: u64,
         data: &[u8],
+    ) -> Result<(), MapError> {
+        self.write_bytes_inner(device, offset, data, false)
+    }
+
+    /// Same as `write_bytes`, but atoms of non-coherent memory at start and end of written range
+    /// that are only partially overwritten are invalidated before `data` is copied,
+    /// so flush doesn't write back stale host copy of bytes around written range.
+    ///
+    /// Fails with `MapError::AlreadyMapped` if range mapped with `map` shares those atoms.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `write_bytes`, and:
+    /// * Any previously submitted command that writes to partially overwritten atoms must have completed.
+    /// * Host writes to partially overwritten atoms that were not flushed yet are discarded,
+    ///   including writes through pointers returned by `map_shared` and `mapped_ptr`.
+    #[inline(always)]
+    pub unsafe fn write_bytes_preserving(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), MapError> {
+        self.write_bytes_inner(device, offset, data, true)
+    }
+
+    unsafe fn write_bytes_inner(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        data: &[u8],
+        preserve: bool,
     ) -> Result<(), MapError> {
         let size = data.len();
         let ptr = self.map(device, offset, size)?;
 
+        if preserve && !self.coherent() {
+            // Ranges mapped with `map` can't share partially overwritten atoms,
+            // see `map`.
+            if let Err(err) = self.invalidate_partial_atoms(device, offset, size as u64) {
+                self.unmap_range(device, offset);
+                return Err(err.into());
+            }
+        }
+
         copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), size);
+        let result = self.flush_range(device, offset, size as u64);
+
+        self.unmap_range(device, offset);
+        result
+    }
+
+    /// Transiently maps block memory range covering all `regions`
+    /// and copies data of each region to specified offset.
+    ///
+    /// Unlike calling `write_bytes` for each region,
+    /// block is mapped once and ranges of all regions of non-coherent memory
+    /// are flushed with single `flush_memory_ranges` call.
+    /// Partially overwritten atoms are flushed whole, see `write_bytes`.
+    ///
+    /// Regions may come in any order, but must not overlap.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any region is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `write_bytes`, for range of each region.
+    pub unsafe fn write_regions(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        regions: &[(u64, &[u8])],
+    ) -> Result<(), MapError> {
+        let mut start = self.size;
+        let mut end = 0;
+        for &(offset, data) in regions {
+            if data.is_empty() {
+                continue;
+            }
+            let size = u64::try_from(data.len()).map_err(|_| MapError::AddressSpaceOverflow)?;
+            assert!(
+                offset <= self.size,
+                "`offset` is out of memory block bounds"
+            );
+            assert!(
+                size <= self.size - offset,
+                "`offset + size` is out of memory block bounds"
+            );
+            start = start.min(offset);
+            end = end.max(offset + size);
+        }
+
+        if start >= end {
+            return Ok(());
+        }
+
+        let ptr = self.map(device, start, (end - start) as usize)?;
+
+        for &(offset, data) in regions {
+            if !data.is_empty() {
+                // Regions are within mapped range, which fits host address space.
+                copy_nonoverlapping(
+                    data.as_ptr(),
+                    ptr.as_ptr().add((offset - start) as usize),
+                    data.len(),
+                );
+            }
+        }
+
         let result = if !self.coherent() {
-            let aligned_offset = align_down(offset, self.atom_mask);
-            let end = align_up(offset + data.len() as u64, self.atom_mask).unwrap();
-
-            device.flush_memory_ranges(&[MappedMemoryRange {
-                memory: self.memory(),
-                offset: self.offset + aligned_offset,
-                size: end - aligned_offset,
-            }])
+            let ranges: Vec<_> = regions
+                .iter()
+                .filter(|(_, data)| !data.is_empty())
+                .map(|&(offset, data)| self.mapped_range(offset, data.len() as u64))
+                .collect();
+            device.flush_memory_ranges(&ranges)
         } else {
             Ok(())
         };
 
-        self.unmap(device);
+        self.unmap_range(device, start);
         result.map_err(Into::into)
     }
 
@@ -256,7 +1346,7 @@ impl<M> MemoryBlock<M> {
     ///
     /// # Panics
     ///
-    /// This function panics if block is currently mapped.
+    /// This function panics if range is out of memory block bounds.
     ///
     /// # Safety
     ///
@@ -268,34 +1358,240 @@ impl<M> MemoryBlock<M> {
         device: &impl MemoryDevice<M>,
         offset: u64,
         data: &mut [u8],
+    ) -> Result<(), MapError> {
+        self.read_bytes_raw(device, offset, data.as_mut_ptr(), data.len())
+    }
+
+    /// Transiently maps block memory range and copies specified data
+    /// from the mapped memory range into uninitialized buffer.
+    /// Returns `data` as initialized slice.
+    ///
+    /// Unlike `read_bytes` this does not require initializing `data` beforehand.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    /// The caller must guarantee that any previously submitted command that reads to this range has completed.
+    #[inline(always)]
+    pub unsafe fn read_bytes_uninit<'a>(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        data: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a mut [u8], MapError> {
+        let size = data.len();
+        let ptr = data.as_mut_ptr() as *mut u8;
+        self.read_bytes_raw(device, offset, ptr, size)?;
+        Ok(from_raw_parts_mut(ptr, size))
+    }
+
+    #[inline(always)]
+    unsafe fn read_bytes_raw(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        data: *mut u8,
+        size: usize,
     ) -> Result<(), MapError> {
         #[cfg(feature = "tracing")]
         {
-            if !self.cached() {
-                tracing::warn!("Reading from non-cached memory may be slow. Consider allocating HOST_CACHED memory block for host reads.")
+            match self.access {
+                Some(access) if !access.reads() => {
+                    tracing::warn!("Reading from memory block requested for {:?} access. Consider requesting it with reading access pattern.", access)
+                }
+                Some(access) if !self.cached() => {
+                    tracing::warn!("Reading from non-cached memory may be slow. No HOST_CACHED memory was found for {:?} access.", access)
+                }
+                None if !self.cached() => {
+                    tracing::warn!("Reading from non-cached memory may be slow. Consider allocating HOST_CACHED memory block for host reads.")
+                }
+                _ => {}
             }
         }
 
-        let size = data.len();
         let ptr = self.map(device, offset, size)?;
-        let result = if !self.coherent() {
-            let aligned_offset = align_down(offset, self.atom_mask);
-            let end = align_up(offset + data.len() as u64, self.atom_mask).unwrap();
-
-            device.invalidate_memory_ranges(&[MappedMemoryRange {
-                memory: self.memory(),
-                offset: self.offset + aligned_offset,
-                size: end - aligned_offset,
-            }])
+        let result = self.invalidate_range(device, offset, size as u64);
+        if result.is_ok() {
+            copy_nonoverlapping(ptr.as_ptr(), data, size);
+        }
+
+        self.unmap_range(device, offset);
+        result
+    }
+
+    /// Copies bytes of `value` to this block at `offset`, see `write_bytes`.
+    ///
+    /// Value is copied byte-wise, so `offset` doesn't need to be aligned for `T`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `write_bytes`.
+    #[cfg(feature = "bytemuck")]
+    #[inline(always)]
+    pub unsafe fn write_pod<T: bytemuck::Pod>(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        value: &T,
+    ) -> Result<(), MapError> {
+        self.write_bytes(device, offset, bytemuck::bytes_of(value))
+    }
+
+    /// Copies bytes of `values` to this block at `offset`, see `write_bytes`.
+    ///
+    /// Values are copied byte-wise, so `offset` doesn't need to be aligned for `T`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `write_bytes`.
+    #[cfg(feature = "bytemuck")]
+    #[inline(always)]
+    pub unsafe fn write_slice<T: bytemuck::Pod>(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        values: &[T],
+    ) -> Result<(), MapError> {
+        self.write_bytes(device, offset, bytemuck::cast_slice(values))
+    }
+
+    /// Reads value of type `T` from this block at `offset`, see `read_bytes`.
+    ///
+    /// Value is copied byte-wise, so `offset` doesn't need to be aligned for `T`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory block bounds.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `read_bytes`.
+    #[cfg(feature = "bytemuck")]
+    #[inline(always)]
+    pub unsafe fn read_pod<T: bytemuck::Pod>(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+    ) -> Result<T, MapError> {
+        let mut value = T::zeroed();
+        self.read_bytes(device, offset, bytemuck::bytes_of_mut(&mut value))?;
+        Ok(value)
+    }
+
+    /// Deallocates memory object of dedicated or imported block directly with `device`,
+    /// unmapping it first if block is mapped.
+    ///
+    /// Blocks that share memory object with other blocks
+    /// can be deallocated only with `GpuAllocator::dealloc`,
+    /// such blocks are returned back in `Err` variant.
+    ///
+    /// # Safety
+    ///
+    /// * `block` must have been allocated from specified `device`.
+    /// * `GpuAllocator` that allocated this block does not learn about deallocation,
+    ///   so its memory budgets are not restored.
+    ///   Prefer `GpuAllocator::dealloc` if allocator is still in use.
+    #[allow(clippy::result_large_err)]
+    pub unsafe fn into_memory_dedicated(self, device: &impl MemoryDevice<M>) -> Result<(), Self> {
+        match self.flavor {
+            MemoryBlockFlavor::Dedicated { .. } | MemoryBlockFlavor::Imported { .. } => {}
+            _ => return Err(self),
+        }
+
+        let mapped = !self.mapped.is_empty();
+        match self.deallocate() {
+            MemoryBlockFlavor::Dedicated { memory, ptr, .. } => {
+                let mut memory = memory.into_inner();
+                if mapped || ptr.is_some() {
+                    device.unmap_memory(&mut memory);
+                }
+                device.deallocate_memory(memory);
+                Ok(())
+            }
+            MemoryBlockFlavor::Imported { memory, .. } => {
+                device.deallocate_memory(memory);
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Invalidates atoms at both ends of range `offset..offset + size`
+    /// that are not fully covered by the range.
+    unsafe fn invalidate_partial_atoms(
+        &self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), OutOfMemory> {
+        match self.partial_atoms(offset, size) {
+            (None, None) => Ok(()),
+            (Some(at), None) | (None, Some(at)) => {
+                device.invalidate_memory_ranges(&[self.atom_range(at)])
+            }
+            (Some(head), Some(tail)) => {
+                device.invalidate_memory_ranges(&[self.atom_range(head), self.atom_range(tail)])
+            }
+        }
+    }
+
+    /// Returns starts of atoms at both ends of range `offset..offset + size`
+    /// that are not fully covered by the range.
+    /// Atom shared by both ends is returned once.
+    fn partial_atoms(&self, offset: u64, size: u64) -> (Option<u64>, Option<u64>) {
+        let end = offset + size;
+
+        let head = offset & self.atom_mask != 0;
+        let tail = end & self.atom_mask != 0 && end < self.size;
+
+        let head_atom = align_down(offset, self.atom_mask);
+        let tail_atom = align_down(end, self.atom_mask);
+
+        match (head, tail) {
+            (false, false) => (None, None),
+            (true, false) => (Some(head_atom), None),
+            (false, true) => (None, Some(tail_atom)),
+            (true, true) if head_atom == tail_atom => (Some(head_atom), None),
+            (true, true) => (Some(head_atom), Some(tail_atom)),
+        }
+    }
+
+    /// Returns record of range `offset..offset + size` to be mapped with `map`.
+    fn mapped_bounds(&self, offset: u64, size: u64) -> MappedRange {
+        let (start, size) = if self.coherent() {
+            (offset, size)
         } else {
-            Ok(())
+            atom_aligned_range(offset, size, self.size, self.atom_mask)
         };
-        if result.is_ok() {
-            copy_nonoverlapping(ptr.as_ptr(), data.as_mut_ptr(), size);
+        MappedRange {
+            offset,
+            start,
+            end: start + size,
         }
+    }
 
-        self.unmap(device);
-        result.map_err(Into::into)
+    /// Returns range of atom starting at `start`, clamped to this block.
+    fn atom_range(&self, start: u64) -> MappedMemoryRange<'_, M> {
+        let end = start
+            .checked_add(self.atom_mask + 1)
+            .map_or(self.size, |end| end.min(self.size));
+        MappedMemoryRange {
+            memory: self.memory(),
+            offset: self.offset + start,
+            size: end - start,
+        }
     }
 
     fn coherent(&self) -> bool {
@@ -308,20 +1604,50 @@ impl<M> MemoryBlock<M> {
     }
 }
 
-fn acquire_mapping(mapped: &mut bool) -> bool {
-    if *mapped {
-        false
-    } else {
-        *mapped = true;
-        true
+/// Expands range `offset..offset + size` within block of `block_size` bytes
+/// to `atom_mask` boundaries and returns offset and size of expanded range.
+///
+/// End of the range is clamped to the end of the block.
+/// Blocks that share memory object are aligned to atom size at both ends
+/// by sub-allocators, so clamped range never touches neighbor blocks.
+/// Dedicated blocks end where memory object ends, which makes clamped range
+/// valid for flushing and invalidation.
+fn atom_aligned_range(offset: u64, size: u64, block_size: u64, atom_mask: u64) -> (u64, u64) {
+    debug_assert!(offset <= block_size && size <= block_size - offset);
+
+    let aligned_offset = align_down(offset, atom_mask);
+    let end = align_up(offset + size, atom_mask).map_or(block_size, |end| end.min(block_size));
+
+    (aligned_offset, end - aligned_offset)
+}
+
+/// Adds `range` to sorted `mapped` ranges.
+/// Returns `false` if it overlaps any of them.
+fn acquire_mapping(mapped: &mut Vec<MappedRange>, range: MappedRange) -> bool {
+    let index = match mapped.binary_search_by_key(&range.start, |mapped| mapped.start) {
+        Ok(_) => return false,
+        Err(index) => index,
+    };
+    if index > 0 && mapped[index - 1].end > range.start {
+        return false;
     }
+    if index < mapped.len() && mapped[index].start < range.end {
+        return false;
+    }
+    mapped.insert(index, range);
+    true
 }
 
-fn release_mapping(mapped: &mut bool) -> bool {
-    if *mapped {
-        *mapped = false;
-        true
-    } else {
-        false
+/// Removes range mapped at `offset` from sorted `mapped` ranges.
+/// Returns `false` if there is no such range.
+///
+/// Ranges don't overlap, so they are sorted by mapped offset too.
+fn release_mapping(mapped: &mut Vec<MappedRange>, offset: u64) -> bool {
+    match mapped.binary_search_by_key(&offset, |mapped| mapped.offset) {
+        Ok(index) => {
+            mapped.remove(index);
+            true
+        }
+        Err(_) => false,
     }
 }
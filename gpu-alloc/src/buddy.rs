@@ -1,7 +1,16 @@
 use {
     crate::{
-        align_up, error::AllocationError, heap::Heap, slab::Slab, unreachable_unchecked,
-        util::try_arc_unwrap, MemoryBounds,
+        align_up,
+        allocator::Strategy,
+        error::AllocationError,
+        heap::Heap,
+        observer::{AllocationObserver, ChunkInfo},
+        plan::reserve,
+        slab::Slab,
+        snapshot::{ChunkSnapshot, RegionSnapshot},
+        unreachable_unchecked,
+        util::try_arc_unwrap,
+        MemoryBounds,
     },
     alloc::{sync::Arc, vec::Vec},
     core::{convert::TryFrom as _, mem::replace, ptr::NonNull},
@@ -165,6 +174,26 @@ impl Size {
         })
     }
 
+    /// Returns number of pairs with one free block.
+    fn ready_count(&self) -> u32 {
+        if self.next_ready >= self.pairs.len() {
+            return 0;
+        }
+
+        let mut count = 0;
+        let mut index = self.next_ready;
+        loop {
+            count += 1;
+            index = match self.pairs.get(index).state {
+                PairState::Exhausted => unreachable!("Exhausted pair in ready list"),
+                PairState::Ready { next, .. } => next,
+            };
+            if index == self.next_ready {
+                return count;
+            }
+        }
+    }
+
     fn release(&mut self, index: usize) -> Release {
         let side = match index & 1 {
             0 => Side::Left,
@@ -267,14 +296,24 @@ struct Chunk<M> {
     size: u64,
 }
 
+/// Buddy allocator over chunks of device memory.
+///
+/// Each chunk is a pair of blocks at its top size level.
+/// New chunks are created at default top level derived from `initial_dedicated_size`,
+/// unless request is larger than block at that level.
+/// In later case chunk is created with top level that fits the request exactly,
+/// i.e. chunk size is twice the request size rounded up to power of two.
+/// Top level of such chunk doesn't affect size of chunks created later.
 #[derive(Debug)]
 pub(crate) struct BuddyAllocator<M> {
     minimal_size: u64,
+    chunk_size_index: usize,
     chunks: Slab<Chunk<M>>,
     sizes: Vec<Size>,
     memory_type: u32,
     props: MemoryPropertyFlags,
     atom_mask: u64,
+    observer: Option<Arc<dyn AllocationObserver>>,
 }
 
 unsafe impl<M> Sync for BuddyAllocator<M> where M: Sync {}
@@ -290,6 +329,7 @@ where
         memory_type: u32,
         props: MemoryPropertyFlags,
         atom_mask: u64,
+        observer: Option<Arc<dyn AllocationObserver>>,
     ) -> Self {
         assert!(
             minimal_size.is_power_of_two(),
@@ -306,14 +346,52 @@ where
 
         BuddyAllocator {
             minimal_size,
+            chunk_size_index: initial_sizes.saturating_sub(1),
             chunks: Slab::new(),
             sizes: (0..initial_sizes).map(|_| Size::new()).collect(),
             memory_type,
             props,
             atom_mask: atom_mask | (minimal_size - 1),
+            observer,
         }
     }
 
+    /// Returns size of memory objects allocated later.
+    pub fn chunk_size(&self) -> u64 {
+        self.minimal_size << (self.chunk_size_index + 1)
+    }
+
+    /// Returns size of memory object with specified index.
+    pub fn memory_size(&self, chunk: usize) -> u64 {
+        self.chunks.get(chunk).size
+    }
+
+    /// Sets size of memory objects allocated later.
+    /// Memory object is still made large enough for request that triggers allocation.
+    pub fn set_chunk_size(&mut self, chunk_size: u64) {
+        let chunk_size = chunk_size.max(self.minimal_size).next_power_of_two();
+        self.chunk_size_index = (chunk_size.trailing_zeros() - self.minimal_size.trailing_zeros())
+            .saturating_sub(1) as usize;
+    }
+
+    /// Returns `true` if request can be served without allocating new memory object.
+    pub fn has_free_block(&self, size: u64, align_mask: u64) -> bool {
+        let align_mask = align_mask | self.atom_mask;
+
+        let size =
+            match align_up(size, align_mask).and_then(|size| size.checked_next_power_of_two()) {
+                Some(size) => size,
+                None => return false,
+            };
+
+        let size_index = (size.trailing_zeros() - self.minimal_size.trailing_zeros()) as usize;
+
+        self.sizes
+            .iter()
+            .skip(size_index)
+            .any(|size| size.ready_count() > 0)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
     pub unsafe fn alloc(
         &mut self,
@@ -328,13 +406,16 @@ where
 
         let size = align_up(size, align_mask)
             .and_then(|size| size.checked_next_power_of_two())
-            .ok_or(AllocationError::OutOfDeviceMemory)?;
+            .ok_or(AllocationError::OutOfDeviceMemory(None))?;
 
         let size_index = size.trailing_zeros() - self.minimal_size.trailing_zeros();
         let size_index =
-            usize::try_from(size_index).map_err(|_| AllocationError::OutOfDeviceMemory)?;
+            usize::try_from(size_index).map_err(|_| AllocationError::OutOfDeviceMemory(None))?;
 
-        while self.sizes.len() <= size_index {
+        // Chunk for this request must have top level not lesser than requested size.
+        let chunk_size_index = self.chunk_size_index.max(size_index);
+
+        while self.sizes.len() <= chunk_size_index {
             self.sizes.push(Size::new());
         }
 
@@ -353,12 +434,23 @@ where
             }
 
             if sizes_len == candidate_size_index + 1 {
+                // No free blocks in existing chunks.
+                // Allocate new chunk with top level at `chunk_size_index`.
+                candidate_size_index = chunk_size_index;
+                let candidate_size_entry = &mut self.sizes[candidate_size_index];
+
                 // That's size of device allocation.
                 if *allocations_remains == 0 {
                     return Err(AllocationError::TooManyObjects);
                 }
 
                 let chunk_size = self.minimal_size << (candidate_size_index + 1);
+
+                if host_visible && isize::try_from(chunk_size).is_err() {
+                    // Chunk can't be mapped into host address space.
+                    return Err(AllocationError::AddressSpaceExhausted);
+                }
+
                 let mut memory = device.allocate_memory(chunk_size, self.memory_type, flags)?;
                 *allocations_remains -= 1;
                 heap.alloc(chunk_size);
@@ -366,11 +458,20 @@ where
                 let ptr = if host_visible {
                     match device.map_memory(&mut memory, 0, chunk_size) {
                         Ok(ptr) => Some(ptr),
-                        Err(DeviceMapError::OutOfDeviceMemory) => {
-                            return Err(AllocationError::OutOfDeviceMemory)
-                        }
-                        Err(DeviceMapError::MapFailed) | Err(DeviceMapError::OutOfHostMemory) => {
-                            return Err(AllocationError::OutOfHostMemory)
+                        Err(err) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!("Failed to map host-visible memory in buddy allocator");
+                            device.deallocate_memory(memory);
+                            *allocations_remains += 1;
+                            heap.dealloc(chunk_size);
+
+                            return Err(match err {
+                                DeviceMapError::OutOfDeviceMemory => {
+                                    AllocationError::OutOfDeviceMemory(None)
+                                }
+                                DeviceMapError::OutOfHostMemory => AllocationError::OutOfHostMemory,
+                                DeviceMapError::MapFailed => AllocationError::AddressSpaceExhausted,
+                            });
                         }
                     }
                 } else {
@@ -383,6 +484,13 @@ where
                     size: chunk_size,
                 });
 
+                if let Some(observer) = &self.observer {
+                    observer.on_chunk_create(&ChunkInfo {
+                        memory_type: self.memory_type,
+                        size: chunk_size,
+                    });
+                }
+
                 let entry = candidate_size_entry.add_pair_and_acquire_left(chunk, 0, None);
 
                 break (entry, candidate_size_index);
@@ -400,10 +508,7 @@ where
         let chunk_entry = self.chunks.get_unchecked(entry.chunk);
 
         debug_assert!(
-            entry
-                .offset
-                .checked_add(size)
-                .map_or(false, |end| end <= chunk_entry.size),
+            matches!(entry.offset.checked_add(size), Some(end) if end <= chunk_entry.size),
             "Offset + size is not in chunk bounds"
         );
 
@@ -411,7 +516,8 @@ where
             memory: chunk_entry.memory.clone(),
             ptr: chunk_entry
                 .ptr
-                .map(|ptr| NonNull::new_unchecked(ptr.as_ptr().add(entry.offset as usize))),
+                // Mapped chunk fits host address space, so does offset within it.
+                .map(|ptr| NonNull::new_unchecked(ptr.as_ptr().offset(entry.offset as isize))),
             offset: entry.offset,
             size,
             chunk: entry.chunk,
@@ -457,6 +563,13 @@ where
                     *allocations_remains += 1;
                     heap.dealloc(chunk.size);
 
+                    if let Some(observer) = &self.observer {
+                        observer.on_chunk_destroy(&ChunkInfo {
+                            memory_type: self.memory_type,
+                            size: chunk.size,
+                        });
+                    }
+
                     return;
                 }
                 Release::None => return,
@@ -464,7 +577,169 @@ where
         }
     }
 
+    /// Drops orders above top orders of regular chunks and memory objects still allocated.
+    ///
+    /// Orders are added for chunks sized for requests larger than regular chunk's top order
+    /// and stay empty after those chunks are deallocated.
+    pub fn shrink_to_fit(&mut self) {
+        let top = self
+            .chunks
+            .iter()
+            .map(|(_, chunk)| {
+                (chunk.size.trailing_zeros() - self.minimal_size.trailing_zeros() - 1) as usize
+            })
+            .fold(self.chunk_size_index, usize::max);
+
+        self.sizes.truncate(top + 1);
+        self.sizes.shrink_to_fit();
+    }
+
+    /// Returns number and total size of memory objects allocated by this allocator.
+    pub fn memory_objects(&self) -> (u64, u64) {
+        self.chunks
+            .iter()
+            .fold((0, 0), |(count, bytes), (_, chunk)| {
+                (count + 1, bytes + chunk.size)
+            })
+    }
+
+    /// Appends memory objects of this allocator with their free blocks to `chunks`.
+    pub fn snapshot(&self, strategy: Strategy, chunks: &mut Vec<ChunkSnapshot>) {
+        let first = chunks.len();
+        let mut positions = Vec::new();
+
+        for (index, chunk) in self.chunks.iter() {
+            positions.resize(index + 1, 0);
+            positions[index] = chunks.len();
+            chunks.push(ChunkSnapshot {
+                memory_type: self.memory_type,
+                pool: None,
+                device_address: false,
+                strategy,
+                chunk: index as u64,
+                size: chunk.size,
+                free_regions: Vec::new(),
+            });
+        }
+
+        for (size_index, size) in self.sizes.iter().enumerate() {
+            let block_size = self.minimal_size << size_index;
+            for (_, pair) in size.pairs.iter() {
+                let offset = match pair.state {
+                    PairState::Exhausted => continue,
+                    PairState::Ready { ready: Left, .. } => pair.offset,
+                    PairState::Ready { ready: Right, .. } => pair.offset + block_size,
+                };
+
+                chunks[positions[pair.chunk]]
+                    .free_regions
+                    .push(RegionSnapshot {
+                        offset,
+                        size: block_size,
+                    });
+            }
+        }
+
+        for chunk in &mut chunks[first..] {
+            chunk.free_regions.sort_by_key(|region| region.offset);
+        }
+    }
+
+    /// Returns copy of this allocator state sufficient to simulate allocations.
+    pub fn plan(&self) -> BuddyPlan {
+        BuddyPlan {
+            minimal_size: self.minimal_size,
+            chunk_size_index: self.chunk_size_index,
+            atom_mask: self.atom_mask,
+            free: self.sizes.iter().map(Size::ready_count).collect(),
+        }
+    }
+
     fn host_visible(&self) -> bool {
         self.props.contains(MemoryPropertyFlags::HOST_VISIBLE)
     }
 }
+
+/// State of `BuddyAllocator` reduced to number of free blocks of each size.
+///
+/// Any free block of the size is as good as another to serve allocation,
+/// so this is enough to predict `BuddyAllocator::alloc` outcome
+/// without touching memory objects.
+#[derive(Clone, Debug)]
+pub(crate) struct BuddyPlan {
+    minimal_size: u64,
+    chunk_size_index: usize,
+    atom_mask: u64,
+    free: Vec<u32>,
+}
+
+impl BuddyPlan {
+    /// Returns state of `BuddyAllocator` created with the same arguments.
+    pub fn new(minimal_size: u64, initial_dedicated_size: u64, atom_mask: u64) -> Self {
+        let initial_sizes = (initial_dedicated_size
+            .trailing_zeros()
+            .saturating_sub(minimal_size.trailing_zeros())) as usize;
+
+        BuddyPlan {
+            minimal_size,
+            chunk_size_index: initial_sizes.saturating_sub(1),
+            atom_mask: atom_mask | (minimal_size - 1),
+            free: (0..initial_sizes).map(|_| 0).collect(),
+        }
+    }
+
+    /// Mirrors `BuddyAllocator::chunk_size`.
+    pub fn chunk_size(&self) -> u64 {
+        self.minimal_size << (self.chunk_size_index + 1)
+    }
+
+    /// Mirrors `BuddyAllocator::set_chunk_size`.
+    pub fn set_chunk_size(&mut self, chunk_size: u64) {
+        let chunk_size = chunk_size.max(self.minimal_size).next_power_of_two();
+        self.chunk_size_index = (chunk_size.trailing_zeros() - self.minimal_size.trailing_zeros())
+            .saturating_sub(1) as usize;
+    }
+
+    /// Simulates `BuddyAllocator::alloc`.
+    /// Returns size of new memory object if one would be allocated.
+    pub fn alloc(
+        &mut self,
+        size: u64,
+        align_mask: u64,
+        heap_available: &mut u64,
+        allocations_remains: &mut u32,
+    ) -> Result<Option<u64>, AllocationError> {
+        let align_mask = align_mask | self.atom_mask;
+
+        let size = align_up(size, align_mask)
+            .and_then(|size| size.checked_next_power_of_two())
+            .ok_or(AllocationError::OutOfDeviceMemory(None))?;
+
+        let size_index = (size.trailing_zeros() - self.minimal_size.trailing_zeros()) as usize;
+        let chunk_size_index = self.chunk_size_index.max(size_index);
+
+        while self.free.len() <= chunk_size_index {
+            self.free.push(0);
+        }
+
+        match (size_index..self.free.len()).find(|&index| self.free[index] > 0) {
+            Some(found) => {
+                self.free[found] -= 1;
+                for free in &mut self.free[size_index..found] {
+                    *free += 1;
+                }
+                Ok(None)
+            }
+            None => {
+                let chunk_size = self.minimal_size << (chunk_size_index + 1);
+                reserve(chunk_size, heap_available, allocations_remains)?;
+
+                // Right half of the chunk and right halves of split blocks are left free.
+                for free in &mut self.free[size_index..=chunk_size_index] {
+                    *free += 1;
+                }
+                Ok(Some(chunk_size))
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use {
+    crate::config::BudgetPolicy,
+    core::ptr::NonNull,
+    gpu_alloc_types::{
+        AllocationFlags, DedicatedResource, DeviceBindError, DeviceImportError, DeviceMapError,
+        MappedMemoryRange, MemoryDevice, MemoryHeapBudget, OutOfMemory,
+    },
+};
+
+/// Device wrapper that checks heap budget before allocating memory objects
+/// and allocates them with `priority` if specified.
+/// Refuses to allocate memory objects at all if `never_allocate` is set,
+/// and memory objects larger than `max_size` if specified.
+/// All other calls are forwarded as is.
+pub(crate) struct BudgetedDevice<'a, D> {
+    pub device: &'a D,
+    pub heap: u32,
+    pub policy: BudgetPolicy,
+    pub priority: Option<f32>,
+    pub never_allocate: bool,
+    pub max_size: Option<u64>,
+}
+
+impl<D> BudgetedDevice<'_, D> {
+    fn check_budget<M>(&self, size: u64) -> Result<(), OutOfMemory>
+    where
+        D: MemoryDevice<M>,
+    {
+        if self.never_allocate || matches!(self.max_size, Some(max_size) if size > max_size) {
+            return Err(OutOfMemory::OutOfDeviceMemory);
+        }
+
+        if self.policy != BudgetPolicy::Ignore {
+            if let Some(budget) = self.device.memory_budget(self.heap) {
+                if budget.usage.saturating_add(size) > budget.budget {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        "Allocation of `{}` bytes exceeds budget `{:?}` of heap `{}`",
+                        size,
+                        budget,
+                        self.heap
+                    );
+
+                    if self.policy == BudgetPolicy::Fail {
+                        return Err(OutOfMemory::OutOfDeviceMemory);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<M, D> MemoryDevice<M> for BudgetedDevice<'_, D>
+where
+    D: MemoryDevice<M>,
+{
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<M, OutOfMemory> {
+        self.check_budget::<M>(size)?;
+        match self.priority {
+            Some(priority) => {
+                self.device
+                    .allocate_memory_with_priority(size, memory_type, flags, None, priority)
+            }
+            None => self.device.allocate_memory(size, memory_type, flags),
+        }
+    }
+
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<M, OutOfMemory> {
+        self.check_budget::<M>(size)?;
+        match self.priority {
+            Some(priority) => self.device.allocate_memory_with_priority(
+                size,
+                memory_type,
+                flags,
+                Some(resource),
+                priority,
+            ),
+            None => self
+                .device
+                .allocate_dedicated_memory(size, memory_type, flags, resource),
+        }
+    }
+
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<M, OutOfMemory> {
+        self.check_budget::<M>(size)?;
+        self.device
+            .allocate_memory_with_priority(size, memory_type, flags, resource, priority)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        self.device.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        self.device.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<M, DeviceImportError> {
+        self.device.import_host_memory(ptr, size, memory_type)
+    }
+
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_buffer_memory(buffer, memory, offset)
+    }
+
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_image_memory(image, memory, offset)
+    }
+
+    fn memory_budget(&self, heap: u32) -> Option<MemoryHeapBudget> {
+        self.device.memory_budget(heap)
+    }
+}
@@ -0,0 +1,308 @@
+use {
+    crate::{
+        allocator::GpuAllocator,
+        block::MemoryBlock,
+        error::{AllocationError, BindError},
+        tlsf::{VirtualAllocator, VirtualBlock},
+        MemoryBounds, Request,
+    },
+    alloc::vec::Vec,
+    gpu_alloc_types::{MemoryDevice, OutOfMemory},
+};
+
+/// Memory requirements of buffer created by `BufferFactory`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BufferRequirements {
+    /// Size of memory block required by the buffer.
+    pub size: u64,
+
+    /// Alignment mask of memory block required by the buffer.
+    pub align_mask: u64,
+
+    /// Bitset of memory types the buffer can be bound to.
+    pub memory_types: u32,
+}
+
+/// Creates and destroys buffers for `BufferAllocator`.
+pub trait BufferFactory {
+    /// Creates buffer of `size` bytes.
+    /// Returns raw handle of the buffer and its memory requirements.
+    ///
+    /// # Safety
+    ///
+    /// Implementation may require that buffers are created from one device only.
+    unsafe fn create_buffer(&mut self, size: u64)
+        -> Result<(u64, BufferRequirements), OutOfMemory>;
+
+    /// Destroys buffer created by `create_buffer`.
+    ///
+    /// # Safety
+    ///
+    /// `buffer` must have been created by this factory and must not be used anymore.
+    unsafe fn destroy_buffer(&mut self, buffer: u64);
+}
+
+/// Range of buffer allocated from `BufferAllocator`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[must_use = "Buffer slice must be returned with `BufferAllocator::dealloc`"]
+pub struct BufferSlice {
+    buffer: u64,
+    index: usize,
+    block: VirtualBlock,
+}
+
+impl BufferSlice {
+    /// Returns raw handle of the buffer this slice is part of.
+    pub fn buffer(&self) -> u64 {
+        self.buffer
+    }
+
+    /// Returns offset of this slice in the buffer.
+    pub fn offset(&self) -> u64 {
+        self.block.offset()
+    }
+
+    /// Returns size of this slice.
+    pub fn size(&self) -> u64 {
+        self.block.size()
+    }
+}
+
+#[derive(Debug)]
+struct Buffer<M> {
+    handle: u64,
+    block: MemoryBlock<M>,
+    space: VirtualAllocator,
+}
+
+/// Suballocator of slices of few large buffers.
+///
+/// Buffers are created with `BufferFactory` as needed
+/// and bound to memory blocks allocated from `GpuAllocator`,
+/// so that many allocations share one buffer object.
+/// Slice larger than buffer size gets buffer of its own.
+///
+/// Offsets of slices are aligned by alignment provided on creation,
+/// which should cover `minUniformBufferOffsetAlignment`, `minStorageBufferOffsetAlignment`
+/// or other offset alignments required for buffer usage.
+///
+/// Buffers must be destroyed with `destroy`.
+#[derive(Debug)]
+pub struct BufferAllocator<M> {
+    request: Request,
+    align_mask: u64,
+    buffers: Vec<Option<Buffer<M>>>,
+}
+
+impl<M> BufferAllocator<M> {
+    /// Creates allocator with no buffers.
+    ///
+    /// `request.size` is size of each buffer,
+    /// other fields of `request` are used to allocate memory for buffers.
+    /// Offsets of slices are aligned by `align_mask`.
+    pub fn new(request: Request, align_mask: u64) -> Self {
+        BufferAllocator {
+            request,
+            align_mask,
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Returns number of buffers created by this allocator.
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.iter().flatten().count()
+    }
+
+    /// Returns memory block the buffer of `slice` is bound to.
+    /// Slice starts at `slice.offset()` bytes in the block.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `slice` wasn't allocated by this allocator.
+    pub fn memory_block(&self, slice: &BufferSlice) -> &MemoryBlock<M> {
+        &self.buffer(slice).block
+    }
+
+    /// Returns memory block the buffer of `slice` is bound to,
+    /// for functions that map or write it.
+    /// Slice starts at `slice.offset()` bytes in the block.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `slice` wasn't allocated by this allocator.
+    pub fn memory_block_mut(&mut self, slice: &BufferSlice) -> &mut MemoryBlock<M> {
+        let buffer = self.buffers[slice.index]
+            .as_mut()
+            .filter(|buffer| buffer.handle == slice.buffer)
+            .expect("Slice wasn't allocated by this allocator");
+        &mut buffer.block
+    }
+
+    /// Returns slice to its buffer.
+    /// Buffer is kept for following allocations, even if it is empty.
+    ///
+    /// # Safety
+    ///
+    /// * Slice must not be used by device anymore.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `slice` wasn't allocated by this allocator.
+    pub unsafe fn dealloc(&mut self, slice: BufferSlice) {
+        let buffer = self.buffers[slice.index]
+            .as_mut()
+            .filter(|buffer| buffer.handle == slice.buffer)
+            .expect("Slice wasn't allocated by this allocator");
+        buffer.space.dealloc(slice.block);
+    }
+
+    fn buffer(&self, slice: &BufferSlice) -> &Buffer<M> {
+        self.buffers[slice.index]
+            .as_ref()
+            .filter(|buffer| buffer.handle == slice.buffer)
+            .expect("Slice wasn't allocated by this allocator")
+    }
+}
+
+impl<M> BufferAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Allocates slice of `size` bytes from one of the buffers,
+    /// creating new buffer if none has space for it.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device`, `allocator` and `factory` instances must be used
+    ///   for all interactions with this buffer allocator.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device, factory))
+    )]
+    pub unsafe fn alloc(
+        &mut self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+        factory: &mut impl BufferFactory,
+        size: u64,
+    ) -> Result<BufferSlice, BindError> {
+        for (index, buffer) in self.buffers.iter_mut().enumerate() {
+            if let Some(buffer) = buffer {
+                if let Some(block) = buffer.space.alloc(size, self.align_mask) {
+                    return Ok(BufferSlice {
+                        buffer: buffer.handle,
+                        index,
+                        block,
+                    });
+                }
+            }
+        }
+
+        let buffer_size = self.request.size.max(size);
+        let (handle, requirements) = factory
+            .create_buffer(buffer_size)
+            .map_err(AllocationError::from)?;
+
+        let request = Request {
+            size: requirements.size,
+            align_mask: requirements.align_mask | self.request.align_mask,
+            memory_types: requirements.memory_types & self.request.memory_types,
+            ..self.request
+        };
+
+        let memory = match allocator.alloc_and_bind_buffer(device, handle, request) {
+            Ok(block) => block,
+            Err(err) => {
+                factory.destroy_buffer(handle);
+                return Err(err);
+            }
+        };
+
+        // Offset 0 is aligned for any alignment.
+        let mut space = VirtualAllocator::new(buffer_size);
+        let block = space.alloc(size, 0).expect("New buffer must fit the slice");
+
+        let buffer = Buffer {
+            handle,
+            block: memory,
+            space,
+        };
+
+        let index = match self.buffers.iter().position(Option::is_none) {
+            Some(index) => {
+                self.buffers[index] = Some(buffer);
+                index
+            }
+            None => {
+                self.buffers.push(Some(buffer));
+                self.buffers.len() - 1
+            }
+        };
+
+        Ok(BufferSlice {
+            buffer: handle,
+            index,
+            block,
+        })
+    }
+
+    /// Destroys buffers with no allocated slices
+    /// and deallocates their memory blocks.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device`, `allocator` and `factory` instances must be used
+    ///   for all interactions with this buffer allocator.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device, factory))
+    )]
+    pub unsafe fn trim(
+        &mut self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+        factory: &mut impl BufferFactory,
+    ) {
+        for slot in &mut self.buffers {
+            let empty = match slot {
+                Some(buffer) => buffer.space.block_count() == 0,
+                None => false,
+            };
+            if empty {
+                if let Some(buffer) = slot.take() {
+                    factory.destroy_buffer(buffer.handle);
+                    allocator.dealloc(device, buffer.block);
+                }
+            }
+        }
+        while let Some(None) = self.buffers.last() {
+            self.buffers.pop();
+        }
+    }
+
+    /// Destroys all buffers and deallocates their memory blocks.
+    ///
+    /// # Safety
+    ///
+    /// * Buffers must not be used by device anymore.
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device`, `allocator` and `factory` instances must be used
+    ///   for all interactions with this buffer allocator.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device, factory))
+    )]
+    pub unsafe fn destroy(
+        self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+        factory: &mut impl BufferFactory,
+    ) {
+        for buffer in self.buffers.into_iter().flatten() {
+            factory.destroy_buffer(buffer.handle);
+            allocator.dealloc(device, buffer.block);
+        }
+    }
+}
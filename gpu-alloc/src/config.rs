@@ -1,8 +1,126 @@
+use {
+    crate::{
+        observer::AllocationObserver,
+        usage::{UsageFallback, UsageFlags},
+    },
+    alloc::{sync::Arc, vec::Vec},
+    gpu_alloc_types::{DeviceProperties, MemoryPropertyFlags},
+};
+
+/// Reaction on memory object allocation that would exceed heap budget
+/// reported by `MemoryDevice::memory_budget`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub enum BudgetPolicy {
+    /// Budget is not queried.
+    Ignore,
+
+    /// Allocation proceeds and warning is logged with `tracing`.
+    Warn,
+
+    /// Allocation fails with `AllocationError::OutOfDeviceMemory`,
+    /// so allocator tries other compatible memory types first.
+    Fail,
+}
+
+/// Growth of sizes of memory objects allocated one after another
+/// by buddy and TLSF allocators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub enum ChunkGrowth {
+    /// All memory objects are of initial size.
+    Fixed,
+
+    /// Each new memory object is `factor` times larger than previous one,
+    /// until it reaches `max_size` bytes.
+    Exponential {
+        /// Multiplier applied to size of memory object after each allocation.
+        factor: u32,
+
+        /// Upper limit for size in bytes of memory objects.
+        /// Memory objects never shrink below initial size.
+        max_size: u64,
+    },
+}
+
+impl ChunkGrowth {
+    /// Returns size of memory object allocated after one of `chunk_size` bytes.
+    /// Result is capped by 1/32 of heap size, like initial sizes.
+    pub(crate) fn next(self, chunk_size: u64, heap_size: u64) -> u64 {
+        match self {
+            ChunkGrowth::Fixed => chunk_size,
+            ChunkGrowth::Exponential { factor, max_size } => chunk_size
+                .saturating_mul(u64::from(factor))
+                .min(max_size)
+                .min(heap_size / 32)
+                .max(chunk_size),
+        }
+    }
+}
+
+/// Sizes of memory objects and blocks that override ones of `Config`
+/// for memory types preferred by usage, see `Config::usage_chunk_sizes`.
+/// `None` keeps size from `Config`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct ChunkSizes {
+    /// Overrides `Config::initial_buddy_dedicated_size`
+    /// for buddy and TLSF allocators.
+    pub buddy: Option<u64>,
+
+    /// Overrides both `Config::starting_free_list_chunk` and `Config::final_free_list_chunk`
+    /// for free-list allocator of transient requests.
+    /// Chunks still grow to fit requests up to `Config::transient_dedicated_threshold`.
+    pub free_list: Option<u64>,
+
+    /// Overrides `Config::minimal_buddy_size`.
+    pub minimal_buddy: Option<u64>,
+}
+
+/// Size thresholds of strategy selection that override ones of `Config`,
+/// see `Config::usage_thresholds` and `Config::memory_type_thresholds`.
+/// `None` keeps threshold from `Config`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct StrategyThresholds {
+    /// Overrides `Config::dedicated_threshold`.
+    pub dedicated: Option<u64>,
+
+    /// Overrides `Config::preferred_dedicated_threshold`.
+    pub preferred_dedicated: Option<u64>,
+
+    /// Overrides `Config::transient_dedicated_threshold`.
+    pub transient_dedicated: Option<u64>,
+
+    /// Overrides `Config::large_object_threshold`.
+    pub large_object: Option<u64>,
+}
+
 /// Configuration for [`GpuAllocator`]
 ///
 /// [`GpuAllocator`]: type.GpuAllocator
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
 pub struct Config {
     /// Size in bytes of request that will be served by dedicated memory object.
     /// This value should be large enough to not exhaust memory object limit
@@ -29,12 +147,172 @@ pub struct Config {
     /// Upper limit for size in bytes of chunks in free-list allocator.
     pub final_free_list_chunk: u64,
 
-    /// Minimal size for buddy allocator.
+    /// Size of the smallest blocks of small-object buddy allocator, rounded up to power of two.
+    ///
+    /// Smaller requests still take block of this size,
+    /// so workloads with many tiny blocks benefit from lower value.
+    /// Each halving of this value adds one more size class to track in every chunk,
+    /// so workloads with only large blocks benefit from higher value.
+    /// Value is capped by 1/1024 of heap size.
     pub minimal_buddy_size: u64,
 
-    /// Initial memory object size for buddy allocator.
+    /// Memory object size for buddy allocator.
     /// If less than `minimal_buddy_size` then `minimal_buddy_size` is used instead.
+    ///
+    /// Requests larger than half of this size, but still below `dedicated_threshold`,
+    /// are not served by dedicated memory objects.
+    /// Instead buddy allocator creates new memory object twice as large as the request
+    /// rounded up to power of two.
+    /// Sizes of memory objects created later are not affected.
     pub initial_buddy_dedicated_size: u64,
+
+    /// Size in bytes of request that will be served by large-object buddy allocator.
+    /// Smaller requests are served by small-object buddy allocator.
+    /// Large-object allocator uses this value as minimal size
+    /// and allocates chunks large enough to fit `dedicated_threshold` request.
+    ///
+    /// Separating large and small objects keeps long-living small blocks
+    /// from pinning chunks with large blocks and vice versa.
+    ///
+    /// This won't make much sense if this value is not lesser than `dedicated_threshold`.
+    pub large_object_threshold: u64,
+
+    /// Enables choosing sizes of new buddy allocator memory objects
+    /// based on sizes of recent requests.
+    /// When enabled, memory object is made large enough to fit several
+    /// requests of 95th percentile size, which is capped by heap size.
+    /// Memory object is never smaller than the request that triggered its allocation.
+    ///
+    /// Leave disabled to keep memory object sizes deterministic.
+    /// See `GpuAllocator::recommended_chunk_size`.
+    pub adaptive_chunk_size: bool,
+
+    /// Growth of sizes of memory objects allocated by buddy and TLSF allocators.
+    /// Starting from initial size, each new memory object is sized according to this policy.
+    /// Sizes are rounded up to power of two for buddy allocators.
+    ///
+    /// Memory objects allocated for requests larger than current size,
+    /// or by `GpuAllocator::alloc_in_chunk_of`, don't advance growth.
+    /// Ignored by small-object buddy allocator when `adaptive_chunk_size` is enabled.
+    pub chunk_growth: ChunkGrowth,
+
+    /// Growth policies that override `chunk_growth` for memory types with specified indices.
+    pub memory_type_chunk_growth: Vec<(u32, ChunkGrowth)>,
+
+    /// Chunk sizes and minimal buddy block sizes for memory types preferred by specified usages,
+    /// e.g. large chunks for device-local memory and small ones for upload memory.
+    ///
+    /// Each entry applies to memory type that is the first choice for requests
+    /// with the usage and no access pattern.
+    /// Entries listed later take precedence for memory types preferred by several usages.
+    pub usage_chunk_sizes: Vec<(UsageFlags, ChunkSizes)>,
+
+    /// Strategy selection thresholds for memory types preferred by specified usages,
+    /// e.g. higher dedicated threshold for device-local memory with many large textures.
+    ///
+    /// Entries apply to memory types the same way as `usage_chunk_sizes`.
+    /// Thresholds are applied after overrides, so preferred dedicated threshold
+    /// is still capped by dedicated one and transient dedicated threshold is not lower than it.
+    pub usage_thresholds: Vec<(UsageFlags, StrategyThresholds)>,
+
+    /// Strategy selection thresholds for memory types with specified indices.
+    /// Take precedence over `usage_thresholds`.
+    pub memory_type_thresholds: Vec<(u32, StrategyThresholds)>,
+
+    /// Enables checking of deallocated blocks against live blocks to detect invalid deallocations,
+    /// such as deallocating the same block twice.
+    /// Invalid deallocation is reported by `GpuAllocator::try_dealloc`
+    /// without corrupting allocator state.
+    ///
+    /// Has effect only in builds with debug assertions enabled.
+    pub validate_deallocations: bool,
+
+    /// Maximum number of memory objects allocator may have allocated at once.
+    /// Never exceeds `DeviceProperties::max_memory_allocation_count`.
+    /// If `None`, device limit is used.
+    ///
+    /// Lower this value if other code allocates memory objects from the same device.
+    /// Allocation that would exceed the limit fails with `AllocationError::TooManyObjects`.
+    /// When few memory objects remain, `Dedicated::Preferred` hint is ignored
+    /// to leave them for requests that cannot be suballocated.
+    pub max_memory_allocation_count: Option<u32>,
+
+    /// Bitset of memory types where buddy allocator spills requests
+    /// over to free-list allocator before allocating new memory object.
+    ///
+    /// When no buddy block is free for the request, free memory left in
+    /// free-list allocator memory objects is used instead,
+    /// trading fragmentation for reuse of already allocated memory.
+    /// See `GpuAllocator::spillover_count`.
+    pub buddy_spillover_memory_types: u32,
+
+    /// Bitset of memory types where two-level segregated fit allocator
+    /// serves requests that would be served by buddy allocators otherwise.
+    ///
+    /// TLSF allocator doesn't round blocks up to power of two,
+    /// which packs awkwardly sized requests tighter at cost of
+    /// external fragmentation. Both allocation and deallocation take constant time.
+    /// Its memory objects are as large as ones of large-object buddy allocator.
+    pub tlsf_memory_types: u32,
+
+    /// Bitset of memory types where best-fit free-list allocator
+    /// serves requests that would be served by large-object buddy allocator otherwise,
+    /// i.e. ones not smaller than `large_object_threshold` and below `dedicated_threshold`.
+    ///
+    /// Blocks are taken from the smallest free region that fits and aren't rounded up
+    /// to power of two, so mid-size resources of awkward sizes, like textures,
+    /// waste less memory. Freed blocks are merged with neighbouring free regions.
+    /// Allocation and deallocation take time linear in number of free regions.
+    /// Its memory objects are as large as ones of TLSF allocator.
+    /// Ignored for memory types selected by `tlsf_memory_types`.
+    pub best_fit_memory_types: u32,
+
+    /// Explicit fallback chains of memory types for requests of specified usages.
+    /// First chain with matching usage is used instead of order chosen by allocator.
+    /// Requests with `Request::memory_type` set ignore chains.
+    pub usage_fallbacks: Vec<UsageFallback>,
+
+    /// Size in bytes of ring memory object used by `GpuAllocator::alloc_in_frame`.
+    /// One ring is allocated for each memory type on first use.
+    /// If zero, `GpuAllocator::alloc_in_frame` always fails.
+    pub frame_ring_size: u64,
+
+    /// Reaction on memory object allocation that would exceed heap budget.
+    /// Has effect only if device reports budget with `MemoryDevice::memory_budget`.
+    pub budget_policy: BudgetPolicy,
+
+    /// Heap utilization thresholds in percents of heap budget, e.g. `[80, 95]`.
+    /// `AllocationObserver::on_heap_pressure` is called when utilization crosses any of them.
+    /// Utilization is checked after memory objects are allocated or deallocated
+    /// by allocator methods.
+    /// Budget is queried with `MemoryDevice::memory_budget`, heap size is used if device reports none.
+    pub pressure_thresholds: Vec<u8>,
+
+    /// Size in bytes of guard regions placed before and after each block
+    /// suballocated from host-visible memory.
+    /// Guard regions are filled with a byte pattern on allocation
+    /// and checked on deallocation, overwritten guards are reported
+    /// by `GpuAllocator::take_guard_corruptions`.
+    /// Only writes through host mapping are guaranteed to be detected
+    /// in non-coherent memory.\
+    /// This is a debugging aid that wastes memory. Zero disables guard regions.
+    pub guard_size: u64,
+
+    /// Alignment required for host pointers imported with `GpuAllocator::import_host_ptr`.
+    /// Should be set to device's `minImportedHostPointerAlignment`.
+    pub min_imported_host_pointer_alignment: u64,
+
+    /// Minimal alignment in bytes of all blocks, applied on top of `Request::align_mask`.
+    /// Must be power of two, `1` keeps alignment of requests.
+    ///
+    /// Useful when driver requires larger alignment than it reports,
+    /// or to keep blocks on page boundaries.
+    pub min_alignment: u64,
+
+    /// Observer notified about allocations and deallocations.
+    /// Not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub observer: Option<Arc<dyn AllocationObserver>>,
 }
 
 impl Config {
@@ -59,6 +337,26 @@ impl Config {
             final_free_list_chunk: potato.final_free_list_chunk * 1024,
             minimal_buddy_size: potato.minimal_buddy_size * 1024,
             initial_buddy_dedicated_size: potato.initial_buddy_dedicated_size * 1024,
+            large_object_threshold: potato.large_object_threshold * 1024,
+            adaptive_chunk_size: potato.adaptive_chunk_size,
+            chunk_growth: potato.chunk_growth,
+            memory_type_chunk_growth: potato.memory_type_chunk_growth,
+            usage_chunk_sizes: potato.usage_chunk_sizes,
+            usage_thresholds: potato.usage_thresholds,
+            memory_type_thresholds: potato.memory_type_thresholds,
+            validate_deallocations: potato.validate_deallocations,
+            max_memory_allocation_count: potato.max_memory_allocation_count,
+            buddy_spillover_memory_types: potato.buddy_spillover_memory_types,
+            tlsf_memory_types: potato.tlsf_memory_types,
+            best_fit_memory_types: potato.best_fit_memory_types,
+            usage_fallbacks: potato.usage_fallbacks,
+            frame_ring_size: potato.frame_ring_size * 1024,
+            budget_policy: potato.budget_policy,
+            pressure_thresholds: potato.pressure_thresholds,
+            guard_size: potato.guard_size,
+            min_imported_host_pointer_alignment: potato.min_imported_host_pointer_alignment,
+            min_alignment: potato.min_alignment,
+            observer: potato.observer,
         }
     }
 
@@ -72,6 +370,82 @@ impl Config {
             final_free_list_chunk: 128 * 1024,
             minimal_buddy_size: 1,
             initial_buddy_dedicated_size: 8 * 1024,
+            large_object_threshold: 4 * 1024,
+            adaptive_chunk_size: false,
+            chunk_growth: ChunkGrowth::Fixed,
+            memory_type_chunk_growth: Vec::new(),
+            usage_chunk_sizes: Vec::new(),
+            usage_thresholds: Vec::new(),
+            memory_type_thresholds: Vec::new(),
+            validate_deallocations: false,
+            max_memory_allocation_count: None,
+            buddy_spillover_memory_types: 0,
+            tlsf_memory_types: 0,
+            best_fit_memory_types: 0,
+            usage_fallbacks: Vec::new(),
+            frame_ring_size: 64 * 1024,
+            budget_policy: BudgetPolicy::Fail,
+            pressure_thresholds: Vec::new(),
+            guard_size: 0,
+            min_imported_host_pointer_alignment: 4096,
+            min_alignment: 1,
+            observer: None,
+        }
+    }
+
+    /// Returns configuration tuned for device with specified properties.
+    ///
+    /// Values of `i_am_potato` are scaled by size of the largest device-local heap,
+    /// one step per 8 MiB of the heap, up to values of `i_am_prototyping` for 8 GiB heap.
+    /// Devices without host-only heap are treated as integrated GPUs sharing memory with the host
+    /// and get half of that.
+    /// Dedicated thresholds never exceed `max_memory_allocation_size`,
+    /// buddy blocks are never smaller than `non_coherent_atom_size`.
+    pub fn auto(props: &DeviceProperties<'_>) -> Self {
+        let potato = Config::i_am_potato();
+
+        let is_device_local_heap = |heap: usize| {
+            props.memory_types.iter().any(|memory_type| {
+                memory_type.heap as usize == heap
+                    && memory_type
+                        .props
+                        .contains(MemoryPropertyFlags::DEVICE_LOCAL)
+            })
+        };
+
+        let heap_size = (0..props.memory_heaps.len())
+            .filter(|&heap| is_device_local_heap(heap))
+            .map(|heap| props.memory_heaps[heap].size)
+            .max()
+            .or_else(|| props.memory_heaps.iter().map(|heap| heap.size).max())
+            .unwrap_or(0);
+
+        let integrated = (0..props.memory_heaps.len()).all(is_device_local_heap);
+
+        // Number of 8 MiB steps rounded down to power of two.
+        let mut scale = match (heap_size / (8 * 1024 * 1024)).min(1024) {
+            0 => 1,
+            steps => 1 << (63 - steps.leading_zeros()),
+        };
+        if integrated {
+            scale = (scale / 2).max(1);
+        }
+
+        let max_size = props.max_memory_allocation_size;
+
+        Config {
+            dedicated_threshold: (potato.dedicated_threshold * scale).min(max_size),
+            preferred_dedicated_threshold: (potato.preferred_dedicated_threshold * scale)
+                .min(max_size),
+            transient_dedicated_threshold: (potato.transient_dedicated_threshold * scale)
+                .min(max_size),
+            starting_free_list_chunk: potato.starting_free_list_chunk * scale,
+            final_free_list_chunk: potato.final_free_list_chunk * scale,
+            minimal_buddy_size: potato.minimal_buddy_size.max(props.non_coherent_atom_size),
+            initial_buddy_dedicated_size: potato.initial_buddy_dedicated_size * scale,
+            large_object_threshold: potato.large_object_threshold * scale,
+            frame_ring_size: potato.frame_ring_size * scale,
+            ..potato
         }
     }
 }
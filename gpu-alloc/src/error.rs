@@ -1,15 +1,44 @@
 use {
     core::fmt::{self, Display},
-    gpu_alloc_types::{DeviceMapError, OutOfMemory},
+    gpu_alloc_types::{DeviceBindError, DeviceImportError, DeviceMapError, OutOfMemory},
 };
 
+/// Details of allocation that failed with `AllocationError::OutOfDeviceMemory`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OutOfMemoryDetails {
+    /// Index of the last memory type allocation was tried from.
+    pub memory_type: u32,
+
+    /// Index of heap of the memory type.
+    pub heap: u32,
+
+    /// Requested size in bytes.
+    pub size: u64,
+
+    /// Size in bytes of memory allocated by allocator from the heap
+    /// when allocation failed.
+    pub heap_usage: u64,
+
+    /// Size of the heap in bytes.
+    pub heap_size: u64,
+
+    /// Other compatible memory types were tried before the last one.
+    pub fallbacks_attempted: bool,
+}
+
 /// Enumeration of possible errors that may occur during memory allocation.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum AllocationError {
     /// Backend reported that device memory has been exhausted.\
     /// Deallocating device memory from the same heap may increase chance
     /// that another allocation would succeed.
-    OutOfDeviceMemory,
+    ///
+    /// `GpuAllocator` methods that choose memory type for the request
+    /// report details of the failed allocation.
+    /// Details are `None` if no memory type was tried,
+    /// e.g. when request is larger than `DeviceProperties::max_memory_allocation_size`,
+    /// and for errors converted from backend errors.
+    OutOfDeviceMemory(Option<OutOfMemoryDetails>),
 
     /// Backend reported that host memory has been exhausted.\
     /// Deallocating host memory may increase chance that another allocation would succeed.
@@ -26,12 +55,24 @@ pub enum AllocationError {
     /// If this error is returned when memory heaps are far from exhausted
     /// `Config` should be tweaked to allocate larger memory objects.
     TooManyObjects,
+
+    /// Memory object cannot be persistently mapped
+    /// as host address space is exhausted, while host memory may be not.\
+    /// This may happen in processes with 32-bit pointers.
+    /// Allocator deallocates idle mapped memory objects and retries once
+    /// before reporting this error.
+    /// Deallocating host-visible memory blocks may increase chance
+    /// that another allocation would succeed.
+    AddressSpaceExhausted,
+
+    /// Device was marked as lost with `GpuAllocator::mark_device_lost`.
+    DeviceLost,
 }
 
 impl From<OutOfMemory> for AllocationError {
     fn from(err: OutOfMemory) -> Self {
         match err {
-            OutOfMemory::OutOfDeviceMemory => AllocationError::OutOfDeviceMemory,
+            OutOfMemory::OutOfDeviceMemory => AllocationError::OutOfDeviceMemory(None),
             OutOfMemory::OutOfHostMemory => AllocationError::OutOfHostMemory,
         }
     }
@@ -40,7 +81,22 @@ impl From<OutOfMemory> for AllocationError {
 impl Display for AllocationError {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            AllocationError::OutOfDeviceMemory => fmt.write_str("Device memory exhausted"),
+            AllocationError::OutOfDeviceMemory(None) => fmt.write_str("Device memory exhausted"),
+            AllocationError::OutOfDeviceMemory(Some(details)) => {
+                write!(
+                    fmt,
+                    "Device memory exhausted allocating {} bytes from memory type {}, heap {} has {} of {} bytes allocated",
+                    details.size,
+                    details.memory_type,
+                    details.heap,
+                    details.heap_usage,
+                    details.heap_size,
+                )?;
+                if details.fallbacks_attempted {
+                    fmt.write_str(", other memory types were tried before")?;
+                }
+                Ok(())
+            }
             AllocationError::OutOfHostMemory => fmt.write_str("Host memory exhausted"),
             AllocationError::NoCompatibleMemoryTypes => fmt.write_str(
                 "No compatible memory types from requested types support requested usage",
@@ -48,6 +104,8 @@ impl Display for AllocationError {
             AllocationError::TooManyObjects => {
                 fmt.write_str("Reached limit on allocated memory objects count")
             }
+            AllocationError::AddressSpaceExhausted => fmt.write_str("Host address space exhausted"),
+            AllocationError::DeviceLost => fmt.write_str("Device is lost"),
         }
     }
 }
@@ -79,6 +137,11 @@ pub enum MapError {
 
     /// Mapping failed due to block being already mapped.
     AlreadyMapped,
+
+    /// Mapped range doesn't fit host address space.\
+    /// This may happen on hosts with 32-bit pointers
+    /// when mapping ranges of large memory blocks.
+    AddressSpaceOverflow,
 }
 
 impl From<DeviceMapError> for MapError {
@@ -108,9 +171,335 @@ impl Display for MapError {
             MapError::MapFailed => fmt.write_str("Failed to map memory object"),
             MapError::NonHostVisible => fmt.write_str("Impossible to map non-host-visible memory"),
             MapError::AlreadyMapped => fmt.write_str("Block is already mapped"),
+            MapError::AddressSpaceOverflow => {
+                fmt.write_str("Mapped range doesn't fit host address space")
+            }
         }
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for MapError {}
+
+/// Enumeration of possible errors detected when deallocation is validated.
+///
+/// See `Config::validate_deallocations`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeallocError {
+    /// Block is not allocated.\
+    /// It was already deallocated or was never allocated by this allocator.
+    NotAllocated,
+
+    /// Block offset or size doesn't match range that was allocated for it.
+    RangeMismatch,
+}
+
+impl Display for DeallocError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeallocError::NotAllocated => {
+                fmt.write_str("Block is already deallocated or was never allocated")
+            }
+            DeallocError::RangeMismatch => {
+                fmt.write_str("Block range doesn't match allocated range")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeallocError {}
+
+/// Enumeration of possible errors that may occur
+/// when range of memory block is deallocated.
+///
+/// See `GpuAllocator::dealloc_range`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeallocRangeError {
+    /// Block is not allocated.\
+    /// Reported only if `Config::validate_deallocations` is enabled.
+    NotAllocated,
+
+    /// Block is not sub-allocated from free-list memory object.
+    Unsupported,
+
+    /// Range is empty, out of block bounds, covers whole block
+    /// or is not aligned to `non_coherent_atom_size` of non-coherent memory type.
+    InvalidRange,
+
+    /// Block is currently mapped.
+    Mapped,
+}
+
+impl Display for DeallocRangeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeallocRangeError::NotAllocated => {
+                fmt.write_str("Block is already deallocated or was never allocated")
+            }
+            DeallocRangeError::Unsupported => {
+                fmt.write_str("Only free-list blocks support range deallocation")
+            }
+            DeallocRangeError::InvalidRange => fmt.write_str("Invalid range to deallocate"),
+            DeallocRangeError::Mapped => fmt.write_str("Block is mapped"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeallocRangeError {}
+
+/// Enumeration of problems with request found by `RequestBuilder::build`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RequestError {
+    /// Requested size is zero.
+    ZeroSize,
+
+    /// Requested alignment is not power of two.
+    AlignmentNotPowerOfTwo {
+        /// Requested alignment in bytes.
+        align: u64,
+    },
+
+    /// Bitset of allowed memory types is empty.
+    NoMemoryTypes,
+
+    /// Requested memory type is not in bitset of allowed memory types.
+    MemoryTypeNotAllowed {
+        /// Index of requested memory type.
+        memory_type: u32,
+    },
+}
+
+impl Display for RequestError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::ZeroSize => fmt.write_str("Requested size is zero"),
+            RequestError::AlignmentNotPowerOfTwo { align } => {
+                write!(fmt, "Requested alignment {} is not power of two", align)
+            }
+            RequestError::NoMemoryTypes => fmt.write_str("No memory types are allowed by request"),
+            RequestError::MemoryTypeNotAllowed { memory_type } => write!(
+                fmt,
+                "Requested memory type {} is not allowed by request",
+                memory_type
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RequestError {}
+
+/// Enumeration of possible errors that may occur during host memory import.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImportError {
+    /// Backend reported that device memory has been exhausted.
+    OutOfDeviceMemory,
+
+    /// Backend reported that host memory has been exhausted.
+    OutOfHostMemory,
+
+    /// Host pointer or size is not multiple of
+    /// `Config::min_imported_host_pointer_alignment`.
+    Misaligned,
+
+    /// Memory type is not `HOST_VISIBLE` and `HOST_COHERENT`.
+    IncompatibleMemoryType,
+
+    /// Backend rejected host pointer for specified memory type.
+    InvalidHostPointer,
+
+    /// Backend does not support importing host memory.
+    Unsupported,
+
+    /// Reached limit on allocated memory objects count.
+    TooManyObjects,
+
+    /// Device was marked as lost with `GpuAllocator::mark_device_lost`.
+    DeviceLost,
+}
+
+impl From<DeviceImportError> for ImportError {
+    fn from(err: DeviceImportError) -> Self {
+        match err {
+            DeviceImportError::OutOfDeviceMemory => ImportError::OutOfDeviceMemory,
+            DeviceImportError::OutOfHostMemory => ImportError::OutOfHostMemory,
+            DeviceImportError::InvalidExternalHandle => ImportError::InvalidHostPointer,
+            DeviceImportError::Unsupported => ImportError::Unsupported,
+        }
+    }
+}
+
+impl Display for ImportError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::OutOfDeviceMemory => fmt.write_str("Device memory exhausted"),
+            ImportError::OutOfHostMemory => fmt.write_str("Host memory exhausted"),
+            ImportError::Misaligned => fmt.write_str(
+                "Host pointer or size is not aligned to minimal imported host pointer alignment",
+            ),
+            ImportError::IncompatibleMemoryType => {
+                fmt.write_str("Host memory can be imported only into host-coherent memory types")
+            }
+            ImportError::InvalidHostPointer => {
+                fmt.write_str("Host pointer cannot be imported into specified memory type")
+            }
+            ImportError::Unsupported => fmt.write_str("Host memory import is not supported"),
+            ImportError::TooManyObjects => {
+                fmt.write_str("Reached limit on allocated memory objects count")
+            }
+            ImportError::DeviceLost => fmt.write_str("Device is lost"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ImportError {}
+
+/// Enumeration of possible errors that may occur during allocation
+/// of memory block for resource and binding the resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BindError {
+    /// Backend reported that device memory has been exhausted.
+    OutOfDeviceMemory,
+
+    /// Backend reported that host memory has been exhausted.
+    OutOfHostMemory,
+
+    /// No available memory types allowed by `Request.memory_types` mask
+    /// is compatible with `request.usage`.
+    NoCompatibleMemoryTypes,
+
+    /// Reached limit on allocated memory objects count.
+    TooManyObjects,
+
+    /// Host address space is exhausted by mapped memory objects.
+    AddressSpaceExhausted,
+
+    /// Device was marked as lost with `GpuAllocator::mark_device_lost`.
+    DeviceLost,
+
+    /// Backend does not support binding resources.
+    Unsupported,
+}
+
+impl From<AllocationError> for BindError {
+    fn from(err: AllocationError) -> Self {
+        match err {
+            AllocationError::OutOfDeviceMemory(_) => BindError::OutOfDeviceMemory,
+            AllocationError::OutOfHostMemory => BindError::OutOfHostMemory,
+            AllocationError::NoCompatibleMemoryTypes => BindError::NoCompatibleMemoryTypes,
+            AllocationError::TooManyObjects => BindError::TooManyObjects,
+            AllocationError::AddressSpaceExhausted => BindError::AddressSpaceExhausted,
+            AllocationError::DeviceLost => BindError::DeviceLost,
+        }
+    }
+}
+
+impl From<DeviceBindError> for BindError {
+    fn from(err: DeviceBindError) -> Self {
+        match err {
+            DeviceBindError::OutOfDeviceMemory => BindError::OutOfDeviceMemory,
+            DeviceBindError::OutOfHostMemory => BindError::OutOfHostMemory,
+            DeviceBindError::Unsupported => BindError::Unsupported,
+        }
+    }
+}
+
+impl Display for BindError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindError::OutOfDeviceMemory => fmt.write_str("Device memory exhausted"),
+            BindError::OutOfHostMemory => fmt.write_str("Host memory exhausted"),
+            BindError::NoCompatibleMemoryTypes => fmt.write_str(
+                "No compatible memory types from requested types support requested usage",
+            ),
+            BindError::TooManyObjects => {
+                fmt.write_str("Reached limit on allocated memory objects count")
+            }
+            BindError::AddressSpaceExhausted => fmt.write_str("Host address space exhausted"),
+            BindError::DeviceLost => fmt.write_str("Device is lost"),
+            BindError::Unsupported => fmt.write_str("Device does not support binding resources"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BindError {}
+
+/// Enumeration of possible errors that may occur during upload of data to memory block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UploadError {
+    /// Backend reported that device memory has been exhausted.
+    OutOfDeviceMemory,
+
+    /// Backend reported that host memory has been exhausted.
+    OutOfHostMemory,
+
+    /// No available memory types are compatible with staging usage.
+    NoCompatibleMemoryTypes,
+
+    /// Reached limit on allocated memory objects count.
+    TooManyObjects,
+
+    /// Host address space is exhausted by mapped memory objects
+    /// or written range doesn't fit it.
+    AddressSpaceExhausted,
+
+    /// Device was marked as lost with `GpuAllocator::mark_device_lost`.
+    DeviceLost,
+
+    /// Map failed for implementation specific reason.
+    MapFailed,
+
+    /// Destination block is already mapped.
+    AlreadyMapped,
+}
+
+impl From<AllocationError> for UploadError {
+    fn from(err: AllocationError) -> Self {
+        match err {
+            AllocationError::OutOfDeviceMemory(_) => UploadError::OutOfDeviceMemory,
+            AllocationError::OutOfHostMemory => UploadError::OutOfHostMemory,
+            AllocationError::NoCompatibleMemoryTypes => UploadError::NoCompatibleMemoryTypes,
+            AllocationError::TooManyObjects => UploadError::TooManyObjects,
+            AllocationError::AddressSpaceExhausted => UploadError::AddressSpaceExhausted,
+            AllocationError::DeviceLost => UploadError::DeviceLost,
+        }
+    }
+}
+
+impl From<MapError> for UploadError {
+    fn from(err: MapError) -> Self {
+        match err {
+            MapError::OutOfDeviceMemory => UploadError::OutOfDeviceMemory,
+            MapError::OutOfHostMemory => UploadError::OutOfHostMemory,
+            MapError::NonHostVisible | MapError::MapFailed => UploadError::MapFailed,
+            MapError::AlreadyMapped => UploadError::AlreadyMapped,
+            MapError::AddressSpaceOverflow => UploadError::AddressSpaceExhausted,
+        }
+    }
+}
+
+impl Display for UploadError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadError::OutOfDeviceMemory => fmt.write_str("Device memory exhausted"),
+            UploadError::OutOfHostMemory => fmt.write_str("Host memory exhausted"),
+            UploadError::NoCompatibleMemoryTypes => {
+                fmt.write_str("No compatible memory types support staging usage")
+            }
+            UploadError::TooManyObjects => {
+                fmt.write_str("Reached limit on allocated memory objects count")
+            }
+            UploadError::AddressSpaceExhausted => fmt.write_str("Host address space exhausted"),
+            UploadError::DeviceLost => fmt.write_str("Device is lost"),
+            UploadError::MapFailed => fmt.write_str("Failed to map memory object"),
+            UploadError::AlreadyMapped => fmt.write_str("Block is already mapped"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UploadError {}
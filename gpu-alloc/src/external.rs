@@ -0,0 +1,66 @@
+use {crate::usage::ResourceKind, gpu_alloc_types::AllocationFlags};
+
+/// Type of OS handle exportable memory object is exported as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExternalHandleType {
+    /// Opaque POSIX file descriptor.\
+    /// Corresponds to `cudaExternalMemoryHandleTypeOpaqueFd`.
+    OpaqueFd,
+
+    /// Opaque Win32 handle.\
+    /// Corresponds to `cudaExternalMemoryHandleTypeOpaqueWin32`.
+    OpaqueWin32,
+}
+
+impl ExternalHandleType {
+    /// Returns allocation flag memory object must be allocated with
+    /// to be exportable as this handle type.
+    pub fn allocation_flag(self) -> AllocationFlags {
+        match self {
+            ExternalHandleType::OpaqueFd => AllocationFlags::EXPORT_OPAQUE_FD,
+            ExternalHandleType::OpaqueWin32 => AllocationFlags::EXPORT_OPAQUE_WIN32,
+        }
+    }
+}
+
+/// Description of exportable memory block,
+/// required to import its memory object into another API.
+///
+/// For CUDA handle obtained from backend for `MemoryBlock::memory`,
+/// e.g. with `vkGetMemoryFdKHR`, is imported with `cudaImportExternalMemory`
+/// and block is mapped with `cudaExternalMemoryGetMappedBuffer`.
+///
+/// For OpenGL with `GL_EXT_memory_object` handle is imported with `glImportMemoryFdEXT`
+/// or `glImportMemoryWin32HandleEXT` into memory object
+/// with `GL_DEDICATED_MEMORY_OBJECT_EXT` set to `dedicated`,
+/// and resource is created in it at `offset`,
+/// e.g. with `glBufferStorageMemEXT` or `glTexStorageMem2DEXT`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExternalMemory {
+    /// Handle type memory object is exported as.\
+    /// Corresponds to `cudaExternalMemoryHandleDesc::type`.
+    pub handle_type: ExternalHandleType,
+
+    /// Size of whole memory object.\
+    /// Corresponds to `cudaExternalMemoryHandleDesc::size`.
+    pub memory_size: u64,
+
+    /// Offset of the block in memory object.\
+    /// Corresponds to `cudaExternalMemoryBufferDesc::offset`.
+    pub offset: u64,
+
+    /// Size of the block.\
+    /// Corresponds to `cudaExternalMemoryBufferDesc::size`.
+    pub size: u64,
+
+    /// Memory object is dedicated to resource.\
+    /// Corresponds to `cudaExternalMemoryDedicated` flag of `cudaExternalMemoryHandleDesc::flags`
+    /// and `GL_DEDICATED_MEMORY_OBJECT_EXT` parameter of OpenGL memory object.
+    pub dedicated: bool,
+
+    /// Kind of resource the block was requested for.\
+    /// OpenGL texture must be created with `GL_TEXTURE_TILING_EXT` of `GL_OPTIMAL_TILING_EXT`
+    /// for `ResourceKind::NonLinear` and `GL_LINEAR_TILING_EXT` for linear images,
+    /// matching tiling of Vulkan image bound to the block.
+    pub resource_kind: ResourceKind,
+}
@@ -1,16 +1,24 @@
 use {
     crate::{
         align_down, align_up,
+        allocator::Strategy,
         error::AllocationError,
         heap::Heap,
+        observer::{AllocationObserver, ChunkInfo},
+        plan::reserve,
+        snapshot::{ChunkSnapshot, RegionSnapshot},
         util::{arc_unwrap, is_arc_unique},
         MemoryBounds,
     },
-    alloc::{sync::Arc, vec::Vec},
+    alloc::{collections::BTreeMap, sync::Arc, vec::Vec},
     core::{cmp::Ordering, ptr::NonNull},
     gpu_alloc_types::{AllocationFlags, DeviceMapError, MemoryDevice, MemoryPropertyFlags},
 };
 
+/// Padding after aligned block that is kept free instead of being handed out with the block.
+/// Smaller padding is unlikely to fit other blocks.
+const MIN_TRIMMED_PADDING: u64 = 4096;
+
 unsafe fn opt_ptr_add(ptr: Option<NonNull<u8>>, size: u64) -> Option<NonNull<u8>> {
     ptr.map(|ptr| {
         // Size is within memory region started at `ptr`.
@@ -54,17 +62,14 @@ impl<M> FreeList<M> {
         self.get_block_at(self.array.len() - 1, align_mask, size)
     }
 
-    pub fn get_block(&mut self, align_mask: u64, size: u64) -> Option<FreeListBlock<M>> {
-        let (index, _) = self.array.iter().enumerate().rev().find(|(_, region)| {
-            match region.end.checked_sub(size) {
-                Some(start) => {
-                    let aligned_start = align_down(start, align_mask);
-                    aligned_start >= region.start
-                }
-                None => false,
-            }
-        })?;
-
+    pub fn get_block(
+        &mut self,
+        align_mask: u64,
+        size: u64,
+        best_fit: bool,
+    ) -> Option<FreeListBlock<M>> {
+        let regions = self.array.iter().map(|region| (region.start, region.end));
+        let index = find_region(regions, align_mask, size, best_fit)?;
         Some(self.get_block_at(index, align_mask, size))
     }
 
@@ -74,6 +79,22 @@ impl<M> FreeList<M> {
         let start = region.end - size;
         let aligned_start = align_down(start, align_mask);
 
+        let padding = start - aligned_start;
+        if padding >= size && padding >= MIN_TRIMMED_PADDING {
+            // Alignment is so large that padding after the block would outgrow it.
+            // Keep the padding free instead.
+            let tail = FreeListRegion {
+                memory: region.memory.clone(),
+                ptr: unsafe { opt_ptr_add(region.ptr, aligned_start + size - region.start) },
+                chunk: region.chunk,
+                start: aligned_start + size,
+                end: region.end,
+            };
+            region.end = aligned_start + size;
+            self.array.insert(index + 1, tail);
+            return self.get_block_at(index, align_mask, size);
+        }
+
         if aligned_start > region.start {
             let block = FreeListBlock {
                 offset: aligned_start,
@@ -142,7 +163,7 @@ impl<M> FreeList<M> {
         }
     }
 
-    pub fn drain(&mut self, keep_last: bool) -> Option<impl Iterator<Item = (M, u64)> + '_> {
+    pub fn drain(&mut self, keep_last: bool) -> Option<impl Iterator<Item = (M, u64, u64)> + '_> {
         // Time to deallocate
 
         let len = self.array.len();
@@ -163,7 +184,11 @@ impl<M> FreeList<M> {
         if del > 0 {
             Some(self.array.drain(len - del..).map(move |region| {
                 debug_assert_eq!(region.start, 0);
-                (unsafe { arc_unwrap(region.memory) }, region.end)
+                (
+                    unsafe { arc_unwrap(region.memory) },
+                    region.chunk,
+                    region.end,
+                )
             }))
         } else {
             None
@@ -299,6 +324,14 @@ pub struct FreeListBlock<M> {
 unsafe impl<M> Sync for FreeListBlock<M> where M: Sync {}
 unsafe impl<M> Send for FreeListBlock<M> where M: Send {}
 
+/// Allocator that carves blocks from the ends of free regions of its chunks.
+/// Blocks are taken from the last region that fits,
+/// or from the smallest one in best-fit mode.
+///
+/// Deallocated blocks are returned to free list right away and merged with
+/// neighbouring free regions, so freed ranges are reused before whole chunk is free.
+/// Chunk is deallocated once it becomes single free region,
+/// except one kept around to serve next allocations.
 #[derive(Debug)]
 pub(crate) struct FreeListAllocator<M> {
     freelist: FreeList<M>,
@@ -307,9 +340,13 @@ pub(crate) struct FreeListAllocator<M> {
     memory_type: u32,
     props: MemoryPropertyFlags,
     atom_mask: u64,
+    best_fit: bool,
+    observer: Option<Arc<dyn AllocationObserver>>,
 
     total_allocations: u64,
     total_deallocations: u64,
+    /// Sizes of memory objects allocated by this allocator.
+    chunks: BTreeMap<u64, u64>,
 }
 
 impl<M> Drop for FreeListAllocator<M> {
@@ -342,16 +379,18 @@ where
         memory_type: u32,
         props: MemoryPropertyFlags,
         atom_mask: u64,
+        best_fit: bool,
+        observer: Option<Arc<dyn AllocationObserver>>,
     ) -> Self {
         debug_assert_eq!(
             align_down(starting_chunk_size, atom_mask),
             starting_chunk_size
         );
 
-        let starting_chunk_size = min(starting_chunk_size, isize::max_value());
+        let starting_chunk_size = min(starting_chunk_size, isize::MAX);
 
         debug_assert_eq!(align_down(final_chunk_size, atom_mask), final_chunk_size);
-        let final_chunk_size = min(final_chunk_size, isize::max_value());
+        let final_chunk_size = min(final_chunk_size, isize::MAX);
 
         FreeListAllocator {
             freelist: FreeList::new(),
@@ -360,12 +399,49 @@ where
             memory_type,
             props,
             atom_mask,
+            best_fit,
+            observer,
 
             total_allocations: 0,
             total_deallocations: 0,
+            chunks: BTreeMap::new(),
         }
     }
 
+    /// Returns size of memory object allocated next.
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Returns size of memory object with specified index.
+    pub fn memory_size(&self, chunk: u64) -> u64 {
+        self.chunks[&chunk]
+    }
+
+    /// Sets size of memory object allocated next.
+    /// Memory object is still made large enough for request that triggers allocation.
+    pub fn set_chunk_size(&mut self, chunk_size: u64) {
+        let chunk_size =
+            align_up(chunk_size.max(1), self.atom_mask).unwrap_or(self.final_chunk_size);
+        self.chunk_size = min(chunk_size, isize::MAX);
+    }
+
+    /// Allocates block from memory objects already allocated.
+    /// Returns `None` if no free region is large enough.
+    pub fn alloc_existing(&mut self, size: u64, align_mask: u64) -> Option<FreeListBlock<M>> {
+        let size = align_up(size, self.atom_mask)?;
+        let align_mask = align_mask | self.atom_mask;
+
+        if size > self.chunk_size {
+            // There can't be any sufficiently large free blocks
+            return None;
+        }
+
+        let block = self.freelist.get_block(align_mask, size, self.best_fit)?;
+        self.total_allocations += 1;
+        Some(block)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
     pub unsafe fn alloc(
         &mut self,
@@ -385,17 +461,13 @@ where
             "Any value not greater than final chunk size (which is aligned) has to fit for alignment",
         );
 
+        if let Some(block) = self.alloc_existing(size, align_mask) {
+            return Ok(block);
+        }
+
         let align_mask = align_mask | self.atom_mask;
         let host_visible = self.host_visible();
 
-        if size <= self.chunk_size {
-            // Otherwise there can't be any sufficiently large free blocks
-            if let Some(block) = self.freelist.get_block(align_mask, size) {
-                self.total_allocations += 1;
-                return Ok(block);
-            }
-        }
-
         // New allocation is required.
         if *allocations_remains == 0 {
             return Err(AllocationError::TooManyObjects);
@@ -416,30 +488,38 @@ where
         let ptr = if host_visible {
             match device.map_memory(&mut memory, 0, self.chunk_size) {
                 Ok(ptr) => Some(ptr),
-                Err(DeviceMapError::MapFailed) => {
+                Err(err) => {
                     #[cfg(feature = "tracing")]
                     tracing::error!("Failed to map host-visible memory in linear allocator");
                     device.deallocate_memory(memory);
                     *allocations_remains += 1;
                     heap.dealloc(self.chunk_size);
 
-                    return Err(AllocationError::OutOfHostMemory);
-                }
-                Err(DeviceMapError::OutOfDeviceMemory) => {
-                    return Err(AllocationError::OutOfDeviceMemory);
-                }
-                Err(DeviceMapError::OutOfHostMemory) => {
-                    return Err(AllocationError::OutOfHostMemory);
+                    return Err(match err {
+                        DeviceMapError::OutOfDeviceMemory => {
+                            AllocationError::OutOfDeviceMemory(None)
+                        }
+                        DeviceMapError::OutOfHostMemory => AllocationError::OutOfHostMemory,
+                        DeviceMapError::MapFailed => AllocationError::AddressSpaceExhausted,
+                    });
                 }
             }
         } else {
             None
         };
 
+        if let Some(observer) = &self.observer {
+            observer.on_chunk_create(&ChunkInfo {
+                memory_type: self.memory_type,
+                size: self.chunk_size,
+            });
+        }
+
         let memory = Arc::new(memory);
         let block =
             self.freelist
                 .get_block_from_new_memory(memory, self.chunk_size, ptr, align_mask, size);
+        self.chunks.insert(block.chunk, self.chunk_size);
 
         if self.chunk_size < self.final_chunk_size {
             // Double next chunk size
@@ -459,20 +539,41 @@ where
         heap: &mut Heap,
         allocations_remains: &mut u32,
     ) {
-        debug_assert!(block.size < self.chunk_size);
+        debug_assert!(matches!(self.chunks.get(&block.chunk), Some(&size) if block.size <= size));
         debug_assert_ne!(block.size, 0);
         self.freelist.insert_block(block);
         self.total_deallocations += 1;
 
         if let Some(memory) = self.freelist.drain(true) {
-            memory.for_each(|(memory, size)| {
+            let memory_type = self.memory_type;
+            let observer = &self.observer;
+            let chunks = &mut self.chunks;
+            memory.for_each(|(memory, chunk, size)| {
                 device.deallocate_memory(memory);
                 *allocations_remains += 1;
                 heap.dealloc(size);
+                chunks.remove(&chunk);
+
+                if let Some(observer) = observer {
+                    observer.on_chunk_destroy(&ChunkInfo { memory_type, size });
+                }
             });
         }
     }
 
+    /// Returns range cut out of allocated block to free list.
+    /// `split` is `true` if block was split in two by the cut.
+    ///
+    /// Memory object is never deallocated here,
+    /// as remains of the block keep it alive.
+    pub fn dealloc_range(&mut self, range: FreeListBlock<M>, split: bool) {
+        debug_assert_ne!(range.size, 0);
+        self.freelist.insert_block(range);
+        if split {
+            self.total_allocations += 1;
+        }
+    }
+
     /// Deallocates leftover memory objects.
     /// Should be used before dropping.
     ///
@@ -489,10 +590,18 @@ where
         allocations_remains: &mut u32,
     ) {
         if let Some(memory) = self.freelist.drain(false) {
-            memory.for_each(|(memory, size)| {
+            let memory_type = self.memory_type;
+            let observer = &self.observer;
+            let chunks = &mut self.chunks;
+            memory.for_each(|(memory, chunk, size)| {
                 device.deallocate_memory(memory);
                 *allocations_remains += 1;
                 heap.dealloc(size);
+                chunks.remove(&chunk);
+
+                if let Some(observer) = observer {
+                    observer.on_chunk_destroy(&ChunkInfo { memory_type, size });
+                }
             });
         }
 
@@ -511,11 +620,176 @@ where
         }
     }
 
+    /// Forgets memory objects and blocks after device is lost,
+    /// so this allocator is dropped without reporting them.
+    pub fn device_lost(&mut self) {
+        self.freelist.array.clear();
+        self.chunks.clear();
+        self.total_deallocations = self.total_allocations;
+    }
+
+    /// Returns number and total size of memory objects allocated by this allocator.
+    pub fn memory_objects(&self) -> (u64, u64) {
+        (self.chunks.len() as u64, self.chunks.values().sum())
+    }
+
+    /// Appends memory objects of this allocator with their free regions to `chunks`.
+    pub fn snapshot(&self, chunks: &mut Vec<ChunkSnapshot>) {
+        for (&chunk, &size) in &self.chunks {
+            let mut free_regions: Vec<_> = self
+                .freelist
+                .array
+                .iter()
+                .filter(|region| region.chunk == chunk)
+                .map(|region| RegionSnapshot {
+                    offset: region.start,
+                    size: region.end - region.start,
+                })
+                .collect();
+            free_regions.sort_by_key(|region| region.offset);
+
+            chunks.push(ChunkSnapshot {
+                memory_type: self.memory_type,
+                pool: None,
+                device_address: false,
+                strategy: if self.best_fit {
+                    Strategy::BestFit
+                } else {
+                    Strategy::FreeList
+                },
+                chunk,
+                size,
+                free_regions,
+            });
+        }
+    }
+
+    /// Returns copy of this allocator state sufficient to simulate allocations.
+    pub fn plan(&self) -> FreeListPlan {
+        FreeListPlan {
+            regions: self
+                .freelist
+                .array
+                .iter()
+                .map(|region| (region.start, region.end))
+                .collect(),
+            chunk_size: self.chunk_size,
+            final_chunk_size: self.final_chunk_size,
+            atom_mask: self.atom_mask,
+            best_fit: self.best_fit,
+        }
+    }
+
     fn host_visible(&self) -> bool {
         self.props.contains(MemoryPropertyFlags::HOST_VISIBLE)
     }
 }
 
+/// State of `FreeListAllocator` with free regions detached from memory objects.
+#[derive(Clone, Debug)]
+pub(crate) struct FreeListPlan {
+    regions: Vec<(u64, u64)>,
+    chunk_size: u64,
+    final_chunk_size: u64,
+    atom_mask: u64,
+    best_fit: bool,
+}
+
+impl FreeListPlan {
+    /// Returns state of `FreeListAllocator` created with the same arguments.
+    pub fn new(
+        starting_chunk_size: u64,
+        final_chunk_size: u64,
+        atom_mask: u64,
+        best_fit: bool,
+    ) -> Self {
+        FreeListPlan {
+            regions: Vec::new(),
+            chunk_size: min(starting_chunk_size, isize::MAX),
+            final_chunk_size: min(final_chunk_size, isize::MAX),
+            atom_mask,
+            best_fit,
+        }
+    }
+
+    /// Simulates `FreeListAllocator::alloc`.
+    /// Returns size of new memory object if one would be allocated.
+    pub fn alloc(
+        &mut self,
+        size: u64,
+        align_mask: u64,
+        heap_available: &mut u64,
+        allocations_remains: &mut u32,
+    ) -> Result<Option<u64>, AllocationError> {
+        let size =
+            align_up(size, self.atom_mask).ok_or(AllocationError::OutOfDeviceMemory(None))?;
+        let align_mask = align_mask | self.atom_mask;
+
+        if size <= self.chunk_size {
+            let regions = self.regions.iter().copied();
+            let found = find_region(regions, align_mask, size, self.best_fit);
+
+            if let Some(index) = found {
+                self.take_block(index, align_mask, size);
+                return Ok(None);
+            }
+        }
+
+        let mut chunk_size = self.chunk_size;
+        if size > chunk_size {
+            let multiple = (size - 1) / chunk_size + 1;
+            let multiple = multiple.next_power_of_two();
+
+            chunk_size = (chunk_size * multiple).min(self.final_chunk_size);
+        }
+
+        reserve(chunk_size, heap_available, allocations_remains)?;
+
+        self.chunk_size = chunk_size;
+        self.regions.push((0, chunk_size));
+        self.take_block(self.regions.len() - 1, align_mask, size);
+
+        if self.chunk_size < self.final_chunk_size {
+            self.chunk_size = (self.chunk_size * 2).min(self.final_chunk_size);
+        }
+
+        Ok(Some(chunk_size))
+    }
+
+    fn take_block(&mut self, index: usize, align_mask: u64, size: u64) {
+        let (start, end) = self.regions[index];
+        let aligned_start = align_down(end - size, align_mask);
+
+        if aligned_start > start {
+            self.regions[index].1 = aligned_start;
+        } else {
+            self.regions.remove(index);
+        }
+    }
+}
+
+/// Returns index of free region to take block from.
+/// Picks the last region that fits, or the smallest one if `best_fit` is `true`.
+fn find_region(
+    regions: impl Iterator<Item = (u64, u64)>,
+    align_mask: u64,
+    size: u64,
+    best_fit: bool,
+) -> Option<usize> {
+    let mut found: Option<(usize, u64)> = None;
+    for (index, (start, end)) in regions.enumerate() {
+        let fits = matches!(end.checked_sub(size), Some(block_start) if align_down(block_start, align_mask) >= start);
+        let better = match found {
+            Some((_, found_size)) => !best_fit || end - start <= found_size,
+            None => true,
+        };
+        if fits && better {
+            found = Some((index, end - start));
+        }
+    }
+    found.map(|(index, _)| index)
+}
+
 fn min<L, R>(l: L, r: R) -> L
 where
     R: core::convert::TryInto<L>,
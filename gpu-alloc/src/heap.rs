@@ -1,9 +1,28 @@
+#[cfg(feature = "std")]
+use {
+    alloc::sync::Arc,
+    core::sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
 #[derive(Debug)]
 pub(crate) struct Heap {
     size: u64,
     used: u64,
     allocated: u128,
     deallocated: u128,
+    /// Number of pressure thresholds reached at last check.
+    pressure_level: usize,
+    /// State shared with other allocators of the same heaps, see `SyncGpuAllocator`.
+    #[cfg(feature = "std")]
+    shared: Option<Arc<SharedHeap>>,
+}
+
+/// Heap accounting shared by allocators of the same device.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub(crate) struct SharedHeap {
+    used: AtomicU64,
+    pressure_level: AtomicUsize,
 }
 
 impl Heap {
@@ -13,20 +32,78 @@ impl Heap {
             used: 0,
             allocated: 0,
             deallocated: 0,
+            pressure_level: 0,
+            #[cfg(feature = "std")]
+            shared: None,
         }
     }
 
-    pub(crate) fn size(&mut self) -> u64 {
+    /// Makes this heap account its usage in `shared` state together with other allocators.
+    #[cfg(feature = "std")]
+    pub(crate) fn share(&mut self, shared: Arc<SharedHeap>) {
+        debug_assert_eq!(self.used, 0, "Heap must be shared before use");
+        self.shared = Some(shared);
+    }
+
+    pub(crate) fn size(&self) -> u64 {
         self.size
     }
 
+    /// Returns size of allocated memory objects,
+    /// including ones allocated by other allocators sharing the heap.
+    pub(crate) fn used(&self) -> u64 {
+        #[cfg(feature = "std")]
+        {
+            if let Some(shared) = &self.shared {
+                return shared.used.load(Ordering::Relaxed);
+            }
+        }
+        self.used
+    }
+
+    /// Returns size of memory objects allocated by this allocator.
+    pub(crate) fn own_used(&self) -> u64 {
+        self.used
+    }
+
+    /// Returns size of the heap not used by allocated memory objects.
+    pub(crate) fn available(&self) -> u64 {
+        self.size.saturating_sub(self.used())
+    }
+
     pub(crate) fn alloc(&mut self, size: u64) {
         self.used += size;
         self.allocated += u128::from(size);
+        #[cfg(feature = "std")]
+        {
+            if let Some(shared) = &self.shared {
+                shared.used.fetch_add(size, Ordering::Relaxed);
+            }
+        }
     }
 
     pub(crate) fn dealloc(&mut self, size: u64) {
         self.used -= size;
         self.deallocated += u128::from(size);
+        #[cfg(feature = "std")]
+        {
+            if let Some(shared) = &self.shared {
+                shared.used.fetch_sub(size, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Stores number of pressure thresholds reached by the heap and returns previous one.
+    ///
+    /// Level of shared heap is shared too,
+    /// so only one of allocators sharing the heap observes each change.
+    pub(crate) fn swap_pressure_level(&mut self, level: usize) -> usize {
+        #[cfg(feature = "std")]
+        {
+            if let Some(shared) = &self.shared {
+                return shared.pressure_level.swap(level, Ordering::Relaxed);
+            }
+        }
+        core::mem::replace(&mut self.pressure_level, level)
     }
 }
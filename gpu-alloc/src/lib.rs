@@ -22,6 +22,11 @@
 
 extern crate alloc;
 
+// Dependency is renamed in manifest so that "serde" feature can also enable
+// serde support in `gpu-alloc-types`. Derives find it with `serde(crate = "serde")`.
+#[cfg(feature = "serde")]
+extern crate serde_crate as serde;
+
 #[cfg(feature = "tracing")]
 macro_rules! report_error_on_drop {
     ($($tokens:tt)*) => {{
@@ -53,24 +58,92 @@ macro_rules! report_error_on_drop {
     }};
 }
 
+/// Reports error that caller can recover from,
+/// unlike `report_error_on_drop` it never panics nor returns.
+macro_rules! report_error {
+    ($($tokens:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        tracing::error!($($tokens)*);
+
+        #[cfg(all(not(feature = "tracing"), feature = "std"))]
+        eprintln!($($tokens)*);
+
+        #[cfg(all(not(feature = "tracing"), not(feature = "std")))]
+        let _ = format_args!($($tokens)*);
+    }};
+}
+
 mod allocator;
 mod block;
 mod buddy;
+mod budget;
+mod buffer;
 mod config;
 mod error;
+mod external;
 mod freelist;
 mod heap;
+mod memory_map;
+mod observer;
+mod plan;
+mod pool;
+mod preallocated;
+mod recording;
+mod relocation;
+mod report;
+mod request;
+mod ring;
+mod scope;
+#[cfg(feature = "std")]
+mod shared;
 mod slab;
+mod snapshot;
+mod sparse;
+mod staging;
+#[cfg(feature = "std")]
+mod sync;
+mod tlsf;
+#[cfg(feature = "tracy")]
+mod tracy;
+mod tuning;
+mod upload;
 mod usage;
 mod util;
 
 pub use {
-    self::{allocator::*, block::MemoryBlock, config::*, error::*, usage::*},
+    self::{
+        allocator::*,
+        block::{MapGuard, MappedBlock, MemoryBlock},
+        buffer::*,
+        config::*,
+        error::*,
+        external::*,
+        observer::*,
+        plan::*,
+        pool::{PoolConfig, PoolId, PoolStrategy},
+        recording::*,
+        relocation::*,
+        report::*,
+        request::*,
+        scope::*,
+        snapshot::*,
+        sparse::*,
+        staging::*,
+        tlsf::{VirtualAllocator, VirtualBlock},
+        upload::*,
+        usage::*,
+    },
     gpu_alloc_types::*,
 };
 
+#[cfg(feature = "std")]
+pub use self::{
+    shared::{SharedGpuAllocator, SharedMemoryBlock},
+    sync::SyncGpuAllocator,
+};
+
 /// Memory request for allocator.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Request {
     /// Minimal size of memory block required.
     /// Returned block may have larger size,
@@ -91,6 +164,83 @@ pub struct Request {
     /// Returned block will be from memory type corresponding to one of set bits,
     /// use `MemoryBlock::memory_type` to learn memory type index of returned block.
     pub memory_types: u32,
+
+    /// Index of memory type to allocate from, bypassing selection based on
+    /// `usage` and `access`.\
+    /// Memory type must still be in `memory_types`,
+    /// otherwise allocation fails with `AllocationError::NoCompatibleMemoryTypes`.
+    /// Caller is responsible for memory type to support intended usage.
+    pub memory_type: Option<u32>,
+
+    /// Hint on how host will access memory.
+    /// Implies `HOST_ACCESS` usage flag if set.
+    /// If `None`, preferred memory properties are derived from `usage`.
+    pub access: Option<AccessPattern>,
+
+    /// Label reported to `AllocationObserver` for returned block.\
+    /// Also identifies the block in `GpuAllocator::report_leaks`.
+    pub label: Option<&'static str>,
+
+    /// Hint to serve the request with dedicated memory object.
+    /// Set according to `VkMemoryDedicatedRequirements` of the resource.
+    /// Hint passed to `GpuAllocator::alloc_with_dedicated` takes precedence.
+    pub dedicated: Option<Dedicated>,
+
+    /// Resource that dedicated memory object is allocated for,
+    /// passed to `MemoryDevice::allocate_dedicated_memory`.\
+    /// Ignored if the request is not served by dedicated memory object.
+    /// Blocks in memory objects dedicated to resource are never relocated.
+    pub dedicated_resource: Option<DedicatedResource>,
+
+    /// Handle types memory object must be exportable as.
+    /// Only `AllocationFlags::EXPORT_OPAQUE_FD` and `AllocationFlags::EXPORT_OPAQUE_WIN32`
+    /// are allowed.\
+    /// Request with export flags is always served by dedicated memory object
+    /// that is never shared with other blocks nor relocated.
+    /// Memory type must support export of requested handle types.
+    pub export: AllocationFlags,
+
+    /// Priority of memory in range `[0.0, 1.0]`, passed to `MemoryDevice::allocate_memory_with_priority`.
+    /// Memory with lower priority is evicted first when device-local heap is oversubscribed.\
+    /// Dedicated memory object gets priority of its request.
+    /// Memory objects are shared only by blocks of the same priority class:
+    /// blocks without priority, with priority `0.0`,
+    /// or with priority rounded up to the same quarter of `[0.0, 1.0]`.
+    /// Shared memory object gets highest priority of its class,
+    /// so it is never evicted earlier than any of its blocks asks for.
+    /// If `None`, memory object is allocated with `MemoryDevice::allocate_memory`.
+    pub priority: Option<f32>,
+
+    /// Flags controlling how allocator may serve the request.
+    pub flags: RequestFlags,
+
+    /// Kind of resource the block is requested for.
+    /// Blocks for non-linear resources are padded to `DeviceProperties::buffer_image_granularity`,
+    /// other requests don't need to be over-aligned for it.
+    pub resource_kind: ResourceKind,
+}
+
+impl Default for Request {
+    /// Returns request for empty block with any alignment
+    /// from any memory type with implied `FAST_DEVICE_ACCESS` usage,
+    /// for linear resource.
+    fn default() -> Self {
+        Request {
+            size: 0,
+            align_mask: 0,
+            usage: UsageFlags::empty(),
+            memory_types: !0,
+            memory_type: None,
+            access: None,
+            label: None,
+            dedicated: None,
+            dedicated_resource: None,
+            export: AllocationFlags::empty(),
+            priority: None,
+            flags: RequestFlags::empty(),
+            resource_kind: ResourceKind::Linear,
+        }
+    }
 }
 
 /// Aligns `value` up to `align_mask`
@@ -0,0 +1,156 @@
+use {
+    crate::snapshot::{AllocatorSnapshot, ChunkSnapshot},
+    alloc::string::String,
+    core::fmt::{self, Write as _},
+};
+
+const WIDTH: f64 = 1024.0;
+const ROW_HEIGHT: u64 = 24;
+const ROW_GAP: u64 = 20;
+const HEADER: u64 = 40;
+
+const FREE_COLOR: &str = "#9be39b";
+const BLOCK_COLOR: &str = "#4a7fd6";
+const PADDING_COLOR: &str = "#e0b040";
+const UNUSABLE_COLOR: &str = "#d65a4a";
+
+/// Renders memory objects of the snapshot as SVG.
+///
+/// Each memory object is a row scaled to full width.
+/// Blue ranges are occupied by blocks, yellow is padding at the end of blocks,
+/// green is free and red is neither occupied nor available for allocation.
+pub(crate) fn render_svg(snapshot: &AllocatorSnapshot) -> String {
+    let mut svg = String::new();
+    write_svg(&mut svg, snapshot).expect("Writing to string never fails");
+    svg
+}
+
+fn write_svg(out: &mut String, snapshot: &AllocatorSnapshot) -> fmt::Result {
+    let rows = snapshot.chunks.len() as u64;
+    let height = HEADER + rows * (ROW_HEIGHT + ROW_GAP);
+
+    let total: u64 = snapshot.chunks.iter().map(|chunk| chunk.size).sum();
+    let free: u64 = snapshot
+        .chunks
+        .iter()
+        .flat_map(|chunk| chunk.free_regions.iter())
+        .map(|region| region.size)
+        .sum();
+    let largest_free = snapshot
+        .chunks
+        .iter()
+        .flat_map(|chunk| chunk.free_regions.iter())
+        .map(|region| region.size)
+        .max()
+        .unwrap_or(0);
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="12">"#,
+        WIDTH, height
+    )?;
+    writeln!(
+        out,
+        r#"<text x="0" y="16">{} memory objects, {} bytes, {} free, largest free region {} bytes</text>"#,
+        rows, total, free, largest_free
+    )?;
+
+    for (row, chunk) in snapshot.chunks.iter().enumerate() {
+        let y = HEADER + row as u64 * (ROW_HEIGHT + ROW_GAP);
+        write_chunk(out, snapshot, chunk, y)?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+fn write_chunk(
+    out: &mut String,
+    snapshot: &AllocatorSnapshot,
+    chunk: &ChunkSnapshot,
+    y: u64,
+) -> fmt::Result {
+    let scale = WIDTH / chunk.size as f64;
+    let bar = y + ROW_GAP - 6;
+
+    match chunk.pool {
+        None => writeln!(
+            out,
+            r#"<text x="0" y="{}">memory type {} {:?} #{}: {} bytes</text>"#,
+            y + 12,
+            chunk.memory_type,
+            chunk.strategy,
+            chunk.chunk,
+            chunk.size
+        )?,
+        Some(pool) => writeln!(
+            out,
+            r#"<text x="0" y="{}">memory type {} {:?} #{} in {:?}: {} bytes</text>"#,
+            y + 12,
+            chunk.memory_type,
+            chunk.strategy,
+            chunk.chunk,
+            pool,
+            chunk.size
+        )?,
+    }
+
+    // Everything not covered by blocks or free regions is unusable.
+    rect(out, 0, chunk.size, bar, scale, UNUSABLE_COLOR, "unusable")?;
+
+    for region in &chunk.free_regions {
+        rect(
+            out,
+            region.offset,
+            region.size,
+            bar,
+            scale,
+            FREE_COLOR,
+            "free",
+        )?;
+    }
+
+    let blocks = snapshot.blocks.iter().filter(|block| block.is_in(chunk));
+
+    for block in blocks {
+        let used = block.size - block.padding;
+        rect(out, block.offset, used, bar, scale, BLOCK_COLOR, "block")?;
+        rect(
+            out,
+            block.offset + used,
+            block.padding,
+            bar,
+            scale,
+            PADDING_COLOR,
+            "padding",
+        )?;
+    }
+
+    Ok(())
+}
+
+fn rect(
+    out: &mut String,
+    offset: u64,
+    size: u64,
+    y: u64,
+    scale: f64,
+    color: &str,
+    kind: &str,
+) -> fmt::Result {
+    if size == 0 {
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        r#"<rect x="{:.2}" y="{}" width="{:.2}" height="{}" fill="{}"><title>{} {}..{}</title></rect>"#,
+        offset as f64 * scale,
+        y,
+        size as f64 * scale,
+        ROW_HEIGHT,
+        color,
+        kind,
+        offset,
+        offset + size
+    )
+}
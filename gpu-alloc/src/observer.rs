@@ -0,0 +1,250 @@
+use {
+    alloc::{boxed::Box, vec::Vec},
+    core::{
+        fmt::{self, Debug},
+        sync::atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Description of memory block passed to `AllocationObserver`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryBlockInfo<'a> {
+    /// Generation of the block, unique among blocks of one allocator.
+    /// Pairs `on_alloc` and `on_dealloc` calls for the same block,
+    /// e.g. as address of allocation reported to memory profiler.
+    pub generation: u64,
+
+    /// Index of the memory type.
+    pub memory_type: u32,
+
+    /// Offset of the block in its memory object.
+    pub offset: u64,
+
+    /// Size of the block.
+    pub size: u64,
+
+    /// Label from the request.
+    pub label: Option<&'a str>,
+
+    /// Whether the block owns whole memory object.
+    pub dedicated: bool,
+}
+
+/// Description of memory object shared by blocks passed to `AllocationObserver`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChunkInfo {
+    /// Index of the memory type.
+    pub memory_type: u32,
+
+    /// Size of the memory object.
+    pub size: u64,
+}
+
+/// Utilization of memory heap that crossed one of `Config::pressure_thresholds`,
+/// passed to `AllocationObserver`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HeapPressure {
+    /// Index of the memory heap.
+    pub heap: u32,
+
+    /// Size of memory objects allocated by allocator from the heap.
+    pub used: u64,
+
+    /// Budget of the heap reported by `MemoryDevice::memory_budget`,
+    /// or size of the heap if device reports no budget.
+    pub budget: u64,
+
+    /// Highest threshold in percents of `budget` that `used` reaches,
+    /// `None` if it is below all thresholds.
+    pub threshold: Option<u8>,
+
+    /// Highest threshold reached before this change.
+    pub previous: Option<u8>,
+}
+
+/// Receives notifications about allocator state changes as they happen.
+///
+/// Set with `Config::observer` to mirror allocator state in external tools,
+/// such as profilers.
+/// With "tracy" feature allocator emits Tracy memory events
+/// without an observer.
+/// Callbacks are invoked synchronously from allocator methods
+/// and should be cheap.
+///
+/// Dedicated memory objects are reported only as blocks.
+/// Memory objects shared by blocks are additionally reported as chunks.
+pub trait AllocationObserver: Send + Sync {
+    /// Called after memory block is allocated.
+    fn on_alloc(&self, info: &MemoryBlockInfo<'_>) {
+        let _ = info;
+    }
+
+    /// Called before memory block is deallocated.
+    fn on_dealloc(&self, info: &MemoryBlockInfo<'_>) {
+        let _ = info;
+    }
+
+    /// Called after memory object for sub-allocation is allocated from device.
+    fn on_chunk_create(&self, info: &ChunkInfo) {
+        let _ = info;
+    }
+
+    /// Called after memory object for sub-allocation is deallocated.
+    fn on_chunk_destroy(&self, info: &ChunkInfo) {
+        let _ = info;
+    }
+
+    /// Called when heap utilization crosses one of `Config::pressure_thresholds`,
+    /// either rising or falling.
+    /// Can be used to stream resources out before allocations start to fail.
+    fn on_heap_pressure(&self, pressure: &HeapPressure) {
+        let _ = pressure;
+    }
+}
+
+impl Debug for dyn AllocationObserver {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "AllocationObserver({:p})", self)
+    }
+}
+
+/// Observers are equal only if they are the same object.
+impl PartialEq for dyn AllocationObserver {
+    fn eq(&self, other: &Self) -> bool {
+        self as *const Self as *const u8 == other as *const Self as *const u8
+    }
+}
+
+impl Eq for dyn AllocationObserver {}
+
+#[derive(Debug, Default)]
+struct MemoryTypeCounters {
+    bytes_allocated: AtomicU64,
+    block_count: AtomicU64,
+    device_allocations: AtomicU64,
+}
+
+/// Observer that keeps per memory type counters of live blocks and memory objects.
+///
+/// Counters can be read from any thread without locking the allocator,
+/// e.g. to publish them as gauges through a metrics facade.
+///
+/// With "metrics" feature counters are also published through `metrics` facade
+/// as `gpu_alloc.bytes_allocated`, `gpu_alloc.block_count` and `gpu_alloc.device_allocations`
+/// gauges with `memory_type` label.
+#[derive(Debug)]
+pub struct AllocationCounters {
+    memory_types: Box<[MemoryTypeCounters]>,
+}
+
+impl AllocationCounters {
+    /// Creates counters for `memory_type_count` memory types, all zero.
+    pub fn new(memory_type_count: usize) -> Self {
+        AllocationCounters {
+            memory_types: (0..memory_type_count)
+                .map(|_| MemoryTypeCounters::default())
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        }
+    }
+
+    /// Returns number of memory types counters are kept for.
+    pub fn memory_type_count(&self) -> usize {
+        self.memory_types.len()
+    }
+
+    /// Returns total size of live blocks in memory type.
+    pub fn bytes_allocated(&self, memory_type: u32) -> u64 {
+        self.counters(memory_type).map_or(0, |counters| {
+            counters.bytes_allocated.load(Ordering::Relaxed)
+        })
+    }
+
+    /// Returns number of live blocks in memory type.
+    pub fn block_count(&self, memory_type: u32) -> u64 {
+        self.counters(memory_type)
+            .map_or(0, |counters| counters.block_count.load(Ordering::Relaxed))
+    }
+
+    /// Returns number of memory objects allocated from device in memory type,
+    /// both shared by blocks and dedicated.
+    pub fn device_allocations(&self, memory_type: u32) -> u64 {
+        self.counters(memory_type).map_or(0, |counters| {
+            counters.device_allocations.load(Ordering::Relaxed)
+        })
+    }
+
+    fn counters(&self, memory_type: u32) -> Option<&MemoryTypeCounters> {
+        self.memory_types.get(memory_type as usize)
+    }
+}
+
+impl AllocationObserver for AllocationCounters {
+    fn on_alloc(&self, info: &MemoryBlockInfo<'_>) {
+        if let Some(counters) = self.counters(info.memory_type) {
+            counters
+                .bytes_allocated
+                .fetch_add(info.size, Ordering::Relaxed);
+            counters.block_count.fetch_add(1, Ordering::Relaxed);
+            if info.dedicated {
+                counters.device_allocations.fetch_add(1, Ordering::Relaxed);
+            }
+
+            #[cfg(feature = "metrics")]
+            {
+                let size = info.size as f64;
+                publish("gpu_alloc.bytes_allocated", info.memory_type, size);
+                publish("gpu_alloc.block_count", info.memory_type, 1.0);
+                if info.dedicated {
+                    publish("gpu_alloc.device_allocations", info.memory_type, 1.0);
+                }
+            }
+        }
+    }
+
+    fn on_dealloc(&self, info: &MemoryBlockInfo<'_>) {
+        if let Some(counters) = self.counters(info.memory_type) {
+            counters
+                .bytes_allocated
+                .fetch_sub(info.size, Ordering::Relaxed);
+            counters.block_count.fetch_sub(1, Ordering::Relaxed);
+            if info.dedicated {
+                counters.device_allocations.fetch_sub(1, Ordering::Relaxed);
+            }
+
+            #[cfg(feature = "metrics")]
+            {
+                let size = info.size as f64;
+                publish("gpu_alloc.bytes_allocated", info.memory_type, -size);
+                publish("gpu_alloc.block_count", info.memory_type, -1.0);
+                if info.dedicated {
+                    publish("gpu_alloc.device_allocations", info.memory_type, -1.0);
+                }
+            }
+        }
+    }
+
+    fn on_chunk_create(&self, info: &ChunkInfo) {
+        if let Some(counters) = self.counters(info.memory_type) {
+            counters.device_allocations.fetch_add(1, Ordering::Relaxed);
+
+            #[cfg(feature = "metrics")]
+            publish("gpu_alloc.device_allocations", info.memory_type, 1.0);
+        }
+    }
+
+    fn on_chunk_destroy(&self, info: &ChunkInfo) {
+        if let Some(counters) = self.counters(info.memory_type) {
+            counters.device_allocations.fetch_sub(1, Ordering::Relaxed);
+
+            #[cfg(feature = "metrics")]
+            publish("gpu_alloc.device_allocations", info.memory_type, -1.0);
+        }
+    }
+}
+
+/// Adds `delta` to gauge `name` of memory type.
+#[cfg(feature = "metrics")]
+fn publish(name: &'static str, memory_type: u32, delta: f64) {
+    metrics_crate::gauge!(name, "memory_type" => memory_type.to_string()).increment(delta);
+}
@@ -0,0 +1,71 @@
+use {
+    crate::{allocator::Strategy, error::AllocationError},
+    alloc::vec::Vec,
+};
+
+/// Outcome of single request simulated by `GpuAllocator::plan`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlannedAllocation {
+    /// Request would be served from memory object that is already allocated
+    /// or that would be allocated for preceding request.
+    Existing {
+        /// Index of the memory type.
+        memory_type: u32,
+
+        /// Strategy that would serve the request.
+        strategy: Strategy,
+    },
+
+    /// Request would trigger allocation of new memory object.
+    NewMemory {
+        /// Index of the memory type.
+        memory_type: u32,
+
+        /// Strategy that would serve the request.
+        strategy: Strategy,
+
+        /// Size of new memory object.
+        size: u64,
+    },
+
+    /// Request would fail with specified error.
+    Failed(AllocationError),
+}
+
+/// Result of `GpuAllocator::plan`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanResult {
+    /// Outcome for each request in order.
+    pub allocations: Vec<PlannedAllocation>,
+
+    /// Total size of new memory objects required to serve the requests.
+    pub additional_memory: u64,
+}
+
+impl PlanResult {
+    /// Returns `true` if all requests would succeed.
+    pub fn fits(&self) -> bool {
+        self.allocations
+            .iter()
+            .all(|allocation| !matches!(allocation, PlannedAllocation::Failed(_)))
+    }
+}
+
+/// Takes `size` bytes from simulated heap budget and one memory object from allocations limit.
+pub(crate) fn reserve(
+    size: u64,
+    heap_available: &mut u64,
+    allocations_remains: &mut u32,
+) -> Result<(), AllocationError> {
+    if *allocations_remains == 0 {
+        return Err(AllocationError::TooManyObjects);
+    }
+
+    if *heap_available < size {
+        return Err(AllocationError::OutOfDeviceMemory(None));
+    }
+
+    *allocations_remains -= 1;
+    *heap_available -= size;
+    Ok(())
+}
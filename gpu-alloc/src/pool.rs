@@ -0,0 +1,181 @@
+use {
+    crate::{
+        align_up, allocator::Strategy, buddy::BuddyAllocator, freelist::FreeListAllocator,
+        observer::AllocationObserver, tlsf::TlsfAllocator, MemoryBounds,
+    },
+    alloc::{boxed::Box, sync::Arc},
+    gpu_alloc_types::{AllocationFlags, MemoryPropertyFlags},
+};
+
+/// Allocation strategy of memory pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub enum PoolStrategy {
+    /// Blocks are sub-allocated by buddy allocator.\
+    /// Chunk size is rounded up to power of two.
+    Buddy,
+
+    /// Blocks are sub-allocated by free-list allocator.\
+    /// Best suited for short-living blocks.
+    FreeList,
+
+    /// Blocks are sub-allocated by two-level segregated fit allocator.
+    Tlsf,
+}
+
+/// Configuration of memory pool created with `GpuAllocator::create_pool`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(crate = "serde"))]
+pub struct PoolConfig {
+    /// Name of the pool, used in diagnostics.
+    pub label: Option<&'static str>,
+
+    /// Strategy that sub-allocates blocks from memory objects of the pool.
+    pub strategy: PoolStrategy,
+
+    /// Size of memory objects allocated for the pool.\
+    /// Memory object is still made large enough for request that triggers allocation.
+    pub chunk_size: u64,
+
+    /// Bitset of memory types the pool may allocate memory objects from.
+    /// Memory type is still chosen according to request usage.
+    pub memory_types: u32,
+
+    /// Maximum total size of memory objects allocated for the pool.
+    /// Requests that don't fit fail with `AllocationError::OutOfDeviceMemory`.
+    pub max_size: Option<u64>,
+
+    /// Priority of memory objects allocated for the pool.
+    /// `Request::priority` is ignored for blocks allocated from pools.
+    pub priority: Option<f32>,
+
+    /// Handle types memory objects of the pool must be exportable as.
+    /// Only `AllocationFlags::EXPORT_OPAQUE_FD` and `AllocationFlags::EXPORT_OPAQUE_WIN32`
+    /// are allowed.\
+    /// Blocks of exportable pool are sub-allocated from exportable memory objects,
+    /// see `MemoryBlock::external_memory`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub export: AllocationFlags,
+}
+
+impl Default for PoolConfig {
+    /// Returns configuration of buddy pool with 64 MiB chunks
+    /// in any memory type without size limit, not exportable.
+    fn default() -> Self {
+        PoolConfig {
+            label: None,
+            strategy: PoolStrategy::Buddy,
+            chunk_size: 64 * 1024 * 1024,
+            memory_types: !0,
+            max_size: None,
+            priority: None,
+            export: AllocationFlags::empty(),
+        }
+    }
+}
+
+/// Identifier of memory pool created with `GpuAllocator::create_pool`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct PoolId(pub(crate) u32);
+
+/// Memory pool with its own sub-allocator for each memory type.
+#[derive(Debug)]
+pub(crate) struct Pool<M> {
+    pub config: PoolConfig,
+    pub allocators: Box<[Option<PoolAllocator<M>>]>,
+}
+
+impl<M> Pool<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Returns number and total size of memory objects allocated for this pool.
+    pub fn memory_objects(&self) -> (u64, u64) {
+        self.allocators
+            .iter()
+            .flatten()
+            .map(PoolAllocator::memory_objects)
+            .fold((0, 0), |(count, bytes), (c, b)| (count + c, bytes + b))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum PoolAllocator<M> {
+    Buddy(BuddyAllocator<M>),
+    FreeList(FreeListAllocator<M>),
+    Tlsf(TlsfAllocator<M>),
+}
+
+impl<M> PoolAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    pub fn new(
+        config: &PoolConfig,
+        minimal_buddy_size: u64,
+        memory_type: u32,
+        props: MemoryPropertyFlags,
+        atom_mask: u64,
+        observer: Option<Arc<dyn AllocationObserver>>,
+    ) -> Self {
+        match config.strategy {
+            PoolStrategy::Buddy => {
+                let chunk_size = config.chunk_size.next_power_of_two();
+                PoolAllocator::Buddy(BuddyAllocator::new(
+                    minimal_buddy_size.min(chunk_size),
+                    chunk_size,
+                    memory_type,
+                    props,
+                    atom_mask,
+                    observer,
+                ))
+            }
+            PoolStrategy::FreeList => {
+                let chunk_size =
+                    align_up(config.chunk_size, atom_mask).expect("Chunk size is too large");
+                PoolAllocator::FreeList(FreeListAllocator::new(
+                    chunk_size,
+                    chunk_size,
+                    memory_type,
+                    props,
+                    atom_mask,
+                    false,
+                    observer,
+                ))
+            }
+            PoolStrategy::Tlsf => PoolAllocator::Tlsf(TlsfAllocator::new(
+                config.chunk_size,
+                memory_type,
+                props,
+                atom_mask,
+                observer,
+            )),
+        }
+    }
+
+    /// Returns strategy reported for blocks of this allocator.
+    pub fn strategy(&self) -> Strategy {
+        match self {
+            PoolAllocator::Buddy(_) => Strategy::Buddy,
+            PoolAllocator::FreeList(_) => Strategy::FreeList,
+            PoolAllocator::Tlsf(_) => Strategy::Tlsf,
+        }
+    }
+
+    pub fn memory_objects(&self) -> (u64, u64) {
+        match self {
+            PoolAllocator::Buddy(allocator) => allocator.memory_objects(),
+            PoolAllocator::FreeList(allocator) => allocator.memory_objects(),
+            PoolAllocator::Tlsf(allocator) => allocator.memory_objects(),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use {
+    alloc::vec::Vec,
+    core::{cell::RefCell, ptr::NonNull},
+    gpu_alloc_types::{
+        AllocationFlags, DedicatedResource, DeviceBindError, DeviceImportError, DeviceMapError,
+        MappedMemoryRange, MemoryDevice, MemoryHeapBudget, OutOfMemory,
+    },
+};
+
+/// Memory object allocated ahead of time.
+#[derive(Debug)]
+pub(crate) struct Preallocated<M> {
+    pub memory: M,
+    pub size: u64,
+    pub flags: AllocationFlags,
+}
+
+/// Device wrapper that returns memory objects allocated beforehand
+/// from `allocate_memory` calls with matching `size`, `memory_type` and `flags`.
+/// All other calls are forwarded as is.
+///
+/// Memory objects that weren't claimed can be taken back with `into_unused`.
+pub(crate) struct PreallocatedDevice<'a, D, M> {
+    pub device: &'a D,
+    pub memory_type: u32,
+    pub memory: RefCell<Vec<Preallocated<M>>>,
+}
+
+impl<D, M> PreallocatedDevice<'_, D, M> {
+    /// Returns memory objects that weren't claimed.
+    pub fn into_unused(self) -> Vec<Preallocated<M>> {
+        self.memory.into_inner()
+    }
+}
+
+impl<M, D> MemoryDevice<M> for PreallocatedDevice<'_, D, M>
+where
+    D: MemoryDevice<M>,
+{
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<M, OutOfMemory> {
+        if memory_type == self.memory_type {
+            let mut preallocated = self.memory.borrow_mut();
+            let position = preallocated
+                .iter()
+                .position(|memory| memory.size == size && memory.flags == flags);
+
+            if let Some(position) = position {
+                return Ok(preallocated.swap_remove(position).memory);
+            }
+        }
+        self.device.allocate_memory(size, memory_type, flags)
+    }
+
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<M, OutOfMemory> {
+        self.device
+            .allocate_dedicated_memory(size, memory_type, flags, resource)
+    }
+
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<M, OutOfMemory> {
+        self.device
+            .allocate_memory_with_priority(size, memory_type, flags, resource, priority)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        self.device.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        self.device.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<M, DeviceImportError> {
+        self.device.import_host_memory(ptr, size, memory_type)
+    }
+
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_buffer_memory(buffer, memory, offset)
+    }
+
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_image_memory(image, memory, offset)
+    }
+
+    fn memory_budget(&self, heap: u32) -> Option<MemoryHeapBudget> {
+        self.device.memory_budget(heap)
+    }
+}
@@ -0,0 +1,515 @@
+use {
+    crate::{
+        allocator::{Dedicated, GpuAllocator},
+        block::MemoryBlock,
+        error::{AllocationError, OutOfMemoryDetails},
+        report::{AllocatorReport, AllocatorStats},
+        snapshot::{write_str, write_varint, Reader},
+        usage::{AccessPattern, RequestFlags, ResourceKind, UsageFlags},
+        MemoryBounds, Request,
+    },
+    alloc::{
+        borrow::Cow,
+        collections::{BTreeMap, BTreeSet},
+        vec::Vec,
+    },
+    gpu_alloc_types::{AllocationFlags, DedicatedResource, MemoryDevice},
+};
+
+/// Leading bytes of encoded recording.
+const MAGIC: &[u8; 4] = b"GARC";
+
+/// Version of recording encoding.
+const VERSION: u8 = 1;
+
+/// Allocation or deallocation recorded by `GpuAllocator`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordedEvent {
+    /// Request was served by `GpuAllocator::alloc` or one of its variants.
+    Alloc {
+        /// Nanoseconds since recording started.
+        /// Always zero without "std" feature.
+        timestamp: u64,
+
+        /// Request as provided by user, with `label` moved out of it.
+        request: Request,
+
+        /// Label the request was provided with.\
+        /// Owned for recordings loaded with `Recording::decode`.
+        label: Option<Cow<'static, str>>,
+
+        /// Allocation strategy forced with `GpuAllocator::alloc_with_dedicated`.
+        dedicated: Option<Dedicated>,
+
+        /// Memory object size forced with `GpuAllocator::alloc_in_chunk_of`.
+        chunk_size: Option<u64>,
+
+        /// Generation of allocated block or error.
+        result: Result<u64, AllocationError>,
+    },
+
+    /// Block allocated while recording was deallocated.
+    Dealloc {
+        /// Nanoseconds since recording started.
+        /// Always zero without "std" feature.
+        timestamp: u64,
+
+        /// Generation of deallocated block.
+        block: u64,
+    },
+}
+
+/// Log of allocations and deallocations,
+/// recorded between `GpuAllocator::start_recording` and `GpuAllocator::take_recording`.
+///
+/// Only requests served by `GpuAllocator::alloc`, `alloc_with_dedicated`, `alloc_in_chunk_of`
+/// and functions built on them are recorded, together with deallocations of their blocks.
+/// Recording can be replayed against another allocator and device, e.g. mock device,
+/// to reproduce fragmentation offline.
+/// Use `Recording::encode` to save recording, e.g. to replay it in another process.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    events: Vec<RecordedEvent>,
+    /// Blocks allocated while recording and not deallocated yet.
+    live: BTreeSet<u64>,
+    #[cfg(feature = "std")]
+    start: std::time::Instant,
+}
+
+/// Outcome of `Recording::replay`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Number of replayed allocations.
+    pub allocations: u64,
+
+    /// Number of replayed allocations that failed.
+    pub failures: u64,
+
+    /// Number of replayed allocations that failed while recorded ones succeeded, or vice versa.
+    pub mismatches: u64,
+
+    /// Usage of allocator after last event,
+    /// before blocks that were never deallocated are freed.
+    pub report: AllocatorReport,
+
+    /// Counters of allocator after last event,
+    /// before blocks that were never deallocated are freed.
+    pub stats: AllocatorStats,
+}
+
+impl Recording {
+    pub(crate) fn new() -> Self {
+        Recording {
+            events: Vec::new(),
+            live: BTreeSet::new(),
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Returns recorded events in order they happened.
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    pub(crate) fn record_alloc(
+        &mut self,
+        mut request: Request,
+        dedicated: Option<Dedicated>,
+        chunk_size: Option<u64>,
+        result: Result<u64, AllocationError>,
+    ) {
+        if let Ok(block) = result {
+            self.live.insert(block);
+        }
+        let timestamp = self.timestamp();
+        let label = request.label.take().map(Cow::Borrowed);
+        self.events.push(RecordedEvent::Alloc {
+            timestamp,
+            request,
+            label,
+            dedicated,
+            chunk_size,
+            result,
+        });
+    }
+
+    pub(crate) fn record_dealloc(&mut self, block: u64) {
+        // Blocks allocated before recording started are not in the log.
+        if self.live.remove(&block) {
+            let timestamp = self.timestamp();
+            self.events
+                .push(RecordedEvent::Dealloc { timestamp, block });
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn timestamp(&self) -> u64 {
+        let nanos = self.start.elapsed().as_nanos();
+        if nanos > u128::from(u64::MAX) {
+            u64::MAX
+        } else {
+            nanos as u64
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn timestamp(&self) -> u64 {
+        0
+    }
+
+    /// Re-runs recorded events against `allocator`.
+    /// Blocks that were never deallocated in recording are deallocated after last event.
+    ///
+    /// Timestamps are not reproduced, events are replayed back to back.
+    /// Owned labels of decoded recording are not passed to replayed requests.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` instance must be used for all interactions with `allocator`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn replay<M>(
+        &self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+    ) -> ReplayReport
+    where
+        M: MemoryBounds + 'static,
+    {
+        let mut blocks: BTreeMap<u64, MemoryBlock<M>> = BTreeMap::new();
+        let mut allocations = 0;
+        let mut failures = 0;
+        let mut mismatches = 0;
+
+        for event in &self.events {
+            match *event {
+                RecordedEvent::Alloc {
+                    request,
+                    ref label,
+                    dedicated,
+                    chunk_size,
+                    result,
+                    ..
+                } => {
+                    let request = Request {
+                        label: match label {
+                            Some(Cow::Borrowed(label)) => Some(*label),
+                            _ => None,
+                        },
+                        ..request
+                    };
+                    allocations += 1;
+                    match (
+                        allocator.alloc_internal(device, request, dedicated, chunk_size),
+                        result,
+                    ) {
+                        (Ok(block), Ok(recorded)) => {
+                            blocks.insert(recorded, block);
+                        }
+                        (Ok(block), Err(_)) => {
+                            mismatches += 1;
+                            allocator.dealloc(device, block);
+                        }
+                        (Err(_), recorded) => {
+                            failures += 1;
+                            if recorded.is_ok() {
+                                mismatches += 1;
+                            }
+                        }
+                    }
+                }
+                RecordedEvent::Dealloc { block, .. } => {
+                    // Block is missing if its replayed allocation failed.
+                    if let Some(block) = blocks.remove(&block) {
+                        allocator.dealloc(device, block);
+                    }
+                }
+            }
+        }
+
+        let report = ReplayReport {
+            allocations,
+            failures,
+            mismatches,
+            report: allocator.report(),
+            stats: allocator.stats(),
+        };
+
+        for (_, block) in blocks {
+            allocator.dealloc(device, block);
+        }
+
+        report
+    }
+
+    /// Encodes recording into compact byte string,
+    /// e.g. to save it and replay later.
+    ///
+    /// Encoded recording can be loaded back with `Recording::decode`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+
+        write_varint(&mut out, self.events.len() as u64);
+        for event in &self.events {
+            match event {
+                RecordedEvent::Alloc {
+                    timestamp,
+                    request,
+                    label,
+                    dedicated,
+                    chunk_size,
+                    result,
+                } => {
+                    out.push(0);
+                    write_varint(&mut out, *timestamp);
+                    write_request(&mut out, request);
+                    write_option(&mut out, label.as_deref(), write_str);
+                    out.push(dedicated_tag(*dedicated));
+                    write_option(&mut out, *chunk_size, write_varint);
+                    write_result(&mut out, result);
+                }
+                RecordedEvent::Dealloc { timestamp, block } => {
+                    out.push(1);
+                    write_varint(&mut out, *timestamp);
+                    write_varint(&mut out, *block);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Decodes recording encoded with `Recording::encode`.
+    ///
+    /// Returns `None` if `bytes` are not an encoded recording,
+    /// are truncated or encoded by incompatible version.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC || reader.byte()? != VERSION {
+            return None;
+        }
+
+        let count = reader.varint()?;
+        let mut events = Vec::new();
+        let mut live = BTreeSet::new();
+        for _ in 0..count {
+            let event = match reader.byte()? {
+                0 => {
+                    let timestamp = reader.varint()?;
+                    let request = read_request(&mut reader)?;
+                    let label = read_option(&mut reader, |reader| reader.str())?;
+                    let dedicated = read_dedicated(&mut reader)?;
+                    let chunk_size = read_option(&mut reader, Reader::varint)?;
+                    let result = read_result(&mut reader)?;
+                    if let Ok(block) = result {
+                        live.insert(block);
+                    }
+                    RecordedEvent::Alloc {
+                        timestamp,
+                        request,
+                        label: label.map(|label| Cow::Owned(label.into())),
+                        dedicated,
+                        chunk_size,
+                        result,
+                    }
+                }
+                1 => {
+                    let timestamp = reader.varint()?;
+                    let block = reader.varint()?;
+                    live.remove(&block);
+                    RecordedEvent::Dealloc { timestamp, block }
+                }
+                _ => return None,
+            };
+            events.push(event);
+        }
+
+        if !reader.bytes.is_empty() {
+            return None;
+        }
+
+        Some(Recording {
+            events,
+            live,
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        })
+    }
+}
+
+fn write_option<T>(out: &mut Vec<u8>, value: Option<T>, write: impl FnOnce(&mut Vec<u8>, T)) {
+    match value {
+        Some(value) => {
+            out.push(1);
+            write(out, value);
+        }
+        None => out.push(0),
+    }
+}
+
+fn write_request(out: &mut Vec<u8>, request: &Request) {
+    write_varint(out, request.size);
+    write_varint(out, request.align_mask);
+    out.push(request.usage.bits());
+    write_varint(out, request.memory_types.into());
+    write_option(out, request.memory_type.map(u64::from), write_varint);
+    out.push(match request.access {
+        None => 0,
+        Some(AccessPattern::SequentialWrite) => 1,
+        Some(AccessPattern::RandomReadWrite) => 2,
+        Some(AccessPattern::Readback) => 3,
+        Some(AccessPattern::FrequentWrite) => 4,
+    });
+    out.push(dedicated_tag(request.dedicated));
+    match request.dedicated_resource {
+        None => out.push(0),
+        Some(DedicatedResource::Buffer(handle)) => {
+            out.push(1);
+            write_varint(out, handle);
+        }
+        Some(DedicatedResource::Image(handle)) => {
+            out.push(2);
+            write_varint(out, handle);
+        }
+    }
+    out.push(request.export.bits());
+    write_option(
+        out,
+        request.priority.map(|priority| priority.to_bits().into()),
+        write_varint,
+    );
+    out.push(request.flags.bits());
+    out.push(match request.resource_kind {
+        ResourceKind::Linear => 0,
+        ResourceKind::NonLinear => 1,
+    });
+}
+
+fn dedicated_tag(dedicated: Option<Dedicated>) -> u8 {
+    match dedicated {
+        None => 0,
+        Some(Dedicated::Required) => 1,
+        Some(Dedicated::Preferred) => 2,
+    }
+}
+
+fn write_result(out: &mut Vec<u8>, result: &Result<u64, AllocationError>) {
+    match *result {
+        Ok(block) => {
+            out.push(0);
+            write_varint(out, block);
+        }
+        Err(AllocationError::OutOfDeviceMemory(details)) => {
+            out.push(1);
+            write_option(out, details, |out, details| {
+                write_varint(out, details.memory_type.into());
+                write_varint(out, details.heap.into());
+                write_varint(out, details.size);
+                write_varint(out, details.heap_usage);
+                write_varint(out, details.heap_size);
+                out.push(details.fallbacks_attempted.into());
+            });
+        }
+        Err(AllocationError::OutOfHostMemory) => out.push(2),
+        Err(AllocationError::NoCompatibleMemoryTypes) => out.push(3),
+        Err(AllocationError::TooManyObjects) => out.push(4),
+        Err(AllocationError::AddressSpaceExhausted) => out.push(5),
+        Err(AllocationError::DeviceLost) => out.push(6),
+    }
+}
+
+fn read_option<'a, T>(
+    reader: &mut Reader<'a>,
+    read: impl FnOnce(&mut Reader<'a>) -> Option<T>,
+) -> Option<Option<T>> {
+    match reader.byte()? {
+        0 => Some(None),
+        1 => Some(Some(read(reader)?)),
+        _ => None,
+    }
+}
+
+fn read_request(reader: &mut Reader<'_>) -> Option<Request> {
+    let size = reader.varint()?;
+    let align_mask = reader.varint()?;
+    let usage = UsageFlags::from_bits(reader.byte()?)?;
+    let memory_types = reader.u32()?;
+    let memory_type = read_option(reader, Reader::u32)?;
+    let access = match reader.byte()? {
+        0 => None,
+        1 => Some(AccessPattern::SequentialWrite),
+        2 => Some(AccessPattern::RandomReadWrite),
+        3 => Some(AccessPattern::Readback),
+        4 => Some(AccessPattern::FrequentWrite),
+        _ => return None,
+    };
+    let dedicated = read_dedicated(reader)?;
+    let dedicated_resource = match reader.byte()? {
+        0 => None,
+        1 => Some(DedicatedResource::Buffer(reader.varint()?)),
+        2 => Some(DedicatedResource::Image(reader.varint()?)),
+        _ => return None,
+    };
+    let export = AllocationFlags::from_bits(reader.byte()?)?;
+    let priority = read_option(reader, |reader| Some(f32::from_bits(reader.u32()?)))?;
+    let flags = RequestFlags::from_bits(reader.byte()?)?;
+    let resource_kind = match reader.byte()? {
+        0 => ResourceKind::Linear,
+        1 => ResourceKind::NonLinear,
+        _ => return None,
+    };
+
+    Some(Request {
+        size,
+        align_mask,
+        usage,
+        memory_types,
+        memory_type,
+        access,
+        label: None,
+        dedicated,
+        dedicated_resource,
+        export,
+        priority,
+        flags,
+        resource_kind,
+    })
+}
+
+fn read_dedicated(reader: &mut Reader<'_>) -> Option<Option<Dedicated>> {
+    match reader.byte()? {
+        0 => Some(None),
+        1 => Some(Some(Dedicated::Required)),
+        2 => Some(Some(Dedicated::Preferred)),
+        _ => None,
+    }
+}
+
+fn read_result(reader: &mut Reader<'_>) -> Option<Result<u64, AllocationError>> {
+    let error = match reader.byte()? {
+        0 => return Some(Ok(reader.varint()?)),
+        1 => AllocationError::OutOfDeviceMemory(read_option(reader, |reader| {
+            Some(OutOfMemoryDetails {
+                memory_type: reader.u32()?,
+                heap: reader.u32()?,
+                size: reader.varint()?,
+                heap_usage: reader.varint()?,
+                heap_size: reader.varint()?,
+                fallbacks_attempted: reader.bool()?,
+            })
+        })?),
+        2 => AllocationError::OutOfHostMemory,
+        3 => AllocationError::NoCompatibleMemoryTypes,
+        4 => AllocationError::TooManyObjects,
+        5 => AllocationError::AddressSpaceExhausted,
+        6 => AllocationError::DeviceLost,
+        _ => return None,
+    };
+    Some(Err(error))
+}
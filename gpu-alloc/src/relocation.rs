@@ -0,0 +1,36 @@
+use crate::block::MemoryBlock;
+
+/// Relocation of single memory block planned by `GpuAllocator::compact_dedicated`
+/// or `GpuAllocator::defragment`.
+#[derive(Debug)]
+pub enum Relocation<M> {
+    /// Block stays where it is.
+    Keep(MemoryBlock<M>),
+
+    /// Block content should be moved to new block.
+    Move {
+        /// Block to copy content from.
+        src: MemoryBlock<M>,
+
+        /// Block to copy content to.
+        dst: MemoryBlock<M>,
+    },
+}
+
+impl<M> Relocation<M> {
+    /// Returns block that currently holds content.
+    pub fn src(&self) -> &MemoryBlock<M> {
+        match self {
+            Relocation::Keep(block) => block,
+            Relocation::Move { src, .. } => src,
+        }
+    }
+
+    /// Returns block content should be copied to, if any.
+    pub fn dst(&self) -> Option<&MemoryBlock<M>> {
+        match self {
+            Relocation::Keep(_) => None,
+            Relocation::Move { dst, .. } => Some(dst),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use {
+    crate::{allocator::Strategy, pool::PoolId, usage::UsageFlags},
+    alloc::vec::Vec,
+};
+
+#[cfg(feature = "backtrace")]
+use {
+    alloc::sync::Arc,
+    core::{
+        fmt::{self, Debug, Display},
+        hash::{Hash, Hasher},
+    },
+    std::backtrace::Backtrace,
+};
+
+/// Usage of single memory type reported by `GpuAllocator::report`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct MemoryTypeReport {
+    /// Number of live blocks.
+    pub block_count: u64,
+
+    /// Total size of live blocks, including padding.
+    pub live_bytes: u64,
+
+    /// Bytes of live blocks beyond requested sizes.\
+    /// This is padding inserted to satisfy alignment,
+    /// non-coherent atom size and buddy allocator size classes.
+    pub wasted_bytes: u64,
+}
+
+/// Snapshot of allocator usage reported by `GpuAllocator::report`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct AllocatorReport {
+    /// Usage of each memory type, indexed by memory type index.
+    pub memory_types: Vec<MemoryTypeReport>,
+}
+
+impl AllocatorReport {
+    /// Returns total size of live blocks.
+    pub fn live_bytes(&self) -> u64 {
+        self.memory_types
+            .iter()
+            .map(|report| report.live_bytes)
+            .sum()
+    }
+
+    /// Returns total bytes wasted for padding in live blocks.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.memory_types
+            .iter()
+            .map(|report| report.wasted_bytes)
+            .sum()
+    }
+}
+
+/// Counters of single allocation strategy reported by `GpuAllocator::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct StrategyStats {
+    /// Number of memory objects allocated from device.
+    pub memory_objects: u64,
+
+    /// Total size of memory objects allocated from device.
+    pub device_bytes: u64,
+
+    /// Number of live blocks.
+    pub block_count: u64,
+
+    /// Total size of live blocks handed to users.
+    pub user_bytes: u64,
+
+    /// Bytes of live blocks beyond requested sizes.
+    pub internal_fragmentation: u64,
+}
+
+impl StrategyStats {
+    /// Returns bytes of memory objects not occupied by live blocks.
+    pub fn free_bytes(&self) -> u64 {
+        self.device_bytes.saturating_sub(self.user_bytes)
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.memory_objects += other.memory_objects;
+        self.device_bytes += other.device_bytes;
+        self.block_count += other.block_count;
+        self.user_bytes += other.user_bytes;
+        self.internal_fragmentation += other.internal_fragmentation;
+    }
+}
+
+/// Counters of single memory type reported by `GpuAllocator::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct MemoryTypeStats {
+    /// Dedicated and imported memory objects.
+    pub dedicated: StrategyStats,
+
+    /// Buddy allocator of small objects.
+    pub buddy: StrategyStats,
+
+    /// Buddy allocator of large objects.
+    pub large_buddy: StrategyStats,
+
+    /// Free-list allocator of transient objects.
+    pub free_list: StrategyStats,
+
+    /// Two-level segregated fit allocator.
+    pub tlsf: StrategyStats,
+
+    /// Best-fit free-list allocator of mid-size objects.
+    pub best_fit: StrategyStats,
+
+    /// Ring of frame blocks allocated with `GpuAllocator::alloc_in_frame`.\
+    /// Frame blocks are not tracked one by one,
+    /// so `block_count` and `internal_fragmentation` are always zero
+    /// and `user_bytes` is the range occupied by frames not retired yet.
+    pub frame_ring: StrategyStats,
+}
+
+impl MemoryTypeStats {
+    /// Returns counters summed over all strategies.
+    pub fn total(&self) -> StrategyStats {
+        let mut total = self.dedicated;
+        total.add(&self.buddy);
+        total.add(&self.large_buddy);
+        total.add(&self.free_list);
+        total.add(&self.tlsf);
+        total.add(&self.best_fit);
+        total.add(&self.frame_ring);
+        total
+    }
+
+    pub(crate) fn strategy_mut(&mut self, strategy: Strategy) -> &mut StrategyStats {
+        match strategy {
+            Strategy::Dedicated => &mut self.dedicated,
+            Strategy::Buddy => &mut self.buddy,
+            Strategy::LargeBuddy => &mut self.large_buddy,
+            Strategy::FreeList => &mut self.free_list,
+            Strategy::Tlsf => &mut self.tlsf,
+            Strategy::BestFit => &mut self.best_fit,
+        }
+    }
+}
+
+/// Allocation counters reported by `GpuAllocator::stats`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct AllocatorStats {
+    /// Counters of each memory type, indexed by memory type index.
+    pub memory_types: Vec<MemoryTypeStats>,
+
+    /// Total size of memory objects allocated from memory types
+    /// that are both `DEVICE_LOCAL` and `HOST_VISIBLE`,
+    /// usually backed by small resizable BAR heap.
+    pub bar_device_bytes: u64,
+}
+
+impl AllocatorStats {
+    /// Returns counters summed over all memory types and strategies.
+    pub fn total(&self) -> StrategyStats {
+        let mut total = StrategyStats::default();
+        for stats in &self.memory_types {
+            total.add(&stats.total());
+        }
+        total
+    }
+}
+
+/// Backtrace captured when block was allocated.
+///
+/// Blocks allocated together, such as aliases of one memory range, share one backtrace.
+/// Backtraces compare equal only to themselves.
+#[cfg(feature = "backtrace")]
+#[derive(Clone)]
+pub struct AllocationBacktrace(Arc<Backtrace>);
+
+#[cfg(feature = "backtrace")]
+impl AllocationBacktrace {
+    pub(crate) fn capture() -> Self {
+        AllocationBacktrace(Arc::new(Backtrace::force_capture()))
+    }
+
+    /// Returns captured backtrace.
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.0
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl Debug for AllocationBacktrace {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.0, fmt)
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl Display for AllocationBacktrace {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&*self.0, fmt)
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl PartialEq for AllocationBacktrace {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl Eq for AllocationBacktrace {}
+
+#[cfg(feature = "backtrace")]
+impl Hash for AllocationBacktrace {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+/// Block that is still allocated, reported by `GpuAllocator::report_leaks`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(crate = "serde"))]
+pub struct LeakedBlock {
+    /// Label of the request that allocated the block.
+    pub label: Option<&'static str>,
+
+    /// Memory type index of the block.
+    pub memory_type: u32,
+
+    /// Strategy that served the block.
+    pub strategy: Strategy,
+
+    /// Offset in bytes from start of memory object.
+    pub offset: u64,
+
+    /// Size of the block in bytes.
+    pub size: u64,
+
+    /// Backtrace of the allocation.
+    #[cfg(feature = "backtrace")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub backtrace: AllocationBacktrace,
+}
+
+/// Live block reported by `GpuAllocator::report_live_allocations`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(crate = "serde"))]
+pub struct LiveAllocation {
+    /// Label of the request that allocated the block.
+    pub label: Option<&'static str>,
+
+    /// Memory type index of the block.
+    pub memory_type: u32,
+
+    /// Pool the block is allocated from.
+    pub pool: Option<PoolId>,
+
+    /// Strategy that served the block.
+    pub strategy: Strategy,
+
+    /// Usage of the request that allocated the block.
+    pub usage: UsageFlags,
+
+    /// Offset in bytes from start of memory object.
+    pub offset: u64,
+
+    /// Size of the block in bytes, including padding.
+    pub size: u64,
+
+    /// Bytes of the block beyond requested size.
+    pub padding: u64,
+
+    /// Backtrace of the allocation.
+    #[cfg(feature = "backtrace")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub backtrace: AllocationBacktrace,
+}
+
+/// Block with overwritten guard regions,
+/// reported by `GpuAllocator::take_guard_corruptions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(crate = "serde"))]
+pub struct GuardCorruption {
+    /// Label of the request that allocated the block.
+    pub label: Option<&'static str>,
+
+    /// Memory type index of the block.
+    pub memory_type: u32,
+
+    /// Offset in bytes of the block from start of memory object.
+    pub offset: u64,
+
+    /// Size of the block in bytes.
+    pub size: u64,
+}
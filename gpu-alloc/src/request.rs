@@ -0,0 +1,144 @@
+use {
+    crate::{
+        allocator::Dedicated,
+        error::RequestError,
+        usage::{AccessPattern, RequestFlags, ResourceKind, UsageFlags},
+        Request,
+    },
+    gpu_alloc_types::{AllocationFlags, DedicatedResource},
+};
+
+impl Request {
+    /// Returns builder of request.
+    /// Builder starts with values of `Request::default`,
+    /// size must be set before building.
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder {
+            request: Request::default(),
+            align: 1,
+        }
+    }
+}
+
+/// Builder of `Request` that validates it on `build`.
+///
+/// Unlike constructing `Request` directly, alignment is specified in bytes
+/// and values that would make allocation fail or misbehave are reported
+/// with `RequestError` instead of being passed to allocator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RequestBuilder {
+    request: Request,
+    align: u64,
+}
+
+impl RequestBuilder {
+    /// Sets minimal size of memory block required.
+    /// Must not be zero.
+    pub fn size(mut self, size: u64) -> Self {
+        self.request.size = size;
+        self
+    }
+
+    /// Sets minimal alignment of memory block in bytes.
+    /// Must be power of two.
+    pub fn align(mut self, align: u64) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Sets intended memory usage.
+    pub fn usage(mut self, usage: UsageFlags) -> Self {
+        self.request.usage = usage;
+        self
+    }
+
+    /// Sets bitset of memory types block may be allocated from.
+    /// Must not be empty.
+    pub fn memory_types(mut self, memory_types: u32) -> Self {
+        self.request.memory_types = memory_types;
+        self
+    }
+
+    /// Sets index of memory type to allocate from.
+    /// Memory type must be in bitset of allowed memory types.
+    pub fn memory_type(mut self, memory_type: u32) -> Self {
+        self.request.memory_type = Some(memory_type);
+        self
+    }
+
+    /// Sets hint on how host will access memory.
+    pub fn access(mut self, access: AccessPattern) -> Self {
+        self.request.access = Some(access);
+        self
+    }
+
+    /// Sets label of the block.
+    pub fn label(mut self, label: &'static str) -> Self {
+        self.request.label = Some(label);
+        self
+    }
+
+    /// Sets hint to serve the request with dedicated memory object.
+    pub fn dedicated(mut self, dedicated: Dedicated) -> Self {
+        self.request.dedicated = Some(dedicated);
+        self
+    }
+
+    /// Sets resource that dedicated memory object is allocated for.
+    pub fn dedicated_resource(mut self, resource: DedicatedResource) -> Self {
+        self.request.dedicated_resource = Some(resource);
+        self
+    }
+
+    /// Sets handle types memory object must be exportable as.
+    pub fn export(mut self, export: AllocationFlags) -> Self {
+        self.request.export = export;
+        self
+    }
+
+    /// Sets priority of memory in range `[0.0, 1.0]`.
+    pub fn priority(mut self, priority: f32) -> Self {
+        self.request.priority = Some(priority);
+        self
+    }
+
+    /// Sets flags controlling how allocator may serve the request.
+    pub fn flags(mut self, flags: RequestFlags) -> Self {
+        self.request.flags = flags;
+        self
+    }
+
+    /// Sets kind of resource the block is requested for.
+    pub fn resource_kind(mut self, resource_kind: ResourceKind) -> Self {
+        self.request.resource_kind = resource_kind;
+        self
+    }
+
+    /// Returns built request or first problem found in it.
+    pub fn build(self) -> Result<Request, RequestError> {
+        let request = self.request;
+
+        if request.size == 0 {
+            return Err(RequestError::ZeroSize);
+        }
+
+        if !self.align.is_power_of_two() {
+            return Err(RequestError::AlignmentNotPowerOfTwo { align: self.align });
+        }
+
+        if request.memory_types == 0 {
+            return Err(RequestError::NoMemoryTypes);
+        }
+
+        if let Some(memory_type) = request.memory_type {
+            if memory_type >= 32 || request.memory_types & (1 << memory_type) == 0 {
+                return Err(RequestError::MemoryTypeNotAllowed { memory_type });
+            }
+        }
+
+        Ok(Request {
+            align_mask: self.align - 1,
+            ..request
+        })
+    }
+}
@@ -0,0 +1,214 @@
+use {
+    crate::{
+        align_up,
+        error::AllocationError,
+        heap::Heap,
+        util::{arc_unwrap, is_arc_unique},
+        MemoryBounds,
+    },
+    alloc::{collections::VecDeque, sync::Arc},
+    core::{convert::TryFrom as _, ptr::NonNull},
+    gpu_alloc_types::{AllocationFlags, DeviceMapError, MemoryDevice, MemoryPropertyFlags},
+};
+
+#[derive(Debug)]
+struct Frame {
+    index: u64,
+    start: u64,
+    retired: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct RingBlock<M> {
+    pub memory: Arc<M>,
+    pub ptr: Option<NonNull<u8>>,
+    pub offset: u64,
+    pub size: u64,
+}
+
+unsafe impl<M> Sync for RingBlock<M> where M: Sync {}
+unsafe impl<M> Send for RingBlock<M> where M: Send {}
+
+/// Ring buffer over single memory object.
+///
+/// Blocks are allocated one after another and wrap to the start of memory object.
+/// Blocks are tagged with frame they were allocated in
+/// and are reclaimed together when the frame is retired.
+/// Range occupied by frames that are not retired yet
+/// starts at the beginning of the oldest one and ends at the last allocated block.
+#[derive(Debug)]
+pub(crate) struct RingAllocator<M> {
+    memory: Arc<M>,
+    ptr: Option<NonNull<u8>>,
+    size: u64,
+    atom_mask: u64,
+    head: u64,
+    tail: u64,
+    frames: VecDeque<Frame>,
+}
+
+unsafe impl<M> Sync for RingAllocator<M> where M: Sync {}
+unsafe impl<M> Send for RingAllocator<M> where M: Send {}
+
+impl<M> RingAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Allocates memory object for the ring.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(device)))]
+    pub unsafe fn new(
+        device: &impl MemoryDevice<M>,
+        size: u64,
+        memory_type: u32,
+        props: MemoryPropertyFlags,
+        atom_mask: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+    ) -> Result<Self, AllocationError> {
+        let size = align_up(size, atom_mask).ok_or(AllocationError::OutOfDeviceMemory(None))?;
+        let host_visible = props.contains(MemoryPropertyFlags::HOST_VISIBLE);
+
+        if host_visible && isize::try_from(size).is_err() {
+            // Ring can't be mapped into host address space.
+            return Err(AllocationError::AddressSpaceExhausted);
+        }
+
+        let mut memory = device.allocate_memory(size, memory_type, flags)?;
+
+        let ptr = if host_visible {
+            match device.map_memory(&mut memory, 0, size) {
+                Ok(ptr) => Some(ptr),
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Failed to map host-visible memory in ring allocator");
+                    device.deallocate_memory(memory);
+
+                    return Err(match err {
+                        DeviceMapError::OutOfDeviceMemory => {
+                            AllocationError::OutOfDeviceMemory(None)
+                        }
+                        DeviceMapError::OutOfHostMemory => AllocationError::OutOfHostMemory,
+                        DeviceMapError::MapFailed => AllocationError::AddressSpaceExhausted,
+                    });
+                }
+            }
+        } else {
+            None
+        };
+
+        heap.alloc(size);
+
+        Ok(RingAllocator {
+            memory: Arc::new(memory),
+            ptr,
+            size,
+            atom_mask,
+            head: 0,
+            tail: 0,
+            frames: VecDeque::new(),
+        })
+    }
+
+    /// Returns size of ring memory object.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns number of bytes occupied by frames that are not retired yet.
+    pub fn used(&self) -> u64 {
+        if self.head >= self.tail {
+            self.head - self.tail
+        } else {
+            self.size - self.tail + self.head
+        }
+    }
+
+    /// Starts tagging blocks with frame `index`.
+    pub fn begin_frame(&mut self, index: u64) {
+        self.frames.push_back(Frame {
+            index,
+            start: self.head,
+            retired: false,
+        });
+    }
+
+    /// Retires frame `index`.
+    /// Range occupied by retired frames is reclaimed
+    /// once all older frames are retired too.
+    pub fn retire_frame(&mut self, index: u64) {
+        for frame in self.frames.iter_mut().filter(|frame| frame.index == index) {
+            frame.retired = true;
+        }
+
+        while let Some(frame) = self.frames.front() {
+            if !frame.retired {
+                break;
+            }
+            self.frames.pop_front();
+        }
+
+        match self.frames.front() {
+            Some(frame) => self.tail = frame.start,
+            None => {
+                // Ring is empty.
+                self.head = 0;
+                self.tail = 0;
+            }
+        }
+    }
+
+    /// Allocates block in current frame.
+    /// Returns `None` if ring has no space left until older frames are retired.
+    pub fn alloc(&mut self, size: u64, align_mask: u64) -> Option<RingBlock<M>> {
+        debug_assert!(!self.frames.is_empty(), "Frame must be started");
+
+        let size = align_up(size.max(1), self.atom_mask)?;
+        let align_mask = align_mask | self.atom_mask;
+
+        let offset = if self.head >= self.tail {
+            // Free range is after head and before tail after wrapping.
+            let offset = align_up(self.head, align_mask)?;
+            match offset.checked_add(size) {
+                Some(end) if end <= self.size => offset,
+                // Block must end before tail to tell full ring from empty one.
+                _ if size < self.tail => 0,
+                _ => return None,
+            }
+        } else {
+            let offset = align_up(self.head, align_mask)?;
+            match offset.checked_add(size) {
+                Some(end) if end < self.tail => offset,
+                _ => return None,
+            }
+        };
+
+        self.head = offset + size;
+
+        Some(RingBlock {
+            memory: self.memory.clone(),
+            ptr: self
+                .ptr
+                // Mapped ring fits host address space, so does offset within it.
+                .map(|ptr| unsafe { NonNull::new_unchecked(ptr.as_ptr().offset(offset as isize)) }),
+            offset,
+            size,
+        })
+    }
+
+    /// Deallocates ring memory object.
+    /// Returns back the ring if blocks allocated from it are still alive.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn destroy(
+        mut self,
+        device: &impl MemoryDevice<M>,
+        heap: &mut Heap,
+    ) -> Result<(), Self> {
+        if !is_arc_unique(&mut self.memory) {
+            return Err(self);
+        }
+
+        device.deallocate_memory(arc_unwrap(self.memory));
+        heap.dealloc(self.size);
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use {
+    crate::{
+        allocator::GpuAllocator, block::MemoryBlock, error::AllocationError, usage::UsageFlags,
+        MemoryBounds, Request,
+    },
+    alloc::vec::Vec,
+    gpu_alloc_types::MemoryDevice,
+};
+
+/// Group of short-living memory blocks deallocated together.
+///
+/// Suited for allocations that are freed at the end of a frame,
+/// e.g. debug geometry or immediate-mode UI vertices.
+/// Blocks are allocated as `TRANSIENT` so memory objects
+/// are reused by blocks allocated after `reset`.
+///
+/// Blocks stay owned by the scope until `reset`,
+/// use `promote` to take block out of the scope.
+#[derive(Debug)]
+pub struct FrameScope<M> {
+    blocks: Vec<MemoryBlock<M>>,
+}
+
+impl<M> Default for FrameScope<M> {
+    fn default() -> Self {
+        FrameScope::new()
+    }
+}
+
+impl<M> FrameScope<M> {
+    /// Creates empty scope.
+    pub fn new() -> Self {
+        FrameScope { blocks: Vec::new() }
+    }
+
+    /// Returns blocks allocated through this scope in allocation order.
+    pub fn blocks(&self) -> &[MemoryBlock<M>] {
+        &self.blocks
+    }
+
+    /// Returns blocks allocated through this scope in allocation order.
+    pub fn blocks_mut(&mut self) -> &mut [MemoryBlock<M>] {
+        &mut self.blocks
+    }
+
+    /// Takes block with specified index out of the scope.
+    /// Returned block is not deallocated by `reset`
+    /// and must be deallocated with `GpuAllocator::dealloc`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `index` is out of bounds.
+    pub fn promote(&mut self, index: usize) -> MemoryBlock<M> {
+        self.blocks.remove(index)
+    }
+}
+
+impl<M> FrameScope<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Allocates memory block according to the `request`
+    /// and keeps it in this scope.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this scope.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn alloc(
+        &mut self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+        mut request: Request,
+    ) -> Result<&mut MemoryBlock<M>, AllocationError> {
+        request.usage |= UsageFlags::TRANSIENT;
+        let block = allocator.alloc(device, request)?;
+        self.blocks.push(block);
+        Ok(self.blocks.last_mut().unwrap())
+    }
+
+    /// Deallocates all blocks in this scope.
+    ///
+    /// # Safety
+    ///
+    /// * Blocks must not be used by device anymore.
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this scope.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn reset(&mut self, allocator: &mut GpuAllocator<M>, device: &impl MemoryDevice<M>) {
+        for block in self.blocks.drain(..) {
+            allocator.dealloc(device, block);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use {
+    crate::{
+        allocator::{Dedicated, GpuAllocator},
+        block::MemoryBlock,
+        error::AllocationError,
+        MemoryBounds, Request,
+    },
+    alloc::sync::{Arc, Weak},
+    core::{mem::ManuallyDrop, ops::Deref},
+    gpu_alloc_types::MemoryDevice,
+    std::sync::{Mutex, MutexGuard, PoisonError},
+};
+
+#[derive(Debug)]
+struct Shared<M, D> {
+    allocator: Mutex<GpuAllocator<M>>,
+    device: D,
+}
+
+/// Shared handle to `GpuAllocator` that owns the device it allocates from.
+///
+/// Blocks allocated through the handle keep weak reference to the allocator
+/// and deallocate themselves on drop, so they can't be forgotten.
+/// Handle can be cloned and sent to other threads,
+/// allocator is kept alive as long as any of its handles.
+#[derive(Debug)]
+pub struct SharedGpuAllocator<M, D> {
+    shared: Arc<Shared<M, D>>,
+}
+
+impl<M, D> Clone for SharedGpuAllocator<M, D> {
+    fn clone(&self) -> Self {
+        SharedGpuAllocator {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<M, D> SharedGpuAllocator<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    /// Wraps `allocator` together with `device` all its memory blocks are allocated from.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * All memory blocks previously allocated by `allocator` must have been allocated from `device`.
+    /// * Memory blocks allocated through this handle must not be used by device when dropped.
+    pub unsafe fn new(allocator: GpuAllocator<M>, device: D) -> Self {
+        SharedGpuAllocator {
+            shared: Arc::new(Shared {
+                allocator: Mutex::new(allocator),
+                device,
+            }),
+        }
+    }
+
+    /// Returns device this allocator allocates from.
+    pub fn device(&self) -> &D {
+        &self.shared.device
+    }
+
+    /// Locks the allocator for direct access.
+    ///
+    /// Blocks allocated directly from locked allocator are not freed on drop
+    /// and must be deallocated as usual.
+    pub fn lock(&self) -> MutexGuard<'_, GpuAllocator<M>> {
+        self.shared.lock()
+    }
+
+    /// Allocates memory block according to the `request`.
+    /// See `GpuAllocator::alloc`.
+    ///
+    /// Returned block is deallocated when dropped.
+    pub fn alloc(&self, request: Request) -> Result<SharedMemoryBlock<M, D>, AllocationError> {
+        let block = unsafe { self.shared.lock().alloc(&self.shared.device, request) }?;
+        Ok(self.wrap(block))
+    }
+
+    /// Allocates memory block according to the `request`,
+    /// forcing specific allocation strategy.
+    /// See `GpuAllocator::alloc_with_dedicated`.
+    ///
+    /// Returned block is deallocated when dropped.
+    pub fn alloc_with_dedicated(
+        &self,
+        request: Request,
+        dedicated: Dedicated,
+    ) -> Result<SharedMemoryBlock<M, D>, AllocationError> {
+        let block = unsafe {
+            self.shared
+                .lock()
+                .alloc_with_dedicated(&self.shared.device, request, dedicated)
+        }?;
+        Ok(self.wrap(block))
+    }
+
+    /// Deallocates leftover memory objects.
+    /// See `GpuAllocator::cleanup`.
+    pub fn cleanup(&self) {
+        unsafe { self.shared.lock().cleanup(&self.shared.device) }
+    }
+
+    fn wrap(&self, block: MemoryBlock<M>) -> SharedMemoryBlock<M, D> {
+        SharedMemoryBlock {
+            block: ManuallyDrop::new(block),
+            allocator: Arc::downgrade(&self.shared),
+        }
+    }
+}
+
+impl<M, D> Shared<M, D> {
+    fn lock(&self) -> MutexGuard<'_, GpuAllocator<M>> {
+        self.allocator.lock().expect("Allocator lock is poisoned")
+    }
+}
+
+/// Memory block allocated from `SharedGpuAllocator`.
+///
+/// Dereferences to `MemoryBlock`.
+/// Deallocates itself on drop if allocator is still alive,
+/// even if allocator lock is poisoned,
+/// otherwise reports the block as leaked.
+#[derive(Debug)]
+pub struct SharedMemoryBlock<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    block: ManuallyDrop<MemoryBlock<M>>,
+    allocator: Weak<Shared<M, D>>,
+}
+
+impl<M, D> SharedMemoryBlock<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    /// Returns allocator this block was allocated from, if it is still alive.
+    pub fn allocator(&self) -> Option<SharedGpuAllocator<M, D>> {
+        let shared = self.allocator.upgrade()?;
+        Some(SharedGpuAllocator { shared })
+    }
+
+    /// Returns mutable reference to the memory block,
+    /// for functions that map or write it.
+    ///
+    /// # Safety
+    ///
+    /// Memory block must not be replaced through returned reference.
+    pub unsafe fn block_mut(&mut self) -> &mut MemoryBlock<M> {
+        &mut self.block
+    }
+}
+
+impl<M, D> Deref for SharedMemoryBlock<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    type Target = MemoryBlock<M>;
+
+    fn deref(&self) -> &MemoryBlock<M> {
+        &self.block
+    }
+}
+
+impl<M, D> Drop for SharedMemoryBlock<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        let block = unsafe { ManuallyDrop::take(&mut self.block) };
+        match self.allocator.upgrade() {
+            Some(shared) => {
+                // Panicking here would abort if block is dropped during unwinding.
+                let mut allocator = shared
+                    .allocator
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                unsafe { allocator.dealloc(&shared.device, block) }
+            }
+            // Device is gone with the allocator, block reports itself as leaked.
+            None => drop(block),
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use {crate::unreachable_unchecked, alloc::vec::Vec, core::mem::replace};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum Entry<T> {
     Vacant(usize),
     Occupied(T),
 }
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Slab<T> {
     next_vacant: usize,
     entries: Vec<Entry<T>>,
@@ -42,6 +42,17 @@ impl<T> Slab<T> {
         self.entries.len()
     }
 
+    /// Returns iterator over occupied entries and their indices.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match entry {
+                Entry::Occupied(value) => Some((index, value)),
+                Entry::Vacant(_) => None,
+            })
+    }
+
     pub unsafe fn get_unchecked(&self, index: usize) -> &T {
         debug_assert!(index < self.len());
 
@@ -0,0 +1,460 @@
+use {
+    crate::{allocator::Strategy, pool::PoolId, usage::UsageFlags},
+    alloc::{string::String, vec::Vec},
+    core::fmt::{self, Display},
+};
+
+/// Leading bytes of encoded snapshot.
+const MAGIC: &[u8; 4] = b"GASN";
+
+/// Version of snapshot encoding.
+const VERSION: u8 = 1;
+
+/// Range of memory object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct RegionSnapshot {
+    /// Offset in bytes from start of memory object.
+    pub offset: u64,
+
+    /// Size of the range in bytes.
+    pub size: u64,
+}
+
+/// Memory object shared by blocks of one allocation strategy.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct ChunkSnapshot {
+    /// Memory type index of the memory object.
+    pub memory_type: u32,
+
+    /// Pool the memory object is allocated for.
+    pub pool: Option<PoolId>,
+
+    /// Memory object is reserved for blocks with `UsageFlags::DEVICE_ADDRESS`.\
+    /// Memory objects of pools are shared by all blocks of the pool.
+    pub device_address: bool,
+
+    /// Strategy that suballocates blocks from the memory object.
+    pub strategy: Strategy,
+
+    /// Identifier of the memory object,
+    /// unique among memory objects of the same memory type, pool and strategy.
+    pub chunk: u64,
+
+    /// Size of the memory object in bytes.
+    pub size: u64,
+
+    /// Ranges of the memory object available for allocation, sorted by offset.
+    pub free_regions: Vec<RegionSnapshot>,
+}
+
+/// Live block allocated by `GpuAllocator`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct BlockSnapshot {
+    /// Memory type index of the block.
+    pub memory_type: u32,
+
+    /// Pool the block is allocated from.
+    pub pool: Option<PoolId>,
+
+    /// Block is allocated from memory object reserved for blocks with `UsageFlags::DEVICE_ADDRESS`.
+    pub device_address: bool,
+
+    /// Strategy that served the block.
+    pub strategy: Strategy,
+
+    /// Identifier of memory object the block is suballocated from.\
+    /// `None` for blocks that own whole memory object.
+    pub chunk: Option<u64>,
+
+    /// Offset in bytes from start of memory object.
+    pub offset: u64,
+
+    /// Size of the block in bytes.
+    pub size: u64,
+
+    /// Bytes of the block beyond requested size.
+    pub padding: u64,
+
+    /// Usage the block was requested with.
+    pub usage: UsageFlags,
+
+    /// Label the block was requested with.
+    pub label: Option<String>,
+}
+
+/// State of allocator returned by `GpuAllocator::snapshot`.
+///
+/// Memory objects of frame rings and frame blocks are not included.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct AllocatorSnapshot {
+    /// Memory objects suballocated by allocator.
+    pub chunks: Vec<ChunkSnapshot>,
+
+    /// All live blocks, in order of allocation.
+    pub blocks: Vec<BlockSnapshot>,
+}
+
+impl BlockSnapshot {
+    /// Returns `true` if this block is suballocated from memory object `chunk`.
+    pub fn is_in(&self, chunk: &ChunkSnapshot) -> bool {
+        self.memory_type == chunk.memory_type
+            && self.pool == chunk.pool
+            && self.device_address == chunk.device_address
+            && self.strategy == chunk.strategy
+            && self.chunk == Some(chunk.chunk)
+    }
+}
+
+impl AllocatorSnapshot {
+    /// Encodes snapshot into compact byte string,
+    /// e.g. to attach it to crash report.
+    ///
+    /// Encoded snapshot can be loaded back with `AllocatorSnapshot::decode`
+    /// and pretty-printed with `Display` implementation.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+
+        write_varint(&mut out, self.chunks.len() as u64);
+        for chunk in &self.chunks {
+            write_varint(&mut out, chunk.memory_type.into());
+            write_pool(&mut out, chunk.pool);
+            out.push(chunk.device_address.into());
+            out.push(strategy_tag(chunk.strategy));
+            write_varint(&mut out, chunk.chunk);
+            write_varint(&mut out, chunk.size);
+            write_varint(&mut out, chunk.free_regions.len() as u64);
+            for region in &chunk.free_regions {
+                write_varint(&mut out, region.offset);
+                write_varint(&mut out, region.size);
+            }
+        }
+
+        write_varint(&mut out, self.blocks.len() as u64);
+        for block in &self.blocks {
+            write_varint(&mut out, block.memory_type.into());
+            write_pool(&mut out, block.pool);
+            out.push(block.device_address.into());
+            out.push(strategy_tag(block.strategy));
+            match block.chunk {
+                Some(chunk) => {
+                    out.push(1);
+                    write_varint(&mut out, chunk);
+                }
+                None => out.push(0),
+            }
+            write_varint(&mut out, block.offset);
+            write_varint(&mut out, block.size);
+            write_varint(&mut out, block.padding);
+            out.push(block.usage.bits());
+            match &block.label {
+                Some(label) => {
+                    out.push(1);
+                    write_str(&mut out, label);
+                }
+                None => out.push(0),
+            }
+        }
+
+        out
+    }
+
+    /// Decodes snapshot encoded with `AllocatorSnapshot::encode`.
+    ///
+    /// Returns `None` if `bytes` are not an encoded snapshot,
+    /// are truncated or encoded by incompatible version.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC || reader.byte()? != VERSION {
+            return None;
+        }
+
+        let count = reader.varint()?;
+        let mut chunks = Vec::new();
+        for _ in 0..count {
+            let memory_type = reader.u32()?;
+            let pool = reader.pool()?;
+            let device_address = reader.bool()?;
+            let strategy = reader.strategy()?;
+            let chunk = reader.varint()?;
+            let size = reader.varint()?;
+            let count = reader.varint()?;
+            let mut free_regions = Vec::new();
+            for _ in 0..count {
+                free_regions.push(RegionSnapshot {
+                    offset: reader.varint()?,
+                    size: reader.varint()?,
+                });
+            }
+            chunks.push(ChunkSnapshot {
+                memory_type,
+                pool,
+                device_address,
+                strategy,
+                chunk,
+                size,
+                free_regions,
+            });
+        }
+
+        let count = reader.varint()?;
+        let mut blocks = Vec::new();
+        for _ in 0..count {
+            let memory_type = reader.u32()?;
+            let pool = reader.pool()?;
+            let device_address = reader.bool()?;
+            let strategy = reader.strategy()?;
+            let chunk = match reader.byte()? {
+                0 => None,
+                1 => Some(reader.varint()?),
+                _ => return None,
+            };
+            let offset = reader.varint()?;
+            let size = reader.varint()?;
+            let padding = reader.varint()?;
+            let usage = UsageFlags::from_bits(reader.byte()?)?;
+            let label = match reader.byte()? {
+                0 => None,
+                1 => Some(reader.str()?.into()),
+                _ => return None,
+            };
+            blocks.push(BlockSnapshot {
+                memory_type,
+                pool,
+                device_address,
+                strategy,
+                chunk,
+                offset,
+                size,
+                padding,
+                usage,
+                label,
+            });
+        }
+
+        if !reader.bytes.is_empty() {
+            return None;
+        }
+
+        Some(AllocatorSnapshot { chunks, blocks })
+    }
+}
+
+/// Lists memory objects with their blocks and free regions sorted by offset,
+/// followed by blocks that own whole memory object.
+impl Display for AllocatorSnapshot {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in &self.chunks {
+            let free: u64 = chunk.free_regions.iter().map(|region| region.size).sum();
+            write!(
+                fmt,
+                "memory type {} {:?} chunk {}",
+                chunk.memory_type, chunk.strategy, chunk.chunk
+            )?;
+            if let Some(pool) = chunk.pool {
+                write!(fmt, " pool {}", pool.0)?;
+            }
+            if chunk.device_address {
+                fmt.write_str(" device address")?;
+            }
+            writeln!(fmt, ": {} bytes, {} free", chunk.size, free)?;
+
+            let mut ranges: Vec<(u64, u64, Option<&BlockSnapshot>)> = chunk
+                .free_regions
+                .iter()
+                .map(|region| (region.offset, region.size, None))
+                .chain(
+                    self.blocks
+                        .iter()
+                        .filter(|block| block.is_in(chunk))
+                        .map(|block| (block.offset, block.size, Some(block))),
+                )
+                .collect();
+            ranges.sort_by_key(|&(offset, _, _)| offset);
+
+            for (offset, size, block) in ranges {
+                match block {
+                    Some(block) => {
+                        fmt.write_str("  ")?;
+                        write_block(fmt, block)?;
+                    }
+                    None => writeln!(
+                        fmt,
+                        "  {:#x}..{:#x} free",
+                        offset,
+                        offset.saturating_add(size)
+                    )?,
+                }
+            }
+        }
+
+        let mut dedicated = self
+            .blocks
+            .iter()
+            .filter(|block| !self.chunks.iter().any(|chunk| block.is_in(chunk)))
+            .peekable();
+
+        if dedicated.peek().is_some() {
+            writeln!(fmt, "dedicated:")?;
+            for block in dedicated {
+                write!(fmt, "  memory type {}", block.memory_type)?;
+                if let Some(pool) = block.pool {
+                    write!(fmt, " pool {}", pool.0)?;
+                }
+                write!(fmt, " {:?}: ", block.strategy)?;
+                write_block(fmt, block)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_block(fmt: &mut fmt::Formatter<'_>, block: &BlockSnapshot) -> fmt::Result {
+    write!(
+        fmt,
+        "{:#x}..{:#x} block {:?}",
+        block.offset,
+        block.offset.saturating_add(block.size),
+        block.usage
+    )?;
+    if block.padding != 0 {
+        write!(fmt, ", {} bytes padding", block.padding)?;
+    }
+    if let Some(label) = &block.label {
+        write!(fmt, " \"{}\"", label)?;
+    }
+    writeln!(fmt)
+}
+
+fn strategy_tag(strategy: Strategy) -> u8 {
+    match strategy {
+        Strategy::Dedicated => 0,
+        Strategy::Buddy => 1,
+        Strategy::LargeBuddy => 2,
+        Strategy::FreeList => 3,
+        Strategy::Tlsf => 4,
+        Strategy::BestFit => 5,
+    }
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub(crate) fn write_str(out: &mut Vec<u8>, value: &str) {
+    write_varint(out, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn write_pool(out: &mut Vec<u8>, pool: Option<PoolId>) {
+    // Zero is reserved for blocks and chunks outside of pools.
+    write_varint(out, pool.map_or(0, |pool| u64::from(pool.0) + 1));
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Some(head)
+    }
+
+    pub(crate) fn byte(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    pub(crate) fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift == 63 && byte > 1 {
+                return None;
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+            shift += 7;
+            if shift > 63 {
+                return None;
+            }
+        }
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        let value = self.varint()?;
+        if value > u64::from(u32::MAX) {
+            return None;
+        }
+        Some(value as u32)
+    }
+
+    pub(crate) fn str(&mut self) -> Option<&'a str> {
+        let len = self.varint()?;
+        if len > self.bytes.len() as u64 {
+            return None;
+        }
+        core::str::from_utf8(self.take(len as usize)?).ok()
+    }
+
+    fn pool(&mut self) -> Option<Option<PoolId>> {
+        match self.varint()? {
+            0 => Some(None),
+            value if value <= u64::from(u32::MAX) + 1 => Some(Some(PoolId((value - 1) as u32))),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn bool(&mut self) -> Option<bool> {
+        match self.byte()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    fn strategy(&mut self) -> Option<Strategy> {
+        match self.byte()? {
+            0 => Some(Strategy::Dedicated),
+            1 => Some(Strategy::Buddy),
+            2 => Some(Strategy::LargeBuddy),
+            3 => Some(Strategy::FreeList),
+            4 => Some(Strategy::Tlsf),
+            5 => Some(Strategy::BestFit),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use {
+    crate::{
+        allocator::GpuAllocator, block::MemoryBlock, error::AllocationError, MemoryBounds, Request,
+    },
+    alloc::{
+        collections::{BTreeMap, BTreeSet},
+        vec::Vec,
+    },
+    gpu_alloc_types::MemoryDevice,
+};
+
+/// Binding of one page of sparse resource to memory.
+///
+/// Corresponds to `VkSparseMemoryBind` of opaque memory range of the resource,
+/// to be submitted with `vkQueueBindSparse`.
+#[derive(Debug)]
+pub struct SparseBind<'a, M> {
+    /// Offset of the page in resource memory range.
+    pub resource_offset: u64,
+
+    /// Size of the page.
+    pub size: u64,
+
+    /// Memory object and offset in it the page is bound to.
+    /// `None` if page must be unbound.
+    pub memory: Option<(&'a M, u64)>,
+}
+
+/// Memory of sparse resource, bound page by page.
+///
+/// Pages are ranges of sparse block size in memory range of the resource,
+/// each resident page is backed by its own memory block.
+/// Changes of residency are collected until `flush_binds`
+/// and listed by `pending_binds` in the meantime.
+///
+/// Pages must be deallocated with `destroy`.
+#[derive(Debug)]
+pub struct SparseResource<M> {
+    request: Request,
+    page_count: u64,
+    resident: BTreeMap<u64, MemoryBlock<M>>,
+    /// Pages with binding changed since last `flush_binds`.
+    pending: BTreeSet<u64>,
+    /// Blocks of evicted pages that may still be bound.
+    evicted: Vec<MemoryBlock<M>>,
+}
+
+impl<M> SparseResource<M> {
+    /// Creates sparse resource with no resident pages.
+    ///
+    /// `request` must contain memory requirements of the resource.
+    /// Its alignment is the sparse block size, which is size of each page.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `request` has `dedicated_resource`.
+    pub fn new(request: Request) -> Self {
+        assert!(
+            request.dedicated_resource.is_none(),
+            "Sparse resource cannot have dedicated memory"
+        );
+
+        let page_size = request.align_mask + 1;
+        let page_count =
+            request.size / page_size + u64::from(request.size & request.align_mask != 0);
+
+        SparseResource {
+            request: Request {
+                size: page_size,
+                ..request
+            },
+            page_count,
+            resident: BTreeMap::new(),
+            pending: BTreeSet::new(),
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Returns size of each page.
+    pub fn page_size(&self) -> u64 {
+        self.request.size
+    }
+
+    /// Returns number of pages in the resource.
+    pub fn page_count(&self) -> u64 {
+        self.page_count
+    }
+
+    /// Returns `true` if page with specified index is resident.
+    pub fn is_resident(&self, page: u64) -> bool {
+        self.resident.contains_key(&page)
+    }
+
+    /// Returns indices of resident pages in ascending order.
+    pub fn resident_pages(&self) -> impl Iterator<Item = u64> + '_ {
+        self.resident.keys().copied()
+    }
+
+    /// Returns number of resident pages.
+    pub fn resident_count(&self) -> usize {
+        self.resident.len()
+    }
+
+    /// Returns block backing page with specified index, if it is resident.
+    pub fn page_block(&self, page: u64) -> Option<&MemoryBlock<M>> {
+        self.resident.get(&page)
+    }
+
+    /// Makes page with specified index non-resident.
+    /// Its block is deallocated after the page is unbound, see `flush_binds`.
+    ///
+    /// Returns `false` if page is not resident.
+    pub fn evict(&mut self, page: u64) -> bool {
+        match self.resident.remove(&page) {
+            Some(block) => {
+                self.evicted.push(block);
+                self.pending.insert(page);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns binds for pages made resident or evicted since last `flush_binds`,
+    /// ordered by page.
+    pub fn pending_binds(&self) -> Vec<SparseBind<'_, M>> {
+        let page_size = self.page_size();
+        self.pending
+            .iter()
+            .map(|&page| SparseBind {
+                resource_offset: page * page_size,
+                size: page_size,
+                memory: self
+                    .resident
+                    .get(&page)
+                    .map(|block| (block.memory(), block.offset())),
+            })
+            .collect()
+    }
+}
+
+impl<M> SparseResource<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Allocates block for page with specified index and makes the page resident.
+    /// Page can be used once its bind from `pending_binds` is executed.
+    ///
+    /// Returns `false` if page is already resident.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this resource.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `page` is out of bounds.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn make_resident(
+        &mut self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+        page: u64,
+    ) -> Result<bool, AllocationError> {
+        assert!(page < self.page_count, "`page` is out of bounds");
+
+        if self.resident.contains_key(&page) {
+            return Ok(false);
+        }
+
+        let block = allocator.alloc(device, self.request)?;
+        self.resident.insert(page, block);
+        self.pending.insert(page);
+        Ok(true)
+    }
+
+    /// Clears pending binds after they are submitted in frame `frame`.
+    /// Blocks of evicted pages are passed to `GpuAllocator::dealloc_deferred`
+    /// and deallocated once `frame` is completed.
+    ///
+    /// # Safety
+    ///
+    /// * Same `allocator` instance must be used for all interactions with this resource.
+    /// * Binds returned by `pending_binds` must be submitted in frame `frame`.
+    pub unsafe fn flush_binds(&mut self, allocator: &mut GpuAllocator<M>, frame: u64) {
+        self.pending.clear();
+        for block in self.evicted.drain(..) {
+            allocator.dealloc_deferred(block, frame);
+        }
+    }
+
+    /// Deallocates blocks of all pages.
+    ///
+    /// # Safety
+    ///
+    /// * Resource must not be used by device anymore.
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this resource.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn destroy(self, allocator: &mut GpuAllocator<M>, device: &impl MemoryDevice<M>) {
+        for block in self.evicted {
+            allocator.dealloc(device, block);
+        }
+        for (_, block) in self.resident {
+            allocator.dealloc(device, block);
+        }
+    }
+}
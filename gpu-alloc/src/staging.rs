@@ -0,0 +1,266 @@
+use {
+    crate::{
+        align_up,
+        allocator::GpuAllocator,
+        block::MemoryBlock,
+        error::{AllocationError, MapError},
+        usage::UsageFlags,
+        MemoryBounds, Request,
+    },
+    alloc::vec::Vec,
+    gpu_alloc_types::{MemoryDevice, MemoryPropertyFlags},
+};
+
+#[derive(Debug)]
+struct StagingChunk<M> {
+    block: MemoryBlock<M>,
+    cursor: u64,
+}
+
+/// Range of staging memory block handed out by `StagingBelt`.
+///
+/// Copy commands read `size()` bytes at `offset()` in `block()`.
+#[derive(Debug)]
+pub struct StagingSlice<'a, M> {
+    block: &'a mut MemoryBlock<M>,
+    offset: u64,
+    size: u64,
+}
+
+impl<'a, M> StagingSlice<'a, M> {
+    /// Returns memory block this slice is part of.
+    pub fn block(&self) -> &MemoryBlock<M> {
+        self.block
+    }
+
+    /// Returns offset of this slice in the block.
+    /// Offset in memory object is `block().offset() + offset()`.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns size of this slice.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl<'a, M> StagingSlice<'a, M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Copies `data` to this slice at `offset` bytes from its start.
+    /// Non-coherent memory is flushed.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of slice bounds.
+    ///
+    /// # Safety
+    ///
+    /// `device` must be one the belt allocates blocks with.
+    pub unsafe fn write_bytes(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), MapError> {
+        assert!(
+            matches!(offset.checked_add(data.len() as u64), Some(end) if end <= self.size),
+            "Write is out of staging slice bounds"
+        );
+
+        self.block.write_bytes(device, self.offset + offset, data)
+    }
+}
+
+/// Rotating set of host-visible memory blocks for uploads.
+///
+/// Slices are handed out one after another from blocks of chunk size,
+/// slice larger than chunk size gets block of its own.
+/// Blocks written since last `finish` are tagged with submission index passed to it.
+/// Once device completes the submission, `retire` with the same index
+/// makes its blocks available for following slices.
+///
+/// Offsets of slices are aligned by alignment provided on creation,
+/// which should cover `optimalBufferCopyOffsetAlignment`
+/// or other alignment required for copy commands.
+///
+/// Blocks must be deallocated with `destroy`.
+#[derive(Debug)]
+pub struct StagingBelt<M> {
+    chunk_size: u64,
+    align_mask: u64,
+    active: Vec<StagingChunk<M>>,
+    in_flight: Vec<(u64, StagingChunk<M>)>,
+    free: Vec<StagingChunk<M>>,
+}
+
+impl<M> StagingBelt<M> {
+    /// Creates belt with no blocks.
+    ///
+    /// Blocks are allocated with `chunk_size` bytes
+    /// and offsets of slices are aligned by `align_mask`.
+    pub fn new(chunk_size: u64, align_mask: u64) -> Self {
+        StagingBelt {
+            chunk_size,
+            align_mask,
+            active: Vec::new(),
+            in_flight: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns number of blocks allocated by this belt.
+    pub fn block_count(&self) -> usize {
+        self.active.len() + self.in_flight.len() + self.free.len()
+    }
+
+    /// Returns number of blocks waiting for `retire`.
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Tags blocks written since previous call with `submission` index.
+    /// Following slices are allocated from other blocks
+    /// until `retire` is called with this index.
+    pub fn finish(&mut self, submission: u64) {
+        self.in_flight
+            .extend(self.active.drain(..).map(|chunk| (submission, chunk)));
+    }
+
+    /// Makes blocks tagged with `submission` index available again.
+    ///
+    /// # Safety
+    ///
+    /// Device must have completed commands that read slices of the submission.
+    pub unsafe fn retire(&mut self, submission: u64) {
+        let mut index = 0;
+        while index < self.in_flight.len() {
+            if self.in_flight[index].0 == submission {
+                let (_, mut chunk) = self.in_flight.swap_remove(index);
+                chunk.cursor = 0;
+                self.free.push(chunk);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Returns index of active block that fits slice of `size` bytes,
+    /// activating retired block if none does.
+    fn find_chunk(&mut self, size: u64) -> Option<usize> {
+        let align_mask = self.align_mask;
+        let fits = |chunk: &StagingChunk<M>| match align_up(chunk.cursor, align_mask) {
+            Some(offset) => {
+                matches!(offset.checked_add(size), Some(end) if end <= chunk.block.size())
+            }
+            None => false,
+        };
+
+        match self.active.iter().position(&fits) {
+            Some(index) => Some(index),
+            None => {
+                let index = self.free.iter().position(fits)?;
+                self.active.push(self.free.swap_remove(index));
+                Some(self.active.len() - 1)
+            }
+        }
+    }
+
+    fn take_slice(&mut self, index: usize, size: u64) -> StagingSlice<'_, M> {
+        let chunk = &mut self.active[index];
+        let offset = align_up(chunk.cursor, self.align_mask).unwrap();
+        chunk.cursor = offset + size;
+
+        StagingSlice {
+            block: &mut chunk.block,
+            offset,
+            size,
+        }
+    }
+}
+
+impl<M> StagingBelt<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Allocates slice of `size` bytes,
+    /// allocating new block if no block written since last `finish`
+    /// nor retired one has space for it.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this belt.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn alloc(
+        &mut self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+        size: u64,
+    ) -> Result<StagingSlice<'_, M>, AllocationError> {
+        let index = match self.find_chunk(size) {
+            Some(index) => index,
+            None => {
+                let block = allocator.alloc(
+                    device,
+                    Request {
+                        size: self.chunk_size.max(size),
+                        align_mask: self.align_mask,
+                        usage: UsageFlags::UPLOAD,
+                        ..Request::default()
+                    },
+                )?;
+                debug_assert!(block.props().contains(MemoryPropertyFlags::HOST_VISIBLE));
+
+                self.active.push(StagingChunk { block, cursor: 0 });
+                self.active.len() - 1
+            }
+        };
+
+        Ok(self.take_slice(index, size))
+    }
+
+    /// Deallocates retired blocks.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this belt.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn trim(&mut self, allocator: &mut GpuAllocator<M>, device: &impl MemoryDevice<M>) {
+        for chunk in self.free.drain(..) {
+            allocator.dealloc(device, chunk.block);
+        }
+    }
+
+    /// Deallocates all blocks.
+    ///
+    /// # Safety
+    ///
+    /// * Blocks must not be used by device anymore.
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this belt.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device))
+    )]
+    pub unsafe fn destroy(self, allocator: &mut GpuAllocator<M>, device: &impl MemoryDevice<M>) {
+        let chunks = self
+            .active
+            .into_iter()
+            .chain(self.in_flight.into_iter().map(|(_, chunk)| chunk))
+            .chain(self.free);
+
+        for chunk in chunks {
+            allocator.dealloc(device, chunk.block);
+        }
+    }
+}
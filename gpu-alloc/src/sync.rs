@@ -0,0 +1,451 @@
+use {
+    crate::{
+        allocator::{with_implicit_usage_flags, Dedicated, GpuAllocator},
+        block::MemoryBlock,
+        config::Config,
+        error::{AllocationError, OutOfMemoryDetails},
+        heap::SharedHeap,
+        usage::{MemoryForUsage, UsageFallback},
+        MemoryBounds, Request,
+    },
+    alloc::{borrow::Cow, boxed::Box, sync::Arc, vec::Vec},
+    core::{
+        ptr::NonNull,
+        sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    gpu_alloc_types::{
+        AllocationFlags, DedicatedResource, DeviceBindError, DeviceImportError, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeapBudget, OutOfMemory,
+    },
+    std::sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// Internally synchronized memory allocator.
+///
+/// Keeps separate `GpuAllocator` for each memory type, each behind its own lock,
+/// so allocations from different memory types never wait for each other.
+/// Heap usage is shared by all of them,
+/// so heap pressure is observed and out of memory errors report usage of whole heap.
+/// Limit on number of memory objects is shared by all memory types,
+/// reaching it fails allocation with `AllocationError::TooManyObjects`
+/// same as with `GpuAllocator`.
+///
+/// Memory type is chosen by usage same way as `GpuAllocator::alloc` does,
+/// trying next compatible memory type when preferred one is exhausted,
+/// or next tier of `Config::usage_fallbacks` chain if one applies to the request.
+#[derive(Debug)]
+pub struct SyncGpuAllocator<M> {
+    memory_for_usage: MemoryForUsage,
+    usage_fallbacks: Box<[UsageFallback]>,
+    shards: Box<[Mutex<GpuAllocator<M>>]>,
+    allocations: AtomicU32,
+    max_allocation_count: u32,
+}
+
+impl<M> SyncGpuAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Creates new instance of `SyncGpuAllocator`.
+    /// Provided `DeviceProperties` should match properties of `MemoryDevice` that will be used
+    /// with created `SyncGpuAllocator` instance.
+    pub fn new(config: Config, props: DeviceProperties<'_>) -> Self {
+        let heaps: Vec<_> = props
+            .memory_heaps
+            .iter()
+            .map(|_| Arc::new(SharedHeap::default()))
+            .collect();
+
+        let usage_fallbacks = config.usage_fallbacks.clone().into_boxed_slice();
+
+        let shards: Box<[_]> = (0..props.memory_types.len())
+            .map(|_| {
+                let mut allocator = GpuAllocator::new(
+                    config.clone(),
+                    DeviceProperties {
+                        memory_types: Cow::Borrowed(&props.memory_types),
+                        memory_heaps: Cow::Borrowed(&props.memory_heaps),
+                        max_memory_allocation_count: props.max_memory_allocation_count,
+                        max_memory_allocation_size: props.max_memory_allocation_size,
+                        non_coherent_atom_size: props.non_coherent_atom_size,
+                        buffer_device_address: props.buffer_device_address,
+                        buffer_image_granularity: props.buffer_image_granularity,
+                    },
+                );
+                allocator.share_heaps(&heaps);
+                Mutex::new(allocator)
+            })
+            .collect();
+
+        let max_allocation_count = match shards.first() {
+            Some(shard) => lock(shard).max_allocation_count(),
+            None => 0,
+        };
+
+        SyncGpuAllocator {
+            memory_for_usage: MemoryForUsage::new(&props.memory_types),
+            usage_fallbacks,
+            shards,
+            allocations: AtomicU32::new(0),
+            max_allocation_count,
+        }
+    }
+
+    /// Allocates memory block from specified `device` according to the `request`.
+    /// See `GpuAllocator::alloc`.
+    ///
+    /// Only allocator of chosen memory type is locked.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `SyncGpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `SyncGpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    pub unsafe fn alloc(
+        &self,
+        device: &impl MemoryDevice<M>,
+        request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        self.alloc_internal(device, request, None)
+    }
+
+    /// Allocates memory block from specified `device` according to the `request`,
+    /// forcing specific allocation strategy.
+    /// See `GpuAllocator::alloc_with_dedicated`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `SyncGpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `SyncGpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    pub unsafe fn alloc_with_dedicated(
+        &self,
+        device: &impl MemoryDevice<M>,
+        request: Request,
+        dedicated: Dedicated,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        self.alloc_internal(device, request, Some(dedicated))
+    }
+
+    /// Deallocates memory block previously allocated from this `SyncGpuAllocator` instance.
+    ///
+    /// Only allocator of block's memory type is locked.
+    ///
+    /// # Safety
+    ///
+    /// * Memory block must have been allocated by this `SyncGpuAllocator` instance.
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `SyncGpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `SyncGpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    pub unsafe fn dealloc(&self, device: &impl MemoryDevice<M>, block: MemoryBlock<M>) {
+        let device = self.counted(device);
+        lock(&self.shards[block.memory_type() as usize]).dealloc(&device, block)
+    }
+
+    /// Deallocates leftover memory objects of all memory types.
+    /// See `GpuAllocator::cleanup`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `SyncGpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `SyncGpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    pub unsafe fn cleanup(&self, device: &impl MemoryDevice<M>) {
+        let device = self.counted(device);
+        for shard in self.shards.iter() {
+            lock(shard).cleanup(&device);
+        }
+    }
+
+    /// Returns number of memory objects currently allocated by this allocator.
+    pub fn allocation_count(&self) -> u32 {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    /// Returns maximum number of memory objects this allocator may allocate at once.
+    pub fn max_allocation_count(&self) -> u32 {
+        self.max_allocation_count
+    }
+
+    unsafe fn alloc_internal(
+        &self,
+        device: &impl MemoryDevice<M>,
+        request: Request,
+        dedicated: Option<Dedicated>,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        let device = self.counted(device);
+
+        if let Some(index) = request.memory_type {
+            assert!(
+                (index as usize) < self.shards.len(),
+                "Invalid memory type index"
+            );
+            let result = alloc_in_shard(&self.shards[index as usize], &device, request, dedicated);
+            return device.check_limit(result);
+        }
+
+        let fallback = self
+            .usage_fallbacks
+            .iter()
+            .find(|fallback| fallback.usage == request.usage);
+
+        let usage = with_implicit_usage_flags(&request);
+        if 0 == self.memory_for_usage.mask(usage, request.access) & request.memory_types {
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let types = self.memory_for_usage.types(usage, request.access);
+        let mut failed = None;
+
+        if let Some(fallback) = fallback {
+            for (tier, &tier_types) in fallback.tiers.iter().enumerate() {
+                for &index in types {
+                    if 0 == tier_types & request.memory_types & (1 << index) {
+                        continue;
+                    }
+
+                    let result =
+                        self.alloc_from_type(&device, request, dedicated, index, &mut failed);
+                    if let Some(result) = result {
+                        return result.map(|mut block| {
+                            block.fallback_tier = Some(tier as u32);
+                            block
+                        });
+                    }
+                }
+            }
+        } else {
+            for &index in types {
+                if 0 == request.memory_types & (1 << index) {
+                    continue;
+                }
+
+                let result = self.alloc_from_type(&device, request, dedicated, index, &mut failed);
+                if let Some(result) = result {
+                    return result;
+                }
+            }
+        }
+
+        Err(AllocationError::OutOfDeviceMemory(failed.flatten()))
+    }
+
+    /// Allocates from shard of memory type compatible with the request.
+    ///
+    /// Returns `None` if memory type is exhausted and next one should be tried,
+    /// recording details of the failure in `failed`.
+    unsafe fn alloc_from_type<D>(
+        &self,
+        device: &CountedDevice<'_, D>,
+        request: Request,
+        dedicated: Option<Dedicated>,
+        index: u32,
+        failed: &mut Option<Option<OutOfMemoryDetails>>,
+    ) -> Option<Result<MemoryBlock<M>, AllocationError>>
+    where
+        D: MemoryDevice<M>,
+    {
+        // Memory type is compatible with the request,
+        // so shard doesn't need to choose it again.
+        let request = Request {
+            memory_type: Some(index),
+            ..request
+        };
+
+        let result = alloc_in_shard(&self.shards[index as usize], device, request, dedicated);
+        match device.check_limit(result) {
+            Err(AllocationError::OutOfDeviceMemory(details)) => {
+                let fallbacks_attempted = failed.is_some();
+                *failed = Some(details.map(|details| OutOfMemoryDetails {
+                    fallbacks_attempted,
+                    ..details
+                }));
+                None
+            }
+            result => Some(result),
+        }
+    }
+
+    fn counted<'a, D>(&'a self, device: &'a D) -> CountedDevice<'a, D> {
+        CountedDevice {
+            device,
+            allocations: &self.allocations,
+            max_allocation_count: self.max_allocation_count,
+            refused: AtomicBool::new(false),
+        }
+    }
+}
+
+unsafe fn alloc_in_shard<M>(
+    shard: &Mutex<GpuAllocator<M>>,
+    device: &impl MemoryDevice<M>,
+    request: Request,
+    dedicated: Option<Dedicated>,
+) -> Result<MemoryBlock<M>, AllocationError>
+where
+    M: MemoryBounds + 'static,
+{
+    let mut allocator = lock(shard);
+    match dedicated {
+        Some(dedicated) => allocator.alloc_with_dedicated(device, request, dedicated),
+        None => allocator.alloc(device, request),
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Device wrapper that counts memory objects allocated by all shards
+/// and refuses to allocate more than `max_allocation_count` of them.
+/// All other calls are forwarded as is.
+struct CountedDevice<'a, D> {
+    device: &'a D,
+    allocations: &'a AtomicU32,
+    max_allocation_count: u32,
+    /// Allocation was refused as limit is reached.
+    refused: AtomicBool,
+}
+
+impl<D> CountedDevice<'_, D> {
+    /// Replaces out of memory error caused by refused allocation
+    /// with `AllocationError::TooManyObjects`, same as `GpuAllocator` reports
+    /// when limit is reached.
+    fn check_limit<T>(&self, result: Result<T, AllocationError>) -> Result<T, AllocationError> {
+        match result {
+            Err(AllocationError::OutOfDeviceMemory(_)) if self.refused.load(Ordering::Relaxed) => {
+                Err(AllocationError::TooManyObjects)
+            }
+            result => result,
+        }
+    }
+
+    fn acquire<T>(
+        &self,
+        allocate: impl FnOnce() -> Result<T, OutOfMemory>,
+    ) -> Result<T, OutOfMemory> {
+        let max = self.max_allocation_count;
+        self.allocations
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                if count < max {
+                    Some(count + 1)
+                } else {
+                    None
+                }
+            })
+            .map_err(|_| {
+                self.refused.store(true, Ordering::Relaxed);
+                OutOfMemory::OutOfDeviceMemory
+            })?;
+
+        let result = allocate();
+        if result.is_err() {
+            self.allocations.fetch_sub(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+impl<M, D> MemoryDevice<M> for CountedDevice<'_, D>
+where
+    D: MemoryDevice<M>,
+{
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<M, OutOfMemory> {
+        self.acquire(|| self.device.allocate_memory(size, memory_type, flags))
+    }
+
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<M, OutOfMemory> {
+        self.acquire(|| {
+            self.device
+                .allocate_dedicated_memory(size, memory_type, flags, resource)
+        })
+    }
+
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<M, OutOfMemory> {
+        self.acquire(|| {
+            self.device
+                .allocate_memory_with_priority(size, memory_type, flags, resource, priority)
+        })
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        self.device.deallocate_memory(memory);
+        self.allocations.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        self.device.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<M, DeviceImportError> {
+        self.device.import_host_memory(ptr, size, memory_type)
+    }
+
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_buffer_memory(buffer, memory, offset)
+    }
+
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.device.bind_image_memory(image, memory, offset)
+    }
+
+    fn memory_budget(&self, heap: u32) -> Option<MemoryHeapBudget> {
+        self.device.memory_budget(heap)
+    }
+}
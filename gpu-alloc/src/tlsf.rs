@@ -0,0 +1,733 @@
+use {
+    crate::{
+        align_up,
+        allocator::Strategy,
+        error::AllocationError,
+        heap::Heap,
+        observer::{AllocationObserver, ChunkInfo},
+        plan::reserve,
+        slab::Slab,
+        snapshot::{ChunkSnapshot, RegionSnapshot},
+        util::try_arc_unwrap,
+        MemoryBounds,
+    },
+    alloc::{sync::Arc, vec, vec::Vec},
+    core::{convert::TryFrom as _, ptr::NonNull},
+    gpu_alloc_types::{AllocationFlags, DeviceMapError, MemoryDevice, MemoryPropertyFlags},
+};
+
+/// Log2 of number of second level size classes in each first level class.
+const SL_LOG2: u32 = 4;
+const SL_COUNT: usize = 1 << SL_LOG2;
+
+/// First level class `0` holds sizes below `SL_COUNT` exactly.
+/// Each following class holds sizes in range `2^n..2^(n+1)`.
+const FL_COUNT: usize = (64 - SL_LOG2 + 1) as usize;
+
+/// Returns size class that contains `size`.
+fn mapping_insert(size: u64) -> (usize, usize) {
+    debug_assert_ne!(size, 0);
+    let log2 = 63 - size.leading_zeros();
+    if log2 < SL_LOG2 {
+        (0, size as usize)
+    } else {
+        (
+            (log2 - SL_LOG2 + 1) as usize,
+            (size >> (log2 - SL_LOG2)) as usize & (SL_COUNT - 1),
+        )
+    }
+}
+
+/// Returns first size class where every free region is not smaller than `size`.
+fn mapping_search(size: u64) -> Option<(usize, usize)> {
+    debug_assert_ne!(size, 0);
+    let log2 = 63 - size.leading_zeros();
+    if log2 < SL_LOG2 {
+        Some(mapping_insert(size))
+    } else {
+        let round = (1u64 << (log2 - SL_LOG2)) - 1;
+        Some(mapping_insert(size.checked_add(round)?))
+    }
+}
+
+/// Physical region of a chunk, either free or allocated.
+#[derive(Clone, Debug)]
+struct Region {
+    chunk: usize,
+    offset: u64,
+    size: u64,
+    free: bool,
+    prev_phys: Option<usize>,
+    next_phys: Option<usize>,
+    prev_free: Option<usize>,
+    next_free: Option<usize>,
+}
+
+/// Two-level segregated fit index of regions.
+///
+/// Tracks only offsets and sizes of regions of chunks,
+/// so it is shared by allocator and its plan.
+#[derive(Clone, Debug)]
+struct Tlsf {
+    regions: Slab<Region>,
+    fl_bitmap: u64,
+    sl_bitmaps: [u16; FL_COUNT],
+    heads: Vec<Option<usize>>,
+}
+
+impl Tlsf {
+    fn new() -> Self {
+        Tlsf {
+            regions: Slab::new(),
+            fl_bitmap: 0,
+            sl_bitmaps: [0; FL_COUNT],
+            heads: vec![None; FL_COUNT * SL_COUNT],
+        }
+    }
+
+    fn insert_free(&mut self, index: usize) {
+        let (fl, sl) = mapping_insert(self.regions.get(index).size);
+        let head = self.heads[fl * SL_COUNT + sl];
+
+        let region = self.regions.get_mut(index);
+        region.free = true;
+        region.prev_free = None;
+        region.next_free = head;
+
+        if let Some(head) = head {
+            self.regions.get_mut(head).prev_free = Some(index);
+        }
+
+        self.heads[fl * SL_COUNT + sl] = Some(index);
+        self.fl_bitmap |= 1 << fl;
+        self.sl_bitmaps[fl] |= 1 << sl;
+    }
+
+    fn remove_free(&mut self, index: usize) {
+        let region = self.regions.get_mut(index);
+        debug_assert!(region.free);
+        region.free = false;
+        let (prev, next, size) = (region.prev_free, region.next_free, region.size);
+
+        if let Some(next) = next {
+            self.regions.get_mut(next).prev_free = prev;
+        }
+
+        match prev {
+            Some(prev) => self.regions.get_mut(prev).next_free = next,
+            None => {
+                let (fl, sl) = mapping_insert(size);
+                self.heads[fl * SL_COUNT + sl] = next;
+                if next.is_none() {
+                    self.sl_bitmaps[fl] &= !(1 << sl);
+                    if self.sl_bitmaps[fl] == 0 {
+                        self.fl_bitmap &= !(1 << fl);
+                    }
+                }
+            }
+        }
+    }
+
+    fn find_free(&self, size: u64) -> Option<usize> {
+        let (fl, sl) = mapping_search(size)?;
+
+        let sl_map = self.sl_bitmaps[fl] & (!0u16 << sl);
+        let (fl, sl_map) = if sl_map != 0 {
+            (fl, sl_map)
+        } else {
+            let fl_map = match fl + 1 {
+                FL_COUNT => 0,
+                next => self.fl_bitmap & (!0u64 << next),
+            };
+            if fl_map == 0 {
+                return None;
+            }
+            let fl = fl_map.trailing_zeros() as usize;
+            (fl, self.sl_bitmaps[fl])
+        };
+
+        let sl = sl_map.trailing_zeros() as usize;
+        self.heads[fl * SL_COUNT + sl]
+    }
+
+    /// Returns free region that fits `size` bytes aligned by `align_mask`
+    /// from size classes between ones of `size` and `size + align_mask`.
+    /// Such regions are skipped by `find_free` as not all regions of those classes fit.
+    /// With alignment larger than chunks no region fits `size + align_mask`,
+    /// yet regions at aligned offsets still fit `size`.
+    fn find_fitting(&self, size: u64, align_mask: u64) -> Option<usize> {
+        let (fl, sl) = mapping_insert(size);
+        let (last_fl, last_sl) = mapping_insert(size.saturating_add(align_mask));
+
+        for head in fl * SL_COUNT + sl..=last_fl * SL_COUNT + last_sl {
+            let mut next = self.heads[head];
+
+            while let Some(index) = next {
+                let region = self.regions.get(index);
+                if let Some(offset) = align_up(region.offset, align_mask) {
+                    let padding = offset - region.offset;
+                    if padding <= region.size && size <= region.size - padding {
+                        return Some(index);
+                    }
+                }
+                next = region.next_free;
+            }
+        }
+
+        None
+    }
+
+    /// Splits region that is not in free lists at `at`.
+    /// Part after `at` becomes new free region.
+    fn split_free_tail(&mut self, index: usize, at: u64) {
+        let region = self.regions.get_mut(index);
+        debug_assert!(at > 0 && at < region.size);
+
+        let tail = Region {
+            chunk: region.chunk,
+            offset: region.offset + at,
+            size: region.size - at,
+            free: false,
+            prev_phys: Some(index),
+            next_phys: region.next_phys,
+            prev_free: None,
+            next_free: None,
+        };
+        region.size = at;
+
+        let next = tail.next_phys;
+        let tail = self.regions.insert(tail);
+        self.regions.get_mut(index).next_phys = Some(tail);
+        if let Some(next) = next {
+            self.regions.get_mut(next).prev_phys = Some(tail);
+        }
+
+        self.insert_free(tail);
+    }
+
+    /// Takes `size` bytes aligned by `align_mask` out of region that is not in free lists.
+    /// Returns allocated region and its offset.
+    fn take(&mut self, mut index: usize, size: u64, align_mask: u64) -> (usize, u64) {
+        let region = self.regions.get(index);
+        let offset = align_up(region.offset, align_mask).expect("Region must fit aligned block");
+        let padding = offset - region.offset;
+        debug_assert!(padding + size <= region.size);
+
+        if padding > 0 {
+            // Leave padding as free region before allocated one.
+            self.split_free_tail(index, padding);
+            let tail = self
+                .regions
+                .get(index)
+                .next_phys
+                .expect("Region was just split");
+            self.remove_free(tail);
+            self.insert_free(index);
+            index = tail;
+        }
+
+        if self.regions.get(index).size > size {
+            self.split_free_tail(index, size);
+        }
+
+        (index, offset)
+    }
+
+    /// Allocates region from existing chunks.
+    fn alloc(&mut self, size: u64, align_mask: u64) -> Option<(usize, u64)> {
+        let index = match self.find_free(size.checked_add(align_mask)?) {
+            Some(index) => index,
+            None => self.find_fitting(size, align_mask)?,
+        };
+        self.remove_free(index);
+        Some(self.take(index, size, align_mask))
+    }
+
+    /// Adds new chunk and allocates region from its start.
+    fn alloc_in_new_chunk(&mut self, chunk: usize, chunk_size: u64, size: u64) -> usize {
+        debug_assert!(size <= chunk_size);
+        let index = self.regions.insert(Region {
+            chunk,
+            offset: 0,
+            size: chunk_size,
+            free: false,
+            prev_phys: None,
+            next_phys: None,
+            prev_free: None,
+            next_free: None,
+        });
+        self.take(index, size, 0).0
+    }
+
+    /// Adds new chunk as single free region.
+    fn insert_chunk(&mut self, chunk: usize, chunk_size: u64) {
+        debug_assert_ne!(chunk_size, 0);
+        let index = self.regions.insert(Region {
+            chunk,
+            offset: 0,
+            size: chunk_size,
+            free: false,
+            prev_phys: None,
+            next_phys: None,
+            prev_free: None,
+            next_free: None,
+        });
+        self.insert_free(index);
+    }
+
+    /// Frees allocated region, merging it with free neighbours.
+    /// Returns chunk index if whole chunk is free now.
+    /// Such chunk is removed.
+    fn dealloc(&mut self, mut index: usize) -> Option<usize> {
+        debug_assert!(!self.regions.get(index).free);
+
+        if let Some(prev) = self.regions.get(index).prev_phys {
+            if self.regions.get(prev).free {
+                self.remove_free(prev);
+                let region = self.regions.remove(index);
+                let merged = self.regions.get_mut(prev);
+                merged.size += region.size;
+                merged.next_phys = region.next_phys;
+                if let Some(next) = region.next_phys {
+                    self.regions.get_mut(next).prev_phys = Some(prev);
+                }
+                index = prev;
+            }
+        }
+
+        if let Some(next) = self.regions.get(index).next_phys {
+            if self.regions.get(next).free {
+                self.remove_free(next);
+                let region = self.regions.remove(next);
+                let merged = self.regions.get_mut(index);
+                merged.size += region.size;
+                merged.next_phys = region.next_phys;
+                if let Some(next) = region.next_phys {
+                    self.regions.get_mut(next).prev_phys = Some(index);
+                }
+            }
+        }
+
+        let region = self.regions.get(index);
+        if region.prev_phys.is_none() && region.next_phys.is_none() {
+            Some(self.regions.remove(index).chunk)
+        } else {
+            self.insert_free(index);
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Chunk<M> {
+    memory: Arc<M>,
+    ptr: Option<NonNull<u8>>,
+    size: u64,
+}
+
+unsafe impl<M> Sync for Chunk<M> where M: Sync {}
+unsafe impl<M> Send for Chunk<M> where M: Send {}
+
+#[derive(Debug)]
+pub(crate) struct TlsfBlock<M> {
+    pub memory: Arc<M>,
+    pub ptr: Option<NonNull<u8>>,
+    pub offset: u64,
+    pub size: u64,
+    pub chunk: usize,
+    pub region: usize,
+}
+
+unsafe impl<M> Sync for TlsfBlock<M> where M: Sync {}
+unsafe impl<M> Send for TlsfBlock<M> where M: Send {}
+
+/// Two-level segregated fit allocator over chunks of device memory.
+///
+/// Free regions are kept in size classes indexed by two-level bitmap,
+/// so suitable region is found in constant time.
+/// Blocks are not rounded to size classes,
+/// the remainder of the region is returned to free regions.
+/// Chunk is deallocated once all its blocks are deallocated.
+#[derive(Debug)]
+pub(crate) struct TlsfAllocator<M> {
+    tlsf: Tlsf,
+    chunks: Slab<Chunk<M>>,
+    chunk_size: u64,
+    memory_type: u32,
+    props: MemoryPropertyFlags,
+    atom_mask: u64,
+    observer: Option<Arc<dyn AllocationObserver>>,
+}
+
+unsafe impl<M> Sync for TlsfAllocator<M> where M: Sync {}
+unsafe impl<M> Send for TlsfAllocator<M> where M: Send {}
+
+impl<M> TlsfAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    pub fn new(
+        chunk_size: u64,
+        memory_type: u32,
+        props: MemoryPropertyFlags,
+        atom_mask: u64,
+        observer: Option<Arc<dyn AllocationObserver>>,
+    ) -> Self {
+        TlsfAllocator {
+            tlsf: Tlsf::new(),
+            chunks: Slab::new(),
+            chunk_size: align_up(chunk_size, atom_mask).expect("Chunk size is too large"),
+            memory_type,
+            props,
+            atom_mask,
+            observer,
+        }
+    }
+
+    /// Returns size of memory object allocated next.
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Returns size of memory object with specified index.
+    pub fn memory_size(&self, chunk: usize) -> u64 {
+        self.chunks.get(chunk).size
+    }
+
+    /// Sets size of memory object allocated next.
+    /// Memory object is still made large enough for request that triggers allocation.
+    pub fn set_chunk_size(&mut self, chunk_size: u64) {
+        self.chunk_size = align_up(chunk_size.max(1), self.atom_mask).unwrap_or(self.chunk_size);
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        size: u64,
+        align_mask: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+    ) -> Result<TlsfBlock<M>, AllocationError> {
+        let size = align_up(size.max(1), self.atom_mask)
+            .ok_or(AllocationError::OutOfDeviceMemory(None))?;
+        let align_mask = align_mask | self.atom_mask;
+
+        let (region, offset) = match self.tlsf.alloc(size, align_mask) {
+            Some(allocated) => allocated,
+            None => {
+                // New allocation is required.
+                if *allocations_remains == 0 {
+                    return Err(AllocationError::TooManyObjects);
+                }
+
+                let chunk_size = self.chunk_size.max(size);
+                let host_visible = self.props.contains(MemoryPropertyFlags::HOST_VISIBLE);
+
+                if host_visible && isize::try_from(chunk_size).is_err() {
+                    // Chunk can't be mapped into host address space.
+                    return Err(AllocationError::AddressSpaceExhausted);
+                }
+
+                let mut memory = device.allocate_memory(chunk_size, self.memory_type, flags)?;
+                *allocations_remains -= 1;
+                heap.alloc(chunk_size);
+
+                let ptr = if host_visible {
+                    match device.map_memory(&mut memory, 0, chunk_size) {
+                        Ok(ptr) => Some(ptr),
+                        Err(err) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!("Failed to map host-visible memory in TLSF allocator");
+                            device.deallocate_memory(memory);
+                            *allocations_remains += 1;
+                            heap.dealloc(chunk_size);
+
+                            return Err(match err {
+                                DeviceMapError::OutOfDeviceMemory => {
+                                    AllocationError::OutOfDeviceMemory(None)
+                                }
+                                DeviceMapError::OutOfHostMemory => AllocationError::OutOfHostMemory,
+                                DeviceMapError::MapFailed => AllocationError::AddressSpaceExhausted,
+                            });
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                let chunk = self.chunks.insert(Chunk {
+                    memory: Arc::new(memory),
+                    ptr,
+                    size: chunk_size,
+                });
+
+                if let Some(observer) = &self.observer {
+                    observer.on_chunk_create(&ChunkInfo {
+                        memory_type: self.memory_type,
+                        size: chunk_size,
+                    });
+                }
+
+                (self.tlsf.alloc_in_new_chunk(chunk, chunk_size, size), 0)
+            }
+        };
+
+        let chunk_index = self.tlsf.regions.get(region).chunk;
+        let chunk = self.chunks.get(chunk_index);
+
+        debug_assert!(
+            matches!(offset.checked_add(size), Some(end) if end <= chunk.size),
+            "Offset + size is not in chunk bounds"
+        );
+
+        Ok(TlsfBlock {
+            memory: chunk.memory.clone(),
+            ptr: chunk
+                .ptr
+                // Mapped chunk fits host address space, so does offset within it.
+                .map(|ptr| NonNull::new_unchecked(ptr.as_ptr().offset(offset as isize))),
+            offset,
+            size,
+            chunk: chunk_index,
+            region,
+        })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn dealloc(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        block: TlsfBlock<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+    ) {
+        if let Some(chunk) = self.tlsf.dealloc(block.region) {
+            let chunk = self.chunks.remove(chunk);
+            drop(block);
+
+            let memory =
+                try_arc_unwrap(chunk.memory).expect("Memory shared after last block deallocated");
+
+            device.deallocate_memory(memory);
+            *allocations_remains += 1;
+            heap.dealloc(chunk.size);
+
+            if let Some(observer) = &self.observer {
+                observer.on_chunk_destroy(&ChunkInfo {
+                    memory_type: self.memory_type,
+                    size: chunk.size,
+                });
+            }
+        }
+    }
+
+    /// Returns number and total size of memory objects allocated by this allocator.
+    pub fn memory_objects(&self) -> (u64, u64) {
+        self.chunks
+            .iter()
+            .fold((0, 0), |(count, bytes), (_, chunk)| {
+                (count + 1, bytes + chunk.size)
+            })
+    }
+
+    /// Appends memory objects of this allocator with their free regions to `chunks`.
+    pub fn snapshot(&self, chunks: &mut Vec<ChunkSnapshot>) {
+        let first = chunks.len();
+        let mut positions = Vec::new();
+
+        for (index, chunk) in self.chunks.iter() {
+            positions.resize(index + 1, 0);
+            positions[index] = chunks.len();
+            chunks.push(ChunkSnapshot {
+                memory_type: self.memory_type,
+                pool: None,
+                device_address: false,
+                strategy: Strategy::Tlsf,
+                chunk: index as u64,
+                size: chunk.size,
+                free_regions: Vec::new(),
+            });
+        }
+
+        for (_, region) in self.tlsf.regions.iter().filter(|(_, region)| region.free) {
+            chunks[positions[region.chunk]]
+                .free_regions
+                .push(RegionSnapshot {
+                    offset: region.offset,
+                    size: region.size,
+                });
+        }
+
+        for chunk in &mut chunks[first..] {
+            chunk.free_regions.sort_by_key(|region| region.offset);
+        }
+    }
+
+    /// Returns copy of this allocator state sufficient to simulate allocations.
+    pub fn plan(&self) -> TlsfPlan {
+        TlsfPlan {
+            tlsf: self.tlsf.clone(),
+            chunk_size: self.chunk_size,
+            atom_mask: self.atom_mask,
+        }
+    }
+}
+
+/// State of `TlsfAllocator` with regions detached from memory objects.
+#[derive(Clone, Debug)]
+pub(crate) struct TlsfPlan {
+    tlsf: Tlsf,
+    chunk_size: u64,
+    atom_mask: u64,
+}
+
+impl TlsfPlan {
+    /// Returns state of `TlsfAllocator` created with the same arguments.
+    pub fn new(chunk_size: u64, atom_mask: u64) -> Self {
+        TlsfPlan {
+            tlsf: Tlsf::new(),
+            chunk_size: align_up(chunk_size, atom_mask).expect("Chunk size is too large"),
+            atom_mask,
+        }
+    }
+
+    /// Mirrors `TlsfAllocator::chunk_size`.
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Mirrors `TlsfAllocator::set_chunk_size`.
+    pub fn set_chunk_size(&mut self, chunk_size: u64) {
+        self.chunk_size = align_up(chunk_size.max(1), self.atom_mask).unwrap_or(self.chunk_size);
+    }
+
+    /// Simulates `TlsfAllocator::alloc`.
+    /// Returns size of new memory object if one would be allocated.
+    pub fn alloc(
+        &mut self,
+        size: u64,
+        align_mask: u64,
+        heap_available: &mut u64,
+        allocations_remains: &mut u32,
+    ) -> Result<Option<u64>, AllocationError> {
+        let size = align_up(size.max(1), self.atom_mask)
+            .ok_or(AllocationError::OutOfDeviceMemory(None))?;
+        let align_mask = align_mask | self.atom_mask;
+
+        if self.tlsf.alloc(size, align_mask).is_some() {
+            return Ok(None);
+        }
+
+        let chunk_size = self.chunk_size.max(size);
+        reserve(chunk_size, heap_available, allocations_remains)?;
+
+        // Chunk indices are never used to access memory in plan.
+        self.tlsf.alloc_in_new_chunk(!0, chunk_size, size);
+        Ok(Some(chunk_size))
+    }
+}
+
+/// Range of `VirtualAllocator`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[must_use = "Virtual block must be returned with `VirtualAllocator::dealloc`"]
+pub struct VirtualBlock {
+    offset: u64,
+    size: u64,
+    region: usize,
+}
+
+impl VirtualBlock {
+    /// Returns offset of this block from start of the range.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns size of this block.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Allocator of abstract range of addresses.
+///
+/// Suballocates range of `size` bytes with the same two-level segregated fit index
+/// that `Strategy::Tlsf` uses for memory objects, without touching any device.
+/// Use it to manage space in descriptor buffers, large vertex buffers and the like.
+#[derive(Clone, Debug)]
+pub struct VirtualAllocator {
+    tlsf: Tlsf,
+    size: u64,
+    used: u64,
+    block_count: usize,
+}
+
+impl VirtualAllocator {
+    /// Creates allocator of range of `size` bytes with no blocks allocated.
+    pub fn new(size: u64) -> Self {
+        let mut tlsf = Tlsf::new();
+        if size != 0 {
+            tlsf.insert_chunk(0, size);
+        }
+
+        VirtualAllocator {
+            tlsf,
+            size,
+            used: 0,
+            block_count: 0,
+        }
+    }
+
+    /// Returns size of the range.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns total size of allocated blocks.
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Returns number of allocated blocks.
+    pub fn block_count(&self) -> usize {
+        self.block_count
+    }
+
+    /// Allocates block of `size` bytes with offset aligned by `align_mask`.
+    /// Zero sized blocks occupy one byte.
+    ///
+    /// Returns `None` if no free part of the range fits the block.
+    pub fn alloc(&mut self, size: u64, align_mask: u64) -> Option<VirtualBlock> {
+        let size = size.max(1);
+        let (region, offset) = self.tlsf.alloc(size, align_mask)?;
+
+        self.used += size;
+        self.block_count += 1;
+        Some(VirtualBlock {
+            offset,
+            size,
+            region,
+        })
+    }
+
+    /// Returns block to the range.
+    ///
+    /// Block must have been allocated by this allocator,
+    /// otherwise allocator state is corrupted.
+    pub fn dealloc(&mut self, block: VirtualBlock) {
+        self.used -= block.size;
+        self.block_count -= 1;
+
+        if let Some(chunk) = self.tlsf.dealloc(block.region) {
+            // Whole range is free again.
+            self.tlsf.insert_chunk(chunk, self.size);
+        }
+    }
+
+    /// Returns all blocks to the range at once.
+    /// Blocks allocated before must not be deallocated afterwards.
+    pub fn reset(&mut self) {
+        *self = VirtualAllocator::new(self.size);
+    }
+}
@@ -0,0 +1,76 @@
+use {
+    crate::observer::MemoryBlockInfo,
+    core::{convert::TryFrom, ffi::c_void},
+    std::os::raw::c_char,
+    tracy_client::{sys, Client},
+};
+
+macro_rules! pool_names {
+    ($($index:literal)*) => {
+        [$(concat!("gpu-alloc memory type ", stringify!($index), "\0"),)*]
+    };
+}
+
+/// Names of Tracy memory pools, one per memory type.
+/// Tracy identifies pools by name pointers, so names must be static.
+static POOL_NAMES: [&str; 32] = pool_names!(
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+    16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+);
+
+/// Emits Tracy memory allocation event for the block.
+///
+/// Block generation is used as allocation address,
+/// so events pair up within memory type pool of one allocator.
+/// Label of the block is emitted as message.
+pub(crate) fn alloc(info: &MemoryBlockInfo<'_>) {
+    let client = match Client::running() {
+        Some(client) => client,
+        None => return,
+    };
+
+    if let Some(label) = info.label {
+        client.message(
+            &format!(
+                "gpu-alloc: `{}` allocated {} bytes in memory type {}",
+                label, info.size, info.memory_type
+            ),
+            0,
+        );
+    }
+
+    let size = usize::try_from(info.size).unwrap_or(usize::MAX);
+    unsafe {
+        sys::___tracy_emit_memory_alloc_named(address(info), size, 0, pool_name(info));
+    }
+}
+
+/// Emits Tracy memory free event paired with `alloc` event of the block.
+pub(crate) fn free(info: &MemoryBlockInfo<'_>) {
+    let client = match Client::running() {
+        Some(client) => client,
+        None => return,
+    };
+
+    if let Some(label) = info.label {
+        client.message(
+            &format!(
+                "gpu-alloc: `{}` freed {} bytes in memory type {}",
+                label, info.size, info.memory_type
+            ),
+            0,
+        );
+    }
+
+    unsafe {
+        sys::___tracy_emit_memory_free_named(address(info), 0, pool_name(info));
+    }
+}
+
+fn address(info: &MemoryBlockInfo<'_>) -> *const c_void {
+    info.generation as usize as *const c_void
+}
+
+fn pool_name(info: &MemoryBlockInfo<'_>) -> *const c_char {
+    POOL_NAMES[info.memory_type as usize].as_ptr() as *const c_char
+}
@@ -0,0 +1,60 @@
+/// Number of recorded sizes after which histogram decays.
+const WINDOW: u32 = 1024;
+
+/// Histogram of request sizes with power-of-two buckets.
+/// Older records decay, so histogram reflects recent requests.
+#[derive(Debug)]
+pub(crate) struct SizeHistogram {
+    buckets: [u32; 65],
+    total: u32,
+}
+
+impl SizeHistogram {
+    pub fn new() -> Self {
+        SizeHistogram {
+            buckets: [0; 65],
+            total: 0,
+        }
+    }
+
+    pub fn record(&mut self, size: u64) {
+        if self.total >= WINDOW {
+            self.total = 0;
+            for bucket in &mut self.buckets {
+                *bucket /= 2;
+                self.total += *bucket;
+            }
+        }
+
+        self.buckets[bucket(size)] += 1;
+        self.total += 1;
+    }
+
+    /// Returns smallest power of two not lesser than `percent`% of recorded sizes.
+    pub fn percentile(&self, percent: u32) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+
+        let scaled = u64::from(self.total) * u64::from(percent);
+        let threshold = scaled / 100 + u64::from(scaled % 100 != 0);
+
+        let mut sum = 0;
+        for (index, &count) in self.buckets.iter().enumerate() {
+            sum += u64::from(count);
+            if sum >= threshold {
+                return Some(1u64.checked_shl(index as u32).unwrap_or(u64::MAX));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns index of smallest power-of-two bucket that fits `size`.
+fn bucket(size: u64) -> usize {
+    match size {
+        0 | 1 => 0,
+        size => (64 - (size - 1).leading_zeros()) as usize,
+    }
+}
@@ -0,0 +1,186 @@
+use {
+    crate::{
+        allocator::GpuAllocator, block::MemoryBlock, error::UploadError, staging::StagingBelt,
+        MemoryBounds,
+    },
+    gpu_alloc_types::{MemoryDevice, MemoryPropertyFlags},
+};
+
+/// Copy from staging block to destination block recorded by `CopyRecorder`.
+/// Offsets are relative to blocks.
+#[derive(Debug)]
+pub struct UploadCopy<'a, M> {
+    /// Staging block to copy from.
+    pub src: &'a MemoryBlock<M>,
+
+    /// Offset of copied range in `src`.
+    pub src_offset: u64,
+
+    /// Block to copy to.
+    pub dst: &'a MemoryBlock<M>,
+
+    /// Offset of copied range in `dst`.
+    pub dst_offset: u64,
+
+    /// Size of copied range.
+    pub size: u64,
+}
+
+/// Records copy commands for `UploadManager`.
+///
+/// Implemented for closures taking `UploadCopy`.
+pub trait CopyRecorder<M> {
+    /// Records command that copies range of staging block to destination block,
+    /// e.g. `vkCmdCopyBuffer` between buffers bound to the blocks.
+    fn record_copy(&mut self, copy: UploadCopy<'_, M>);
+}
+
+impl<M, F> CopyRecorder<M> for F
+where
+    F: FnMut(UploadCopy<'_, M>),
+{
+    fn record_copy(&mut self, copy: UploadCopy<'_, M>) {
+        self(copy)
+    }
+}
+
+/// Way data reached destination block in `UploadManager::upload`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UploadPath {
+    /// Data was written to host-visible destination directly
+    /// and non-coherent memory was flushed.
+    Direct,
+
+    /// Data was written to staging block and copy was recorded.
+    /// It reaches destination once recorded commands are executed.
+    Staged,
+}
+
+/// Uploads data to memory blocks of any memory type.
+///
+/// Host-visible blocks, including device-local ones on devices with
+/// resizable BAR or unified memory, are written directly.
+/// Data for other blocks is written to `StagingBelt`
+/// and copy to destination is recorded with user-provided `CopyRecorder`.
+///
+/// Submissions of recorded copies are tracked the same way as with `StagingBelt`:
+/// `finish` tags staging blocks written since the previous call with submission index
+/// and `retire` recycles them once device completes the submission.
+///
+/// Staging blocks must be deallocated with `destroy`.
+#[derive(Debug)]
+pub struct UploadManager<M> {
+    belt: StagingBelt<M>,
+}
+
+impl<M> UploadManager<M> {
+    /// Creates manager with staging blocks of `chunk_size` bytes.
+    ///
+    /// Offsets of staging ranges are aligned by `align_mask`,
+    /// which should cover `optimalBufferCopyOffsetAlignment`
+    /// or other alignment required for copy commands.
+    pub fn new(chunk_size: u64, align_mask: u64) -> Self {
+        UploadManager {
+            belt: StagingBelt::new(chunk_size, align_mask),
+        }
+    }
+
+    /// Returns staging belt of this manager.
+    pub fn staging_belt(&self) -> &StagingBelt<M> {
+        &self.belt
+    }
+
+    /// Tags staging blocks written since previous call with `submission` index.
+    /// Call it when commands recorded since previous call are submitted.
+    pub fn finish(&mut self, submission: u64) {
+        self.belt.finish(submission)
+    }
+
+    /// Makes staging blocks tagged with `submission` index available again.
+    ///
+    /// # Safety
+    ///
+    /// Device must have completed commands of the submission.
+    pub unsafe fn retire(&mut self, submission: u64) {
+        self.belt.retire(submission)
+    }
+}
+
+impl<M> UploadManager<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Uploads `data` to `dst` block at `offset` bytes from its start.
+    ///
+    /// Writes directly if `dst` is host-visible,
+    /// otherwise writes `data` to staging block and records copy with `recorder`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of `dst` bounds.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this manager.
+    /// * The caller must guarantee that any previously submitted command that reads or writes
+    ///   to the range of `dst` has completed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, allocator, device, dst, data, recorder))
+    )]
+    pub unsafe fn upload(
+        &mut self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl MemoryDevice<M>,
+        dst: &mut MemoryBlock<M>,
+        offset: u64,
+        data: &[u8],
+        recorder: &mut impl CopyRecorder<M>,
+    ) -> Result<UploadPath, UploadError> {
+        let size = data.len() as u64;
+        assert!(
+            matches!(offset.checked_add(size), Some(end) if end <= dst.size()),
+            "Upload is out of block bounds"
+        );
+
+        if dst.props().contains(MemoryPropertyFlags::HOST_VISIBLE) {
+            dst.write_bytes(device, offset, data)?;
+            return Ok(UploadPath::Direct);
+        }
+
+        let mut slice = self.belt.alloc(allocator, device, size)?;
+        slice.write_bytes(device, 0, data)?;
+
+        recorder.record_copy(UploadCopy {
+            src: slice.block(),
+            src_offset: slice.offset(),
+            dst,
+            dst_offset: offset,
+            size,
+        });
+
+        Ok(UploadPath::Staged)
+    }
+
+    /// Deallocates retired staging blocks.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this manager.
+    pub unsafe fn trim(&mut self, allocator: &mut GpuAllocator<M>, device: &impl MemoryDevice<M>) {
+        self.belt.trim(allocator, device)
+    }
+
+    /// Deallocates all staging blocks.
+    ///
+    /// # Safety
+    ///
+    /// * Staging blocks must not be used by device anymore.
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * Same `device` and `allocator` instances must be used for all interactions with this manager.
+    pub unsafe fn destroy(self, allocator: &mut GpuAllocator<M>, device: &impl MemoryDevice<M>) {
+        self.belt.destroy(allocator, device)
+    }
+}
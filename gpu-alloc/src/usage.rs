@@ -1,4 +1,5 @@
 use {
+    alloc::{boxed::Box, vec::Vec},
     core::fmt::{self, Debug},
     gpu_alloc_types::{MemoryPropertyFlags, MemoryType},
 };
@@ -6,7 +7,7 @@ use {
 bitflags::bitflags! {
     /// Memory usage type.
     /// Bits set define intended usage for requested memory.
-    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(crate = "serde"))]
     pub struct UsageFlags: u8 {
         /// Hints for allocator to find memory with faster device access.
         /// If no flags is specified than `FAST_DEVICE_ACCESS` is implied.
@@ -38,10 +39,182 @@ bitflags::bitflags! {
 
         /// Requests memory that can be addressed with `u64`.
         /// Allows fetching device address for resources bound to that memory.
+        /// Memory objects of such blocks are allocated with `AllocationFlags::DEVICE_ADDRESS`
+        /// and are not shared with blocks without this flag,
+        /// except for memory objects of pools and frame rings that have it
+        /// whenever `DeviceProperties::buffer_device_address` is set.
         const DEVICE_ADDRESS = 0x20;
+
+        /// Requests protected memory.
+        /// Only memory types with `MemoryPropertyFlags::PROTECTED` are used,
+        /// and those are never used for requests without this flag.
+        /// Cannot be combined with host access.
+        const PROTECTED = 0x40;
+
+        /// Hints allocator that memory will back transient attachments,
+        /// such as multisampled color or depth attachments resolved within render pass.
+        /// Allocator will prefer memory types with `MemoryPropertyFlags::LAZILY_ALLOCATED`,
+        /// that may never be backed by real memory on tile-based GPUs,
+        /// and fall back to other device-local memory types.
+        /// Lazily allocated memory types are never used for requests without this flag.
+        const TRANSIENT_ATTACHMENT = 0x80;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags controlling how allocator may serve a request.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(crate = "serde"))]
+    pub struct RequestFlags: u8 {
+        /// Never allocate new memory objects for the request.
+        /// Only free space in memory objects already allocated is used,
+        /// otherwise allocation fails with `AllocationError::OutOfDeviceMemory`.
+        const NEVER_ALLOCATE = 0x01;
+
+        /// Fail instead of allocating memory object that would exceed heap budget,
+        /// regardless of `Config::budget_policy`.
+        const WITHIN_BUDGET = 0x02;
+
+        /// Serve the request with its own memory object.
+        /// Same as `Dedicated::Required` hint, and takes precedence over other hints.
+        const DONT_SUBALLOCATE = 0x04;
+
+        /// Keep returned block mapped for its whole lifetime,
+        /// so `MemoryBlock::mapped_ptr` and `MemoryBlock::mapped_slice` are always available.
+        /// Implies `UsageFlags::HOST_ACCESS` usage flag.\
+        /// Requests for memory type that is not `HOST_VISIBLE` with `Request::memory_type`
+        /// fail with `AllocationError::NoCompatibleMemoryTypes`.\
+        /// Dedicated memory object is mapped once when allocated
+        /// and unmapped when deallocated.
+        const PERSISTENTLY_MAPPED = 0x08;
     }
 }
 
+/// Kind of resource memory block is requested for.
+///
+/// Linear and non-linear resources placed in the same page of
+/// `DeviceProperties::buffer_image_granularity` bytes may alias on some devices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub enum ResourceKind {
+    /// Buffer or image with linear tiling.
+    Linear,
+
+    /// Image with optimal tiling.\
+    /// Block offset and size are aligned to `DeviceProperties::buffer_image_granularity`,
+    /// so that linear resources never share a page with it.
+    NonLinear,
+}
+
+/// Ordered tiers of memory types tried for requests with specified usage,
+/// set in `Config::usage_fallbacks`.
+///
+/// Memory types of a tier are tried in order of preference for the usage,
+/// next tier is tried only when all memory types of previous tiers are exhausted.
+/// Request fails with `AllocationError::OutOfDeviceMemory` when all tiers are exhausted.
+/// Tier that served the request is reported by `MemoryBlock::fallback_tier`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub struct UsageFallback {
+    /// Usage flags of requests this chain applies to.
+    /// Must be equal to `Request::usage` as provided.
+    pub usage: UsageFlags,
+
+    /// Bitsets of memory types in each tier, from most to least preferred.
+    /// Memory types not supporting the usage are skipped.
+    pub tiers: Vec<u32>,
+}
+
+/// Hint on how host will access memory.
+///
+/// Access pattern overrides preference of `HOST_COHERENT` and `HOST_CACHED`
+/// memory otherwise derived from `UPLOAD` and `DOWNLOAD` usage flags.
+/// Memory types are tried in order of preference listed for each pattern,
+/// so that memory type lacking preferred properties is used
+/// only if no better one exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(crate = "serde")
+)]
+pub enum AccessPattern {
+    /// Host writes memory sequentially and never reads it.
+    /// Example is staging buffer for uploads.
+    ///
+    /// Preference order:
+    /// coherent non-cached, coherent cached, non-coherent non-cached, non-coherent cached.
+    SequentialWrite,
+
+    /// Host both reads and writes memory in arbitrary order.
+    ///
+    /// Preference order:
+    /// cached coherent, cached non-coherent, non-cached coherent, non-cached non-coherent.
+    RandomReadWrite,
+
+    /// Host reads memory written by device.
+    /// Cached memory is strongly preferred even if it requires explicit invalidation.
+    ///
+    /// Preference order:
+    /// cached coherent, cached non-coherent, non-cached coherent, non-cached non-coherent.
+    Readback,
+
+    /// Host writes memory sequentially and often, device reads it directly.
+    /// Example is uniform buffer updated every frame.
+    /// Device-local memory types are preferred regardless of usage flags,
+    /// so that host writes go through resizable BAR into device memory.
+    /// Once device-local host-visible memory is absent or exhausted,
+    /// other host-visible memory types are used.
+    ///
+    /// Preference order:
+    /// device-local before non-device-local, then same as for `SequentialWrite`.
+    FrequentWrite,
+}
+
+impl AccessPattern {
+    fn index(access: Option<Self>) -> usize {
+        match access {
+            None => 0,
+            Some(AccessPattern::SequentialWrite) => 1,
+            Some(AccessPattern::RandomReadWrite) => 2,
+            Some(AccessPattern::Readback) => 3,
+            Some(AccessPattern::FrequentWrite) => 4,
+        }
+    }
+
+    fn from_index(index: usize) -> Option<Self> {
+        match index {
+            1 => Some(AccessPattern::SequentialWrite),
+            2 => Some(AccessPattern::RandomReadWrite),
+            3 => Some(AccessPattern::Readback),
+            4 => Some(AccessPattern::FrequentWrite),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if host is expected to read memory.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn reads(self) -> bool {
+        match self {
+            AccessPattern::SequentialWrite | AccessPattern::FrequentWrite => false,
+            AccessPattern::RandomReadWrite | AccessPattern::Readback => true,
+        }
+    }
+}
+
+/// Number of access pattern variants, including absence of the hint.
+const ACCESS_PATTERNS: usize = 5;
+
+/// Number of usage flags combinations.
+const USAGES: usize = UsageFlags::all().bits() as usize + 1;
+
 #[derive(Clone, Copy, Debug)]
 struct MemoryForOneUsage {
     mask: u32,
@@ -50,7 +223,7 @@ struct MemoryForOneUsage {
 }
 
 pub(crate) struct MemoryForUsage {
-    usages: [MemoryForOneUsage; 64],
+    usages: Box<[MemoryForOneUsage]>,
 }
 
 impl Debug for MemoryForUsage {
@@ -68,37 +241,46 @@ impl MemoryForUsage {
             "Only up to 32 memory types supported"
         );
 
-        let mut mfu = MemoryForUsage {
-            usages: [MemoryForOneUsage {
-                mask: 0,
-                types: [0; 32],
-                types_count: 0,
-            }; 64],
-        };
-
-        for usage in 0..64 {
-            mfu.usages[usage as usize] =
-                one_usage(UsageFlags::from_bits_truncate(usage), memory_types);
-        }
+        let usages = (0..ACCESS_PATTERNS)
+            .flat_map(|access| {
+                (0..USAGES).map(move |usage| {
+                    one_usage(
+                        UsageFlags::from_bits_truncate(usage as u8),
+                        AccessPattern::from_index(access),
+                        memory_types,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
 
-        mfu
+        MemoryForUsage {
+            usages: usages.into_boxed_slice(),
+        }
     }
 
     /// Returns mask with bits set for memory type indices that support the
     /// usage.
-    pub fn mask(&self, usage: UsageFlags) -> u32 {
-        self.usages[usage.bits() as usize].mask
+    pub fn mask(&self, usage: UsageFlags, access: Option<AccessPattern>) -> u32 {
+        self.usages[usage_index(usage, access)].mask
     }
 
     /// Returns slice of memory type indices that support the usage.
     /// Earlier memory type has priority over later.
-    pub fn types(&self, usage: UsageFlags) -> &[u32] {
-        let usage = &self.usages[usage.bits() as usize];
+    pub fn types(&self, usage: UsageFlags, access: Option<AccessPattern>) -> &[u32] {
+        let usage = &self.usages[usage_index(usage, access)];
         &usage.types[..usage.types_count as usize]
     }
 }
 
-fn one_usage(usage: UsageFlags, memory_types: &[MemoryType]) -> MemoryForOneUsage {
+fn usage_index(usage: UsageFlags, access: Option<AccessPattern>) -> usize {
+    AccessPattern::index(access) * USAGES + usage.bits() as usize
+}
+
+fn one_usage(
+    usage: UsageFlags,
+    access: Option<AccessPattern>,
+    memory_types: &[MemoryType],
+) -> MemoryForOneUsage {
     let mut types = [0; 32];
     let mut types_count = 0;
 
@@ -109,8 +291,12 @@ fn one_usage(usage: UsageFlags, memory_types: &[MemoryType]) -> MemoryForOneUsag
         }
     }
 
-    types[..types_count as usize]
-        .sort_unstable_by_key(|&index| priority(usage, memory_types[index as usize].props));
+    types[..types_count as usize].sort_unstable_by_key(|&index| {
+        (
+            priority(usage, access, memory_types[index as usize].props),
+            index,
+        )
+    });
 
     let mask = types[..types_count as usize]
         .iter()
@@ -125,8 +311,12 @@ fn one_usage(usage: UsageFlags, memory_types: &[MemoryType]) -> MemoryForOneUsag
 
 fn compatible(usage: UsageFlags, flags: MemoryPropertyFlags) -> bool {
     type Flags = MemoryPropertyFlags;
-    if flags.contains(Flags::LAZILY_ALLOCATED) || flags.contains(Flags::PROTECTED) {
-        // Unsupported
+    if flags.contains(Flags::LAZILY_ALLOCATED) && !usage.contains(UsageFlags::TRANSIENT_ATTACHMENT)
+    {
+        // Only transient attachments may use memory without backing storage.
+        false
+    } else if flags.contains(Flags::PROTECTED) != usage.contains(UsageFlags::PROTECTED) {
+        // Protected memory is kept apart from unprotected.
         false
     } else if usage.intersects(UsageFlags::HOST_ACCESS | UsageFlags::UPLOAD | UsageFlags::DOWNLOAD)
     {
@@ -139,7 +329,7 @@ fn compatible(usage: UsageFlags, flags: MemoryPropertyFlags) -> bool {
 
 /// Returns priority of memory with specified flags for specified usage.
 /// Lesser value returned = more prioritized.
-fn priority(usage: UsageFlags, flags: MemoryPropertyFlags) -> u32 {
+fn priority(usage: UsageFlags, access: Option<AccessPattern>, flags: MemoryPropertyFlags) -> u32 {
     type Flags = MemoryPropertyFlags;
 
     // Highly prefer device local memory when `FAST_DEVICE_ACCESS` usage is specified
@@ -153,15 +343,42 @@ fn priority(usage: UsageFlags, flags: MemoryPropertyFlags) -> u32 {
                 .intersects(UsageFlags::HOST_ACCESS | UsageFlags::UPLOAD | UsageFlags::DOWNLOAD)
     );
 
-    // Prefer cached memory for downloads.
-    // Or non-cached if downloads are not expected.
-    let cached: bool = flags.contains(Flags::HOST_CACHED) ^ usage.contains(UsageFlags::DOWNLOAD);
+    // Prefer lazily allocated memory for transient attachments above all.
+    let lazily_allocated: bool =
+        flags.contains(Flags::LAZILY_ALLOCATED) ^ usage.contains(UsageFlags::TRANSIENT_ATTACHMENT);
+
+    let host_cached = flags.contains(Flags::HOST_CACHED);
+    let host_coherent = flags.contains(Flags::HOST_COHERENT);
+
+    let preference = match access {
+        None => {
+            // Prefer cached memory for downloads.
+            // Or non-cached if downloads are not expected.
+            let cached: bool = host_cached ^ usage.contains(UsageFlags::DOWNLOAD);
 
-    // Prefer coherent for both uploads and downloads.
-    // Prefer non-coherent if neither flags is set.
-    let coherent: bool = flags.contains(Flags::HOST_COHERENT)
-        ^ (usage.intersects(UsageFlags::UPLOAD | UsageFlags::DOWNLOAD));
+            // Prefer coherent for both uploads and downloads.
+            // Prefer non-coherent if neither flags is set.
+            let coherent: bool =
+                host_coherent ^ (usage.intersects(UsageFlags::UPLOAD | UsageFlags::DOWNLOAD));
+
+            // Each boolean is false if flags are preferred.
+            device_local as u32 * 4 + cached as u32 * 2 + coherent as u32
+        }
+        Some(AccessPattern::SequentialWrite) => {
+            // Coherent memory doesn't need flushes, write-combined memory is fine for writes.
+            device_local as u32 * 4 + !host_coherent as u32 * 2 + host_cached as u32
+        }
+        Some(AccessPattern::FrequentWrite) => {
+            // Writes to device-local memory save copy on device.
+            !flags.contains(Flags::DEVICE_LOCAL) as u32 * 4
+                + !host_coherent as u32 * 2
+                + host_cached as u32
+        }
+        Some(AccessPattern::RandomReadWrite) | Some(AccessPattern::Readback) => {
+            // Reads from non-cached memory are slow, flushes and invalidations are cheaper.
+            device_local as u32 * 4 + !host_cached as u32 * 2 + !host_coherent as u32
+        }
+    };
 
-    // Each boolean is false if flags are preferred.
-    device_local as u32 * 4 + cached as u32 * 2 + coherent as u32
+    lazily_allocated as u32 * 8 + preference
 }
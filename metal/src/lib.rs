@@ -0,0 +1,297 @@
+//!
+//! # Metal backend for `gpu-alloc`
+//!
+//! Memory objects are placement `MTLHeap`s.
+//! Memory types map to heap storage and CPU cache modes:
+//!
+//! * `MTLStorageModePrivate` - `DEVICE_LOCAL`.
+//! * `MTLStorageModeShared` with `MTLCPUCacheModeWriteCombined` - `HOST_VISIBLE | HOST_COHERENT`.
+//! * `MTLStorageModeShared` with `MTLCPUCacheModeDefaultCache` - `HOST_VISIBLE | HOST_COHERENT | HOST_CACHED`.
+//!
+//! Shared memory types are `DEVICE_LOCAL` as well on devices with unified memory.
+//!
+//! Resources are placed into memory block with `MTLHeap` returned by `MetalMemory::heap`
+//! at offset `MemoryBlock::offset`.
+//! Host-visible heaps also contain buffer spanning whole heap, used for mapping.
+//!
+//! # Usage example
+//!
+//! ```ignore
+//! use {
+//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc_metal::{device_properties, MetalMemoryDevice},
+//!     metal::{Device, MTLResourceOptions},
+//! };
+//!
+//! fn main() -> eyre::Result<()> {
+//!     let device = Device::system_default().ok_or_else(|| eyre::eyre!("No Metal device"))?;
+//!
+//!     let mut allocator = GpuAllocator::new(Config::i_am_potato(), device_properties(&device));
+//!
+//!     let mut block = unsafe {
+//!         allocator.alloc(
+//!             MetalMemoryDevice::wrap(&device),
+//!             Request {
+//!                 size: 10,
+//!                 align_mask: 255,
+//!                 usage: UsageFlags::HOST_ACCESS,
+//!                 ..Request::default()
+//!             },
+//!         )
+//!     }?;
+//!
+//!     unsafe {
+//!         block.write_bytes(
+//!             MetalMemoryDevice::wrap(&device),
+//!             0,
+//!             &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+//!         )
+//!     }?;
+//!
+//!     let buffer = block
+//!         .memory()
+//!         .heap()
+//!         .new_buffer_with_offset(10, MTLResourceOptions::StorageModeShared, block.offset());
+//!
+//!     drop(buffer);
+//!
+//!     unsafe { allocator.dealloc(MetalMemoryDevice::wrap(&device), block) }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+
+use {
+    core::fmt::{self, Debug},
+    gpu_alloc_types::{
+        AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
+        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+    },
+    metal::{
+        Buffer, DeviceRef, Heap, HeapDescriptor, HeapRef, MTLCPUCacheMode, MTLHeapType,
+        MTLResourceOptions, MTLStorageMode,
+    },
+    std::ptr::NonNull,
+};
+
+/// Index of memory type with `MTLStorageModePrivate`.
+pub const PRIVATE_MEMORY_TYPE: u32 = 0;
+
+/// Index of memory type with `MTLStorageModeShared` and `MTLCPUCacheModeWriteCombined`.
+pub const SHARED_WRITE_COMBINED_MEMORY_TYPE: u32 = 1;
+
+/// Index of memory type with `MTLStorageModeShared` and `MTLCPUCacheModeDefaultCache`.
+pub const SHARED_CACHED_MEMORY_TYPE: u32 = 2;
+
+/// Memory object allocated by `MetalMemoryDevice`.
+pub struct MetalMemory {
+    heap: Heap,
+    buffer: Option<Buffer>,
+    size: u64,
+}
+
+impl MetalMemory {
+    /// Returns heap to place resources into.
+    pub fn heap(&self) -> &HeapRef {
+        &self.heap
+    }
+
+    /// Returns buffer spanning whole heap.
+    /// Available for host-visible memory types only.
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+}
+
+impl Debug for MetalMemory {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("MetalMemory")
+            .field("size", &self.size)
+            .field("host_visible", &self.buffer.is_some())
+            .finish()
+    }
+}
+
+/// Memory device over Metal device.
+#[repr(transparent)]
+pub struct MetalMemoryDevice {
+    device: DeviceRef,
+}
+
+impl MetalMemoryDevice {
+    pub fn wrap(device: &DeviceRef) -> &Self {
+        unsafe {
+            // Safe because `Self` is `repr(transparent)`
+            // with only field being `DeviceRef`.
+            &*(device as *const DeviceRef as *const Self)
+        }
+    }
+}
+
+/// Returns storage mode and CPU cache mode of memory type with specified index.
+pub fn memory_type_modes(memory_type: u32) -> (MTLStorageMode, MTLCPUCacheMode) {
+    match memory_type {
+        PRIVATE_MEMORY_TYPE => (MTLStorageMode::Private, MTLCPUCacheMode::DefaultCache),
+        SHARED_WRITE_COMBINED_MEMORY_TYPE => {
+            (MTLStorageMode::Shared, MTLCPUCacheMode::WriteCombined)
+        }
+        SHARED_CACHED_MEMORY_TYPE => (MTLStorageMode::Shared, MTLCPUCacheMode::DefaultCache),
+        _ => panic!("Invalid memory type index: `{}`", memory_type),
+    }
+}
+
+/// Returns resource options matching memory type with specified index.
+/// Resources placed into memory block must be created with these options.
+pub fn memory_type_resource_options(memory_type: u32) -> MTLResourceOptions {
+    let mut options = MTLResourceOptions::HazardTrackingModeUntracked;
+    match memory_type_modes(memory_type) {
+        (MTLStorageMode::Private, _) => options |= MTLResourceOptions::StorageModePrivate,
+        (_, MTLCPUCacheMode::WriteCombined) => {
+            options |= MTLResourceOptions::StorageModeShared
+                | MTLResourceOptions::CPUCacheModeWriteCombined
+        }
+        _ => options |= MTLResourceOptions::StorageModeShared,
+    }
+    options
+}
+
+impl MemoryDevice<MetalMemory> for MetalMemoryDevice {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<MetalMemory, OutOfMemory> {
+        assert!(
+            flags.is_empty(),
+            "Metal backend does not support allocation flags"
+        );
+
+        let (storage_mode, cpu_cache_mode) = memory_type_modes(memory_type);
+
+        let descriptor = HeapDescriptor::new();
+        descriptor.set_size(size);
+        descriptor.set_storage_mode(storage_mode);
+        descriptor.set_cpu_cache_mode(cpu_cache_mode);
+        descriptor.set_heap_type(MTLHeapType::Placement);
+
+        // Metal returns nil heap when it is unable to allocate memory,
+        // and size of nil heap reads as zero.
+        let heap = self.device.new_heap(&descriptor);
+        if heap.size() < size {
+            return Err(OutOfMemory::OutOfDeviceMemory);
+        }
+
+        let buffer = match storage_mode {
+            MTLStorageMode::Private => None,
+            _ => {
+                let buffer = heap
+                    .new_buffer_with_offset(size, memory_type_resource_options(memory_type), 0)
+                    .ok_or(OutOfMemory::OutOfHostMemory)?;
+                Some(buffer)
+            }
+        };
+
+        Ok(MetalMemory { heap, buffer, size })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn deallocate_memory(&self, memory: MetalMemory) {
+        // Dropping last references releases the heap.
+        drop(memory);
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn map_memory(
+        &self,
+        memory: &mut MetalMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        debug_assert!(offset + size <= memory.size);
+
+        // Shared buffer contents are always accessible to the host.
+        let buffer = memory.buffer.as_ref().ok_or(DeviceMapError::MapFailed)?;
+        let ptr = buffer.contents().cast::<u8>().add(offset as usize);
+        NonNull::new(ptr).ok_or(DeviceMapError::MapFailed)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn unmap_memory(&self, _memory: &mut MetalMemory) {}
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, MetalMemory>],
+    ) -> Result<(), OutOfMemory> {
+        // All host-visible memory types are coherent.
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn flush_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, MetalMemory>],
+    ) -> Result<(), OutOfMemory> {
+        // All host-visible memory types are coherent.
+        Ok(())
+    }
+}
+
+/// Returns `DeviceProperties` for specified Metal device, required to create `GpuAllocator`.
+///
+/// Memory types are indexed by `PRIVATE_MEMORY_TYPE`, `SHARED_WRITE_COMBINED_MEMORY_TYPE`
+/// and `SHARED_CACHED_MEMORY_TYPE`.
+/// Heap sizes are set to device's recommended working set size.
+/// Devices with unified memory have single heap,
+/// otherwise private memory type use heap 0 and shared memory types use heap 1.
+pub fn device_properties(device: &DeviceRef) -> DeviceProperties<'static> {
+    let unified = device.has_unified_memory();
+    let size = device.recommended_max_working_set_size();
+
+    let (shared_heap, shared_props, memory_heaps) = if unified {
+        (
+            0,
+            MemoryPropertyFlags::DEVICE_LOCAL,
+            vec![MemoryHeap { size }],
+        )
+    } else {
+        (
+            1,
+            MemoryPropertyFlags::empty(),
+            vec![MemoryHeap { size }, MemoryHeap { size }],
+        )
+    };
+
+    DeviceProperties {
+        memory_types: vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: shared_heap,
+                props: shared_props
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: shared_heap,
+                props: shared_props
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT
+                    | MemoryPropertyFlags::HOST_CACHED,
+            },
+        ]
+        .into(),
+        memory_heaps: memory_heaps.into(),
+        // Metal does not limit number of heaps.
+        max_memory_allocation_count: u32::MAX,
+        max_memory_allocation_size: device.max_buffer_length(),
+        non_coherent_atom_size: 1,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    }
+}
@@ -1,29 +1,107 @@
+//!
+//! Mock backend for `gpu-alloc`.
+//!
+//! `MockMemoryDevice` simulates memory heaps of configurable sizes
+//! and validates all calls made by allocator.
+//! Memory of host-visible memory objects is backed by host memory,
+//! so data written through mapping can be read back later.
+//!
+//! Failures can be injected for specific allocation or mapping
+//! to test recovery paths.
+//!
+//! Device counts memory objects, mappings, flushes and invalidations,
+//! so tests can check how allocator uses the device without a GPU.
+//!
+//! # Usage example
+//!
+//! ```
+//! use {
+//!     gpu_alloc::{Config, GpuAllocator, MemoryPropertyFlags, Request, UsageFlags},
+//!     gpu_alloc_mock::MockMemoryDevice,
+//!     gpu_alloc_types::{DeviceProperties, MemoryHeap, MemoryType},
+//! };
+//!
+//! let device = MockMemoryDevice::new(DeviceProperties {
+//!     memory_types: vec![MemoryType {
+//!         heap: 0,
+//!         props: MemoryPropertyFlags::HOST_VISIBLE,
+//!     }]
+//!     .into(),
+//!     memory_heaps: vec![MemoryHeap { size: 1 << 20 }].into(),
+//!     max_memory_allocation_count: 16,
+//!     max_memory_allocation_size: 1 << 20,
+//!     non_coherent_atom_size: 64,
+//!     buffer_device_address: false,
+//!     buffer_image_granularity: 1,
+//! });
+//!
+//! let mut allocator = GpuAllocator::new(Config::i_am_prototyping(), device.props());
+//!
+//! let mut block = unsafe {
+//!     allocator.alloc(
+//!         &device,
+//!         Request {
+//!             size: 16,
+//!             align_mask: 0,
+//!             usage: UsageFlags::UPLOAD,
+//!             memory_types: !0,
+//!             ..Request::default()
+//!         },
+//!     )
+//! }
+//! .unwrap();
+//!
+//! unsafe { block.write_bytes(&device, 0, &[1; 16]) }.unwrap();
+//! assert_eq!(device.total_flushes(), 1);
+//!
+//! unsafe {
+//!     allocator.dealloc(&device, block);
+//!     allocator.cleanup(&device);
+//! }
+//! assert_eq!(device.live_allocations(), 0);
+//! ```
+//!
+
 use {
     gpu_alloc_types::{
-        AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
-        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        AllocationFlags, DedicatedResource, DeviceBindError, DeviceImportError, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryHeapBudget,
+        MemoryPropertyFlags, MemoryType, OutOfMemory,
     },
     slab::Slab,
     std::{
         borrow::Cow,
-        cell::{Cell, RefCell, UnsafeCell},
+        cell::{Cell, RefCell},
         convert::TryFrom as _,
-        mem::transmute,
         ptr::NonNull,
     },
 };
 
+#[cfg(feature = "async")]
+use gpu_alloc_types::{AllocateMemoryFuture, AsyncMemoryDevice};
+
 struct MemoryMapping {
-    content: Box<UnsafeCell<[u8]>>,
     offset: u64,
+    size: u64,
 }
 
 struct MockMemory {
     memory_type: u32,
     size: u64,
+    content: Vec<u8>,
     mapped: Option<MemoryMapping>,
+    imported: bool,
+    flags: AllocationFlags,
+    priority: Option<f32>,
+    dedicated_resource: Option<DedicatedResource>,
+    bindings: Vec<(DedicatedResource, u64)>,
 }
 
+/// Memory device that allocates memory objects from simulated heaps.
+///
+/// Memory objects are identified by `usize` keys.
+/// Invalid calls, such as mapping memory twice, flushing unmapped range
+/// or allocating more memory objects than allowed, cause panic.
 pub struct MockMemoryDevice {
     memory_types: Box<[MemoryType]>,
     memory_heaps: Box<[MemoryHeap]>,
@@ -31,6 +109,7 @@ pub struct MockMemoryDevice {
     max_memory_allocation_size: u64,
     non_coherent_atom_size: u64,
     buffer_device_address: bool,
+    buffer_image_granularity: u64,
 
     allocations_remains: Cell<u32>,
     memory_heaps_remaining_capacity: Box<[Cell<u64>]>,
@@ -38,9 +117,22 @@ pub struct MockMemoryDevice {
 
     total_allocations_counter: Cell<u64>,
     total_deallocations_counter: Cell<u64>,
+    #[cfg(feature = "async")]
+    total_async_allocations_counter: Cell<u64>,
+
+    allocation_attempts: Cell<u64>,
+    map_attempts: Cell<u64>,
+    allocation_failures: RefCell<Vec<(u64, OutOfMemory)>>,
+    map_failures: RefCell<Vec<(u64, DeviceMapError)>>,
+
+    total_flushes_counter: Cell<u64>,
+    total_invalidations_counter: Cell<u64>,
+
+    memory_heaps_budget: Box<[Cell<Option<u64>>]>,
 }
 
 impl MockMemoryDevice {
+    /// Creates device with memory types, heaps and limits from `props`.
     pub fn new(props: DeviceProperties<'_>) -> Self {
         MockMemoryDevice {
             memory_heaps_remaining_capacity: props
@@ -49,6 +141,7 @@ impl MockMemoryDevice {
                 .iter()
                 .map(|heap| Cell::new(heap.size))
                 .collect(),
+            memory_heaps_budget: props.memory_heaps.iter().map(|_| Cell::new(None)).collect(),
 
             memory_types: props.memory_types.into_owned().into_boxed_slice(),
             memory_heaps: props.memory_heaps.into_owned().into_boxed_slice(),
@@ -56,15 +149,27 @@ impl MockMemoryDevice {
             max_memory_allocation_size: props.max_memory_allocation_size,
             non_coherent_atom_size: props.non_coherent_atom_size,
             buffer_device_address: props.buffer_device_address,
+            buffer_image_granularity: props.buffer_image_granularity,
 
             allocations_remains: Cell::new(props.max_memory_allocation_count),
             allocations: RefCell::new(Slab::new()),
 
             total_allocations_counter: Cell::new(0),
             total_deallocations_counter: Cell::new(0),
+            #[cfg(feature = "async")]
+            total_async_allocations_counter: Cell::new(0),
+
+            allocation_attempts: Cell::new(0),
+            map_attempts: Cell::new(0),
+            allocation_failures: RefCell::new(Vec::new()),
+            map_failures: RefCell::new(Vec::new()),
+
+            total_flushes_counter: Cell::new(0),
+            total_invalidations_counter: Cell::new(0),
         }
     }
 
+    /// Returns properties of the device to create `GpuAllocator` with.
     pub fn props(&self) -> DeviceProperties<'_> {
         DeviceProperties {
             memory_types: Cow::Borrowed(&self.memory_types),
@@ -73,16 +178,197 @@ impl MockMemoryDevice {
             max_memory_allocation_size: self.max_memory_allocation_size,
             non_coherent_atom_size: self.non_coherent_atom_size,
             buffer_device_address: self.buffer_device_address,
+            buffer_image_granularity: self.buffer_image_granularity,
         }
     }
 
+    /// Returns number of memory objects allocated or imported since device creation.
     pub fn total_allocations(&self) -> u64 {
         self.total_allocations_counter.get()
     }
 
+    /// Returns number of memory objects deallocated since device creation.
     pub fn total_deallocations(&self) -> u64 {
         self.total_deallocations_counter.get()
     }
+
+    /// Returns number of `allocate_memory_async` calls since device creation,
+    /// including ones that failed.
+    #[cfg(feature = "async")]
+    pub fn total_async_allocations(&self) -> u64 {
+        self.total_async_allocations_counter.get()
+    }
+
+    /// Returns number of `map_memory` calls since device creation,
+    /// including ones that failed.
+    pub fn total_maps(&self) -> u64 {
+        self.map_attempts.get()
+    }
+
+    /// Returns number of ranges flushed since device creation.
+    pub fn total_flushes(&self) -> u64 {
+        self.total_flushes_counter.get()
+    }
+
+    /// Returns number of ranges invalidated since device creation.
+    pub fn total_invalidations(&self) -> u64 {
+        self.total_invalidations_counter.get()
+    }
+
+    /// Returns number of memory objects that are currently mapped.
+    pub fn live_mappings(&self) -> usize {
+        self.allocations
+            .borrow()
+            .iter()
+            .filter(|(_, memory)| memory.mapped.is_some())
+            .count()
+    }
+
+    /// Returns number of memory objects currently allocated from this device.
+    pub fn live_allocations(&self) -> usize {
+        self.allocations.borrow().len()
+    }
+
+    /// Returns number of bytes currently allocated from specified heap.
+    pub fn heap_usage(&self, heap: u32) -> u64 {
+        self.memory_heaps[heap as usize].size
+            - self.memory_heaps_remaining_capacity[heap as usize].get()
+    }
+
+    /// Makes `nth` call to `allocate_memory` fail with specified error.
+    /// Calls are counted from device creation, starting with 1.
+    pub fn fail_allocation(&self, nth: u64, error: OutOfMemory) {
+        self.allocation_failures.borrow_mut().push((nth, error));
+    }
+
+    /// Makes `nth` call to `map_memory` fail with specified error.
+    /// Calls are counted from device creation, starting with 1.
+    pub fn fail_map(&self, nth: u64, error: DeviceMapError) {
+        self.map_failures.borrow_mut().push((nth, error));
+    }
+
+    /// Makes device report `budget` for specified heap.
+    /// Budget is not enforced by the device itself.
+    pub fn set_memory_budget(&self, heap: u32, budget: u64) {
+        self.memory_heaps_budget[heap as usize].set(Some(budget));
+    }
+
+    /// Copies content of the memory object.
+    ///
+    /// Content of memory objects that were never mapped is empty.
+    pub fn memory_content(&self, memory: usize) -> Vec<u8> {
+        let allocations = self.allocations.borrow();
+        let memory = allocations.get(memory).expect("Non-existing memory object");
+        memory.content.clone()
+    }
+
+    /// Returns resource the memory object is dedicated to.
+    pub fn memory_dedicated_resource(&self, memory: usize) -> Option<DedicatedResource> {
+        let allocations = self.allocations.borrow();
+        let memory = allocations.get(memory).expect("Non-existing memory object");
+        memory.dedicated_resource
+    }
+
+    /// Returns flags the memory object was allocated with.
+    pub fn memory_allocation_flags(&self, memory: usize) -> AllocationFlags {
+        let allocations = self.allocations.borrow();
+        let memory = allocations.get(memory).expect("Non-existing memory object");
+        memory.flags
+    }
+
+    /// Returns priority the memory object was allocated with.
+    pub fn memory_priority(&self, memory: usize) -> Option<f32> {
+        let allocations = self.allocations.borrow();
+        let memory = allocations.get(memory).expect("Non-existing memory object");
+        memory.priority
+    }
+
+    /// Returns memory object and offset the resource is bound to.
+    /// Bindings are forgotten when memory object is deallocated.
+    pub fn resource_binding(&self, resource: DedicatedResource) -> Option<(usize, u64)> {
+        self.allocations
+            .borrow()
+            .iter()
+            .find_map(|(index, memory)| {
+                memory
+                    .bindings
+                    .iter()
+                    .find(|&&(bound, _)| bound == resource)
+                    .map(|&(_, offset)| (index, offset))
+            })
+    }
+
+    fn bind(
+        &self,
+        resource: DedicatedResource,
+        memory: usize,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        assert!(
+            self.resource_binding(resource).is_none(),
+            "Resource is already bound to memory"
+        );
+
+        let mut allocations = self.allocations.borrow_mut();
+        let memory = allocations
+            .get_mut(memory)
+            .expect("Non-existing memory object");
+
+        assert!(offset < memory.size, "`offset` is out of memory object");
+        assert!(
+            memory.dedicated_resource.is_none() || memory.dedicated_resource == Some(resource),
+            "Dedicated memory object can be bound only to its resource"
+        );
+
+        memory.bindings.push((resource, offset));
+        tracing::info!("Resource bound");
+        Ok(())
+    }
+
+    fn validate_range(&self, range: &MappedMemoryRange<'_, usize>) {
+        let allocations = self.allocations.borrow();
+        let memory = allocations
+            .get(*range.memory)
+            .expect("Non-existing memory object");
+
+        let mapped = memory.mapped.as_ref().expect("Not mapped");
+
+        let coherent = self.memory_types[memory.memory_type as usize]
+            .props
+            .contains(MemoryPropertyFlags::HOST_COHERENT);
+
+        if coherent {
+            tracing::warn!("Invalidating or flushing host-coherent memory");
+        }
+
+        assert!(
+            range.offset >= mapped.offset,
+            "range `offset` specifies range before mapped region"
+        );
+        assert!(
+            range.offset - mapped.offset <= mapped.size,
+            "range `offset` specifies range after mapped region"
+        );
+        assert!(
+            range.size <= mapped.size - (range.offset - mapped.offset),
+            "range `size` specifies range after mapped region"
+        );
+        assert!(
+            range.offset & (self.non_coherent_atom_size - 1) == 0,
+            "`offset` must be a multiple of `non_coherent_atom_size`"
+        );
+        assert!(
+            range.size & (self.non_coherent_atom_size - 1) == 0
+                || range.offset + range.size == memory.size,
+            "`size` must either be a multiple of `non_coherent_atom_size`, or `offset + size` must equal the size of memory"
+        );
+    }
+}
+
+fn injected<E: Copy>(failures: &RefCell<Vec<(u64, E)>>, attempt: u64) -> Option<E> {
+    let mut failures = failures.borrow_mut();
+    let index = failures.iter().position(|&(nth, _)| nth == attempt)?;
+    Some(failures.swap_remove(index).1)
 }
 
 impl MemoryDevice<usize> for MockMemoryDevice {
@@ -101,12 +387,19 @@ impl MemoryDevice<usize> for MockMemoryDevice {
             "Allocation size exceeds limit"
         );
 
+        let attempt = self.allocation_attempts.get() + 1;
+        self.allocation_attempts.set(attempt);
+
+        if let Some(error) = injected(&self.allocation_failures, attempt) {
+            tracing::info!("Injected memory object allocation failure");
+            return Err(error);
+        }
+
         let allocations_remains = self.allocations_remains.get();
         assert!(
             allocations_remains > 0,
             "Allocator should not try to allocate too many objects"
         );
-        self.allocations_remains.set(allocations_remains - 1);
 
         let heap = &self.memory_heaps_remaining_capacity
             [self.memory_types[memory_type as usize].heap as usize];
@@ -114,6 +407,7 @@ impl MemoryDevice<usize> for MockMemoryDevice {
             return Err(OutOfMemory::OutOfDeviceMemory);
         }
         heap.set(heap.get() - size);
+        self.allocations_remains.set(allocations_remains - 1);
 
         tracing::info!("Memory object allocated");
 
@@ -123,13 +417,63 @@ impl MemoryDevice<usize> for MockMemoryDevice {
         Ok(self.allocations.borrow_mut().insert(MockMemory {
             memory_type,
             size,
+            content: Vec::new(),
             mapped: None,
+            imported: false,
+            flags,
+            priority: None,
+            dedicated_resource: None,
+            bindings: Vec::new(),
         }))
     }
 
+    #[tracing::instrument(skip(self))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<usize, OutOfMemory> {
+        assert!(
+            self.allocations
+                .borrow()
+                .iter()
+                .all(|(_, memory)| memory.dedicated_resource != Some(resource)),
+            "Resource already has dedicated memory object"
+        );
+
+        let memory = self.allocate_memory(size, memory_type, flags)?;
+        self.allocations.borrow_mut()[memory].dedicated_resource = Some(resource);
+        Ok(memory)
+    }
+
+    #[tracing::instrument(skip(self))]
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<usize, OutOfMemory> {
+        assert!(
+            (0.0..=1.0).contains(&priority),
+            "Priority must be in range [0.0, 1.0]"
+        );
+
+        let memory = match resource {
+            Some(resource) => self.allocate_dedicated_memory(size, memory_type, flags, resource)?,
+            None => self.allocate_memory(size, memory_type, flags)?,
+        };
+        self.allocations.borrow_mut()[memory].priority = Some(priority);
+        Ok(memory)
+    }
+
     #[tracing::instrument(skip(self))]
     unsafe fn deallocate_memory(&self, memory: usize) {
         let memory = self.allocations.borrow_mut().remove(memory);
+        // Resources bound to memory object are destroyed with it.
         self.allocations_remains
             .set(self.allocations_remains.get() + 1);
         let heap = &self.memory_heaps_remaining_capacity
@@ -162,26 +506,40 @@ impl MemoryDevice<usize> for MockMemoryDevice {
             "Attempt to map non-host-visible memory"
         );
 
+        assert!(
+            !memory.imported,
+            "Imported memory is accessed through host pointer"
+        );
         assert!(memory.mapped.is_none(), "Already mapped");
 
         assert!(
             offset < memory.size,
             "offset must be less than the size of memory"
         );
-        assert_ne!(size, 0, "Mapping size must be greater than 0");
         assert!(
             size <= memory.size - offset,
             "size must be less than or equal to the size of the memory minus offset"
         );
 
-        let size_usize = usize::try_from(size).map_err(|_| DeviceMapError::OutOfHostMemory)?;
-        let mapping = memory.mapped.get_or_insert(MemoryMapping {
-            content: transmute(vec![0; size_usize].into_boxed_slice()),
-            offset,
-        });
+        let attempt = self.map_attempts.get() + 1;
+        self.map_attempts.set(attempt);
+
+        if let Some(error) = injected(&self.map_failures, attempt) {
+            tracing::info!("Injected memory object mapping failure");
+            return Err(error);
+        }
+
+        if memory.content.is_empty() {
+            let size = usize::try_from(memory.size).map_err(|_| DeviceMapError::OutOfHostMemory)?;
+            memory.content = vec![0; size];
+        }
+
+        memory.mapped = Some(MemoryMapping { offset, size });
 
         tracing::info!("Memory object mapped");
-        Ok(NonNull::from(&mut (&mut *mapping.content.get())[0]))
+        Ok(NonNull::new_unchecked(
+            memory.content.as_mut_ptr().add(offset as usize),
+        ))
     }
 
     unsafe fn unmap_memory(&self, memory: &mut usize) {
@@ -197,47 +555,10 @@ impl MemoryDevice<usize> for MockMemoryDevice {
         ranges: &[MappedMemoryRange<'_, usize>],
     ) -> Result<(), OutOfMemory> {
         for range in ranges {
-            let mut allocations = self.allocations.borrow_mut();
-            let memory = allocations
-                .get_mut(*range.memory)
-                .expect("Non-existing memory object");
-
-            let mapped = memory.mapped.as_ref().expect("Not mapped");
-
-            let coherent = self.memory_types[memory.memory_type as usize]
-                .props
-                .contains(MemoryPropertyFlags::HOST_COHERENT);
-
-            if coherent {
-                tracing::warn!("Invalidating host-coherent memory");
-            }
-
-            let mapped_size = (&*mapped.content.get()).len() as u64;
-
-            assert!(
-                range.offset >= mapped.offset,
-                "range `offset` specifies range before mapped region"
-            );
-            assert!(
-                range.offset - mapped.offset <= mapped_size,
-                "range `offset` specifies range after mapped region"
-            );
-            assert!(
-                range.size < mapped_size - (range.offset - mapped.offset),
-                "range `size` specifies range after mapped region"
-            );
-            assert_eq!(
-                range.offset % self.non_coherent_atom_size,
-                0,
-                "`offset` must be a multiple of `non_coherent_atom_size`"
-            );
-            assert!(
-                range.size % self.non_coherent_atom_size == 0
-                    || range.offset + range.size == memory.size,
-                "`size` must either be a multiple of `non_coherent_atom_size`, or `offset + size` must equal the size of memory"
-            );
+            self.validate_range(range);
         }
-
+        self.total_invalidations_counter
+            .set(self.total_invalidations_counter.get() + ranges.len() as u64);
         Ok(())
     }
 
@@ -246,46 +567,101 @@ impl MemoryDevice<usize> for MockMemoryDevice {
         ranges: &[MappedMemoryRange<'_, usize>],
     ) -> Result<(), OutOfMemory> {
         for range in ranges {
-            let mut allocations = self.allocations.borrow_mut();
-            let memory = allocations
-                .get_mut(*range.memory)
-                .expect("Non-existing memory object");
+            self.validate_range(range);
+        }
+        self.total_flushes_counter
+            .set(self.total_flushes_counter.get() + ranges.len() as u64);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self))]
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &usize,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.bind(DedicatedResource::Buffer(buffer), *memory, offset)
+    }
 
-            let mapped = memory.mapped.as_ref().expect("Not mapped");
+    #[tracing::instrument(skip(self))]
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &usize,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        self.bind(DedicatedResource::Image(image), *memory, offset)
+    }
 
-            let coherent = self.memory_types[memory.memory_type as usize]
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<usize, DeviceImportError> {
+        assert!(
+            self.memory_types[memory_type as usize]
                 .props
-                .contains(MemoryPropertyFlags::HOST_COHERENT);
-
-            if coherent {
-                tracing::warn!("Invalidating host-coherent memory");
-            }
-
-            let mapped_size = (&*mapped.content.get()).len() as u64;
-
-            assert!(
-                range.offset >= mapped.offset,
-                "`offset` specifies range before mapped region"
-            );
-            assert!(
-                range.offset - mapped.offset <= mapped_size,
-                "`offset` specifies range after mapped region"
-            );
-            assert!(
-                range.size < mapped_size - (range.offset - mapped.offset),
-                "`size` specifies range after mapped region"
-            );
-            assert_eq!(
-                range.offset % self.non_coherent_atom_size,
-                0,
-                "`offset` must be a multiple of `non_coherent_atom_size`"
-            );
-            assert!(
-                range.size % self.non_coherent_atom_size == 0
-                    || range.offset + range.size == memory.size,
-                "`size` must either be a multiple of `non_coherent_atom_size`, or `offset + size` must equal the size of memory"
-            );
+                .contains(MemoryPropertyFlags::HOST_VISIBLE),
+            "Attempt to import host memory into non-host-visible memory type"
+        );
+
+        let allocations_remains = self.allocations_remains.get();
+        assert!(
+            allocations_remains > 0,
+            "Allocator should not try to allocate too many objects"
+        );
+
+        let heap = &self.memory_heaps_remaining_capacity
+            [self.memory_types[memory_type as usize].heap as usize];
+        if heap.get() < size {
+            return Err(DeviceImportError::OutOfDeviceMemory);
         }
-        Ok(())
+        heap.set(heap.get() - size);
+        self.allocations_remains.set(allocations_remains - 1);
+
+        tracing::info!("Host memory at {:p} imported", ptr);
+
+        self.total_allocations_counter
+            .set(self.total_allocations_counter.get() + 1);
+
+        Ok(self.allocations.borrow_mut().insert(MockMemory {
+            memory_type,
+            size,
+            content: Vec::new(),
+            mapped: None,
+            imported: true,
+            flags: AllocationFlags::empty(),
+            priority: None,
+            dedicated_resource: None,
+            bindings: Vec::new(),
+        }))
+    }
+
+    fn memory_budget(&self, heap: u32) -> Option<MemoryHeapBudget> {
+        let budget = self.memory_heaps_budget[heap as usize].get()?;
+        Some(MemoryHeapBudget {
+            usage: self.heap_usage(heap),
+            budget,
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncMemoryDevice<usize> for MockMemoryDevice {
+    /// Allocates memory object when returned future is first polled.
+    unsafe fn allocate_memory_async(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> AllocateMemoryFuture<'_, usize> {
+        Box::pin(async move {
+            self.total_async_allocations_counter
+                .set(self.total_async_allocations_counter.get() + 1);
+            self.allocate_memory(size, memory_type, flags)
+        })
     }
 }
@@ -1,11 +1,17 @@
 use {
-    crate::types::{MemoryHeap, MemoryType},
+    crate::types::{MemoryHeap, MemoryHeapBudget, MemoryType},
     alloc::borrow::Cow,
     core::ptr::NonNull,
 };
 
+#[cfg(feature = "async")]
+use {
+    alloc::boxed::Box,
+    core::{future::Future, pin::Pin},
+};
+
 /// Memory exhausted error.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum OutOfMemory {
     /// Device memory exhausted.
     OutOfDeviceMemory,
@@ -15,7 +21,7 @@ pub enum OutOfMemory {
 }
 
 /// Memory mapped error.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum DeviceMapError {
     /// Device memory exhausted.
     OutOfDeviceMemory,
@@ -27,6 +33,35 @@ pub enum DeviceMapError {
     MapFailed,
 }
 
+/// Host memory import error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceImportError {
+    /// Device memory exhausted.
+    OutOfDeviceMemory,
+
+    /// Host memory exhausted.
+    OutOfHostMemory,
+
+    /// Host pointer cannot be imported into specified memory type.
+    InvalidExternalHandle,
+
+    /// Device does not support importing host memory.
+    Unsupported,
+}
+
+/// Resource binding error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceBindError {
+    /// Device memory exhausted.
+    OutOfDeviceMemory,
+
+    /// Host memory exhausted.
+    OutOfHostMemory,
+
+    /// Device does not support binding resources through memory device.
+    Unsupported,
+}
+
 /// Specifies range of the mapped memory region.
 #[derive(Debug)]
 pub struct MappedMemoryRange<'a, M> {
@@ -44,6 +79,7 @@ pub struct MappedMemoryRange<'a, M> {
 ///
 /// See `gpu-alloc-<backend>` crate to learn how to obtain one for backend of choice.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviceProperties<'a> {
     /// Array of memory types provided by the device.
     pub memory_types: Cow<'a, [MemoryType]>,
@@ -62,6 +98,11 @@ pub struct DeviceProperties<'a> {
 
     /// Specifies if feature required to fetch device address is enabled.
     pub buffer_device_address: bool,
+
+    /// Granularity in bytes at which buffers and linear images
+    /// must not share memory with optimal images.
+    /// Must be power of two.
+    pub buffer_image_granularity: u64,
 }
 
 bitflags::bitflags! {
@@ -71,9 +112,27 @@ bitflags::bitflags! {
         /// Specifies that the memory can be used for buffers created
         /// with flag that allows fetching device address.
         const DEVICE_ADDRESS = 0x1;
+
+        /// Specifies that the memory can be exported as opaque POSIX file descriptor.
+        const EXPORT_OPAQUE_FD = 0x2;
+
+        /// Specifies that the memory can be exported as opaque Win32 handle.
+        const EXPORT_OPAQUE_WIN32 = 0x4;
     }
 }
 
+/// Resource that memory object is dedicated to.
+/// Holds raw handle of the resource as defined by backend,
+/// for example `VkBuffer` or `VkImage` handle converted to `u64`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DedicatedResource {
+    /// Buffer with raw handle.
+    Buffer(u64),
+
+    /// Image with raw handle.
+    Image(u64),
+}
+
 /// Abstract device that can be used to allocate memory objects.
 pub trait MemoryDevice<M> {
     /// Allocates new memory object from device.
@@ -94,6 +153,54 @@ pub trait MemoryDevice<M> {
         flags: AllocationFlags,
     ) -> Result<M, OutOfMemory>;
 
+    /// Allocates new memory object that can be bound only to specified `resource`,
+    /// as "VK_KHR_dedicated_allocation" does.
+    ///
+    /// Default implementation ignores `resource` and calls `allocate_memory`.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `allocate_memory`.\
+    /// Additionally `resource` must be valid handle of resource created from this device
+    /// that is not bound to memory yet.
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<M, OutOfMemory> {
+        let _ = resource;
+        self.allocate_memory(size, memory_type, flags)
+    }
+
+    /// Allocates new memory object with `priority` in range `[0.0, 1.0]`,
+    /// as "VK_EXT_memory_priority" does.
+    /// Memory with higher priority is less likely to be evicted from device-local heap.\
+    /// If `resource` is specified, memory object can be bound only to it.
+    ///
+    /// Default implementation ignores `priority` and calls `allocate_memory`
+    /// or `allocate_dedicated_memory`.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `allocate_dedicated_memory` if `resource` is specified,
+    /// otherwise same as for `allocate_memory`.
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<M, OutOfMemory> {
+        let _ = priority;
+        match resource {
+            Some(resource) => self.allocate_dedicated_memory(size, memory_type, flags, resource),
+            None => self.allocate_memory(size, memory_type, flags),
+        }
+    }
+
     /// Deallocate memory object.
     ///
     /// # Safety
@@ -154,4 +261,101 @@ pub trait MemoryDevice<M> {
         &self,
         ranges: &[MappedMemoryRange<'_, M>],
     ) -> Result<(), OutOfMemory>;
+
+    /// Imports host memory as new memory object.
+    /// Imported memory object is accessed by host through `ptr`
+    /// and is never mapped.
+    ///
+    /// Default implementation returns `DeviceImportError::Unsupported`.
+    ///
+    /// # Safety
+    ///
+    /// * `memory_type` must be valid index for memory type associated with this device
+    ///   and must support importing `ptr`.
+    /// * `ptr` must point to host memory range of `size` bytes
+    ///   that outlives imported memory object.
+    /// * `ptr` and `size` must be aligned to device's minimal imported host pointer alignment.
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<M, DeviceImportError> {
+        let _ = (ptr, size, memory_type);
+        Err(DeviceImportError::Unsupported)
+    }
+
+    /// Binds buffer to memory object at specified offset.
+    ///
+    /// Default implementation returns `DeviceBindError::Unsupported`.
+    ///
+    /// # Safety
+    ///
+    /// * `buffer` must be valid raw handle of buffer created from this device
+    ///   that is not bound to memory yet.
+    /// * Memory object must have been allocated from this device.
+    /// * Range of memory object starting at `offset` must satisfy
+    ///   memory requirements of the buffer.
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        let _ = (buffer, memory, offset);
+        Err(DeviceBindError::Unsupported)
+    }
+
+    /// Binds image to memory object at specified offset.
+    ///
+    /// Default implementation returns `DeviceBindError::Unsupported`.
+    ///
+    /// # Safety
+    ///
+    /// * `image` must be valid raw handle of image created from this device
+    ///   that is not bound to memory yet.
+    /// * Memory object must have been allocated from this device.
+    /// * Range of memory object starting at `offset` must satisfy
+    ///   memory requirements of the image.
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &M,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        let _ = (image, memory, offset);
+        Err(DeviceBindError::Unsupported)
+    }
+
+    /// Returns current usage and budget of memory heap with specified index,
+    /// as reported by "VK_EXT_memory_budget" or equivalent.
+    /// Queried before each memory object allocation.
+    ///
+    /// Default implementation returns `None`, meaning budget is unknown.
+    fn memory_budget(&self, heap: u32) -> Option<MemoryHeapBudget> {
+        let _ = heap;
+        None
+    }
+}
+
+/// Future returned by `AsyncMemoryDevice::allocate_memory_async`.
+#[cfg(feature = "async")]
+pub type AllocateMemoryFuture<'a, M> = Pin<Box<dyn Future<Output = Result<M, OutOfMemory>> + 'a>>;
+
+/// Memory device that can allocate memory objects without blocking,
+/// for devices where allocation may take milliseconds in the driver.
+#[cfg(feature = "async")]
+pub trait AsyncMemoryDevice<M>: MemoryDevice<M> {
+    /// Allocates new memory object from device asynchronously,
+    /// as `MemoryDevice::allocate_memory` does.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `MemoryDevice::allocate_memory`.
+    unsafe fn allocate_memory_async(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> AllocateMemoryFuture<'_, M>;
 }
@@ -38,6 +38,7 @@ bitflags::bitflags! {
 
 /// Defines memory type.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryType {
     /// Heap index of the memory type.
     pub heap: u32,
@@ -48,7 +49,20 @@ pub struct MemoryType {
 
 /// Defines memory heap.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryHeap {
     /// Size of memory heap in bytes.
     pub size: u64,
 }
+
+/// Current usage and budget of memory heap.
+/// Corresponds to `VkPhysicalDeviceMemoryBudgetPropertiesEXT` values for one heap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryHeapBudget {
+    /// Size in bytes of memory currently allocated from the heap by the process.
+    pub usage: u64,
+
+    /// Size in bytes of memory the process can allocate from the heap
+    /// without allocation failure or performance degradation.
+    pub budget: u64,
+}
@@ -0,0 +1,316 @@
+//!
+//! # WebGPU backend for `gpu-alloc`
+//!
+//! Allows browser builds targeting `wasm32-unknown-unknown`
+//! to sub-allocate `GPUBuffer`s with `gpu-alloc`.
+//! WebGPU bindings of `web-sys` are unstable and require `--cfg=web_sys_unstable_apis`
+//! in `RUSTFLAGS`.
+//!
+//! Memory objects are `GPUBuffer`s, blocks are ranges of them
+//! to be bound at offset `MemoryBlock::offset` of `WebGpuMemory::buffer`.
+//! WebGPU doesn't allow placing textures into memory allocated beforehand,
+//! so only buffers can be sub-allocated.
+//!
+//! Memory types map to buffer usages:
+//!
+//! * `DEVICE_MEMORY_TYPE` - `DEVICE_LOCAL`, all buffer usages except mapping.
+//! * `UPLOAD_MEMORY_TYPE` - `DEVICE_LOCAL | HOST_VISIBLE`, same usages.
+//! * `READBACK_MEMORY_TYPE` - `HOST_VISIBLE | HOST_CACHED`, `MAP_READ | COPY_DST` usages.
+//!
+//! `GPUBuffer.mapAsync` can't be awaited in `MemoryDevice::map_memory`,
+//! so host-visible memory objects keep copy of their content in wasm memory,
+//! which is what mapping returns.
+//! Host-visible memory types are not `HOST_COHERENT`:
+//! flushing upload memory writes flushed ranges with `GPUQueue.writeBuffer`,
+//! while readback memory must be copied back with `WebGpuMemory::read_back`
+//! after device writes it and before it is invalidated and read.
+//!
+//! # Usage example
+//!
+//! ```ignore
+//! use {
+//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc_webgpu::{device_properties, WebGpuMemoryDevice},
+//!     web_sys::GpuDevice,
+//! };
+//!
+//! fn write_data(device: GpuDevice) -> eyre::Result<()> {
+//!     let props = device_properties(&device, 256 * 1024 * 1024);
+//!     let device = WebGpuMemoryDevice::new(device);
+//!
+//!     let mut allocator = GpuAllocator::new(Config::i_am_potato(), props);
+//!
+//!     let mut block = unsafe {
+//!         allocator.alloc(
+//!             &device,
+//!             Request {
+//!                 size: 12,
+//!                 align_mask: 255,
+//!                 usage: UsageFlags::UPLOAD,
+//!                 ..Request::default()
+//!             },
+//!         )
+//!     }?;
+//!
+//!     unsafe { block.write_bytes(&device, 0, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]) }?;
+//!
+//!     unsafe {
+//!         allocator.dealloc(&device, block);
+//!         allocator.cleanup(&device);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+
+use {
+    core::{
+        cell::UnsafeCell,
+        convert::TryFrom as _,
+        fmt::{self, Debug},
+        ptr::NonNull,
+    },
+    gpu_alloc_types::{
+        AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
+        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+    },
+    js_sys::Uint8Array,
+    wasm_bindgen::JsValue,
+    wasm_bindgen_futures::JsFuture,
+    web_sys::{
+        gpu_buffer_usage, gpu_map_mode, GpuBuffer, GpuBufferDescriptor, GpuDevice, GpuQueue,
+    },
+};
+
+/// Index of device-local memory type.
+pub const DEVICE_MEMORY_TYPE: u32 = 0;
+
+/// Index of device-local memory type written by host with `GPUQueue.writeBuffer`.
+pub const UPLOAD_MEMORY_TYPE: u32 = 1;
+
+/// Index of host-visible memory type read by host with `GPUBuffer.mapAsync`.
+pub const READBACK_MEMORY_TYPE: u32 = 2;
+
+/// Memory object allocated by `WebGpuMemoryDevice`.
+pub struct WebGpuMemory {
+    buffer: GpuBuffer,
+    memory_type: u32,
+    size: u64,
+    /// Copy of buffer content for host-visible memory types.
+    /// Never resized, so mapped pointers stay valid.
+    shadow: Option<UnsafeCell<Box<[u8]>>>,
+}
+
+impl WebGpuMemory {
+    /// Returns buffer to bind ranges of.
+    pub fn buffer(&self) -> &GpuBuffer {
+        &self.buffer
+    }
+
+    /// Copies content of readback memory object into its host copy,
+    /// awaiting `GPUBuffer.mapAsync`.
+    ///
+    /// Call it after device finished writing the memory object
+    /// and before mapped blocks of it are invalidated and read.
+    ///
+    /// # Safety
+    ///
+    /// * Memory object must be of `READBACK_MEMORY_TYPE`.
+    /// * Slices of mapped blocks of the memory object must not be alive.
+    pub async unsafe fn read_back(&self) -> Result<(), JsValue> {
+        debug_assert_eq!(self.memory_type, READBACK_MEMORY_TYPE);
+
+        let shadow = self
+            .shadow
+            .as_ref()
+            .expect("Only host-visible memory can be read back");
+
+        JsFuture::from(self.buffer.map_async(gpu_map_mode::READ)).await?;
+        let range = self.buffer.get_mapped_range()?;
+        Uint8Array::new(&range).copy_to(&mut *shadow.get());
+        self.buffer.unmap();
+        Ok(())
+    }
+}
+
+impl Debug for WebGpuMemory {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("WebGpuMemory")
+            .field("memory_type", &self.memory_type)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// Memory device over WebGPU device.
+#[derive(Debug)]
+pub struct WebGpuMemoryDevice {
+    device: GpuDevice,
+    queue: GpuQueue,
+}
+
+impl WebGpuMemoryDevice {
+    /// Wraps WebGPU device, flushing upload memory through its queue.
+    pub fn new(device: GpuDevice) -> Self {
+        let queue = device.queue();
+        WebGpuMemoryDevice { device, queue }
+    }
+
+    /// Returns wrapped device.
+    pub fn device(&self) -> &GpuDevice {
+        &self.device
+    }
+}
+
+/// Returns usage of buffers allocated for memory type with specified index.
+pub fn memory_type_buffer_usage(memory_type: u32) -> u32 {
+    match memory_type {
+        DEVICE_MEMORY_TYPE | UPLOAD_MEMORY_TYPE => {
+            gpu_buffer_usage::STORAGE
+                | gpu_buffer_usage::UNIFORM
+                | gpu_buffer_usage::VERTEX
+                | gpu_buffer_usage::INDEX
+                | gpu_buffer_usage::INDIRECT
+                | gpu_buffer_usage::COPY_SRC
+                | gpu_buffer_usage::COPY_DST
+        }
+        READBACK_MEMORY_TYPE => gpu_buffer_usage::MAP_READ | gpu_buffer_usage::COPY_DST,
+        _ => panic!("Invalid memory type index: `{}`", memory_type),
+    }
+}
+
+impl MemoryDevice<WebGpuMemory> for WebGpuMemoryDevice {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<WebGpuMemory, OutOfMemory> {
+        assert!(
+            flags.is_empty(),
+            "WebGPU backend does not support allocation flags"
+        );
+
+        // Out of memory is reported asynchronously through error scopes,
+        // `createBuffer` throws only for invalid sizes.
+        let descriptor =
+            GpuBufferDescriptor::new(size as f64, memory_type_buffer_usage(memory_type));
+        let buffer = self
+            .device
+            .create_buffer(&descriptor)
+            .map_err(|_| OutOfMemory::OutOfDeviceMemory)?;
+
+        let shadow = match memory_type {
+            DEVICE_MEMORY_TYPE => None,
+            _ => {
+                let len = usize::try_from(size).map_err(|_| OutOfMemory::OutOfHostMemory)?;
+                Some(UnsafeCell::new(vec![0; len].into_boxed_slice()))
+            }
+        };
+
+        Ok(WebGpuMemory {
+            buffer,
+            memory_type,
+            size,
+            shadow,
+        })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn deallocate_memory(&self, memory: WebGpuMemory) {
+        memory.buffer.destroy();
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn map_memory(
+        &self,
+        memory: &mut WebGpuMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        debug_assert!(offset + size <= memory.size);
+
+        let shadow = memory.shadow.as_mut().ok_or(DeviceMapError::MapFailed)?;
+        let ptr = shadow.get_mut().as_mut_ptr().add(offset as usize);
+        Ok(NonNull::new_unchecked(ptr))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn unmap_memory(&self, _memory: &mut WebGpuMemory) {}
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, WebGpuMemory>],
+    ) -> Result<(), OutOfMemory> {
+        // Host copy is updated by `WebGpuMemory::read_back`.
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, WebGpuMemory>],
+    ) -> Result<(), OutOfMemory> {
+        for range in ranges {
+            let memory = range.memory;
+            if memory.memory_type != UPLOAD_MEMORY_TYPE {
+                continue;
+            }
+
+            let shadow = match &memory.shadow {
+                Some(shadow) => &*shadow.get(),
+                None => continue,
+            };
+
+            let end = (range.offset + range.size).min(memory.size);
+            let data = &shadow[range.offset as usize..end as usize];
+
+            self.queue
+                .write_buffer_with_f64_and_u8_slice(&memory.buffer, range.offset as f64, data)
+                .map_err(|_| OutOfMemory::OutOfHostMemory)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `DeviceProperties` for specified WebGPU device, required to create `GpuAllocator`.
+///
+/// Memory types are indexed by `DEVICE_MEMORY_TYPE`, `UPLOAD_MEMORY_TYPE`
+/// and `READBACK_MEMORY_TYPE`.
+/// Browsers don't expose memory sizes,
+/// so both heaps, device-local one and one of readback memory type, are `heap_size` bytes.
+/// Memory objects are limited by `maxBufferSize` of the device.
+pub fn device_properties(device: &GpuDevice, heap_size: u64) -> DeviceProperties<'static> {
+    DeviceProperties {
+        memory_types: vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL | MemoryPropertyFlags::HOST_VISIBLE,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_CACHED,
+            },
+        ]
+        .into(),
+        memory_heaps: vec![
+            MemoryHeap { size: heap_size },
+            MemoryHeap { size: heap_size },
+        ]
+        .into(),
+        // WebGPU does not limit number of buffers.
+        max_memory_allocation_count: u32::MAX,
+        max_memory_allocation_size: device.limits().max_buffer_size() as u64,
+        // `GPUQueue.writeBuffer` requires offset and size to be multiple of 4.
+        non_coherent_atom_size: 4,
+        buffer_device_address: false,
+        buffer_image_granularity: 1,
+    }
+}
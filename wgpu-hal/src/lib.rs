@@ -0,0 +1,441 @@
+//!
+//! # `wgpu-hal` backend for `gpu-alloc`
+//!
+//! Allows projects built on Vulkan backend of `wgpu-hal`
+//! to allocate memory with `gpu-alloc`.
+//!
+//! # Usage example
+//!
+//! ```ignore
+//! use {
+//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc_wgpu_hal::{device_properties, WgpuHalMemoryDevice},
+//!     wgpu_hal::vulkan,
+//! };
+//!
+//! unsafe fn write_data(
+//!     adapter: &vulkan::Adapter,
+//!     device: &vulkan::Device,
+//! ) -> eyre::Result<()> {
+//!     let props = device_properties(
+//!         adapter.shared_instance().raw_instance(),
+//!         ash::vk::API_VERSION_1_1,
+//!         adapter.raw_physical_device(),
+//!     )?;
+//!
+//!     let mut allocator = GpuAllocator::new(Config::i_am_potato(), props);
+//!
+//!     let mut block = allocator.alloc(
+//!         WgpuHalMemoryDevice::wrap(device),
+//!         Request {
+//!             size: 10,
+//!             align_mask: 1,
+//!             usage: UsageFlags::HOST_ACCESS,
+//!             ..Request::default()
+//!         },
+//!     )?;
+//!
+//!     block.write_bytes(WgpuHalMemoryDevice::wrap(device), 0, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9])?;
+//!
+//!     allocator.dealloc(WgpuHalMemoryDevice::wrap(device), block);
+//!     allocator.cleanup(WgpuHalMemoryDevice::wrap(device));
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+
+use {
+    ash::{
+        vk::{self, Handle as _},
+        Instance,
+    },
+    gpu_alloc_types::{
+        AllocationFlags, DedicatedResource, DeviceBindError, DeviceImportError, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, OutOfMemory,
+    },
+    std::ptr::NonNull,
+    tinyvec::TinyVec,
+    wgpu_hal::vulkan::Device,
+};
+
+/// Memory device over `wgpu-hal` Vulkan device.
+///
+/// Memory objects are allocated, mapped, flushed and invalidated
+/// with raw `ash` device used by `wgpu-hal`.
+#[repr(transparent)]
+pub struct WgpuHalMemoryDevice {
+    device: Device,
+}
+
+impl WgpuHalMemoryDevice {
+    pub fn wrap(device: &Device) -> &Self {
+        unsafe {
+            // Safe because `Self` is `repr(transparent)`
+            // with only field being `Device`.
+            &*(device as *const Device as *const Self)
+        }
+    }
+
+    unsafe fn allocate(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: Option<f32>,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        let mut info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(size)
+            .memory_type_index(memory_type);
+
+        let mut info_flags;
+
+        if flags.contains(AllocationFlags::DEVICE_ADDRESS) {
+            info_flags = vk::MemoryAllocateFlagsInfo::builder()
+                .flags(vk::MemoryAllocateFlags::DEVICE_ADDRESS);
+            info = info.push_next(&mut info_flags);
+        }
+
+        let mut handle_types = vk::ExternalMemoryHandleTypeFlags::empty();
+        if flags.contains(AllocationFlags::EXPORT_OPAQUE_FD) {
+            handle_types |= vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD;
+        }
+        if flags.contains(AllocationFlags::EXPORT_OPAQUE_WIN32) {
+            handle_types |= vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32;
+        }
+
+        let mut info_export;
+
+        if !handle_types.is_empty() {
+            info_export = vk::ExportMemoryAllocateInfo::builder().handle_types(handle_types);
+            info = info.push_next(&mut info_export);
+        }
+
+        let mut info_dedicated;
+
+        if let Some(resource) = resource {
+            info_dedicated = match resource {
+                DedicatedResource::Buffer(buffer) => {
+                    vk::MemoryDedicatedAllocateInfo::builder().buffer(vk::Buffer::from_raw(buffer))
+                }
+                DedicatedResource::Image(image) => {
+                    vk::MemoryDedicatedAllocateInfo::builder().image(vk::Image::from_raw(image))
+                }
+            };
+            info = info.push_next(&mut info_dedicated);
+        }
+
+        let mut info_priority;
+
+        if let Some(priority) = priority {
+            info_priority = vk::MemoryPriorityAllocateInfoEXT::builder().priority(priority);
+            info = info.push_next(&mut info_priority);
+        }
+
+        match self.device.raw_device().allocate_memory(&info, None) {
+            Ok(memory) => Ok(memory),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::OutOfDeviceMemory),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::OutOfHostMemory),
+            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => panic!("Too many objects"),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+}
+
+impl MemoryDevice<vk::DeviceMemory> for WgpuHalMemoryDevice {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, None, None)
+    }
+
+    /// Allocates memory with `VkMemoryDedicatedAllocateInfo`,
+    /// which requires Vulkan 1.1 or "VK_KHR_dedicated_allocation" extension.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: DedicatedResource,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, Some(resource), None)
+    }
+
+    /// Allocates memory with `VkMemoryPriorityAllocateInfoEXT`,
+    /// which requires "VK_EXT_memory_priority" extension to be enabled on device creation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory_with_priority(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        resource: Option<DedicatedResource>,
+        priority: f32,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, resource, Some(priority))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn deallocate_memory(&self, memory: vk::DeviceMemory) {
+        self.device.raw_device().free_memory(memory, None);
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn map_memory(
+        &self,
+        memory: &mut vk::DeviceMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        match self.device.raw_device().map_memory(
+            *memory,
+            offset,
+            size,
+            vk::MemoryMapFlags::empty(),
+        ) {
+            Ok(ptr) => {
+                Ok(NonNull::new(ptr as *mut u8)
+                    .expect("Pointer to memory mapping must not be null"))
+            }
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(DeviceMapError::OutOfDeviceMemory),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceMapError::OutOfHostMemory),
+            Err(vk::Result::ERROR_MEMORY_MAP_FAILED) => Err(DeviceMapError::MapFailed),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn unmap_memory(&self, memory: &mut vk::DeviceMemory) {
+        self.device.raw_device().unmap_memory(*memory);
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, vk::DeviceMemory>],
+    ) -> Result<(), OutOfMemory> {
+        self.device
+            .raw_device()
+            .invalidate_mapped_memory_ranges(
+                &ranges
+                    .iter()
+                    .map(|range| {
+                        vk::MappedMemoryRange::builder()
+                            .memory(*range.memory)
+                            .offset(range.offset)
+                            .size(range.size)
+                            .build()
+                    })
+                    .collect::<TinyVec<[_; 4]>>(),
+            )
+            .map_err(|err| match err {
+                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory::OutOfDeviceMemory,
+                vk::Result::ERROR_OUT_OF_HOST_MEMORY => OutOfMemory::OutOfHostMemory,
+                err => panic!("Unexpected Vulkan error: `{}`", err),
+            })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, vk::DeviceMemory>],
+    ) -> Result<(), OutOfMemory> {
+        self.device
+            .raw_device()
+            .flush_mapped_memory_ranges(
+                &ranges
+                    .iter()
+                    .map(|range| {
+                        vk::MappedMemoryRange::builder()
+                            .memory(*range.memory)
+                            .offset(range.offset)
+                            .size(range.size)
+                            .build()
+                    })
+                    .collect::<TinyVec<[_; 4]>>(),
+            )
+            .map_err(|err| match err {
+                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory::OutOfDeviceMemory,
+                vk::Result::ERROR_OUT_OF_HOST_MEMORY => OutOfMemory::OutOfHostMemory,
+                err => panic!("Unexpected Vulkan error: `{}`", err),
+            })
+    }
+
+    /// Imports host memory with "VK_EXT_external_memory_host" extension,
+    /// which must be enabled on device creation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn import_host_memory(
+        &self,
+        ptr: NonNull<u8>,
+        size: u64,
+        memory_type: u32,
+    ) -> Result<vk::DeviceMemory, DeviceImportError> {
+        let mut import_info = vk::ImportMemoryHostPointerInfoEXT::builder()
+            .handle_type(vk::ExternalMemoryHandleTypeFlags::HOST_ALLOCATION_EXT)
+            .host_pointer(ptr.as_ptr().cast());
+
+        let info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(size)
+            .memory_type_index(memory_type)
+            .push_next(&mut import_info);
+
+        match self.device.raw_device().allocate_memory(&info, None) {
+            Ok(memory) => Ok(memory),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                Err(DeviceImportError::OutOfDeviceMemory)
+            }
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceImportError::OutOfHostMemory),
+            Err(vk::Result::ERROR_INVALID_EXTERNAL_HANDLE) => {
+                Err(DeviceImportError::InvalidExternalHandle)
+            }
+            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => panic!("Too many objects"),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_buffer_memory(
+        &self,
+        buffer: u64,
+        memory: &vk::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        match self.device.raw_device().bind_buffer_memory(
+            vk::Buffer::from_raw(buffer),
+            *memory,
+            offset,
+        ) {
+            Ok(()) => Ok(()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(DeviceBindError::OutOfDeviceMemory),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceBindError::OutOfHostMemory),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn bind_image_memory(
+        &self,
+        image: u64,
+        memory: &vk::DeviceMemory,
+        offset: u64,
+    ) -> Result<(), DeviceBindError> {
+        match self.device.raw_device().bind_image_memory(
+            vk::Image::from_raw(image),
+            *memory,
+            offset,
+        ) {
+            Ok(()) => Ok(()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(DeviceBindError::OutOfDeviceMemory),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(DeviceBindError::OutOfHostMemory),
+            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
+        }
+    }
+}
+
+/// Returns `DeviceProperties` for specified `PhysicalDevice`, required to create `GpuAllocator`.
+///
+/// With `wgpu-hal` instance and physical device are available as
+/// `Adapter::shared_instance().raw_instance()` and `Adapter::raw_physical_device()`.
+///
+/// # Safety
+///
+/// `physical_device` must be queried from `Instance` associated with this `instance`.
+/// Even if returned properties' field `buffer_device_address` is set to true,
+/// feature `PhysicalDeviceBufferDeviceAddressFeatures::buffer_derive_address`  must be enabled explicitly on device creation
+/// and extension "VK_KHR_buffer_device_address" for Vulkan prior 1.2.
+/// Otherwise the field must be set to false before passing to `GpuAllocator::new`.
+pub unsafe fn device_properties(
+    instance: &Instance,
+    version: u32,
+    physical_device: vk::PhysicalDevice,
+) -> Result<DeviceProperties<'static>, vk::Result> {
+    use ash::vk::PhysicalDeviceFeatures2;
+
+    let limits = instance
+        .get_physical_device_properties(physical_device)
+        .limits;
+
+    let memory_properties = instance.get_physical_device_memory_properties(physical_device);
+
+    let buffer_device_address =
+        if vk::api_version_major(version) >= 1 && vk::api_version_minor(version) >= 2 {
+            let mut features = PhysicalDeviceFeatures2::builder();
+            let mut bda_features = vk::PhysicalDeviceBufferDeviceAddressFeatures::default();
+            features.p_next =
+                &mut bda_features as *mut vk::PhysicalDeviceBufferDeviceAddressFeatures as *mut _;
+            instance.get_physical_device_features2(physical_device, &mut features);
+            bda_features.buffer_device_address != 0
+        } else {
+            false
+        };
+
+    Ok(DeviceProperties {
+        max_memory_allocation_count: limits.max_memory_allocation_count,
+        max_memory_allocation_size: u64::MAX, // FIXME: Can query this information if instance is v1.1
+        non_coherent_atom_size: limits.non_coherent_atom_size,
+        memory_types: memory_properties.memory_types
+            [..memory_properties.memory_type_count as usize]
+            .iter()
+            .map(|memory_type| MemoryType {
+                props: memory_properties_from_vk(memory_type.property_flags),
+                heap: memory_type.heap_index,
+            })
+            .collect(),
+        memory_heaps: memory_properties.memory_heaps
+            [..memory_properties.memory_heap_count as usize]
+            .iter()
+            .map(|&memory_heap| MemoryHeap {
+                size: memory_heap.size,
+            })
+            .collect(),
+        buffer_device_address,
+        buffer_image_granularity: limits.buffer_image_granularity,
+    })
+}
+
+pub fn memory_properties_from_vk(props: vk::MemoryPropertyFlags) -> MemoryPropertyFlags {
+    let mut result = MemoryPropertyFlags::empty();
+    if props.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL) {
+        result |= MemoryPropertyFlags::DEVICE_LOCAL;
+    }
+    if props.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
+        result |= MemoryPropertyFlags::HOST_VISIBLE;
+    }
+    if props.contains(vk::MemoryPropertyFlags::HOST_COHERENT) {
+        result |= MemoryPropertyFlags::HOST_COHERENT;
+    }
+    if props.contains(vk::MemoryPropertyFlags::HOST_CACHED) {
+        result |= MemoryPropertyFlags::HOST_CACHED;
+    }
+    if props.contains(vk::MemoryPropertyFlags::LAZILY_ALLOCATED) {
+        result |= MemoryPropertyFlags::LAZILY_ALLOCATED;
+    }
+    result
+}
+
+pub fn memory_properties_to_vk(props: MemoryPropertyFlags) -> vk::MemoryPropertyFlags {
+    let mut result = vk::MemoryPropertyFlags::empty();
+    if props.contains(MemoryPropertyFlags::DEVICE_LOCAL) {
+        result |= vk::MemoryPropertyFlags::DEVICE_LOCAL;
+    }
+    if props.contains(MemoryPropertyFlags::HOST_VISIBLE) {
+        result |= vk::MemoryPropertyFlags::HOST_VISIBLE;
+    }
+    if props.contains(MemoryPropertyFlags::HOST_COHERENT) {
+        result |= vk::MemoryPropertyFlags::HOST_COHERENT;
+    }
+    if props.contains(MemoryPropertyFlags::HOST_CACHED) {
+        result |= vk::MemoryPropertyFlags::HOST_CACHED;
+    }
+    if props.contains(MemoryPropertyFlags::LAZILY_ALLOCATED) {
+        result |= vk::MemoryPropertyFlags::LAZILY_ALLOCATED;
+    }
+    result
+}